@@ -0,0 +1,80 @@
+//! Cost of (de)serializing executions with large maps, by [`FieldMap`] type
+//!
+//! Run once with the default `ordered-maps` feature and once without, then
+//! compare:
+//!
+//! ```text
+//! cargo bench --bench field_maps
+//! cargo bench --no-default-features --features native-tls --bench field_maps
+//! ```
+
+use klikkflow_sdk::{ExecutionResult, FieldMap};
+use serde_json::{json, Value};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const KEYS: usize = 100_000;
+const ROUNDS: usize = 11;
+
+/// Median time of `run` over [`ROUNDS`] runs
+fn median(mut run: impl FnMut()) -> Duration {
+    let mut times: Vec<_> = (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    times[ROUNDS / 2]
+}
+
+/// Execution whose input and output data hold [`KEYS`] keys each
+fn execution() -> Value {
+    let data: serde_json::Map<String, Value> = (0..KEYS)
+        .map(|i| {
+            (
+                format!("field-{:06}", i),
+                json!({ "value": i, "label": "x".repeat(16) }),
+            )
+        })
+        .collect();
+    json!({
+        "id": "ex-1", "workflowId": "wf-1", "status": "success",
+        "startedAt": "2024-01-01T00:00:00Z", "finishedAt": "2024-01-01T00:01:00Z",
+        "inputData": data, "outputData": data, "error": null, "nodeResults": {},
+        "metadata": { "totalNodes": 0, "completedNodes": 0, "failedNodes": 0, "retriedNodes": 0 }
+    })
+}
+
+fn main() {
+    let map = if cfg!(feature = "ordered-maps") {
+        "IndexMap (ordered-maps)"
+    } else {
+        "HashMap"
+    };
+    let json = serde_json::to_vec(&execution()).unwrap();
+    println!(
+        "FieldMap = {}, {} keys per map, {:.1} MB of JSON",
+        map,
+        KEYS,
+        json.len() as f64 / 1e6
+    );
+
+    let execution: ExecutionResult = serde_json::from_slice(&json).unwrap();
+    let deserialize = median(|| {
+        black_box(serde_json::from_slice::<ExecutionResult>(black_box(&json)).unwrap());
+    });
+    let serialize = median(|| {
+        black_box(serde_json::to_vec(black_box(&execution)).unwrap());
+    });
+    let data: &FieldMap = &execution.input_data;
+    let lookup = median(|| {
+        for i in (0..KEYS).step_by(7) {
+            black_box(data.get(&format!("field-{:06}", i)));
+        }
+    });
+    println!("deserialize {:>10.1?}", deserialize);
+    println!("serialize   {:>10.1?}", serialize);
+    println!("lookups     {:>10.1?}", lookup);
+}
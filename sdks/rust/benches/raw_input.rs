@@ -0,0 +1,158 @@
+//! Allocations of executing a workflow with a 10 MB input, by input form
+//!
+//! Run with `cargo bench --features test-util --bench raw_input`.
+
+use bytes::Bytes;
+use klikkflow_sdk::{Client, FieldMap, MemoryTransport, TransportResponse};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Counts every allocation made through the global allocator
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const WORKFLOW_ID: &str = "wf-bulk-import";
+const ROUNDS: u32 = 5;
+
+/// Allocation count, allocated bytes and time per call
+struct Usage {
+    allocations: usize,
+    bytes: usize,
+    elapsed: Duration,
+}
+
+/// Average usage of `call` over [`ROUNDS`] runs, with `setup` excluded from it
+async fn measure<T, F>(mut setup: impl FnMut() -> T, mut call: impl FnMut(T) -> F) -> Usage
+where
+    F: std::future::Future<Output = ()>,
+{
+    let mut usage = Usage {
+        allocations: 0,
+        bytes: 0,
+        elapsed: Duration::ZERO,
+    };
+    for _ in 0..ROUNDS {
+        let input = setup();
+        let (allocations, bytes) = (
+            ALLOCATIONS.load(Ordering::Relaxed),
+            ALLOCATED_BYTES.load(Ordering::Relaxed),
+        );
+        let start = Instant::now();
+        call(input).await;
+        usage.elapsed += start.elapsed();
+        usage.allocations += ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+        usage.bytes += ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes;
+    }
+    Usage {
+        allocations: usage.allocations / ROUNDS as usize,
+        bytes: usage.bytes / ROUNDS as usize,
+        elapsed: usage.elapsed / ROUNDS,
+    }
+}
+
+fn report(name: &str, usage: &Usage) {
+    println!(
+        "{:<28} {:>10} allocations {:>10.1} MB allocated {:>8.1?}",
+        name,
+        usage.allocations,
+        usage.bytes as f64 / 1e6,
+        usage.elapsed
+    );
+}
+
+/// Input data of about 10 MB
+fn input() -> FieldMap {
+    let rows: Vec<_> = (0..100_000)
+        .map(|i| json!({ "id": i, "sku": format!("SKU-{:08}", i), "note": "x".repeat(60) }))
+        .collect();
+    FieldMap::from([("rows".to_string(), json!(rows))])
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let transport = Arc::new(MemoryTransport::new().handle("POST", "/api/executions", |_| {
+        TransportResponse::json(
+            StatusCode::OK,
+            &json!({
+                "id": "ex-1", "workflowId": WORKFLOW_ID, "status": "running",
+                "startedAt": "2024-01-01T00:00:00Z", "finishedAt": null,
+                "inputData": {}, "outputData": {}, "error": null, "nodeResults": {},
+                "metadata": { "totalNodes": 0, "completedNodes": 0, "failedNodes": 0, "retriedNodes": 0 }
+            }),
+        )
+    }));
+    let client = Client::builder()
+        .base_url("https://klikkflow.example.com")
+        .transport(transport)
+        .build()
+        .unwrap();
+    let map = input();
+    let raw = Bytes::from(serde_json::to_vec(&map).unwrap());
+    println!("input: {:.1} MB of JSON", raw.len() as f64 / 1e6);
+
+    let owned = measure(
+        || map.clone(),
+        |map| async {
+            client
+                .execute_workflow(WORKFLOW_ID, map, false)
+                .await
+                .unwrap();
+        },
+    )
+    .await;
+    report("execute_workflow", &owned);
+
+    let borrowed = measure(
+        || (),
+        |()| async {
+            client
+                .execute_workflow_ref(WORKFLOW_ID, &map)
+                .await
+                .unwrap();
+        },
+    )
+    .await;
+    report("execute_workflow_ref", &borrowed);
+
+    let spliced = measure(
+        || raw.clone(),
+        |raw| async {
+            client
+                .execute_workflow_raw_input(WORKFLOW_ID, raw)
+                .await
+                .unwrap();
+        },
+    )
+    .await;
+    report("execute_workflow_raw_input", &spliced);
+
+    // The spliced body is the only copy of the input the call makes
+    assert!(spliced.bytes < borrowed.bytes / 2);
+}
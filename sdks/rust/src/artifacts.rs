@@ -0,0 +1,198 @@
+use crate::client::{path_segment, Client};
+use crate::page::Page;
+use crate::timeouts::OperationClass;
+use crate::transport::transport_error;
+use crate::{Error, Result};
+use bytes::Bytes;
+use reqwest::header::{ACCEPT_ENCODING, ACCEPT_RANGES, CONTENT_RANGE, RANGE};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::time::timeout;
+use tracing::{debug, info, warn};
+
+/// Response header carrying the hex SHA-256 digest of an artifact
+const CHECKSUM_HEADER: &str = "x-checksum-sha256";
+/// Times an interrupted download is resumed before giving up
+const MAX_RESUMES: u32 = 3;
+
+/// A file stored by the server for an execution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactInfo {
+    pub id: String,
+    /// Node that produced the artifact
+    #[serde(rename = "nodeId")]
+    pub node_id: String,
+    pub filename: String,
+    /// Size in bytes
+    pub size: u64,
+    #[serde(rename = "contentType")]
+    pub content_type: String,
+    /// Checksum as `<algorithm>:<hex digest>`, e.g. `sha256:9f86d0...`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+impl Client {
+    /// List the artifacts stored for an execution
+    pub async fn list_execution_artifacts(&self, execution_id: &str) -> Result<Page<ArtifactInfo>> {
+        debug!("Listing artifacts of execution: {}", execution_id);
+        let path = format!("/api/executions/{}/artifacts", path_segment(execution_id));
+        let response: serde_json::Value = self
+            .make_request(OperationClass::Read, "GET", &path, None::<&()>)
+            .await?;
+        self.list_page(response, "artifacts", None, None)
+    }
+
+    /// List the artifacts stored for an execution, returning only the items of the page
+    #[deprecated(note = "use `list_execution_artifacts`, whose `items` hold the artifacts")]
+    pub async fn list_execution_artifacts_items(
+        &self,
+        execution_id: &str,
+    ) -> Result<Vec<ArtifactInfo>> {
+        Ok(self.list_execution_artifacts(execution_id).await?.items)
+    }
+
+    /// Download an artifact into `writer`, returning the number of bytes written
+    ///
+    /// The content is streamed without being buffered in memory. If the
+    /// connection drops and the server advertises `Accept-Ranges: bytes`, the
+    /// download resumes where it stopped. When the server sends an
+    /// `X-Checksum-Sha256` header, the content is verified against it once
+    /// complete, failing with [`Error::ChecksumMismatch`].
+    ///
+    /// The stream timeout of the client bounds the wait for the response and
+    /// for each chunk of content, not the whole download, so large artifacts
+    /// are not cut off while data keeps arriving. A connection that stalls for
+    /// longer is resumed like a dropped one, or fails with [`Error::Timeout`].
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let file = tokio::fs::File::create("report.csv").await.expect("file is writable");
+    /// let written = client.download_artifact("ex-1", "report", file).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_artifact<W>(
+        &self,
+        execution_id: &str,
+        artifact_id: &str,
+        mut writer: W,
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        info!(
+            "Downloading artifact {} of execution {}",
+            artifact_id, execution_id
+        );
+        let path = format!(
+            "/api/executions/{}/artifacts/{}/content",
+            path_segment(execution_id),
+            path_segment(artifact_id)
+        );
+        // Uncompressed, so byte offsets for resuming match what was written
+        let identity = (ACCEPT_ENCODING.as_str(), "identity".to_string());
+        let mut response = self
+            .get_streaming(&path, std::slice::from_ref(&identity))
+            .await?;
+        let resumable = response
+            .headers()
+            .get(ACCEPT_RANGES)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.eq_ignore_ascii_case("bytes"));
+        let expected = response
+            .headers()
+            .get(CHECKSUM_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_ascii_lowercase);
+
+        let mut digest = Sha256::new();
+        let mut written: u64 = 0;
+        let mut resumes = 0;
+        // Bounds each wait for more content rather than the whole download
+        let idle = self.request_timeout(OperationClass::Stream);
+        loop {
+            match next_chunk(&mut response, idle, &path).await {
+                Ok(Some(chunk)) => {
+                    writer.write_all(&chunk).await.map_err(io_error)?;
+                    digest.update(&chunk);
+                    written += chunk.len() as u64;
+                }
+                Ok(None) => break,
+                Err(e) if resumable && resumes < MAX_RESUMES => {
+                    resumes += 1;
+                    warn!(
+                        "Download of artifact {} interrupted after {} bytes, resuming: {}",
+                        artifact_id, written, e
+                    );
+                    let range = (RANGE.as_str(), format!("bytes={}-", written));
+                    response = self
+                        .get_streaming(&path, &[identity.clone(), range])
+                        .await?;
+                    check_resumed_at(&response, written)?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        writer.flush().await.map_err(io_error)?;
+
+        if let Some(expected) = expected {
+            let actual: String = digest
+                .finalize()
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            if actual != expected {
+                return Err(Error::ChecksumMismatch { expected, actual });
+            }
+            debug!("Verified checksum of artifact {}", artifact_id);
+        }
+        Ok(written)
+    }
+}
+
+/// Next chunk of `response`, failing with [`Error::Timeout`] if none arrives within `idle`
+async fn next_chunk(
+    response: &mut reqwest::Response,
+    idle: Option<Duration>,
+    path: &str,
+) -> Result<Option<Bytes>> {
+    let chunk = match idle {
+        Some(idle) => timeout(idle, response.chunk()).await.map_err(|_| {
+            Error::Timeout(format!(
+                "GET {} received no data for {}ms",
+                path,
+                idle.as_millis()
+            ))
+        })?,
+        None => response.chunk().await,
+    };
+    chunk.map_err(|e| transport_error(e, &Method::GET, path))
+}
+
+/// Check that a ranged response continues the content at `offset`
+fn check_resumed_at(response: &reqwest::Response, offset: u64) -> Result<()> {
+    let start = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("bytes "))
+        .and_then(|range| range.split('-').next())
+        .and_then(|start| start.parse::<u64>().ok());
+    if response.status() != StatusCode::PARTIAL_CONTENT || start != Some(offset) {
+        return Err(Error::Http(format!(
+            "server did not resume the download at byte {}",
+            offset
+        )));
+    }
+    Ok(())
+}
+
+fn io_error(error: std::io::Error) -> Error {
+    Error::Io(error.to_string())
+}
@@ -0,0 +1,295 @@
+use crate::models::{
+    Connection, ConnectionPoint, CreateWorkflowRequest, FieldMap, NodeDefinition, Position,
+    STICKY_NOTE_NODE_TYPE,
+};
+use crate::{Error, Result};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// FNV-1a parameters, chosen because the hash must never change between releases
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// How [`WorkflowBuilder::build`] assigns node IDs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// A random UUID per node, different on every build
+    #[default]
+    Uuid,
+    /// The node name in lowercase with runs of other characters replaced by
+    /// `-`, e.g. `fetch-users`; repeated names get `-2`, `-3`, ...
+    Slug,
+    /// A hash of the node's type, name and parameters; identical nodes get `-2`, `-3`, ...
+    ContentHash,
+}
+
+/// Builder for a [`CreateWorkflowRequest`]
+///
+/// Nodes are connected by the IDs they were added with. [`build`](Self::build)
+/// then replaces every node ID according to the [`IdStrategy`] and rewrites
+/// the connections to match. With [`IdStrategy::Slug`] and
+/// [`IdStrategy::ContentHash`] the same input always produces the same JSON,
+/// so workflows generated from code diff cleanly.
+///
+/// ```rust
+/// use klikkflow_sdk::nodes::HttpRequestNode;
+/// use klikkflow_sdk::{IdStrategy, WorkflowBuilder};
+///
+/// let generate = |strategy| {
+///     let fetch = HttpRequestNode::new("Fetch users", "GET", "https://api.example.com/users")
+///         .build();
+///     let retry = HttpRequestNode::new("Fetch users", "GET", "https://backup.example.com/users")
+///         .build();
+///     let store = HttpRequestNode::new("Store", "POST", "https://crm.example.com/users")
+///         .query("upsert", "true")
+///         .build();
+///     WorkflowBuilder::new("Sync users")
+///         .connect(&fetch.id, &store.id)
+///         .connect(&retry.id, &store.id)
+///         .node(fetch)
+///         .node(retry)
+///         .node(store)
+///         .id_strategy(strategy)
+///         .build()
+///         .unwrap()
+/// };
+///
+/// let request = generate(IdStrategy::Slug);
+/// let ids: Vec<&str> = request.nodes.iter().map(|node| node.id.as_str()).collect();
+/// assert_eq!(ids, ["fetch-users", "fetch-users-2", "store"]);
+/// assert_eq!(request.connections[1].source.node_id, "fetch-users-2");
+/// assert_eq!(request.connections[1].destination.node_id, "store");
+///
+/// for strategy in [IdStrategy::Slug, IdStrategy::ContentHash] {
+///     assert_eq!(
+///         serde_json::to_string(&generate(strategy)).unwrap(),
+///         serde_json::to_string(&generate(strategy)).unwrap(),
+///     );
+/// }
+/// assert_ne!(generate(IdStrategy::Uuid).nodes[0].id, generate(IdStrategy::Uuid).nodes[0].id);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WorkflowBuilder {
+    name: String,
+    description: String,
+    nodes: Vec<NodeDefinition>,
+    connections: Vec<Connection>,
+    settings: Option<FieldMap>,
+    id_strategy: IdStrategy,
+}
+
+impl WorkflowBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            nodes: Vec::new(),
+            connections: Vec::new(),
+            settings: None,
+            id_strategy: IdStrategy::default(),
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Add a node; its ID is replaced when the workflow is built
+    pub fn node(mut self, node: NodeDefinition) -> Self {
+        self.nodes.push(node);
+        self
+    }
+
+    /// Add a sticky note documenting the workflow on the canvas
+    ///
+    /// `size` is the note's width and height and `color` any CSS color. The
+    /// note is kept through export and import but never executed, and cannot
+    /// be connected to other nodes.
+    ///
+    /// ```rust
+    /// use klikkflow_sdk::nodes::HttpRequestNode;
+    /// use klikkflow_sdk::{Position, WorkflowBuilder};
+    ///
+    /// let fetch = HttpRequestNode::new("Fetch users", "GET", "https://api.example.com/users").build();
+    /// let request = WorkflowBuilder::new("Sync users")
+    ///     .node(fetch)
+    ///     .sticky_note(
+    ///         "Runs nightly; see the runbook before changing the URL",
+    ///         Position { x: 0.0, y: -200.0 },
+    ///         (320.0, 120.0),
+    ///         "#fff5b1",
+    ///     )
+    ///     .build()?;
+    /// # Ok::<(), klikkflow_sdk::Error>(())
+    /// ```
+    pub fn sticky_note(
+        self,
+        text: impl Into<String>,
+        position: Position,
+        size: (f64, f64),
+        color: impl Into<String>,
+    ) -> Self {
+        let (width, height) = size;
+        let parameters = FieldMap::from([
+            ("content".to_string(), Value::String(text.into())),
+            ("width".to_string(), Value::from(width)),
+            ("height".to_string(), Value::from(height)),
+            ("color".to_string(), Value::String(color.into())),
+        ]);
+        self.node(NodeDefinition {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: "Sticky Note".to_string(),
+            node_type: STICKY_NOTE_NODE_TYPE.to_string(),
+            position,
+            parameters,
+        })
+    }
+
+    /// Connect the first output of node `from` to the first input of node `to`
+    pub fn connect(self, from: &str, to: &str) -> Self {
+        self.connect_at(from, 0, to, 0)
+    }
+
+    /// Connect an output of node `from` to an input of node `to`
+    pub fn connect_at(mut self, from: &str, output: usize, to: &str, input: usize) -> Self {
+        self.connections.push(Connection {
+            source: ConnectionPoint {
+                node_id: from.to_string(),
+                output_index: Some(output),
+                input_index: None,
+            },
+            destination: ConnectionPoint {
+                node_id: to.to_string(),
+                output_index: None,
+                input_index: Some(input),
+            },
+        });
+        self
+    }
+
+    /// Set a workflow setting
+    ///
+    /// With the default `ordered-maps` feature, settings are sent in the
+    /// order they were first set; setting one again keeps its place.
+    /// Without it, they are sent sorted by key.
+    ///
+    /// ```rust
+    /// use klikkflow_sdk::WorkflowBuilder;
+    /// use serde_json::json;
+    ///
+    /// let request = WorkflowBuilder::new("Nightly report")
+    ///     .setting("timezone", json!("Europe/Oslo"))
+    ///     .setting("errorWorkflow", json!("wf-alerts"))
+    ///     .setting("timezone", json!("UTC"))
+    ///     .build()
+    ///     .unwrap();
+    /// let body = serde_json::to_string(&request).unwrap();
+    /// if cfg!(feature = "ordered-maps") {
+    ///     assert!(body.contains(r#""settings":{"timezone":"UTC","errorWorkflow":"wf-alerts"}"#));
+    /// }
+    /// ```
+    pub fn setting(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.settings
+            .get_or_insert_with(FieldMap::new)
+            .insert(key.into(), value);
+        self
+    }
+
+    /// How node IDs are assigned, [`IdStrategy::Uuid`] by default
+    pub fn id_strategy(mut self, strategy: IdStrategy) -> Self {
+        self.id_strategy = strategy;
+        self
+    }
+
+    /// Produce the request
+    ///
+    /// Fails with [`Error::InvalidInput`] if two nodes were added with the
+    /// same ID or a connection names a node that was not added or an annotation.
+    pub fn build(self) -> Result<CreateWorkflowRequest> {
+        let mut ids = HashMap::new();
+        let mut taken = HashSet::new();
+        let mut nodes = self.nodes;
+        for node in &mut nodes {
+            let base = match self.id_strategy {
+                IdStrategy::Uuid => uuid::Uuid::new_v4().to_string(),
+                IdStrategy::Slug => slug(&node.name),
+                IdStrategy::ContentHash => content_hash(node),
+            };
+            let mut id = base.clone();
+            let mut n = 1;
+            while !taken.insert(id.clone()) {
+                n += 1;
+                id = format!("{}-{}", base, n);
+            }
+            let added_as = std::mem::replace(&mut node.id, id.clone());
+            if ids.insert(added_as.clone(), id).is_some() {
+                return Err(Error::InvalidInput(format!(
+                    "workflow has two nodes with ID {}",
+                    added_as
+                )));
+            }
+        }
+
+        let annotations: HashSet<&str> = nodes
+            .iter()
+            .filter(|node| node.is_annotation())
+            .map(|node| node.id.as_str())
+            .collect();
+        let mut connections = self.connections;
+        for point in connections
+            .iter_mut()
+            .flat_map(|c| [&mut c.source, &mut c.destination])
+        {
+            let id = ids.get(&point.node_id).ok_or_else(|| {
+                Error::InvalidInput(format!("connection to unknown node {}", point.node_id))
+            })?;
+            if annotations.contains(id.as_str()) {
+                return Err(Error::InvalidInput(format!(
+                    "connection to annotation {}",
+                    point.node_id
+                )));
+            }
+            point.node_id = id.clone();
+        }
+
+        Ok(CreateWorkflowRequest {
+            name: self.name,
+            description: self.description,
+            nodes,
+            connections,
+            settings: self.settings,
+        })
+    }
+}
+
+fn slug(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "node".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+fn content_hash(node: &NodeDefinition) -> String {
+    // Keys sorted so the hash does not depend on map order
+    let parameters: std::collections::BTreeMap<_, _> = node.parameters.iter().collect();
+    let parameters = serde_json::to_string(&parameters).unwrap_or_default();
+    let mut hash = FNV_OFFSET;
+    for part in [&node.node_type, &node.name, &parameters] {
+        for byte in part.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    format!("{:016x}", hash)
+}
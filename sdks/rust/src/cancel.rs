@@ -0,0 +1,108 @@
+use crate::client::Client;
+use crate::{Error, Result};
+use std::future::Future;
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+type StartedObserver = dyn Fn(&str) + Send + Sync;
+
+/// Execution a call has started and not yet seen finish, cancelled on the
+/// server if the call is abandoned, see [`ExecuteOptions::cancel_on_drop`](crate::ExecuteOptions::cancel_on_drop)
+pub(crate) struct PendingExecution {
+    client: Client,
+    cancel_on_drop: bool,
+    execution_id: Mutex<Option<String>>,
+    on_started: Option<Box<StartedObserver>>,
+}
+
+impl PendingExecution {
+    pub fn new(client: &Client, cancel_on_drop: bool) -> Self {
+        Self {
+            client: client.clone(),
+            cancel_on_drop,
+            execution_id: Mutex::new(None),
+            on_started: None,
+        }
+    }
+
+    /// Call `observer` with the ID of every execution the call starts waiting for
+    pub fn on_started(mut self, observer: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.on_started = Some(Box::new(observer));
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<String>> {
+        self.execution_id.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The call is waiting for this execution
+    pub fn started(&self, execution_id: &str) {
+        *self.lock() = Some(execution_id.to_string());
+        if let Some(observer) = &self.on_started {
+            observer(execution_id);
+        }
+    }
+
+    /// The execution the call waited for finished
+    pub fn finished(&self) {
+        self.lock().take();
+    }
+
+    /// Run `call` until `token` is cancelled, then cancel the pending execution if asked to
+    pub async fn run<T>(
+        &self,
+        token: Option<&CancellationToken>,
+        call: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let Some(token) = token else {
+            return call.await;
+        };
+        let outcome = tokio::select! {
+            biased;
+            _ = token.cancelled() => None,
+            result = call => Some(result),
+        };
+        if let Some(result) = outcome {
+            return result;
+        }
+
+        let execution_id = self.lock().take();
+        info!("Call cancelled while waiting for {:?}", execution_id);
+        if let (Some(id), true) = (&execution_id, self.cancel_on_drop) {
+            if let Err(e) = self.client.cancel_execution(id).await {
+                warn!("Failed to cancel abandoned execution {}: {}", id, e);
+            }
+        }
+        Err(Error::Cancelled { execution_id })
+    }
+}
+
+impl Drop for PendingExecution {
+    fn drop(&mut self) {
+        let Some(execution_id) = self.lock().take() else {
+            return;
+        };
+        if !self.cancel_on_drop {
+            return;
+        }
+        // Dropped outside a runtime, there is nothing to send the request with
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!(
+                "Execution {} abandoned outside a runtime, not cancelling it",
+                execution_id
+            );
+            return;
+        };
+        info!("Call dropped, cancelling execution {}", execution_id);
+        let client = self.client.clone();
+        runtime.spawn(async move {
+            if let Err(e) = client.cancel_execution(&execution_id).await {
+                warn!(
+                    "Failed to cancel abandoned execution {}: {}",
+                    execution_id, e
+                );
+            }
+        });
+    }
+}
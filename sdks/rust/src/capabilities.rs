@@ -0,0 +1,209 @@
+use crate::client::Client;
+use crate::timeouts::OperationClass;
+use crate::{Error, Result};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::sync::{Mutex, MutexGuard};
+use tracing::debug;
+
+const CAPABILITIES_PATH: &str = "/api/capabilities";
+
+/// Optional server feature, see [`Client::capabilities`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Capability {
+    /// Importing several workflows in one request
+    BulkImport,
+    /// Replaying an execution with its original input
+    Replay,
+    /// Deleted workflows kept in a restorable trash
+    Trash,
+    /// Cursor pagination of list endpoints
+    Cursors,
+    /// The lightweight `/api/executions/{id}/status` endpoint
+    ExecutionStatus,
+    /// Execution artifacts
+    Artifacts,
+    /// Executions listed as active as soon as they are submitted, so that
+    /// racing submissions of a workflow see each other
+    ExecutionConflicts,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 7] = [
+        Capability::BulkImport,
+        Capability::Replay,
+        Capability::Trash,
+        Capability::Cursors,
+        Capability::ExecutionStatus,
+        Capability::Artifacts,
+        Capability::ExecutionConflicts,
+    ];
+
+    /// Name of the capability in the server's manifest
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::BulkImport => "bulk-import",
+            Capability::Replay => "replay",
+            Capability::Trash => "trash",
+            Capability::Cursors => "cursors",
+            Capability::ExecutionStatus => "execution-status",
+            Capability::Artifacts => "artifacts",
+            Capability::ExecutionConflicts => "execution-conflicts",
+        }
+    }
+
+    /// Endpoint that only exists when the server has the capability
+    ///
+    /// Capabilities without one cannot be probed and are reported missing
+    /// by servers without a manifest.
+    fn probe_path(&self) -> Option<&'static str> {
+        match self {
+            Capability::BulkImport => Some("/api/workflows/import"),
+            Capability::Replay => Some("/api/executions/replay"),
+            Capability::Trash => Some("/api/workflows/trash"),
+            Capability::Cursors
+            | Capability::ExecutionStatus
+            | Capability::Artifacts
+            | Capability::ExecutionConflicts => None,
+        }
+    }
+}
+
+/// Where a [`Capabilities`] came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapabilitySource {
+    /// The server's capability manifest
+    Manifest,
+    /// Probing well-known endpoints, for servers without a manifest
+    Probed,
+}
+
+/// Features supported by a server, from [`Client::capabilities`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Names of the supported features, including ones this SDK does not know
+    pub features: BTreeSet<String>,
+    pub source: CapabilitySource,
+}
+
+impl Capabilities {
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.features.contains(capability.as_str())
+    }
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    capabilities: BTreeSet<String>,
+}
+
+/// Capabilities of the server, shared by all clones of a client
+#[derive(Debug, Default)]
+pub(crate) struct CapabilityCache(Mutex<Option<Capabilities>>);
+
+impl CapabilityCache {
+    fn lock(&self) -> MutexGuard<'_, Option<Capabilities>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn get(&self) -> Option<Capabilities> {
+        self.lock().clone()
+    }
+}
+
+impl Client {
+    /// Get the features supported by the server
+    ///
+    /// Fetched from the server's capability manifest once and cached. Servers
+    /// without a manifest are probed at a few well-known endpoints instead.
+    /// The cache is dropped when the SDK finds a capability it reports
+    /// missing, so the next call fetches it again.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::Capability;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// if client.capabilities().await?.supports(Capability::BulkImport) {
+    ///     // Import in one request instead of one per workflow
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn capabilities(&self) -> Result<Capabilities> {
+        if let Some(capabilities) = self.capability_cache().get() {
+            return Ok(capabilities);
+        }
+
+        let capabilities = match self
+            .make_request::<Manifest, ()>(OperationClass::Read, "GET", CAPABILITIES_PATH, None)
+            .await
+        {
+            Ok(manifest) => Capabilities {
+                features: manifest.capabilities,
+                source: CapabilitySource::Manifest,
+            },
+            Err(e) if matches!(e.status(), Some(404 | 405 | 501)) => {
+                debug!("Server has no capability manifest, probing endpoints");
+                self.probe_capabilities().await?
+            }
+            Err(e) => return Err(e),
+        };
+        *self.capability_cache().lock() = Some(capabilities.clone());
+        Ok(capabilities)
+    }
+
+    async fn probe_capabilities(&self) -> Result<Capabilities> {
+        let mut features = BTreeSet::new();
+        for capability in Capability::ALL {
+            let Some(path) = capability.probe_path() else {
+                continue;
+            };
+            let found = match self
+                .make_request::<serde_json::Value, ()>(OperationClass::Read, "GET", path, None)
+                .await
+            {
+                Ok(_) => true,
+                Err(Error::NotFound { .. }) => false,
+                Err(Error::Api { status: 501, .. }) => false,
+                // Any other answer, e.g. 405 for a POST-only endpoint, means the route exists
+                Err(e) if e.status().is_some_and(|status| status != 429) => true,
+                Err(e) => return Err(e),
+            };
+            if found {
+                features.insert(capability.as_str().to_string());
+            }
+        }
+        Ok(Capabilities {
+            features,
+            source: CapabilitySource::Probed,
+        })
+    }
+
+    /// Cached capabilities from the manifest, for deciding whether to try an endpoint
+    pub(crate) fn manifest_capabilities(&self) -> Option<Capabilities> {
+        self.capability_cache()
+            .get()
+            .filter(|capabilities| capabilities.source == CapabilitySource::Manifest)
+    }
+
+    /// Note that the server answered 404 or 501 for `capability`
+    ///
+    /// Drops the cached capabilities if they claimed it was supported.
+    pub(crate) fn capability_unavailable(&self, capability: Capability) {
+        let mut cached = self.capability_cache().lock();
+        if cached
+            .as_ref()
+            .is_some_and(|capabilities| capabilities.supports(capability))
+        {
+            debug!(
+                "Server lacks {} despite reporting it, dropping cached capabilities",
+                capability.as_str()
+            );
+            *cached = None;
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use crate::client::Client;
+use crate::error::ErrorCode;
+use crate::{Error, Result};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Configuration of a circuit breaker, see [`Client::with_circuit_breaker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed requests after which the circuit opens
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a probe request is let through
+    pub cool_down: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cool_down: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn failure_threshold(mut self, failures: u32) -> Self {
+        self.failure_threshold = failures;
+        self
+    }
+
+    pub fn cool_down(mut self, cool_down: Duration) -> Self {
+        self.cool_down = cool_down;
+        self
+    }
+}
+
+/// State of a client's circuit breaker, from [`Client::circuit_state`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Requests are sent
+    Closed,
+    /// Requests fail with [`Error::CircuitOpen`] without being sent
+    Open,
+    /// The cool-down is over and a single probe request is let through
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A probe request is in flight
+    HalfOpen,
+}
+
+/// Circuit breaker shared by all clones of a client
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<State>,
+}
+
+/// Permission to send one request, reporting its outcome to the breaker
+pub(crate) struct Admission<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+    reported: bool,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match *self.state.lock().unwrap_or_else(|e| e.into_inner()) {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until } if Instant::now() >= until => CircuitState::HalfOpen,
+            State::Open { .. } => CircuitState::Open,
+            State::HalfOpen => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Let a request through, or fail with [`Error::CircuitOpen`] while open
+    pub fn admit(&self) -> Result<Admission<'_>> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let probe = match *state {
+            State::Closed { .. } => false,
+            State::Open { until } => {
+                let now = Instant::now();
+                if now < until {
+                    return Err(Error::CircuitOpen {
+                        retry_in: until - now,
+                    });
+                }
+                info!("Circuit breaker half-open, sending a probe request");
+                *state = State::HalfOpen;
+                true
+            }
+            State::HalfOpen => {
+                return Err(Error::CircuitOpen {
+                    retry_in: Duration::ZERO,
+                })
+            }
+        };
+        Ok(Admission {
+            breaker: self,
+            probe,
+            reported: false,
+        })
+    }
+
+    fn record_success(&self, probe: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if probe {
+            info!("Circuit breaker probe succeeded, closing the circuit");
+        }
+        if probe || matches!(*state, State::Closed { .. }) {
+            *state = State::Closed { failures: 0 };
+        }
+    }
+
+    fn record_failure(&self, probe: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let until = Instant::now() + self.config.cool_down;
+        match *state {
+            State::Closed { failures } if failures + 1 >= self.config.failure_threshold => {
+                warn!(
+                    "Circuit breaker opened after {} consecutive failures, failing fast for {:?}",
+                    failures + 1,
+                    self.config.cool_down
+                );
+                *state = State::Open { until };
+            }
+            State::Closed { failures } => {
+                *state = State::Closed {
+                    failures: failures + 1,
+                }
+            }
+            State::HalfOpen if probe => {
+                warn!(
+                    "Circuit breaker probe failed, failing fast for another {:?}",
+                    self.config.cool_down
+                );
+                *state = State::Open { until };
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Admission<'_> {
+    /// Count the outcome of the request; only failures of the server or the connection count against it
+    pub fn record<T>(mut self, result: &Result<T>) {
+        self.reported = true;
+        match result {
+            Err(error)
+                if matches!(
+                    error.code(),
+                    ErrorCode::Server | ErrorCode::Transport | ErrorCode::Timeout
+                ) =>
+            {
+                self.breaker.record_failure(self.probe)
+            }
+            _ => self.breaker.record_success(self.probe),
+        }
+    }
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        // A probe abandoned before its outcome lets the next request probe instead
+        if self.probe && !self.reported {
+            *self.breaker.state.lock().unwrap_or_else(|e| e.into_inner()) = State::Open {
+                until: Instant::now(),
+            };
+        }
+    }
+}
+
+impl Client {
+    /// State of the circuit breaker, [`Closed`](CircuitState::Closed) when there is none
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit_breaker()
+            .map_or(CircuitState::Closed, CircuitBreaker::state)
+    }
+}
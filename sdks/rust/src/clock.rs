@@ -0,0 +1,99 @@
+use crate::client::Client;
+use crate::deprecation::parse_http_date;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, DATE};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Clock skew above which a warning is logged, see [`Client::with_clock_skew_warning`]
+pub const DEFAULT_CLOCK_SKEW_WARNING: Duration = Duration::from_secs(30);
+
+/// Marks that no response with a `Date` header was seen yet
+const UNKNOWN: i64 = i64::MIN;
+
+/// Offset of the server clock from the local one, shared by all clones of a client
+#[derive(Debug)]
+pub(crate) struct ClockSkew {
+    /// Server time minus local time, in milliseconds
+    offset_ms: AtomicI64,
+    warned: AtomicBool,
+}
+
+impl Default for ClockSkew {
+    fn default() -> Self {
+        Self {
+            offset_ms: AtomicI64::new(UNKNOWN),
+            warned: AtomicBool::new(false),
+        }
+    }
+}
+
+impl ClockSkew {
+    /// Update the offset from the `Date` header of a response just received
+    pub fn record(&self, headers: &HeaderMap, warn_above: Duration) {
+        let Some(date) = headers
+            .get(DATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_http_date(value.trim()))
+        else {
+            return;
+        };
+        // The header has whole seconds, so the server time lies within the second after it
+        let offset_ms = (date - Utc::now()).num_milliseconds() + 500;
+        self.offset_ms.store(offset_ms, Ordering::Relaxed);
+
+        let exceeded = offset_ms.unsigned_abs() > warn_above.as_millis() as u64;
+        if exceeded && !self.warned.swap(true, Ordering::Relaxed) {
+            warn!(
+                "Server clock is {:.1}s {} the local clock; timestamps are adjusted",
+                offset_ms.unsigned_abs() as f64 / 1000.0,
+                if offset_ms > 0 { "ahead of" } else { "behind" }
+            );
+        } else if !exceeded && self.warned.swap(false, Ordering::Relaxed) {
+            info!(
+                "Server clock is back within {:?} of the local clock",
+                warn_above
+            );
+        }
+    }
+
+    pub fn offset(&self) -> Option<chrono::Duration> {
+        match self.offset_ms.load(Ordering::Relaxed) {
+            UNKNOWN => None,
+            offset_ms => Some(chrono::Duration::milliseconds(offset_ms)),
+        }
+    }
+}
+
+impl Client {
+    /// How far the server clock is ahead of the local one, negative when behind
+    ///
+    /// Estimated from the `Date` header of the latest response that had one,
+    /// to within about half a second; `None` until such a response arrived.
+    /// Timestamps the client compares with server times, such as the cutoff
+    /// of [`verify_retention`](Self::verify_retention), are shifted by it.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// client.health_check().await?;
+    /// if let Some(skew) = client.estimated_clock_skew() {
+    ///     println!("server clock is {}s ahead", skew.num_seconds());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn estimated_clock_skew(&self) -> Option<chrono::Duration> {
+        self.clock_skew().offset()
+    }
+
+    /// The current time by the server's clock, as far as it is known
+    pub(crate) fn server_now(&self) -> DateTime<Utc> {
+        Utc::now()
+            + self
+                .estimated_clock_skew()
+                .unwrap_or_else(chrono::Duration::zero)
+    }
+}
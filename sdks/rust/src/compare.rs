@@ -0,0 +1,242 @@
+use crate::client::Client;
+use crate::models::*;
+use crate::Result;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::info;
+
+/// Options controlling how execution outputs are compared
+#[derive(Debug, Clone, Default)]
+pub struct CompareOptions {
+    /// JSON pointers (e.g. `/0/json/createdAt`) ignored in every node's output
+    pub ignore_pointers: Vec<String>,
+    /// JSON pointers ignored only for the given node
+    pub ignore_pointers_by_node: BTreeMap<String, Vec<String>>,
+    /// Object keys ignored at any depth, e.g. `id` or `timestamp`
+    pub ignore_keys: Vec<String>,
+}
+
+impl CompareOptions {
+    /// Create comparison options that ignore nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ignore a JSON pointer in every node's output
+    pub fn ignore_pointer(mut self, pointer: impl Into<String>) -> Self {
+        self.ignore_pointers.push(pointer.into());
+        self
+    }
+
+    /// Ignore a JSON pointer in one node's output
+    pub fn ignore_pointer_for(
+        mut self,
+        node: impl Into<String>,
+        pointer: impl Into<String>,
+    ) -> Self {
+        self.ignore_pointers_by_node
+            .entry(node.into())
+            .or_default()
+            .push(pointer.into());
+        self
+    }
+
+    /// Ignore an object key wherever it appears
+    pub fn ignore_key(mut self, key: impl Into<String>) -> Self {
+        self.ignore_keys.push(key.into());
+        self
+    }
+}
+
+/// A single difference between two JSON values
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueChange {
+    Added {
+        pointer: String,
+        value: Value,
+    },
+    Removed {
+        pointer: String,
+        value: Value,
+    },
+    Changed {
+        pointer: String,
+        before: Value,
+        after: Value,
+    },
+}
+
+/// Differences between the outputs of two executions
+#[derive(Debug, Clone, Default)]
+pub struct OutputDiff {
+    /// Nodes with a result only in the compared execution
+    pub added_nodes: Vec<String>,
+    /// Nodes with a result only in the baseline execution
+    pub removed_nodes: Vec<String>,
+    /// Value-level changes of nodes present in both executions
+    pub changed_nodes: BTreeMap<String, Vec<ValueChange>>,
+}
+
+impl OutputDiff {
+    /// Whether both executions produced equivalent outputs
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+    }
+}
+
+impl ExecutionResult {
+    /// Compare this execution's node outputs against a baseline execution
+    pub fn compare_outputs(
+        &self,
+        baseline: &ExecutionResult,
+        options: &CompareOptions,
+    ) -> OutputDiff {
+        let ours: BTreeSet<&String> = self.node_results.keys().collect();
+        let theirs: BTreeSet<&String> = baseline.node_results.keys().collect();
+
+        let mut diff = OutputDiff {
+            added_nodes: ours
+                .difference(&theirs)
+                .map(|node| node.to_string())
+                .collect(),
+            removed_nodes: theirs
+                .difference(&ours)
+                .map(|node| node.to_string())
+                .collect(),
+            ..Default::default()
+        };
+
+        for node in ours.intersection(&theirs) {
+            let mut ignored: Vec<&str> =
+                options.ignore_pointers.iter().map(String::as_str).collect();
+            if let Some(pointers) = options.ignore_pointers_by_node.get(*node) {
+                ignored.extend(pointers.iter().map(String::as_str));
+            }
+
+            let before = normalized_output(&baseline.node_results[*node], &ignored, options);
+            let after = normalized_output(&self.node_results[*node], &ignored, options);
+
+            let mut changes = Vec::new();
+            diff_values("", &before, &after, &mut changes);
+            if !changes.is_empty() {
+                diff.changed_nodes.insert(node.to_string(), changes);
+            }
+        }
+        diff
+    }
+}
+
+fn normalized_output(result: &NodeResult, ignored: &[&str], options: &CompareOptions) -> Value {
+    let mut output = result.output.clone().unwrap_or(Value::Null);
+    for pointer in ignored {
+        remove_pointer(&mut output, pointer);
+    }
+    if !options.ignore_keys.is_empty() {
+        remove_keys(&mut output, &options.ignore_keys);
+    }
+    output
+}
+
+fn remove_pointer(value: &mut Value, pointer: &str) {
+    let Some((parent, last)) = pointer.rsplit_once('/') else {
+        return;
+    };
+    let last = last.replace("~1", "/").replace("~0", "~");
+    match value.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.remove(&last);
+        }
+        Some(Value::Array(items)) => {
+            if let Ok(index) = last.parse::<usize>() {
+                // Blank rather than remove so later indices still line up
+                if index < items.len() {
+                    items[index] = Value::Null;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn remove_keys(value: &mut Value, keys: &[String]) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, _| !keys.contains(key));
+            map.values_mut().for_each(|child| remove_keys(child, keys));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|child| remove_keys(child, keys)),
+        _ => {}
+    }
+}
+
+fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn diff_values(pointer: &str, before: &Value, after: &Value, changes: &mut Vec<ValueChange>) {
+    match (before, after) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                let child = format!("{}/{}", pointer, escape_token(key));
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff_values(&child, x, y, changes),
+                    (Some(x), None) => changes.push(ValueChange::Removed {
+                        pointer: child,
+                        value: x.clone(),
+                    }),
+                    (None, Some(y)) => changes.push(ValueChange::Added {
+                        pointer: child,
+                        value: y.clone(),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for index in 0..a.len().max(b.len()) {
+                let child = format!("{}/{}", pointer, index);
+                match (a.get(index), b.get(index)) {
+                    (Some(x), Some(y)) => diff_values(&child, x, y, changes),
+                    (Some(x), None) => changes.push(ValueChange::Removed {
+                        pointer: child,
+                        value: x.clone(),
+                    }),
+                    (None, Some(y)) => changes.push(ValueChange::Added {
+                        pointer: child,
+                        value: y.clone(),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if before != after => changes.push(ValueChange::Changed {
+            pointer: pointer.to_string(),
+            before: before.clone(),
+            after: after.clone(),
+        }),
+        _ => {}
+    }
+}
+
+impl Client {
+    /// Fetch two executions and compare the outputs of `execution_id` against `baseline_id`
+    pub async fn compare_executions(
+        &self,
+        execution_id: &str,
+        baseline_id: &str,
+        options: &CompareOptions,
+    ) -> Result<OutputDiff> {
+        info!(
+            "Comparing execution {} against {}",
+            execution_id, baseline_id
+        );
+        let (execution, baseline) = tokio::try_join!(
+            self.get_execution(execution_id),
+            self.get_execution(baseline_id)
+        )?;
+        Ok(execution.compare_outputs(&baseline, options))
+    }
+}
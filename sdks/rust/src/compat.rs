@@ -0,0 +1,321 @@
+use crate::client::ApiVersion;
+use crate::{Error, Result};
+use reqwest::header::HeaderMap;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+/// Response header naming the version of the server replica that answered
+pub const SERVER_VERSION_HEADER: &str = "x-klikkflow-version";
+
+/// Server version as `major.minor.patch`, ignoring any pre-release suffix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Version(u64, u64, u64);
+
+impl Version {
+    pub(crate) fn parse(version: &str) -> Option<Self> {
+        let version = version.trim().trim_start_matches('v');
+        let end = version
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(version.len());
+        let mut parts = version[..end].split('.').map(|part| part.parse().ok());
+        let major = parts.next()??;
+        let minor = parts.next().unwrap_or(Some(0))?;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        Some(Version(major, minor, patch))
+    }
+}
+
+/// Rewrite of response bodies from some server versions into the shape the models expect
+///
+/// Adapters run on the JSON before it is deserialized, so they work for every
+/// endpoint returning the affected objects. An adapter limited with
+/// [`since`](Self::since) or [`before`](Self::before) still runs when the
+/// server version is unknown, so rewrites must leave bodies that already have
+/// the expected shape unchanged.
+#[derive(Clone)]
+pub struct ResponseAdapter {
+    name: String,
+    since: Option<Version>,
+    before: Option<Version>,
+    rewrite: Arc<dyn Fn(&mut Value) + Send + Sync>,
+}
+
+impl fmt::Debug for ResponseAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseAdapter")
+            .field("name", &self.name)
+            .field("since", &self.since)
+            .field("before", &self.before)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResponseAdapter {
+    /// Adapter applying `rewrite` to every response body
+    pub fn new(
+        name: impl Into<String>,
+        rewrite: impl Fn(&mut Value) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            since: None,
+            before: None,
+            rewrite: Arc::new(rewrite),
+        }
+    }
+
+    /// Adapter renaming field `from` to `to` in the entries of every `collection` field
+    ///
+    /// `collection` may hold an object, whose values are the entries, or an
+    /// array. Entries that already have `to` are left alone.
+    pub fn rename_entry_field(
+        name: impl Into<String>,
+        collection: &str,
+        from: &str,
+        to: &str,
+    ) -> Self {
+        let (collection, from, to) = (collection.to_string(), from.to_string(), to.to_string());
+        Self::new(name, move |body| {
+            rename_entry_field(body, &collection, &from, &to)
+        })
+    }
+
+    /// Only apply to servers at `version` or later
+    pub fn since(mut self, version: &str) -> Result<Self> {
+        self.since = Some(parse_version(version)?);
+        Ok(self)
+    }
+
+    /// Only apply to servers before `version`
+    pub fn before(mut self, version: &str) -> Result<Self> {
+        self.before = Some(parse_version(version)?);
+        Ok(self)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn applies_to(&self, version: Option<Version>) -> bool {
+        let Some(version) = version else {
+            return true;
+        };
+        self.since.is_none_or(|since| version >= since)
+            && self.before.is_none_or(|before| version < before)
+    }
+}
+
+fn parse_version(version: &str) -> Result<Version> {
+    Version::parse(version)
+        .ok_or_else(|| Error::InvalidInput(format!("invalid server version: {}", version)))
+}
+
+fn rename_entry_field(value: &mut Value, collection: &str, from: &str, to: &str) {
+    match value {
+        Value::Object(map) => {
+            if let Some(entries) = map.get_mut(collection) {
+                let entries: Vec<&mut Value> = match entries {
+                    Value::Object(entries) => entries.values_mut().collect(),
+                    Value::Array(entries) => entries.iter_mut().collect(),
+                    _ => Vec::new(),
+                };
+                for entry in entries {
+                    if let Value::Object(entry) = entry {
+                        if !entry.contains_key(to) {
+                            if let Some(field) = entry.remove(from) {
+                                entry.insert(to.to_string(), field);
+                            }
+                        }
+                    }
+                }
+            }
+            for value in map.values_mut() {
+                rename_entry_field(value, collection, from, to);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                rename_entry_field(value, collection, from, to);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Registry of the [`ResponseAdapter`]s a client applies, in registration order
+///
+/// The default registry holds the SDK's built-in adapters:
+///
+/// | Name               | Servers | Rewrite                                     |
+/// |--------------------|---------|---------------------------------------------|
+/// | `node-result-data` | 2.3+    | `nodeResults` entries' `data` to `output`   |
+#[derive(Debug, Clone)]
+pub struct ResponseAdapters {
+    adapters: Vec<ResponseAdapter>,
+}
+
+impl Default for ResponseAdapters {
+    fn default() -> Self {
+        let node_result_data = ResponseAdapter::rename_entry_field(
+            "node-result-data",
+            "nodeResults",
+            "data",
+            "output",
+        );
+        Self::empty().register(ResponseAdapter {
+            since: Version::parse("2.3.0"),
+            ..node_result_data
+        })
+    }
+}
+
+impl ResponseAdapters {
+    /// Registry without any adapters, not even the built-in ones
+    pub fn empty() -> Self {
+        Self {
+            adapters: Vec::new(),
+        }
+    }
+
+    /// Add an adapter, run after the ones already registered
+    pub fn register(mut self, adapter: ResponseAdapter) -> Self {
+        self.adapters.push(adapter);
+        self
+    }
+
+    /// Remove the adapters named `name`, e.g. a built-in one that misfires
+    pub fn remove(mut self, name: &str) -> Self {
+        self.adapters.retain(|adapter| adapter.name != name);
+        self
+    }
+
+    pub fn adapters(&self) -> &[ResponseAdapter] {
+        &self.adapters
+    }
+}
+
+/// Applies a client's adapters, tracking the server version they are selected by
+#[derive(Debug)]
+pub(crate) struct ResponseNormalizer {
+    adapters: ResponseAdapters,
+    /// Version last reported by [`Client::server_info`](crate::Client::server_info)
+    server_version: Mutex<Option<Version>>,
+}
+
+impl Default for ResponseNormalizer {
+    fn default() -> Self {
+        Self::new(ResponseAdapters::default())
+    }
+}
+
+impl ResponseNormalizer {
+    pub fn new(adapters: ResponseAdapters) -> Self {
+        Self {
+            adapters,
+            server_version: Mutex::new(None),
+        }
+    }
+
+    pub fn record_version(&self, version: &str) {
+        debug!("Server reports version {}", version);
+        let version = Version::parse(version);
+        *self
+            .server_version
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = version;
+    }
+
+    /// Deserialize a response body of API `api_version` after applying the adapters for its server version
+    ///
+    /// `V2` bodies are first brought into the `V1` shape the models use. The
+    /// server version comes from the [`SERVER_VERSION_HEADER`] of the response
+    /// when present, since replicas of a cluster being upgraded differ, and
+    /// from the last [`Client::server_info`](crate::Client::server_info) otherwise.
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        body: &[u8],
+        headers: &HeaderMap,
+        api_version: ApiVersion,
+    ) -> serde_json::Result<T> {
+        if self.adapters.adapters.is_empty() && api_version == ApiVersion::V1 {
+            return serde_json::from_slice(body);
+        }
+        let version = headers
+            .get(SERVER_VERSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Version::parse)
+            .or_else(|| {
+                *self
+                    .server_version
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+            });
+        let mut value: Value = serde_json::from_slice(body)?;
+        if api_version == ApiVersion::V2 {
+            v2::to_v1(&mut value)?;
+        }
+        for adapter in &self.adapters.adapters {
+            if adapter.applies_to(version) {
+                (adapter.rewrite)(&mut value);
+            }
+        }
+        serde_json::from_value(value)
+    }
+}
+
+/// Objects whose fields `/api/v2` renamed, read in their `V2` shape and written back as `V1`
+mod v2 {
+    use super::*;
+
+    /// Execution with `startTime`, `endTime` and `nodeRuns` for `startedAt`,
+    /// `finishedAt` and `nodeResults`
+    #[derive(Deserialize)]
+    struct Execution {
+        #[serde(rename = "startTime")]
+        start_time: Value,
+        #[serde(rename = "endTime", default)]
+        end_time: Value,
+        #[serde(rename = "nodeRuns", default)]
+        node_runs: Option<Value>,
+        #[serde(flatten)]
+        rest: Map<String, Value>,
+    }
+
+    impl From<Execution> for Value {
+        fn from(execution: Execution) -> Self {
+            let mut fields = execution.rest;
+            fields.insert("startedAt".to_string(), execution.start_time);
+            fields.insert("finishedAt".to_string(), execution.end_time);
+            if let Some(node_runs) = execution.node_runs {
+                fields.insert("nodeResults".to_string(), node_runs);
+            }
+            Value::Object(fields)
+        }
+    }
+
+    /// Rewrite every `V2` object in `value`, wherever it is nested
+    pub(super) fn to_v1(value: &mut Value) -> serde_json::Result<()> {
+        match value {
+            Value::Object(map) => {
+                for field in map.values_mut() {
+                    to_v1(field)?;
+                }
+                if map.contains_key("workflowId") && map.contains_key("startTime") {
+                    let execution: Execution = serde_json::from_value(value.take())?;
+                    *value = execution.into();
+                }
+            }
+            Value::Array(values) => {
+                for value in values {
+                    to_v1(value)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,339 @@
+use crate::client::Client;
+use crate::credentials::CREDENTIAL_PARAMETERS;
+use crate::lint::{Finding, Severity};
+use crate::models::*;
+use crate::usage::csv_field;
+use crate::{Error, Result};
+use futures_util::TryStreamExt;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Write};
+use tracing::{debug, info};
+
+/// Limits above which a [`ComplexityReport`] carries a finding; `None` disables a check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComplexityThresholds {
+    pub max_nodes: Option<usize>,
+    pub max_connections: Option<usize>,
+    pub max_serialized_bytes: Option<usize>,
+    pub max_branch_depth: Option<usize>,
+    pub max_expressions: Option<usize>,
+    pub max_credential_references: Option<usize>,
+    /// Severity of the findings
+    pub severity: Severity,
+}
+
+impl Default for ComplexityThresholds {
+    fn default() -> Self {
+        Self {
+            max_nodes: Some(100),
+            max_connections: Some(200),
+            max_serialized_bytes: Some(1024 * 1024),
+            max_branch_depth: Some(30),
+            max_expressions: Some(200),
+            max_credential_references: None,
+            severity: Severity::Warning,
+        }
+    }
+}
+
+impl ComplexityThresholds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_nodes(mut self, max: usize) -> Self {
+        self.max_nodes = Some(max);
+        self
+    }
+
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = Some(max);
+        self
+    }
+
+    pub fn max_serialized_bytes(mut self, max: usize) -> Self {
+        self.max_serialized_bytes = Some(max);
+        self
+    }
+
+    pub fn max_branch_depth(mut self, max: usize) -> Self {
+        self.max_branch_depth = Some(max);
+        self
+    }
+
+    pub fn max_expressions(mut self, max: usize) -> Self {
+        self.max_expressions = Some(max);
+        self
+    }
+
+    pub fn max_credential_references(mut self, max: usize) -> Self {
+        self.max_credential_references = Some(max);
+        self
+    }
+
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+}
+
+/// Size and complexity of a workflow definition, from [`WorkflowDefinition::complexity_report`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexityReport {
+    pub workflow_id: String,
+    pub workflow_name: String,
+    pub node_count: usize,
+    pub connection_count: usize,
+    /// Size of the definition serialized as compact JSON
+    pub serialized_bytes: usize,
+    /// Nodes on the longest path through the connections
+    pub max_branch_depth: usize,
+    /// `{{ … }}` expressions in node parameters, at any depth
+    pub expression_count: usize,
+    /// Node parameters referencing a credential
+    pub credential_references: usize,
+    /// Thresholds exceeded, one finding per metric
+    pub findings: Vec<Finding>,
+}
+
+impl ComplexityReport {
+    /// Header row of the CSV written by [`write_csv_row`](Self::write_csv_row)
+    pub const CSV_HEADER: &'static str = "workflow,name,nodes,connections,serialized_bytes,max_branch_depth,expressions,credential_references,findings";
+
+    /// Write the report as a CSV row, the findings as `;`-separated rule names
+    pub fn write_csv_row<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let findings: Vec<&str> = self
+            .findings
+            .iter()
+            .map(|finding| finding.rule.as_str())
+            .collect();
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{}",
+            csv_field(&self.workflow_id),
+            csv_field(&self.workflow_name),
+            self.node_count,
+            self.connection_count,
+            self.serialized_bytes,
+            self.max_branch_depth,
+            self.expression_count,
+            self.credential_references,
+            csv_field(&findings.join(";"))
+        )
+    }
+}
+
+impl WorkflowDefinition {
+    /// Measure the workflow against the default [`ComplexityThresholds`]
+    ///
+    /// Everything is computed locally from the borrowed definition; the
+    /// serialized size is counted without building the JSON.
+    ///
+    /// ```rust
+    /// use klikkflow_sdk::WorkflowDefinition;
+    ///
+    /// fn review(workflow: &WorkflowDefinition) {
+    ///     let report = workflow.complexity_report();
+    ///     println!("{} nodes, branches {} deep", report.node_count, report.max_branch_depth);
+    ///     for finding in &report.findings {
+    ///         println!("{}", finding);
+    ///     }
+    /// }
+    /// ```
+    pub fn complexity_report(&self) -> ComplexityReport {
+        self.complexity_report_with(&ComplexityThresholds::default())
+    }
+
+    /// Measure the workflow against `thresholds`
+    pub fn complexity_report_with(&self, thresholds: &ComplexityThresholds) -> ComplexityReport {
+        let mut counter = ByteCounter(0);
+        // Writing to a counter only fails if serialization does, which it cannot here
+        let _ = serde_json::to_writer(&mut counter, self);
+        let mut expression_count = 0;
+        let mut credential_references = 0;
+        for node in &self.nodes {
+            credential_references += CREDENTIAL_PARAMETERS
+                .iter()
+                .filter(|key| node.parameters.get(**key).is_some_and(Value::is_string))
+                .count();
+            expression_count += node
+                .parameters
+                .values()
+                .map(count_expressions)
+                .sum::<usize>();
+        }
+
+        let mut report = ComplexityReport {
+            workflow_id: self.id.clone(),
+            workflow_name: self.name.clone(),
+            node_count: self.nodes.len(),
+            connection_count: self.connections.len(),
+            serialized_bytes: counter.0,
+            max_branch_depth: self.max_branch_depth(),
+            expression_count,
+            credential_references,
+            findings: Vec::new(),
+        };
+        let checks = [
+            (
+                "max-nodes",
+                "nodes",
+                report.node_count,
+                thresholds.max_nodes,
+            ),
+            (
+                "max-connections",
+                "connections",
+                report.connection_count,
+                thresholds.max_connections,
+            ),
+            (
+                "max-serialized-bytes",
+                "bytes serialized",
+                report.serialized_bytes,
+                thresholds.max_serialized_bytes,
+            ),
+            (
+                "max-branch-depth",
+                "nodes on its longest branch",
+                report.max_branch_depth,
+                thresholds.max_branch_depth,
+            ),
+            (
+                "max-expressions",
+                "expressions",
+                report.expression_count,
+                thresholds.max_expressions,
+            ),
+            (
+                "max-credential-references",
+                "credential references",
+                report.credential_references,
+                thresholds.max_credential_references,
+            ),
+        ];
+        report.findings = checks
+            .into_iter()
+            .filter_map(|(rule, what, value, max)| {
+                let max = max.filter(|max| value > *max)?;
+                Some(Finding {
+                    rule: rule.to_string(),
+                    severity: thresholds.severity,
+                    node_id: None,
+                    message: format!("workflow has {} {}, more than {}", value, what, max),
+                })
+            })
+            .collect();
+        report
+    }
+
+    /// Nodes on the longest path through the connections, ignoring those closing a cycle
+    fn max_branch_depth(&self) -> usize {
+        let index: HashMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), i))
+            .collect();
+        let mut successors = vec![Vec::new(); self.nodes.len()];
+        let mut incoming = vec![0usize; self.nodes.len()];
+        for connection in &self.connections {
+            let source = index.get(connection.source.node_id.as_str());
+            let destination = index.get(connection.destination.node_id.as_str());
+            if let (Some(&source), Some(&destination)) = (source, destination) {
+                successors[source].push(destination);
+                incoming[destination] += 1;
+            }
+        }
+
+        // Longest path in topological order, iteratively so huge workflows cannot overflow the stack
+        let mut depth = vec![1usize; self.nodes.len()];
+        let mut ready: VecDeque<usize> = (0..self.nodes.len())
+            .filter(|&i| incoming[i] == 0)
+            .collect();
+        while let Some(node) = ready.pop_front() {
+            for &next in &successors[node] {
+                depth[next] = depth[next].max(depth[node] + 1);
+                incoming[next] -= 1;
+                if incoming[next] == 0 {
+                    ready.push_back(next);
+                }
+            }
+        }
+        depth.into_iter().max().unwrap_or(0)
+    }
+}
+
+/// `{{ … }}` expressions in the strings of `value`, at any depth
+fn count_expressions(value: &Value) -> usize {
+    match value {
+        Value::String(text) => text.matches("{{").count(),
+        Value::Array(items) => items.iter().map(count_expressions).sum(),
+        Value::Object(map) => map.values().map(count_expressions).sum(),
+        _ => 0,
+    }
+}
+
+/// Writer that only counts the bytes written to it
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Write a CSV of the [`ComplexityReport`]s of every workflow matching `filter`
+    ///
+    /// Workflows are fetched a page at a time and each is dropped once its
+    /// row is written, so the whole set is never held in memory. Returns the
+    /// number of rows written, after the [header](ComplexityReport::CSV_HEADER).
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::ComplexityThresholds;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let file = std::fs::File::create("complexity.csv").expect("file is writable");
+    /// let rows = client
+    ///     .report_all(None, &ComplexityThresholds::new().max_branch_depth(8), file)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn report_all<W: Write>(
+        &self,
+        filter: Option<ListWorkflowsOptions>,
+        thresholds: &ComplexityThresholds,
+        mut writer: W,
+    ) -> Result<usize> {
+        info!("Reporting the complexity of workflows");
+        let io_error = |e: io::Error| Error::Io(e.to_string());
+        writeln!(writer, "{}", ComplexityReport::CSV_HEADER).map_err(io_error)?;
+        let workflows = self.stream_workflows(filter.unwrap_or_default());
+        futures_util::pin_mut!(workflows);
+        let mut rows = 0;
+        while let Some(workflow) = workflows.try_next().await? {
+            let report = workflow.complexity_report_with(thresholds);
+            if !report.findings.is_empty() {
+                debug!(
+                    "Workflow {} exceeds {} complexity threshold(s)",
+                    report.workflow_id,
+                    report.findings.len()
+                );
+            }
+            report.write_csv_row(&mut writer).map_err(io_error)?;
+            rows += 1;
+        }
+        writer.flush().map_err(io_error)?;
+        Ok(rows)
+    }
+}
@@ -0,0 +1,142 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Response header carrying the server's consistency token
+pub const CONSISTENCY_TOKEN_HEADER: &str = "X-Consistency-Token";
+
+/// Read-after-write consistency settings
+///
+/// With read-your-writes enabled, reads of a resource this client created or
+/// updated within the last `window` are retried on `404` or when the returned
+/// `updatedAt` is older than the one seen in the write response, until the
+/// window ends. If the write response carried an `X-Consistency-Token`, it is
+/// sent along with those reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsistencyOptions {
+    pub read_your_writes: bool,
+    /// How long after a write reads of the resource are protected
+    pub window: Duration,
+    /// Delay between retries of a stale read
+    pub retry_interval: Duration,
+    /// Maximum number of recently written resources remembered
+    pub max_tracked: usize,
+}
+
+impl Default for ConsistencyOptions {
+    fn default() -> Self {
+        Self {
+            read_your_writes: false,
+            window: Duration::from_secs(5),
+            retry_interval: Duration::from_millis(200),
+            max_tracked: 1024,
+        }
+    }
+}
+
+impl ConsistencyOptions {
+    /// Enable or disable read-your-writes with default settings
+    pub fn read_your_writes(enabled: bool) -> Self {
+        Self {
+            read_your_writes: enabled,
+            ..Default::default()
+        }
+    }
+
+    /// Set how long after a write reads of the resource are protected
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Set the delay between retries of a stale read
+    pub fn retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Set how many recently written resources are remembered
+    pub fn max_tracked(mut self, max: usize) -> Self {
+        self.max_tracked = max;
+        self
+    }
+}
+
+/// Fields of a write response used to recognise stale reads
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ResourceStamp {
+    pub id: Option<String>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ResourceStamp {
+    pub fn parse(body: &[u8]) -> Self {
+        serde_json::from_slice(body).unwrap_or_default()
+    }
+}
+
+/// Recent write of a resource
+#[derive(Debug, Clone)]
+pub(crate) struct WriteRecord {
+    pub written_at: Instant,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub token: Option<String>,
+}
+
+/// Bounded, per-resource record of recent writes shared by client clones
+#[derive(Debug)]
+pub(crate) struct ConsistencyTracker {
+    pub options: ConsistencyOptions,
+    writes: Mutex<HashMap<String, WriteRecord>>,
+}
+
+impl ConsistencyTracker {
+    pub fn new(options: ConsistencyOptions) -> Self {
+        Self {
+            options,
+            writes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remember a write of the resource at `path`
+    pub fn record(&self, path: &str, stamp: ResourceStamp, token: Option<String>) {
+        let mut writes = self.writes.lock().unwrap_or_else(|e| e.into_inner());
+        let window = self.options.window;
+        writes.retain(|_, record| record.written_at.elapsed() < window);
+        if writes.len() >= self.options.max_tracked {
+            let oldest = writes
+                .iter()
+                .min_by_key(|(_, record)| record.written_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                writes.remove(&oldest);
+            }
+        }
+        writes.insert(
+            path.to_string(),
+            WriteRecord {
+                written_at: Instant::now(),
+                updated_at: stamp.updated_at,
+                token,
+            },
+        );
+    }
+
+    /// Forget the resource at `path`, e.g. after deleting it
+    pub fn forget(&self, path: &str) {
+        let mut writes = self.writes.lock().unwrap_or_else(|e| e.into_inner());
+        writes.remove(path);
+    }
+
+    /// The recent write of the resource at `path`, if still within the window
+    pub fn lookup(&self, path: &str) -> Option<WriteRecord> {
+        let writes = self.writes.lock().unwrap_or_else(|e| e.into_inner());
+        writes
+            .get(path)
+            .filter(|record| record.written_at.elapsed() < self.options.window)
+            .cloned()
+    }
+}
@@ -0,0 +1,162 @@
+use crate::models::*;
+
+/// Node parameter holding the ID a webhook node registers its URL under
+const WEBHOOK_ID_PARAMETER: &str = "webhookId";
+
+/// What [`WorkflowDefinition::to_create_request`] carries over into the copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreateRequestOptions {
+    /// Carry over the workflow settings
+    pub settings: bool,
+    /// Keep the `webhookId` parameter of webhook nodes
+    pub webhook_ids: bool,
+}
+
+impl Default for CreateRequestOptions {
+    fn default() -> Self {
+        Self {
+            settings: true,
+            webhook_ids: false,
+        }
+    }
+}
+
+impl CreateRequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn settings(mut self, settings: bool) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn webhook_ids(mut self, webhook_ids: bool) -> Self {
+        self.webhook_ids = webhook_ids;
+        self
+    }
+}
+
+/// Copy a workflow with the default [`CreateRequestOptions`], see [`WorkflowDefinition::into_create_request`]
+impl From<WorkflowDefinition> for CreateWorkflowRequest {
+    fn from(workflow: WorkflowDefinition) -> Self {
+        workflow.into_create_request(&CreateRequestOptions::default())
+    }
+}
+
+impl WorkflowDefinition {
+    /// Request creating a copy of this workflow
+    ///
+    /// Always stripped, since the server owns them:
+    ///
+    /// - `id`, assigned to the copy on creation
+    /// - `active`, as a new workflow starts inactive, so the copy's triggers
+    ///   do not fire alongside the original's until it is activated
+    /// - `created_at` and `updated_at`, set by the server
+    ///
+    /// Stripped unless `options` keep them:
+    ///
+    /// - the `webhookId` parameter of each node, as two workflows with the
+    ///   same webhook ID compete for the same webhook URL; the server assigns
+    ///   the copy new ones
+    /// - the settings, kept by default
+    ///
+    /// Static data and pinned data are not part of a definition, so a copy
+    /// never has them: static data starts empty, which resets the polling
+    /// cursors of trigger nodes. Copy it with
+    /// [`Client::get_workflow_static_data`](crate::Client::get_workflow_static_data)
+    /// and [`Client::set_workflow_static_data`](crate::Client::set_workflow_static_data)
+    /// if the copy should carry on where the original left off.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::{Client, CreateRequestOptions};
+    ///
+    /// let source = Client::new("https://staging.klikkflow.example.com");
+    /// let target = Client::new("https://klikkflow.example.com");
+    /// let workflow = source.get_workflow("wf-1").await?;
+    /// let request = workflow.to_create_request(&CreateRequestOptions::new().settings(false));
+    /// target.create_workflow(request).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_create_request(&self, options: &CreateRequestOptions) -> CreateWorkflowRequest {
+        let settings = options.settings.then(|| self.settings.clone());
+        let mut request = CreateWorkflowRequest {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            nodes: self.nodes.clone(),
+            connections: self.connections.clone(),
+            settings,
+        };
+        strip_webhook_ids(&mut request, options);
+        request
+    }
+
+    /// [`to_create_request`](Self::to_create_request) without cloning the nodes and connections
+    pub fn into_create_request(self, options: &CreateRequestOptions) -> CreateWorkflowRequest {
+        let mut request = CreateWorkflowRequest {
+            name: self.name,
+            description: self.description,
+            nodes: self.nodes,
+            connections: self.connections,
+            settings: options.settings.then_some(self.settings),
+        };
+        strip_webhook_ids(&mut request, options);
+        request
+    }
+
+    /// Apply `update` locally, to preview the workflow the server would store
+    ///
+    /// Fields the update leaves at `None` or [`FieldUpdate::Keep`] are kept,
+    /// [`FieldUpdate::Clear`] empties a collection and [`FieldUpdate::Set`]
+    /// replaces it. `updated_at` is left alone, since the server sets it.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::UpdateWorkflowRequest;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let mut preview = client.get_workflow("wf-1").await?;
+    /// preview.apply(UpdateWorkflowRequest {
+    ///     name: Some("Orders v2".to_string()),
+    ///     ..Default::default()
+    /// });
+    /// println!("{}", preview.canonical_snapshot());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn apply(&mut self, update: UpdateWorkflowRequest) {
+        if let Some(name) = update.name {
+            self.name = name;
+        }
+        if let Some(description) = update.description {
+            self.description = description;
+        }
+        if let Some(active) = update.active {
+            self.active = active;
+        }
+        apply_field(&mut self.nodes, update.nodes);
+        apply_field(&mut self.connections, update.connections);
+        apply_field(&mut self.settings, update.settings);
+    }
+}
+
+fn apply_field<T: Default>(field: &mut T, update: FieldUpdate<T>) {
+    match update {
+        FieldUpdate::Keep => {}
+        FieldUpdate::Clear => *field = T::default(),
+        FieldUpdate::Set(value) => *field = value,
+    }
+}
+
+fn strip_webhook_ids(request: &mut CreateWorkflowRequest, options: &CreateRequestOptions) {
+    if options.webhook_ids {
+        return;
+    }
+    for node in &mut request.nodes {
+        remove_field(&mut node.parameters, WEBHOOK_ID_PARAMETER);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::client::{path_segment, Client};
+use crate::models::*;
+use crate::timeouts::OperationClass;
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+
+/// Node parameters that hold the ID of the credential a node uses
+pub(crate) const CREDENTIAL_PARAMETERS: [&str; 2] = ["credential", "credentialId"];
+
+/// A stored credential, without its secret data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Credential {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    #[serde(default)]
+    pub integration: String,
+    #[serde(rename = "expiresAt", default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Request to create a credential
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateCredentialRequest {
+    pub name: String,
+    /// One of `oauth2`, `apiKey`, `basic`, `jwt` or `custom`
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub integration: String,
+    pub data: HashMap<String, Value>,
+    #[serde(rename = "expiresAt", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Result of [`Client::test_credential`]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CredentialTest {
+    pub success: bool,
+    #[serde(default)]
+    pub message: String,
+}
+
+/// A node that references a credential, from [`Client::find_credential_usages`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialUsage {
+    pub workflow_id: String,
+    pub node_id: String,
+}
+
+/// Options for [`Client::rotate_credential`]
+#[derive(Debug, Clone, Default)]
+pub struct RotationOptions {
+    /// Only find the references, creating and changing nothing
+    pub dry_run: bool,
+    /// Delete the old credential once every reference was rewritten
+    pub delete_old: bool,
+}
+
+/// A workflow whose references could not be rewritten
+#[derive(Debug)]
+pub struct RotationFailure {
+    pub workflow_id: String,
+    pub error: Error,
+}
+
+/// Result of [`Client::rotate_credential`]
+#[derive(Debug)]
+pub struct CredentialRotation {
+    /// The new credential, or `None` for a dry run
+    pub new_credential: Option<Credential>,
+    /// Every reference to the old credential found before rewriting
+    pub usages: Vec<CredentialUsage>,
+    /// Workflows whose references now point to the new credential
+    pub updated_workflows: Vec<String>,
+    /// Workflows still referencing the old credential
+    pub failures: Vec<RotationFailure>,
+    pub old_deleted: bool,
+}
+
+impl CredentialRotation {
+    /// Whether every reference now points to the new credential
+    ///
+    /// Always `false` for a dry run that found references.
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty() && (self.new_credential.is_some() || self.usages.is_empty())
+    }
+}
+
+impl Client {
+    /// Create a credential
+    pub async fn create_credential(&self, request: CreateCredentialRequest) -> Result<Credential> {
+        info!("Creating credential: {}", request.name);
+        self.make_request(
+            OperationClass::Mutate,
+            "POST",
+            "/api/credentials",
+            Some(&request),
+        )
+        .await
+    }
+
+    /// Delete a credential
+    pub async fn delete_credential(&self, credential_id: &str) -> Result<()> {
+        info!("Deleting credential: {}", credential_id);
+        let path = format!("/api/credentials/{}", path_segment(credential_id));
+        let _: serde_json::Value = self
+            .make_request(OperationClass::Mutate, "DELETE", &path, None::<&()>)
+            .await?;
+        Ok(())
+    }
+
+    /// Check that the server can authenticate with a credential
+    pub async fn test_credential(&self, credential_id: &str) -> Result<CredentialTest> {
+        debug!("Testing credential: {}", credential_id);
+        let path = format!("/api/credentials/{}/test", path_segment(credential_id));
+        self.make_request(OperationClass::Mutate, "POST", &path, None::<&()>)
+            .await
+    }
+
+    /// Find every node that references a credential
+    ///
+    /// A node references a credential when its `credential` or `credentialId`
+    /// parameter holds the credential's ID. All workflows are scanned.
+    pub async fn find_credential_usages(
+        &self,
+        credential_id: &str,
+    ) -> Result<Vec<CredentialUsage>> {
+        debug!("Finding usages of credential: {}", credential_id);
+        let workflows: Vec<WorkflowDefinition> = self
+            .stream_workflows(ListWorkflowsOptions::default())
+            .try_collect()
+            .await?;
+        Ok(workflows
+            .iter()
+            .flat_map(|workflow| {
+                workflow
+                    .nodes
+                    .iter()
+                    .filter(|node| references(node, credential_id))
+                    .map(|node| CredentialUsage {
+                        workflow_id: workflow.id.clone(),
+                        node_id: node.id.clone(),
+                    })
+            })
+            .collect())
+    }
+
+    /// Replace a credential with a new one in every workflow that uses it
+    ///
+    /// Creates `new_credential` and checks it with [`test_credential`](Self::test_credential)
+    /// before any workflow is touched; if the test fails, the new credential
+    /// is deleted again and [`Error::InvalidInput`] is returned. Each workflow
+    /// referencing the old credential is then rewritten on its own. A failed
+    /// workflow does not stop the others; it is listed in
+    /// [`failures`](CredentialRotation::failures) and still uses the old
+    /// credential, so the rotation can be repeated once the cause is fixed.
+    /// The old credential is deleted only with
+    /// [`delete_old`](RotationOptions::delete_old) and only if every workflow
+    /// was rewritten.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::{CreateCredentialRequest, RotationOptions};
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let new_credential = CreateCredentialRequest {
+    ///     name: "Stripe".to_string(),
+    ///     credential_type: "apiKey".to_string(),
+    ///     integration: "stripe".to_string(),
+    ///     data: [("apiKey".to_string(), serde_json::json!("sk_live_2"))].into_iter().collect(),
+    ///     expires_at: None,
+    /// };
+    /// let options = RotationOptions { delete_old: true, ..Default::default() };
+    /// let rotation = client.rotate_credential("cred-old", new_credential, options).await?;
+    /// for failure in &rotation.failures {
+    ///     eprintln!("{} still uses the old credential", failure.workflow_id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rotate_credential(
+        &self,
+        old_id: &str,
+        new_credential: CreateCredentialRequest,
+        options: RotationOptions,
+    ) -> Result<CredentialRotation> {
+        let usages = self.find_credential_usages(old_id).await?;
+        info!("Credential {} is used by {} node(s)", old_id, usages.len());
+        if options.dry_run {
+            return Ok(CredentialRotation {
+                new_credential: None,
+                usages,
+                updated_workflows: Vec::new(),
+                failures: Vec::new(),
+                old_deleted: false,
+            });
+        }
+
+        let created = self.create_credential(new_credential).await?;
+        let test = match self.test_credential(&created.id).await {
+            Ok(test) => test,
+            Err(e) => {
+                self.discard_credential(&created.id).await;
+                return Err(e);
+            }
+        };
+        if !test.success {
+            self.discard_credential(&created.id).await;
+            return Err(Error::InvalidInput(format!(
+                "new credential failed its test: {}",
+                test.message
+            )));
+        }
+
+        let mut workflow_ids: Vec<&str> = usages.iter().map(|u| u.workflow_id.as_str()).collect();
+        workflow_ids.dedup();
+        let mut updated_workflows = Vec::new();
+        let mut failures = Vec::new();
+        for workflow_id in workflow_ids {
+            match self
+                .replace_credential_references(workflow_id, old_id, &created.id)
+                .await
+            {
+                Ok(()) => updated_workflows.push(workflow_id.to_string()),
+                Err(error) => {
+                    warn!(
+                        "Could not move workflow {} to credential {}: {}",
+                        workflow_id, created.id, error
+                    );
+                    failures.push(RotationFailure {
+                        workflow_id: workflow_id.to_string(),
+                        error,
+                    });
+                }
+            }
+        }
+
+        let old_deleted = options.delete_old && failures.is_empty();
+        if old_deleted {
+            self.delete_credential(old_id).await?;
+        }
+        Ok(CredentialRotation {
+            new_credential: Some(created),
+            usages,
+            updated_workflows,
+            failures,
+            old_deleted,
+        })
+    }
+
+    /// Point every reference to `old_id` in one workflow at `new_id`
+    async fn replace_credential_references(
+        &self,
+        workflow_id: &str,
+        old_id: &str,
+        new_id: &str,
+    ) -> Result<()> {
+        // Fetched again so edits made since the scan are kept
+        let path = format!("/api/workflows/{}", path_segment(workflow_id));
+        let mut workflow: WorkflowDefinition = self
+            .make_request(OperationClass::Read, "GET", &path, None::<&()>)
+            .await?;
+        for node in &mut workflow.nodes {
+            for key in CREDENTIAL_PARAMETERS {
+                if let Some(value) = node.parameters.get_mut(key) {
+                    if value.as_str() == Some(old_id) {
+                        *value = Value::String(new_id.to_string());
+                    }
+                }
+            }
+        }
+        let request = UpdateWorkflowRequest {
+            nodes: FieldUpdate::Set(workflow.nodes),
+            ..Default::default()
+        };
+        self.update_workflow(workflow_id, request).await?;
+        Ok(())
+    }
+
+    /// Delete a credential created by an aborted rotation, logging failures
+    async fn discard_credential(&self, credential_id: &str) {
+        if let Err(e) = self.delete_credential(credential_id).await {
+            warn!(
+                "Could not delete unused credential {}: {}",
+                credential_id, e
+            );
+        }
+    }
+}
+
+fn references(node: &NodeDefinition, credential_id: &str) -> bool {
+    CREDENTIAL_PARAMETERS
+        .iter()
+        .any(|key| node.parameters.get(*key).and_then(Value::as_str) == Some(credential_id))
+}
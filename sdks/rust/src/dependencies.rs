@@ -0,0 +1,261 @@
+use crate::client::Client;
+use crate::credentials::CREDENTIAL_PARAMETERS;
+use crate::models::*;
+use crate::Result;
+use futures_util::TryStreamExt;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+use tracing::debug;
+
+/// Node parameter that holds the ID of the workflow a node runs as a sub-workflow
+const SUB_WORKFLOW_PARAMETER: &str = "workflowId";
+
+/// Prefix of variable references in parameter expressions, as in `{{ $vars.region }}`
+const VARIABLE_PREFIX: &str = "$vars";
+
+/// Something a workflow can depend on, or a workflow depending on others
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Resource {
+    Workflow(String),
+    Credential(String),
+    /// A variable, by name
+    Variable(String),
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resource::Workflow(id) => write!(f, "workflow:{}", id),
+            Resource::Credential(id) => write!(f, "credential:{}", id),
+            Resource::Variable(name) => write!(f, "variable:{}", name),
+        }
+    }
+}
+
+/// A node of a workflow referencing a resource
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Dependency {
+    pub workflow_id: String,
+    pub node_id: String,
+    pub resource: Resource,
+}
+
+/// Which workflows use which credentials, variables and sub-workflows, from [`Client::build_dependency_graph`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// Names of the scanned workflows, by ID
+    pub workflows: BTreeMap<String, String>,
+    /// Every reference found, ordered by workflow, node and resource
+    pub dependencies: Vec<Dependency>,
+}
+
+impl DependencyGraph {
+    /// Add the references made by the nodes of `workflow`
+    pub fn add_workflow(&mut self, workflow: &WorkflowDefinition) {
+        self.workflows
+            .insert(workflow.id.clone(), workflow.name.clone());
+        let mut found = BTreeSet::new();
+        for node in &workflow.nodes {
+            for resource in node_references(node) {
+                found.insert(Dependency {
+                    workflow_id: workflow.id.clone(),
+                    node_id: node.id.clone(),
+                    resource,
+                });
+            }
+        }
+        self.dependencies
+            .retain(|dependency| dependency.workflow_id != workflow.id);
+        self.dependencies.extend(found);
+        self.dependencies.sort();
+    }
+
+    /// References to `resource` made directly by workflow nodes
+    pub fn dependents_of(&self, resource: &Resource) -> Vec<&Dependency> {
+        self.dependencies
+            .iter()
+            .filter(|dependency| &dependency.resource == resource)
+            .collect()
+    }
+
+    /// Resources the nodes of a workflow reference directly
+    pub fn dependencies_of(&self, workflow_id: &str) -> BTreeSet<&Resource> {
+        self.dependencies
+            .iter()
+            .filter(|dependency| dependency.workflow_id == workflow_id)
+            .map(|dependency| &dependency.resource)
+            .collect()
+    }
+
+    /// IDs of the workflows that break without `resource`
+    ///
+    /// These are the workflows referencing it and, transitively, the
+    /// workflows running any of them as a sub-workflow. A workflow is not
+    /// counted as breaking without itself.
+    pub fn affected_by(&self, resource: &Resource) -> BTreeSet<&str> {
+        let mut affected = BTreeSet::new();
+        let mut pending = VecDeque::from([resource.clone()]);
+        while let Some(resource) = pending.pop_front() {
+            for dependency in self.dependents_of(&resource) {
+                if affected.insert(dependency.workflow_id.as_str()) {
+                    pending.push_back(Resource::Workflow(dependency.workflow_id.clone()));
+                }
+            }
+        }
+        if let Resource::Workflow(id) = resource {
+            affected.remove(id.as_str());
+        }
+        affected
+    }
+
+    /// The graph in Graphviz DOT format, one edge per workflow and resource
+    ///
+    /// Workflows are boxes labelled with their names, credentials are
+    /// diamonds and variables ellipses; edges point from a workflow to what
+    /// it uses.
+    pub fn to_dot(&self) -> String {
+        let mut resources: BTreeSet<&Resource> = BTreeSet::new();
+        let mut edges: BTreeSet<(&str, &Resource)> = BTreeSet::new();
+        for dependency in &self.dependencies {
+            resources.insert(&dependency.resource);
+            edges.insert((&dependency.workflow_id, &dependency.resource));
+        }
+
+        let mut dot = String::from("digraph dependencies {\n    rankdir=LR;\n");
+        for (id, name) in &self.workflows {
+            dot.push_str(&format!(
+                "    {} [shape=box, label={}];\n",
+                quote(&Resource::Workflow(id.clone()).to_string()),
+                quote(name)
+            ));
+        }
+        for resource in resources {
+            let (shape, label) = match resource {
+                Resource::Workflow(id) if self.workflows.contains_key(id) => continue,
+                Resource::Workflow(id) => ("box", id),
+                Resource::Credential(id) => ("diamond", id),
+                Resource::Variable(name) => ("ellipse", name),
+            };
+            dot.push_str(&format!(
+                "    {} [shape={}, label={}];\n",
+                quote(&resource.to_string()),
+                shape,
+                quote(label)
+            ));
+        }
+        for (workflow_id, resource) in edges {
+            dot.push_str(&format!(
+                "    {} -> {};\n",
+                quote(&Resource::Workflow(workflow_id.to_string()).to_string()),
+                quote(&resource.to_string())
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl Client {
+    /// Find the credentials, variables and sub-workflows used by each workflow
+    ///
+    /// Scans the workflows selected by `scope` one page at a time, so the
+    /// definitions are never all held at once. A node uses a credential
+    /// whose ID is in its `credential` or `credentialId` parameter, a
+    /// sub-workflow whose ID is in its `workflowId` parameter, and every
+    /// variable its parameter values refer to as `$vars.name` or
+    /// `$vars["name"]`.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::{ListWorkflowsOptions, Resource};
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let graph = client.build_dependency_graph(ListWorkflowsOptions::default()).await?;
+    /// // Everything that breaks if the credential goes away
+    /// let affected = graph.affected_by(&Resource::Credential("stripe".to_string()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn build_dependency_graph(
+        &self,
+        scope: ListWorkflowsOptions,
+    ) -> Result<DependencyGraph> {
+        debug!("Building workflow dependency graph");
+        let mut graph = DependencyGraph::default();
+        let workflows = self.stream_workflows(scope);
+        futures_util::pin_mut!(workflows);
+        while let Some(workflow) = workflows.try_next().await? {
+            graph.add_workflow(&workflow);
+        }
+        debug!(
+            "Found {} reference(s) in {} workflow(s)",
+            graph.dependencies.len(),
+            graph.workflows.len()
+        );
+        Ok(graph)
+    }
+}
+
+/// Resources referenced by the parameters of a node
+fn node_references(node: &NodeDefinition) -> BTreeSet<Resource> {
+    let mut references = BTreeSet::new();
+    for key in CREDENTIAL_PARAMETERS {
+        if let Some(id) = node.parameters.get(key).and_then(Value::as_str) {
+            references.insert(Resource::Credential(id.to_string()));
+        }
+    }
+    if let Some(id) = node
+        .parameters
+        .get(SUB_WORKFLOW_PARAMETER)
+        .and_then(Value::as_str)
+    {
+        references.insert(Resource::Workflow(id.to_string()));
+    }
+    for value in node.parameters.values() {
+        collect_variables(value, &mut references);
+    }
+    references
+}
+
+/// Add the variables referred to in the strings of `value`, at any depth
+fn collect_variables(value: &Value, references: &mut BTreeSet<Resource>) {
+    match value {
+        Value::String(text) => {
+            for (start, _) in text.match_indices(VARIABLE_PREFIX) {
+                if let Some(name) = variable_name(&text[start + VARIABLE_PREFIX.len()..]) {
+                    references.insert(Resource::Variable(name.to_string()));
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_variables(item, references)),
+        Value::Object(map) => map
+            .values()
+            .for_each(|item| collect_variables(item, references)),
+        _ => {}
+    }
+}
+
+/// Name at the start of `.name` or `["name"]`, following `$vars`
+fn variable_name(rest: &str) -> Option<&str> {
+    let name = if let Some(rest) = rest.strip_prefix('.') {
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        &rest[..end]
+    } else {
+        let rest = rest.strip_prefix('[')?;
+        let quote = rest.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+        let rest = &rest[1..];
+        &rest[..rest.find(quote)?]
+    };
+    (!name.is_empty()).then_some(name)
+}
+
+/// A DOT identifier for any text
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
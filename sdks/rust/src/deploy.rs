@@ -0,0 +1,252 @@
+use crate::client::{path_segment, Client};
+use crate::models::*;
+use crate::timeouts::OperationClass;
+use crate::{Error, Result};
+use std::collections::HashMap;
+use tracing::{error, info, warn};
+
+/// One change of a [`DeploymentPlan`]
+#[derive(Debug, Clone)]
+pub enum DeploymentStep {
+    Create(CreateWorkflowRequest),
+    Update {
+        workflow_id: String,
+        request: UpdateWorkflowRequest,
+    },
+    Activate(String),
+    Deactivate(String),
+}
+
+impl DeploymentStep {
+    /// The existing workflow this step changes, `None` for a create
+    fn workflow_id(&self) -> Option<&str> {
+        match self {
+            DeploymentStep::Create(_) => None,
+            DeploymentStep::Update { workflow_id, .. } => Some(workflow_id),
+            DeploymentStep::Activate(id) | DeploymentStep::Deactivate(id) => Some(id),
+        }
+    }
+}
+
+/// Changes to several workflows applied together by [`Client::deploy`]
+#[derive(Debug, Clone, Default)]
+pub struct DeploymentPlan {
+    steps: Vec<DeploymentStep>,
+}
+
+impl DeploymentPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a workflow; deleted again on rollback
+    pub fn create(mut self, request: CreateWorkflowRequest) -> Self {
+        self.steps.push(DeploymentStep::Create(request));
+        self
+    }
+
+    pub fn update(
+        mut self,
+        workflow_id: impl Into<String>,
+        request: UpdateWorkflowRequest,
+    ) -> Self {
+        self.steps.push(DeploymentStep::Update {
+            workflow_id: workflow_id.into(),
+            request,
+        });
+        self
+    }
+
+    pub fn activate(mut self, workflow_id: impl Into<String>) -> Self {
+        self.steps
+            .push(DeploymentStep::Activate(workflow_id.into()));
+        self
+    }
+
+    pub fn deactivate(mut self, workflow_id: impl Into<String>) -> Self {
+        self.steps
+            .push(DeploymentStep::Deactivate(workflow_id.into()));
+        self
+    }
+
+    pub fn steps(&self) -> &[DeploymentStep] {
+        &self.steps
+    }
+}
+
+/// A step of the plan that was applied
+#[derive(Debug, Clone)]
+pub struct AppliedStep {
+    /// Index of the step in the plan
+    pub step: usize,
+    /// The workflow as returned by the server after the step
+    pub workflow: WorkflowDefinition,
+}
+
+/// The step that stopped the deployment
+#[derive(Debug)]
+pub struct StepFailure {
+    /// Index of the step in the plan
+    pub step: usize,
+    pub error: Error,
+}
+
+/// A workflow that could not be put back as it was
+#[derive(Debug)]
+pub struct RollbackFailure {
+    pub workflow_id: String,
+    pub error: Error,
+}
+
+/// Result of [`Client::deploy`]
+#[derive(Debug, Default)]
+pub struct DeploymentReport {
+    /// Steps applied, in plan order, including those rolled back since
+    pub applied: Vec<AppliedStep>,
+    /// The failed step, `None` if the whole plan was applied
+    pub failure: Option<StepFailure>,
+    /// Workflows restored to their previous definition, or deleted if the plan created them
+    pub rolled_back: Vec<String>,
+    /// Workflows left half-deployed because rolling them back failed
+    pub rollback_failures: Vec<RollbackFailure>,
+}
+
+impl DeploymentReport {
+    /// Whether every step was applied
+    pub fn is_success(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// Whether some workflows are neither deployed nor restored and need to be fixed by hand
+    pub fn needs_manual_intervention(&self) -> bool {
+        !self.rollback_failures.is_empty()
+    }
+}
+
+impl Client {
+    /// Apply the steps of a plan in order, rolling all of them back if one fails
+    ///
+    /// The definition and activation state of every existing workflow in the
+    /// plan is captured before the first step; if that fails, nothing is
+    /// changed and the error is returned. When a step fails, the workflows
+    /// changed so far, and the one the failed step targeted, are restored from
+    /// these snapshots in reverse order and workflows created by the plan are
+    /// deleted. Rolling back keeps going past failures, which are listed in
+    /// [`rollback_failures`](DeploymentReport::rollback_failures).
+    ///
+    /// The deployment is not isolated: changes made by others in the meantime
+    /// are overwritten on rollback.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::{DeploymentPlan, UpdateWorkflowRequest};
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let plan = DeploymentPlan::new()
+    ///     .update(
+    ///         "wf-orders",
+    ///         UpdateWorkflowRequest { name: Some("Orders v2".to_string()), ..Default::default() },
+    ///     )
+    ///     .activate("wf-orders");
+    /// let report = client.deploy(plan).await?;
+    /// if report.needs_manual_intervention() {
+    ///     eprintln!("rollback incomplete: {:?}", report.rollback_failures);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn deploy(&self, plan: DeploymentPlan) -> Result<DeploymentReport> {
+        info!("Deploying plan of {} step(s)", plan.steps.len());
+        let mut snapshots: HashMap<&str, WorkflowDefinition> = HashMap::new();
+        for workflow_id in plan.steps.iter().filter_map(DeploymentStep::workflow_id) {
+            if !snapshots.contains_key(workflow_id) {
+                // Read past the response cache, a stale snapshot would roll back to the wrong state
+                let path = format!("/api/workflows/{}", path_segment(workflow_id));
+                let workflow = self
+                    .make_request(OperationClass::Read, "GET", &path, None::<&()>)
+                    .await?;
+                snapshots.insert(workflow_id, workflow);
+            }
+        }
+
+        let mut report = DeploymentReport::default();
+        // Workflows to roll back, in the order they were first changed; `true` if created
+        let mut touched: Vec<(String, bool)> = Vec::new();
+        for (index, step) in plan.steps.iter().enumerate() {
+            if let Some(workflow_id) = step.workflow_id() {
+                // Marked before applying, a failed request may still have changed it
+                if !touched.iter().any(|(id, _)| id == workflow_id) {
+                    touched.push((workflow_id.to_string(), false));
+                }
+            }
+            let applied = match step {
+                DeploymentStep::Create(request) => self.create_workflow(request.clone()).await,
+                DeploymentStep::Update {
+                    workflow_id,
+                    request,
+                } => self.update_workflow(workflow_id, request.clone()).await,
+                DeploymentStep::Activate(workflow_id) => self.activate_workflow(workflow_id).await,
+                DeploymentStep::Deactivate(workflow_id) => {
+                    self.deactivate_workflow(workflow_id).await
+                }
+            };
+            match applied {
+                Ok(workflow) => {
+                    if let DeploymentStep::Create(_) = step {
+                        touched.push((workflow.id.clone(), true));
+                    }
+                    report.applied.push(AppliedStep {
+                        step: index,
+                        workflow,
+                    });
+                }
+                Err(error) => {
+                    warn!("Deployment step {} failed, rolling back: {}", index, error);
+                    report.failure = Some(StepFailure { step: index, error });
+                    break;
+                }
+            }
+        }
+        if report.failure.is_none() {
+            info!("Deployed {} step(s)", report.applied.len());
+            return Ok(report);
+        }
+
+        for (workflow_id, created) in touched.into_iter().rev() {
+            let restored = if created {
+                self.delete_workflow(&workflow_id).await
+            } else {
+                self.restore_workflow(&snapshots[workflow_id.as_str()])
+                    .await
+            };
+            match restored {
+                Ok(()) => report.rolled_back.push(workflow_id),
+                Err(error) => {
+                    error!(
+                        "Could not roll back workflow {}, it needs manual intervention: {}",
+                        workflow_id, error
+                    );
+                    report
+                        .rollback_failures
+                        .push(RollbackFailure { workflow_id, error });
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Put a workflow back to a snapshot, including its activation state
+    async fn restore_workflow(&self, snapshot: &WorkflowDefinition) -> Result<()> {
+        let request = UpdateWorkflowRequest {
+            name: Some(snapshot.name.clone()),
+            description: Some(snapshot.description.clone()),
+            active: Some(snapshot.active),
+            nodes: FieldUpdate::Set(snapshot.nodes.clone()),
+            connections: FieldUpdate::Set(snapshot.connections.clone()),
+            settings: FieldUpdate::Set(snapshot.settings.clone()),
+        };
+        self.update_workflow(&snapshot.id, request).await?;
+        Ok(())
+    }
+}
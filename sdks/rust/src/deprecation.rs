@@ -0,0 +1,101 @@
+use crate::client::Client;
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::Method;
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+use tracing::warn;
+
+/// Request header naming the API revision the client expects, see [`Client::with_api_revision`]
+pub const API_VERSION_HEADER: &str = "X-API-Version";
+
+/// A response announcing that its endpoint is deprecated, see [`Client::last_deprecation`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deprecation {
+    pub method: Method,
+    /// Path of the request, including the API version prefix
+    pub path: String,
+    /// `Deprecation` header as sent: `true`, or when the endpoint was deprecated
+    pub deprecated: Option<String>,
+    /// When the endpoint will stop working, from the `Sunset` header
+    pub sunset: Option<DateTime<Utc>>,
+}
+
+/// Deprecation notices seen in responses, shared by all clones of a client
+#[derive(Debug, Default)]
+pub(crate) struct DeprecationTracker {
+    last: Mutex<Option<Deprecation>>,
+    /// Endpoints already warned about, as `METHOD path`
+    warned: Mutex<HashSet<String>>,
+}
+
+impl DeprecationTracker {
+    fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+        mutex.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Remember the notice in `headers`, if any, warning once per endpoint
+    pub fn record(&self, method: &Method, path: &str, headers: &HeaderMap) {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+        };
+        let deprecated = header("deprecation").map(str::to_string);
+        let sunset_header = header("sunset");
+        if deprecated.is_none() && sunset_header.is_none() {
+            return;
+        }
+        let sunset = sunset_header.and_then(parse_http_date);
+
+        let endpoint = format!("{} {}", method, path);
+        if Self::lock(&self.warned).insert(endpoint.clone()) {
+            match (sunset, sunset_header) {
+                (Some(sunset), _) => warn!("{} is deprecated, sunset at {}", endpoint, sunset),
+                (None, Some(raw)) => warn!("{} is deprecated, sunset at {}", endpoint, raw),
+                (None, None) => warn!("{} is deprecated", endpoint),
+            }
+        }
+        *Self::lock(&self.last) = Some(Deprecation {
+            method: method.clone(),
+            path: path.to_string(),
+            deprecated,
+            sunset,
+        });
+    }
+
+    pub fn last(&self) -> Option<Deprecation> {
+        Self::lock(&self.last).clone()
+    }
+}
+
+/// Parse an HTTP date such as `Sat, 01 Jun 2030 00:00:00 GMT`
+pub(crate) fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|date| Utc.from_utc_datetime(&date.naive_utc()))
+}
+
+impl Client {
+    /// The latest response announcing that its endpoint is deprecated
+    ///
+    /// Responses with a `Deprecation` or `Sunset` header are recorded here by
+    /// every clone of the client, and each deprecated endpoint is logged once
+    /// with `tracing::warn!`.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// client.get_workflow("wf-1").await?;
+    /// if let Some(deprecation) = client.last_deprecation() {
+    ///     eprintln!("{} is deprecated, sunset {:?}", deprecation.path, deprecation.sunset);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn last_deprecation(&self) -> Option<Deprecation> {
+        self.deprecation_tracker().last()
+    }
+}
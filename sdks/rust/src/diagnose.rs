@@ -0,0 +1,673 @@
+//! Connectivity diagnostics, run with [`Client::diagnose`]
+
+use crate::client::Client;
+use crate::timeouts::OperationClass;
+use crate::Error;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tracing::info;
+use url::Url;
+
+/// Time allowed for each network step of the diagnostics
+const STEP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A step of the connectivity diagnostics, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckKind {
+    Dns,
+    Tcp,
+    Tls,
+    Http,
+    Auth,
+    #[serde(rename = "websocket")]
+    WebSocket,
+}
+
+impl CheckKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckKind::Dns => "dns",
+            CheckKind::Tcp => "tcp",
+            CheckKind::Tls => "tls",
+            CheckKind::Http => "http",
+            CheckKind::Auth => "auth",
+            CheckKind::WebSocket => "websocket",
+        }
+    }
+}
+
+/// Outcome of a diagnostics step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not applicable, or not run because an earlier step failed
+    Skipped,
+}
+
+/// Result of one diagnostics step
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticCheck {
+    pub kind: CheckKind,
+    pub status: CheckStatus,
+    #[serde(rename = "latencyMs", serialize_with = "serialize_millis")]
+    pub latency: Option<Duration>,
+    /// What was checked, or the error
+    pub detail: String,
+    /// Likely cause and remedy, for failed checks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+/// The server's TLS certificate as presented during the handshake
+///
+/// Only the leaf certificate is available from the platform TLS library;
+/// the chain itself is reflected in `trusted`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CertificateSummary {
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    #[serde(rename = "notAfter")]
+    pub not_after: Option<DateTime<Utc>>,
+    /// Whether the certificate chain verified against the system's trust store
+    pub trusted: bool,
+}
+
+/// Serializable report of [`Client::diagnose`], suitable for pasting into issues
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    #[serde(rename = "baseUrl")]
+    pub base_url: String,
+    #[serde(rename = "sdkVersion")]
+    pub sdk_version: &'static str,
+    pub checks: Vec<DiagnosticCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<CertificateSummary>,
+}
+
+impl DiagnosticsReport {
+    /// Whether no check failed
+    pub fn passed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Fail)
+    }
+
+    /// The first failed check, which usually points at the root cause
+    pub fn first_failure(&self) -> Option<&DiagnosticCheck> {
+        self.checks
+            .iter()
+            .find(|check| check.status == CheckStatus::Fail)
+    }
+
+    /// The report as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("diagnostics report serializes")
+    }
+}
+
+impl fmt::Display for DiagnosticsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Diagnostics for {} (SDK {})",
+            self.base_url, self.sdk_version
+        )?;
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Pass => "PASS",
+                CheckStatus::Fail => "FAIL",
+                CheckStatus::Skipped => "SKIP",
+            };
+            write!(f, "  {} {:<9}", status, check.kind.as_str())?;
+            if let Some(latency) = check.latency {
+                write!(f, " {:>6}ms", latency.as_millis())?;
+            } else {
+                write!(f, " {:>8}", "")?;
+            }
+            writeln!(f, "  {}", check.detail)?;
+            if let Some(hint) = &check.hint {
+                writeln!(f, "       hint: {}", hint)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn serialize_millis<S: Serializer>(
+    latency: &Option<Duration>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match latency {
+        Some(latency) => serializer.serialize_u64(latency.as_millis() as u64),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Checks of a diagnostics run, skipping the remaining ones after a failure
+struct Checks {
+    checks: Vec<DiagnosticCheck>,
+    blocked: Option<CheckKind>,
+}
+
+impl Checks {
+    fn pass(&mut self, kind: CheckKind, latency: Duration, detail: String) {
+        self.push(kind, CheckStatus::Pass, Some(latency), detail, None);
+    }
+
+    fn fail(&mut self, kind: CheckKind, latency: Option<Duration>, detail: String, hint: String) {
+        self.push(kind, CheckStatus::Fail, latency, detail, Some(hint));
+        self.blocked.get_or_insert(kind);
+    }
+
+    fn skip(&mut self, kind: CheckKind, detail: impl Into<String>) {
+        self.push(kind, CheckStatus::Skipped, None, detail.into(), None);
+    }
+
+    /// Skip `kind` if an earlier check failed; returns whether it may run
+    fn runnable(&mut self, kind: CheckKind) -> bool {
+        match self.blocked {
+            Some(failed) => {
+                self.skip(kind, format!("{} check failed", failed.as_str()));
+                false
+            }
+            None => true,
+        }
+    }
+
+    fn push(
+        &mut self,
+        kind: CheckKind,
+        status: CheckStatus,
+        latency: Option<Duration>,
+        detail: String,
+        hint: Option<String>,
+    ) {
+        self.checks.push(DiagnosticCheck {
+            kind,
+            status,
+            latency,
+            detail,
+            hint,
+        });
+    }
+}
+
+async fn timed<T>(future: impl Future<Output = T>) -> (Option<T>, Duration) {
+    let start = Instant::now();
+    let result = timeout(STEP_TIMEOUT, future).await.ok();
+    (result, start.elapsed())
+}
+
+impl Client {
+    /// Check connectivity to the server step by step
+    ///
+    /// Runs DNS resolution, TCP connect, TLS handshake, the health endpoint,
+    /// an authenticated request and a WebSocket upgrade, in that order. Each
+    /// step reports its latency and, on failure, a hint at the likely cause;
+    /// steps after a failed network step are skipped. Never fails itself:
+    /// every problem is part of the report.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let report = client.diagnose().await;
+    /// println!("{}", report);
+    /// if !report.passed() {
+    ///     eprintln!("{}", report.to_json());
+    /// }
+    /// # }
+    /// ```
+    pub async fn diagnose(&self) -> DiagnosticsReport {
+        info!("Running connectivity diagnostics for {}", self.base_url());
+        let mut checks = Checks {
+            checks: Vec::new(),
+            blocked: None,
+        };
+        let mut certificate = None;
+
+        if self.uses_custom_transport() {
+            for kind in [CheckKind::Dns, CheckKind::Tcp, CheckKind::Tls] {
+                checks.skip(kind, "custom transport");
+            }
+        } else if self.uses_unix_socket() {
+            for kind in [CheckKind::Dns, CheckKind::Tcp, CheckKind::Tls] {
+                checks.skip(kind, "Unix domain socket transport");
+            }
+        } else {
+            certificate = self.diagnose_network(&mut checks).await;
+        }
+
+        if checks.runnable(CheckKind::Http) {
+            let (result, latency) = timed(self.health_check()).await;
+            match result {
+                Some(Ok(())) => checks.pass(CheckKind::Http, latency, "GET /health".to_string()),
+                Some(Err(e)) => {
+                    let hint = match &e {
+                        Error::NotFound { .. } => "The server has no /health endpoint at this base URL; check for a missing or extra path prefix".to_string(),
+                        Error::Api { status: 502..=504, .. } => "A gateway or proxy answered, but the server behind it is unavailable".to_string(),
+                        _ => "The server answered the connection but the HTTP request failed; check that the base URL points at the API and not at another service".to_string(),
+                    };
+                    checks.fail(CheckKind::Http, Some(latency), e.to_string(), hint);
+                }
+                None => checks.fail(
+                    CheckKind::Http,
+                    Some(latency),
+                    "GET /health timed out".to_string(),
+                    "The server accepted the connection but did not answer; it may be overloaded or a proxy may be holding the request".to_string(),
+                ),
+            }
+        }
+
+        if !self.has_api_key() {
+            checks.skip(CheckKind::Auth, "no API key configured");
+        } else if checks.runnable(CheckKind::Auth) {
+            let (result, latency) = timed(self.make_request::<serde_json::Value, ()>(
+                OperationClass::Read,
+                "GET",
+                "/api/workflows?limit=1",
+                None,
+            ))
+            .await;
+            match result {
+                Some(Ok(_)) => checks.pass(
+                    CheckKind::Auth,
+                    latency,
+                    "API key accepted by GET /api/workflows".to_string(),
+                ),
+                Some(Err(e)) => {
+                    let hint = match &e {
+                        Error::Unauthorized { .. } => "The API key was rejected; check that it is current and belongs to this instance",
+                        Error::Forbidden { .. } => "The API key is valid but lacks permission to list workflows",
+                        _ => "The authenticated request failed for a reason other than the credentials",
+                    };
+                    checks.fail(CheckKind::Auth, Some(latency), e.to_string(), hint.to_string());
+                }
+                None => checks.fail(
+                    CheckKind::Auth,
+                    Some(latency),
+                    "authenticated request timed out".to_string(),
+                    "The health endpoint answered but the API did not; the API service may be overloaded".to_string(),
+                ),
+            }
+        }
+
+        if self.uses_unix_socket() {
+            checks.skip(
+                CheckKind::WebSocket,
+                "streaming is not supported over a Unix domain socket",
+            );
+        } else if checks.runnable(CheckKind::WebSocket) {
+            let (result, latency) = timed(self.connect_stream("/ws/execution/diagnostics")).await;
+            match result {
+                Some(Ok(mut stream)) => {
+                    let _ = stream.close().await;
+                    checks.pass(
+                        CheckKind::WebSocket,
+                        latency,
+                        "WebSocket upgrade accepted".to_string(),
+                    );
+                }
+                Some(Err(e)) => checks.fail(
+                    CheckKind::WebSocket,
+                    Some(latency),
+                    e.to_string(),
+                    "HTTP works but the WebSocket upgrade failed; proxies and load balancers often drop the Upgrade header or need WebSocket support enabled".to_string(),
+                ),
+                None => checks.fail(
+                    CheckKind::WebSocket,
+                    Some(latency),
+                    "WebSocket upgrade timed out".to_string(),
+                    "A proxy may be buffering the upgrade request; check its WebSocket support".to_string(),
+                ),
+            }
+        }
+
+        DiagnosticsReport {
+            base_url: self.base_url().to_string(),
+            sdk_version: env!("CARGO_PKG_VERSION"),
+            checks: checks.checks,
+            certificate,
+        }
+    }
+
+    /// DNS, TCP and TLS checks; returns the server certificate for HTTPS
+    async fn diagnose_network(&self, checks: &mut Checks) -> Option<CertificateSummary> {
+        let url = match Url::parse(self.base_url()) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.host_str().is_some() => url,
+            Ok(url) => {
+                checks.fail(
+                    CheckKind::Dns,
+                    None,
+                    format!("unsupported base URL {}", url),
+                    "The base URL must be an http:// or https:// URL with a host, or unix:///path/to/socket".to_string(),
+                );
+                checks.skip(CheckKind::Tcp, "invalid base URL");
+                checks.skip(CheckKind::Tls, "invalid base URL");
+                return None;
+            }
+            Err(e) => {
+                checks.fail(
+                    CheckKind::Dns,
+                    None,
+                    format!("invalid base URL: {}", e),
+                    "Pass the full URL including the scheme, e.g. https://klikkflow.example.com"
+                        .to_string(),
+                );
+                checks.skip(CheckKind::Tcp, "invalid base URL");
+                checks.skip(CheckKind::Tls, "invalid base URL");
+                return None;
+            }
+        };
+        let host = url
+            .host_str()
+            .unwrap_or_default()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let port = url.port_or_known_default().unwrap_or(80);
+        let https = url.scheme() == "https";
+
+        let addrs = if let Ok(ip) = host.parse::<IpAddr>() {
+            checks.pass(
+                CheckKind::Dns,
+                Duration::ZERO,
+                format!("{} is an IP address", ip),
+            );
+            vec![SocketAddr::new(ip, port)]
+        } else {
+            let (result, latency) = timed(self.lookup_for_diagnostics(&host, port)).await;
+            match result {
+                Some(Ok(addrs)) if !addrs.is_empty() => {
+                    let listed: Vec<String> = addrs.iter().map(|a| a.ip().to_string()).collect();
+                    checks.pass(
+                        CheckKind::Dns,
+                        latency,
+                        format!("{} resolved to {}", host, listed.join(", ")),
+                    );
+                    addrs
+                }
+                Some(Ok(_)) | Some(Err(_)) | None => {
+                    let detail = match result {
+                        Some(Err(e)) => format!("failed to resolve {}: {}", host, e),
+                        None => format!("resolving {} timed out", host),
+                        _ => format!("{} has no addresses", host),
+                    };
+                    checks.fail(
+                        CheckKind::Dns,
+                        Some(latency),
+                        detail,
+                        "Check the host name in the base URL; internal host names may need a VPN, a corporate DNS server or a with_resolve override".to_string(),
+                    );
+                    checks.skip(CheckKind::Tcp, "dns check failed");
+                    checks.skip(CheckKind::Tls, "dns check failed");
+                    return None;
+                }
+            }
+        };
+
+        let (result, latency) = timed(TcpStream::connect(&addrs[..])).await;
+        let stream = match result {
+            Some(Ok(stream)) => {
+                let peer = stream
+                    .peer_addr()
+                    .map(|addr| addr.to_string())
+                    .unwrap_or_else(|_| format!("{}:{}", host, port));
+                checks.pass(CheckKind::Tcp, latency, format!("connected to {}", peer));
+                stream
+            }
+            Some(Err(e)) => {
+                let hint = match e.kind() {
+                    io::ErrorKind::ConnectionRefused => format!("Nothing is listening on port {}; check the port in the base URL and that the server is running", port),
+                    io::ErrorKind::PermissionDenied => "The connection was blocked locally; check firewall rules".to_string(),
+                    _ => "The host is unreachable; check routing, VPN and firewall rules, or whether a proxy is required".to_string(),
+                };
+                checks.fail(
+                    CheckKind::Tcp,
+                    Some(latency),
+                    format!("failed to connect to {}:{}: {}", host, port, e),
+                    hint,
+                );
+                checks.skip(CheckKind::Tls, "tcp check failed");
+                return None;
+            }
+            None => {
+                checks.fail(
+                    CheckKind::Tcp,
+                    Some(latency),
+                    format!("connecting to {}:{} timed out", host, port),
+                    "Packets are being dropped; a firewall may block the port, or outbound traffic may have to go through a proxy".to_string(),
+                );
+                checks.skip(CheckKind::Tls, "tcp check failed");
+                return None;
+            }
+        };
+
+        if !https {
+            checks.skip(CheckKind::Tls, "plain HTTP base URL");
+            return None;
+        }
+
+        check_tls(checks, stream, &addrs, &host).await
+    }
+
+    async fn lookup_for_diagnostics(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        match self.resolver() {
+            Some(resolver) => Ok(resolver
+                .lookup(host)
+                .await?
+                .into_iter()
+                .map(|addr| SocketAddr::new(addr.ip(), port))
+                .collect()),
+            None => Ok(tokio::net::lookup_host((host, port)).await?.collect()),
+        }
+    }
+}
+
+/// TLS handshake check; returns the server certificate
+#[cfg(feature = "native-tls")]
+async fn check_tls(
+    checks: &mut Checks,
+    stream: TcpStream,
+    addrs: &[SocketAddr],
+    host: &str,
+) -> Option<CertificateSummary> {
+    let (result, latency) = timed(tls_handshake(stream, host, false)).await;
+    match result {
+        Some(Ok(certificate)) => {
+            checks.pass(
+                CheckKind::Tls,
+                latency,
+                format!(
+                    "handshake succeeded, certificate for {}",
+                    certificate.subject.as_deref().unwrap_or("unknown subject")
+                ),
+            );
+            Some(certificate)
+        }
+        Some(Err(e)) => {
+            // Connect again without verification to show what the server presented
+            let certificate = match TcpStream::connect(addrs).await {
+                Ok(stream) => timeout(STEP_TIMEOUT, tls_handshake(stream, host, true))
+                    .await
+                    .ok()
+                    .and_then(|result| result.ok()),
+                Err(_) => None,
+            };
+            let hint = match &certificate {
+                Some(cert) if cert.not_after.is_some_and(|not_after| not_after < Utc::now()) => {
+                    "The server's certificate has expired".to_string()
+                }
+                Some(_) => "The server's certificate is not trusted; it may be self-signed, issued by an internal CA that is missing from the trust store, or issued for a different host name".to_string(),
+                None => "The TLS handshake failed; check that the port serves HTTPS and that a proxy is not intercepting the connection".to_string(),
+            };
+            checks.fail(CheckKind::Tls, Some(latency), e, hint);
+            certificate
+        }
+        None => {
+            checks.fail(
+                CheckKind::Tls,
+                Some(latency),
+                "TLS handshake timed out".to_string(),
+                "The port may not serve TLS; check whether the base URL should use http://"
+                    .to_string(),
+            );
+            None
+        }
+    }
+}
+
+/// TLS handshake check; the handshake needs the platform TLS library
+#[cfg(not(feature = "native-tls"))]
+async fn check_tls(
+    checks: &mut Checks,
+    _stream: TcpStream,
+    _addrs: &[SocketAddr],
+    _host: &str,
+) -> Option<CertificateSummary> {
+    checks.skip(CheckKind::Tls, "requires the native-tls feature");
+    None
+}
+
+/// Run a TLS handshake over `stream` and summarize the server certificate
+#[cfg(feature = "native-tls")]
+async fn tls_handshake(
+    stream: TcpStream,
+    host: &str,
+    accept_invalid: bool,
+) -> std::result::Result<CertificateSummary, String> {
+    let connector = native_tls::TlsConnector::builder()
+        .danger_accept_invalid_certs(accept_invalid)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let tls = tokio_native_tls::TlsConnector::from(connector)
+        .connect(host, stream)
+        .await
+        .map_err(|e| e.to_string())?;
+    let der = tls
+        .get_ref()
+        .peer_certificate()
+        .ok()
+        .flatten()
+        .and_then(|certificate| certificate.to_der().ok());
+    let mut summary =
+        der.as_deref()
+            .and_then(summarize_certificate)
+            .unwrap_or(CertificateSummary {
+                subject: None,
+                issuer: None,
+                not_after: None,
+                trusted: false,
+            });
+    summary.trusted = !accept_invalid;
+    Ok(summary)
+}
+
+/// Split a DER element into its tag, contents and the bytes following it
+#[cfg(feature = "native-tls")]
+fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (length, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let length = rest[..count]
+            .iter()
+            .fold(0usize, |length, &byte| (length << 8) | byte as usize);
+        (length, &rest[count..])
+    };
+    (rest.len() >= length).then(|| (tag, &rest[..length], &rest[length..]))
+}
+
+/// Subject, issuer and expiry of an X.509 certificate
+#[cfg(feature = "native-tls")]
+fn summarize_certificate(der: &[u8]) -> Option<CertificateSummary> {
+    let (_, certificate, _) = der_element(der)?;
+    let (_, mut tbs, _) = der_element(certificate)?;
+    let mut next = || {
+        let (tag, contents, rest) = der_element(tbs)?;
+        tbs = rest;
+        Some((tag, contents))
+    };
+
+    let (mut tag, mut contents) = next()?;
+    if tag == 0xa0 {
+        // Explicit version
+        (tag, contents) = next()?;
+    }
+    let _serial = (tag, contents);
+    let _signature = next()?;
+    let (_, issuer) = next()?;
+    let (_, validity) = next()?;
+    let (_, subject) = next()?;
+
+    let not_after = der_element(validity)
+        .and_then(|(_, _, rest)| der_element(rest))
+        .and_then(|(tag, time, _)| parse_time(tag, time));
+
+    Some(CertificateSummary {
+        subject: name_summary(subject),
+        issuer: name_summary(issuer),
+        not_after,
+        trusted: false,
+    })
+}
+
+/// Common name of an X.509 name, falling back to the organization
+#[cfg(feature = "native-tls")]
+fn name_summary(mut name: &[u8]) -> Option<String> {
+    const COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+    const ORGANIZATION: &[u8] = &[0x55, 0x04, 0x0a];
+
+    let mut organization = None;
+    while let Some((_, set, rest)) = der_element(name) {
+        name = rest;
+        let Some((_, attribute, _)) = der_element(set) else {
+            continue;
+        };
+        let Some((0x06, oid, value)) = der_element(attribute) else {
+            continue;
+        };
+        let Some((_, value, _)) = der_element(value) else {
+            continue;
+        };
+        let value = String::from_utf8_lossy(value).into_owned();
+        match oid {
+            COMMON_NAME => return Some(value),
+            ORGANIZATION => organization = Some(value),
+            _ => {}
+        }
+    }
+    organization
+}
+
+/// Parse an ASN.1 UTCTime (tag 0x17) or GeneralizedTime (tag 0x18)
+#[cfg(feature = "native-tls")]
+fn parse_time(tag: u8, time: &[u8]) -> Option<DateTime<Utc>> {
+    let time = std::str::from_utf8(time).ok()?;
+    let full = match tag {
+        0x17 => {
+            let year: u32 = time.get(..2)?.parse().ok()?;
+            format!("{}{}", if year >= 50 { "19" } else { "20" }, time)
+        }
+        0x18 => time.to_string(),
+        _ => return None,
+    };
+    chrono::NaiveDateTime::parse_from_str(&full, "%Y%m%d%H%M%SZ")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
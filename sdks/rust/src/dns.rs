@@ -0,0 +1,137 @@
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Caching of DNS lookups made by the client
+///
+/// Successful lookups are reused for `positive_ttl` and failed lookups are
+/// failed again without querying DNS for `negative_ttl`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DnsCacheOptions {
+    pub positive_ttl: Duration,
+    pub negative_ttl: Duration,
+    /// Maximum number of cached host names
+    pub max_entries: usize,
+}
+
+impl Default for DnsCacheOptions {
+    fn default() -> Self {
+        Self {
+            positive_ttl: Duration::from_secs(60),
+            negative_ttl: Duration::from_secs(5),
+            max_entries: 256,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    /// Resolved addresses, or the error message of a failed lookup
+    result: std::result::Result<Vec<SocketAddr>, String>,
+    expires_at: Instant,
+}
+
+/// Resolver applying static overrides and an optional cache, shared by the
+/// HTTP client and the WebSocket connector
+#[derive(Debug)]
+pub(crate) struct Resolver {
+    overrides: HashMap<String, Vec<SocketAddr>>,
+    cache_options: Option<DnsCacheOptions>,
+    cache: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl Resolver {
+    pub fn new(
+        overrides: HashMap<String, Vec<SocketAddr>>,
+        cache_options: Option<DnsCacheOptions>,
+    ) -> Self {
+        Self {
+            overrides,
+            cache_options,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Resolve a host name; the port of the returned addresses is unspecified
+    pub async fn lookup(&self, host: &str) -> io::Result<Vec<SocketAddr>> {
+        if let Some(addrs) = self.overrides.get(host) {
+            debug!("Resolved {} from override", host);
+            return Ok(addrs.clone());
+        }
+
+        let Some(options) = self.cache_options else {
+            return lookup_host(host).await;
+        };
+
+        if let Some(entry) = self.cached(host) {
+            debug!("Resolved {} from cache", host);
+            return entry.map_err(|message| io::Error::new(io::ErrorKind::NotFound, message));
+        }
+
+        let result = lookup_host(host).await;
+        let (cached, ttl) = match &result {
+            Ok(addrs) => (Ok(addrs.clone()), options.positive_ttl),
+            Err(e) => (Err(e.to_string()), options.negative_ttl),
+        };
+
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        cache.retain(|_, entry| entry.expires_at > now);
+        if cache.len() >= options.max_entries {
+            let soonest = cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires_at)
+                .map(|(host, _)| host.clone());
+            if let Some(soonest) = soonest {
+                cache.remove(&soonest);
+            }
+        }
+        if options.max_entries > 0 {
+            cache.insert(
+                host.to_string(),
+                CacheEntry {
+                    result: cached,
+                    expires_at: now + ttl,
+                },
+            );
+        }
+        result
+    }
+
+    fn cached(&self, host: &str) -> Option<std::result::Result<Vec<SocketAddr>, String>> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache
+            .get(host)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.result.clone())
+    }
+}
+
+async fn lookup_host(host: &str) -> io::Result<Vec<SocketAddr>> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0)).await?.collect();
+    if addrs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no addresses found for {}", host),
+        ));
+    }
+    Ok(addrs)
+}
+
+/// Adapter exposing a shared [`Resolver`] to reqwest
+pub(crate) struct HttpResolver(pub Arc<Resolver>);
+
+impl Resolve for HttpResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = Arc::clone(&self.0);
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str()).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
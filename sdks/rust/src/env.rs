@@ -0,0 +1,77 @@
+use crate::client::{Client, ClientBuilder};
+use crate::{Error, Result};
+use std::env::{self, VarError};
+use std::time::Duration;
+
+/// Environment variable read by [`Client::from_env`] for the base URL
+pub const BASE_URL_ENV: &str = "REPORUNNER_BASE_URL";
+/// Environment variable read by [`Client::from_env`] for the API key
+pub const API_KEY_ENV: &str = "REPORUNNER_API_KEY";
+/// Environment variable read by [`Client::from_env`] for the request timeout, in seconds
+pub const TIMEOUT_SECS_ENV: &str = "REPORUNNER_TIMEOUT_SECS";
+/// Environment variable that makes [`Client::from_env`] accept invalid TLS certificates
+pub const INSECURE_SKIP_TLS_VERIFY_ENV: &str = "REPORUNNER_INSECURE_SKIP_TLS_VERIFY";
+
+impl Client {
+    /// Create a client configured from the environment
+    ///
+    /// Reads `REPORUNNER_BASE_URL`, falling back to
+    /// [`DEFAULT_BASE_URL`](crate::DEFAULT_BASE_URL), and the optional
+    /// `REPORUNNER_API_KEY` and `REPORUNNER_TIMEOUT_SECS`. An empty API key or
+    /// a timeout that is not a whole number of seconds is an [`Error::Config`].
+    ///
+    /// `REPORUNNER_INSECURE_SKIP_TLS_VERIFY=1` (or `true`) turns on
+    /// [`ClientBuilder::danger_accept_invalid_certs`], for lab environments.
+    ///
+    /// ```rust,no_run
+    /// let client = klikkflow_sdk::Client::from_env()?;
+    /// # Ok::<(), klikkflow_sdk::Error>(())
+    /// ```
+    pub fn from_env() -> Result<Client> {
+        let mut builder = ClientBuilder::new();
+        if let Some(base_url) = var(BASE_URL_ENV)? {
+            builder = builder.base_url(base_url);
+        }
+        if let Some(api_key) = var(API_KEY_ENV)? {
+            if api_key.trim().is_empty() {
+                return Err(Error::Config(format!(
+                    "{} is set but empty; unset it to connect without an API key",
+                    API_KEY_ENV
+                )));
+            }
+            builder = builder.api_key(api_key);
+        }
+        if let Some(secs) = var(TIMEOUT_SECS_ENV)? {
+            let secs: u64 = secs.trim().parse().map_err(|_| {
+                Error::Config(format!(
+                    "{} must be a whole number of seconds, got {:?}",
+                    TIMEOUT_SECS_ENV, secs
+                ))
+            })?;
+            builder = builder.timeout(Duration::from_secs(secs));
+        }
+        if let Some(skip) = var(INSECURE_SKIP_TLS_VERIFY_ENV)? {
+            let skip = match skip.trim().to_ascii_lowercase().as_str() {
+                "1" | "true" => true,
+                "" | "0" | "false" => false,
+                _ => {
+                    return Err(Error::Config(format!(
+                        "{} must be 1, true, 0 or false, got {:?}",
+                        INSECURE_SKIP_TLS_VERIFY_ENV, skip
+                    )))
+                }
+            };
+            builder = builder.danger_accept_invalid_certs(skip);
+        }
+        builder.build()
+    }
+}
+
+/// Value of the environment variable `name`, if it is set
+fn var(name: &str) -> Result<Option<String>> {
+    match env::var(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(VarError::NotUnicode(_)) => Err(Error::Config(format!("{} is not valid UTF-8", name))),
+    }
+}
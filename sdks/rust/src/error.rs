@@ -0,0 +1,595 @@
+use crate::limits::JsonLimit;
+use crate::models::{ExecutionStatus, NodeIssue};
+use crate::spec::SpecIssue;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Result type used throughout the SDK
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors returned by the KlikkFlow SDK
+///
+/// Error responses with a well-known status get their own variant, so
+/// callers need not match on status codes: `401`, `403`, `404`, `409`, `422`
+/// and `429`. Any other status is an [`Error::Api`]. New variants may be
+/// added, so match with a wildcard arm.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The HTTP request could not be sent or the response could not be read
+    #[error("HTTP error: {0}")]
+    Http(String),
+
+    /// The connection to the server failed before a response was received
+    ///
+    /// `kind` tells a server that cannot be reached (DNS, connect and TLS
+    /// failures, after which the request was never sent) from a connection
+    /// that broke while the request or response was under way.
+    #[error("Transport error ({}): {message}", kind.as_str())]
+    Transport {
+        kind: TransportErrorKind,
+        message: String,
+    },
+
+    /// The API responded with a non-success status code not covered by a dedicated variant
+    ///
+    /// `message` holds the response body; `problem` is set when the server
+    /// answered with an `application/problem+json` document. `request_id` is
+    /// the server's `X-Request-Id` response header, when present. The
+    /// variants for well-known statuses carry the same fields.
+    #[error("API error ({status}): {message}")]
+    Api {
+        status: u16,
+        message: String,
+        problem: Option<Box<ProblemDetails>>,
+        request_id: Option<String>,
+    },
+
+    /// The requested resource does not exist (`404 Not Found`)
+    ///
+    /// `resource` and `id` are derived from the request path: the last
+    /// resource the path names by ID, so a `404` for
+    /// `/api/workflows/wf-1/executions` reports workflow `wf-1`. When the
+    /// path names no resource by ID, e.g. for a missing endpoint such as
+    /// `/api/executions/statistics`, `resource` is the endpoint's path below
+    /// `/api` and `id` is `None`.
+    #[error("Not found: {resource}{}", id.as_ref().map(|id| format!(" {}", id)).unwrap_or_default())]
+    NotFound {
+        resource: String,
+        id: Option<String>,
+        message: String,
+        problem: Option<Box<ProblemDetails>>,
+        request_id: Option<String>,
+    },
+
+    /// The request was not authenticated (`401 Unauthorized`), e.g. the API key is missing or revoked
+    #[error("Unauthorized: {message}")]
+    Unauthorized {
+        message: String,
+        problem: Option<Box<ProblemDetails>>,
+        request_id: Option<String>,
+    },
+
+    /// The credentials lack permission for the request (`403 Forbidden`)
+    #[error("Forbidden: {message}")]
+    Forbidden {
+        message: String,
+        problem: Option<Box<ProblemDetails>>,
+        request_id: Option<String>,
+    },
+
+    /// The request conflicts with the current state of a resource (`409 Conflict`)
+    #[error("Conflict: {message}")]
+    Conflict {
+        message: String,
+        problem: Option<Box<ProblemDetails>>,
+        request_id: Option<String>,
+    },
+
+    /// The server rejected the request body (`422 Unprocessable Entity`)
+    ///
+    /// `field_errors` lists the rejected fields when the body has an
+    /// `errors` array of `{"field": ..., "message": ...}` objects, either as
+    /// plain JSON or as a member of a problem document; it is empty otherwise.
+    #[error("Validation failed: {}", if field_errors.is_empty() { message.clone() } else { field_errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ") })]
+    Validation {
+        field_errors: Vec<FieldError>,
+        message: String,
+        problem: Option<Box<ProblemDetails>>,
+        request_id: Option<String>,
+    },
+
+    /// The server is throttling requests and answered `429 Too Many Requests`
+    ///
+    /// `retry_after` is the wait the server asked for in its `Retry-After`
+    /// header, given either in seconds or as an HTTP date. `message` holds the
+    /// response body.
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        retry_after: Option<std::time::Duration>,
+        message: String,
+        request_id: Option<String>,
+    },
+
+    /// A response body exceeded the configured JSON limits and was not parsed
+    #[error("JSON {which} limit of {limit} exceeded")]
+    JsonLimitExceeded { which: JsonLimit, limit: usize },
+
+    /// A request or response body could not be (de)serialized
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    /// An operation did not complete in time
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
+    /// No connection to the server could be established in time; the request was not sent
+    #[error("Connect timeout: {0}")]
+    ConnectTimeout(String),
+
+    /// An execution still running made no progress for the period of its stall policy
+    ///
+    /// See [`WaitOptions::stall_detection`](crate::WaitOptions::stall_detection).
+    #[error("Execution {execution_id} made no progress since {last_progress_at}")]
+    ExecutionStalled {
+        execution_id: String,
+        last_progress_at: chrono::DateTime<chrono::Utc>,
+    },
+
+    /// An execution did not finish within the wait timeout; it may still be running
+    #[error("Execution {execution_id} did not finish within {}s", waited.as_secs_f64())]
+    WaitTimeout {
+        execution_id: String,
+        waited: std::time::Duration,
+    },
+
+    /// The deadline of the operation passed before it completed
+    ///
+    /// `execution_id` is set once the operation got as far as starting an
+    /// execution, which may still be running; `last_status` is the status it
+    /// was last seen in.
+    #[error(
+        "Deadline exceeded{}{}",
+        execution_id.as_ref().map(|id| format!(" waiting for execution {}", id)).unwrap_or_default(),
+        last_status.as_ref().map(|status| format!(" (last seen {})", status.as_str())).unwrap_or_default()
+    )]
+    DeadlineExceeded {
+        execution_id: Option<String>,
+        last_status: Option<ExecutionStatus>,
+    },
+
+    /// An unsupported HTTP method was requested
+    #[error("Invalid HTTP method: {0}")]
+    InvalidMethod(String),
+
+    /// The workflow could not be activated because some nodes have issues
+    #[error("Workflow activation failed: {} node issue(s)", issues.len())]
+    ActivationFailed { issues: Vec<NodeIssue> },
+
+    /// The workflow already has an active execution and the singleton policy rejected a new one
+    #[error("Workflow is already running as execution {execution_id}")]
+    AlreadyRunning { execution_id: String },
+
+    /// A workflow spec could not be parsed or compiled
+    #[error("Invalid workflow spec: {}", issues.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    InvalidSpec { issues: Vec<SpecIssue> },
+
+    /// Input was rejected by client-side validation before sending
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    /// A confirmation hook refused a mutating request
+    #[error("Request vetoed: {0}")]
+    Vetoed(String),
+
+    /// The operation is not supported by the configured transport
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+
+    /// The server is older than the version required by [`Client::assert_compatible`](crate::Client::assert_compatible)
+    #[error("Server version {server_version} is older than the required {min_version}")]
+    IncompatibleServer {
+        server_version: String,
+        min_version: String,
+    },
+
+    /// A client-side execution quota of the workflow is exhausted; nothing was sent
+    #[error("Execution quota of workflow {workflow_id} exceeded, resets in {}s", resets_in.as_secs())]
+    QuotaExceeded {
+        workflow_id: String,
+        resets_in: std::time::Duration,
+    },
+
+    /// An [`ExecutionTracker`](crate::ExecutionTracker) is draining and starts no new executions
+    #[error("Execution tracker is draining")]
+    Draining,
+
+    /// A workflow run with [`Client::run`](crate::Client::run) did not succeed
+    ///
+    /// `node` is the first node that failed, if any did.
+    #[error("Execution {execution_id} ended with status {}: {}", status.as_str(), error.as_deref().unwrap_or("no error reported"))]
+    ExecutionFailed {
+        execution_id: String,
+        status: ExecutionStatus,
+        error: Option<String>,
+        node: Option<String>,
+    },
+
+    /// The client was shut down with [`Client::shutdown`](crate::Client::shutdown)
+    #[error("Client is shut down")]
+    ClientClosed,
+
+    /// The call was cancelled through its cancellation token
+    ///
+    /// `execution_id` is the execution the call had started, if it got that
+    /// far; see [`ExecuteOptions::cancel_on_drop`](crate::ExecuteOptions::cancel_on_drop).
+    #[error("Cancelled{}", execution_id.as_ref().map(|id| format!(" while waiting for execution {}", id)).unwrap_or_default())]
+    Cancelled { execution_id: Option<String> },
+
+    /// The circuit breaker is open after repeated failures; nothing was sent
+    ///
+    /// `retry_in` is the rest of the cool-down, zero while a probe request is
+    /// in flight. See [`Client::with_circuit_breaker`](crate::Client::with_circuit_breaker).
+    #[error("Circuit breaker is open, retry in {}ms", retry_in.as_millis())]
+    CircuitOpen { retry_in: std::time::Duration },
+
+    /// The client configuration is invalid
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    /// A proxy URL is malformed or uses an unsupported scheme
+    #[error("Invalid proxy: {0}")]
+    InvalidProxy(String),
+
+    /// A stream message could not be decoded; `raw` holds the message as received
+    #[error("Failed to decode stream message: {source}")]
+    MessageDecode {
+        raw: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// The WebSocket connection failed
+    #[error("WebSocket error: {0}")]
+    WebSocket(String),
+
+    /// Writing downloaded content failed
+    #[error("I/O error: {0}")]
+    Io(String),
+
+    /// Downloaded content does not match the checksum sent by the server
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+}
+
+/// What failed in an [`Error::Transport`]
+///
+/// Timeouts are not transport errors: running out of the connect timeout
+/// fails with [`Error::ConnectTimeout`], and out of the request timeout with
+/// [`Error::Timeout`].
+///
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use klikkflow_sdk::{Client, Error, TransportErrorKind};
+///
+/// let client = Client::new("https://klikkflow.example.com");
+/// match client.health_check().await {
+///     Err(Error::Transport { kind: TransportErrorKind::Dns | TransportErrorKind::Connect, .. }) => {
+///         eprintln!("server unreachable");
+///     }
+///     Err(e) => eprintln!("health check failed: {}", e),
+///     Ok(_) => {}
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum TransportErrorKind {
+    /// The server's host name could not be resolved
+    Dns,
+    /// No connection to the server could be established, e.g. it was refused
+    Connect,
+    /// The TLS handshake with the server failed
+    Tls,
+    /// The connection broke while the request was sent or before the response arrived
+    Request,
+    /// The response body could not be read
+    Body,
+}
+
+impl TransportErrorKind {
+    /// The kind as used in error messages, e.g. `"dns"`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransportErrorKind::Dns => "dns",
+            TransportErrorKind::Connect => "connect",
+            TransportErrorKind::Tls => "tls",
+            TransportErrorKind::Request => "request",
+            TransportErrorKind::Body => "body",
+        }
+    }
+
+    /// Whether the request was never sent, so it can be retried whatever its method
+    pub fn is_before_send(&self) -> bool {
+        matches!(
+            self,
+            TransportErrorKind::Dns | TransportErrorKind::Connect | TransportErrorKind::Tls
+        )
+    }
+}
+
+impl std::fmt::Display for TransportErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A field the server rejected, from the body of an [`Error::Validation`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Path of the field in the request body, e.g. `nodes[0].type`
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// RFC 7807 problem details returned with `application/problem+json` errors
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    /// URI identifying the problem type
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub problem_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Problem-type specific members
+    #[serde(flatten)]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Stable, machine-readable classification of an [`Error`]
+///
+/// Unlike the `Display` output of [`Error`], codes and their string forms
+/// are a compatibility surface: a code is never renamed or removed, and an
+/// error keeps its code across releases. New codes may be added, so match
+/// with a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// The operation or the server timed out (client timeouts, `408`, `504`)
+    Timeout,
+    /// The server is throttling requests (`429`)
+    RateLimited,
+    /// The requested resource does not exist (`404`, `410`)
+    NotFound,
+    /// Authentication or authorization failed (`401`, `403`)
+    Auth,
+    /// The request was rejected as invalid, by the client or the server
+    Validation,
+    /// The request conflicts with the current state of a resource (`409`, `412`)
+    Conflict,
+    /// The connection failed or broke before a response was received
+    Transport,
+    /// A request or response body could not be encoded or decoded, or failed its checksum
+    Decode,
+    /// A response exceeded the configured size limits
+    LimitExceeded,
+    /// The server failed to handle the request (`5xx`)
+    Server,
+    /// A confirmation hook refused the request
+    Vetoed,
+    /// The operation is not supported by the configured transport
+    Unsupported,
+    /// The client configuration is invalid
+    Config,
+    /// A client or execution tracker is shutting down and starts no new work
+    Draining,
+    /// A workflow execution ended without succeeding
+    ExecutionFailed,
+    /// A workflow execution did not finish, or stopped progressing, while it was waited for
+    ExecutionTimeout,
+    /// The client's circuit breaker is open and failing requests fast
+    CircuitOpen,
+    /// The caller cancelled the operation
+    Cancelled,
+    /// Any other API error status
+    Other,
+}
+
+impl ErrorCode {
+    /// The code as used in serialized reports, e.g. `"rate_limited"`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Timeout => "timeout",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::Auth => "auth",
+            ErrorCode::Validation => "validation",
+            ErrorCode::Conflict => "conflict",
+            ErrorCode::Transport => "transport",
+            ErrorCode::Decode => "decode",
+            ErrorCode::LimitExceeded => "limit_exceeded",
+            ErrorCode::Server => "server",
+            ErrorCode::Vetoed => "vetoed",
+            ErrorCode::Unsupported => "unsupported",
+            ErrorCode::Config => "config",
+            ErrorCode::Draining => "draining",
+            ErrorCode::ExecutionFailed => "execution_failed",
+            ErrorCode::ExecutionTimeout => "execution_timeout",
+            ErrorCode::CircuitOpen => "circuit_open",
+            ErrorCode::Cancelled => "cancelled",
+            ErrorCode::Other => "other",
+        }
+    }
+
+    /// Whether an error with this code may succeed when retried unchanged
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ErrorCode::Timeout | ErrorCode::RateLimited | ErrorCode::Transport | ErrorCode::Server
+        )
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Error {
+    /// Stable code classifying this error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::Http(_) | Error::Transport { .. } | Error::WebSocket(_) => ErrorCode::Transport,
+            Error::Api { status, .. } => match status {
+                401 | 403 => ErrorCode::Auth,
+                404 | 410 => ErrorCode::NotFound,
+                408 | 504 => ErrorCode::Timeout,
+                409 | 412 => ErrorCode::Conflict,
+                400 | 422 => ErrorCode::Validation,
+                429 => ErrorCode::RateLimited,
+                500..=599 => ErrorCode::Server,
+                _ => ErrorCode::Other,
+            },
+            Error::NotFound { .. } => ErrorCode::NotFound,
+            Error::Unauthorized { .. } | Error::Forbidden { .. } => ErrorCode::Auth,
+            Error::Conflict { .. } => ErrorCode::Conflict,
+            Error::Validation { .. } => ErrorCode::Validation,
+            Error::JsonLimitExceeded { .. } => ErrorCode::LimitExceeded,
+            Error::Serialization(_)
+            | Error::MessageDecode { .. }
+            | Error::ChecksumMismatch { .. } => ErrorCode::Decode,
+            Error::Timeout(_) | Error::ConnectTimeout(_) | Error::DeadlineExceeded { .. } => {
+                ErrorCode::Timeout
+            }
+            Error::WaitTimeout { .. } | Error::ExecutionStalled { .. } => {
+                ErrorCode::ExecutionTimeout
+            }
+            Error::InvalidMethod(_)
+            | Error::InvalidInput(_)
+            | Error::InvalidSpec { .. }
+            | Error::ActivationFailed { .. } => ErrorCode::Validation,
+            Error::AlreadyRunning { .. } => ErrorCode::Conflict,
+            Error::RateLimited { .. } | Error::QuotaExceeded { .. } => ErrorCode::RateLimited,
+            Error::Vetoed(_) => ErrorCode::Vetoed,
+            Error::Unsupported(_) | Error::IncompatibleServer { .. } => ErrorCode::Unsupported,
+            Error::Config(_) | Error::InvalidProxy(_) => ErrorCode::Config,
+            Error::Draining | Error::ClientClosed => ErrorCode::Draining,
+            Error::ExecutionFailed { .. } => ErrorCode::ExecutionFailed,
+            Error::CircuitOpen { .. } => ErrorCode::CircuitOpen,
+            Error::Cancelled { .. } => ErrorCode::Cancelled,
+            Error::Io(_) => ErrorCode::Other,
+        }
+    }
+
+    /// Whether the failed operation may succeed when retried unchanged
+    pub fn is_retryable(&self) -> bool {
+        self.code().is_retryable()
+    }
+
+    /// HTTP status of the error response, for errors the server answered with
+    ///
+    /// ```rust
+    /// use klikkflow_sdk::Error;
+    ///
+    /// let forbidden = Error::Forbidden { message: String::new(), problem: None, request_id: None };
+    /// assert_eq!(forbidden.status(), Some(403));
+    /// assert_eq!(Error::Timeout("GET /health".to_string()).status(), None);
+    /// ```
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Api { status, .. } => Some(*status),
+            Error::NotFound { .. } => Some(404),
+            Error::Unauthorized { .. } => Some(401),
+            Error::Forbidden { .. } => Some(403),
+            Error::Conflict { .. } => Some(409),
+            Error::Validation { .. } => Some(422),
+            Error::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+
+    /// Problem document of the error response, if the server sent one
+    pub fn problem(&self) -> Option<&ProblemDetails> {
+        match self {
+            Error::Api { problem, .. }
+            | Error::NotFound { problem, .. }
+            | Error::Unauthorized { problem, .. }
+            | Error::Forbidden { problem, .. }
+            | Error::Conflict { problem, .. }
+            | Error::Validation { problem, .. } => problem.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Server-assigned id of the failed request, for API errors that carry one
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Error::Api { request_id, .. }
+            | Error::NotFound { request_id, .. }
+            | Error::Unauthorized { request_id, .. }
+            | Error::Forbidden { request_id, .. }
+            | Error::Conflict { request_id, .. }
+            | Error::Validation { request_id, .. }
+            | Error::RateLimited { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Diagnostic summary of this error for structured logs
+    pub fn report(&self) -> ErrorReport {
+        ErrorReport::from(self)
+    }
+}
+
+/// Serializable summary of an [`Error`] for structured logs
+///
+/// ```rust
+/// use klikkflow_sdk::Error;
+///
+/// let error = Error::Timeout("GET /api/workflows timed out".to_string());
+/// assert_eq!(
+///     serde_json::to_value(error.report()).unwrap(),
+///     serde_json::json!({
+///         "code": "timeout",
+///         "message": "Timeout: GET /api/workflows timed out",
+///         "requestId": null,
+///         "retryable": true
+///     })
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorReport {
+    pub code: ErrorCode,
+    /// The error's `Display` output; informative only, not stable
+    pub message: String,
+    #[serde(rename = "requestId")]
+    pub request_id: Option<String>,
+    pub retryable: bool,
+}
+
+impl From<&Error> for ErrorReport {
+    fn from(error: &Error) -> Self {
+        let code = error.code();
+        Self {
+            code,
+            message: error.to_string(),
+            request_id: error.request_id().map(str::to_string),
+            retryable: code.is_retryable(),
+        }
+    }
+}
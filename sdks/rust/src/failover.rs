@@ -0,0 +1,111 @@
+use crate::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Default interval between probes of the primary base URL while failed over
+pub const DEFAULT_FAILBACK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Base URLs of a client in order of preference, shared by all its clones
+#[derive(Debug)]
+pub(crate) struct Failover {
+    /// The primary base URL first, then the fallbacks
+    urls: Vec<String>,
+    active: AtomicUsize,
+    /// When the primary was last probed, or failed
+    last_probe: Mutex<Option<Instant>>,
+}
+
+impl Failover {
+    pub fn new(urls: Vec<String>) -> Self {
+        Self {
+            urls,
+            active: AtomicUsize::new(0),
+            last_probe: Mutex::new(None),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    pub fn primary(&self) -> &str {
+        &self.urls[0]
+    }
+
+    /// Index and base URL requests are currently sent to
+    pub fn active(&self) -> (usize, &str) {
+        let index = self.active.load(Ordering::Relaxed);
+        (index, &self.urls[index])
+    }
+
+    /// Move on from the base URL at `failed` to the next one, unless another request already did
+    pub fn fail(&self, failed: usize, error: &Error) {
+        let next = (failed + 1) % self.urls.len();
+        if self
+            .active
+            .compare_exchange(failed, next, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            warn!(
+                endpoint = %self.urls[next],
+                failed_endpoint = %self.urls[failed],
+                "Failing over from {} to {}: {}",
+                self.urls[failed],
+                self.urls[next],
+                error
+            );
+            if failed == 0 {
+                *self.lock() = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Whether the primary is due a probe while failed over, recording the probe if so
+    pub fn probe_due(&self, interval: Duration) -> bool {
+        if self.active.load(Ordering::Relaxed) == 0 {
+            return false;
+        }
+        let mut last_probe = self.lock();
+        let now = Instant::now();
+        if last_probe.is_some_and(|last| now < last + interval) {
+            return false;
+        }
+        *last_probe = Some(now);
+        true
+    }
+
+    /// The primary answered a probe, send requests to it again
+    pub fn restore(&self) {
+        if self.active.swap(0, Ordering::Relaxed) != 0 {
+            info!(endpoint = %self.urls[0], "Primary {} is healthy again, failing back", self.urls[0]);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Instant>> {
+        self.last_probe.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Whether `error` means the endpoint is unhealthy and the next one should take over
+pub(crate) fn fails_over(error: &Error) -> bool {
+    match error {
+        Error::ConnectTimeout(_) => true,
+        Error::Transport { kind, .. } => kind.is_before_send(),
+        Error::Api { status, .. } => (500..=599).contains(status),
+        _ => false,
+    }
+}
+
+/// Whether a request that failed with `error` can be sent again to the next endpoint
+///
+/// Requests the server never received always can; others only when they
+/// are safe to repeat.
+pub(crate) fn resend_safe(method: &str, idempotent: bool, error: &Error) -> bool {
+    match error {
+        Error::ConnectTimeout(_) => true,
+        Error::Transport { kind, .. } if kind.is_before_send() => true,
+        _ => idempotent || matches!(method, "GET" | "DELETE"),
+    }
+}
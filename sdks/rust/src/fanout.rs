@@ -0,0 +1,155 @@
+use crate::models::ExecutionUpdate;
+use crate::websocket::WebSocketStream;
+use crate::Error;
+use futures_util::stream::{self, BoxStream};
+use futures_util::{Stream, StreamExt};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::{broadcast, oneshot};
+use tracing::{debug, warn};
+
+/// Default number of updates buffered for each receiver of a shared stream
+pub const DEFAULT_FAN_OUT_CAPACITY: usize = 256;
+
+/// Item yielded by a [`SharedExecutionStream`]
+#[derive(Debug, Clone)]
+pub enum SharedUpdate {
+    /// An update received from the execution stream
+    Update(ExecutionUpdate),
+    /// An error yielded by the execution stream, shared by all receivers
+    Error(Arc<Error>),
+    /// This receiver fell behind and the given number of updates were skipped
+    Lagged(u64),
+}
+
+/// Keeps the connection open while any receiver is alive
+struct Shared {
+    sender: broadcast::Sender<Option<SharedUpdate>>,
+    ended: Arc<AtomicBool>,
+    _close: oneshot::Sender<()>,
+}
+
+/// Cloneable receiver of a single execution stream shared by several consumers
+///
+/// Updates are read from the WebSocket by a background task and broadcast to
+/// every receiver. Each receiver buffers up to the configured capacity; a
+/// receiver that falls further behind never blocks the socket or the other
+/// receivers, it instead yields [`SharedUpdate::Lagged`] with the number of
+/// skipped updates and continues with the oldest update still buffered.
+///
+/// A clone only receives updates sent after it was created. The connection is
+/// closed once every receiver has been dropped.
+pub struct SharedExecutionStream {
+    inner: BoxStream<'static, SharedUpdate>,
+    shared: Arc<Shared>,
+}
+
+impl SharedExecutionStream {
+    /// Share `source` between `receivers` receivers, all subscribed before the first update is read
+    fn spawn(mut source: WebSocketStream, capacity: usize, receivers: usize) -> Vec<Self> {
+        let (sender, first) = broadcast::channel(capacity.max(1));
+        let mut subscribed = vec![first];
+        subscribed.extend((1..receivers).map(|_| sender.subscribe()));
+        subscribed.truncate(receivers);
+        let (close, mut closed) = oneshot::channel::<()>();
+        let ended = Arc::new(AtomicBool::new(false));
+
+        let pump_sender = sender.clone();
+        let pump_ended = Arc::clone(&ended);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = &mut closed => {
+                        debug!("All shared stream receivers dropped, closing connection");
+                        if let Err(e) = source.close().await {
+                            warn!("Failed to close shared execution stream: {}", e);
+                        }
+                        break;
+                    }
+                    item = source.next() => {
+                        let update = match item {
+                            Some(Ok(update)) => SharedUpdate::Update(update),
+                            Some(Err(e)) => SharedUpdate::Error(Arc::new(e)),
+                            None => {
+                                pump_ended.store(true, Ordering::Release);
+                                let _ = pump_sender.send(None);
+                                break;
+                            }
+                        };
+                        // No receiver currently subscribed is not an error
+                        let _ = pump_sender.send(Some(update));
+                    }
+                }
+            }
+        });
+
+        let shared = Arc::new(Shared {
+            sender,
+            ended,
+            _close: close,
+        });
+        subscribed
+            .into_iter()
+            .map(|receiver| Self::with_receiver(Arc::clone(&shared), receiver))
+            .collect()
+    }
+
+    fn with_receiver(
+        shared: Arc<Shared>,
+        receiver: broadcast::Receiver<Option<SharedUpdate>>,
+    ) -> Self {
+        let inner = if shared.ended.load(Ordering::Acquire) {
+            stream::empty().boxed()
+        } else {
+            stream::unfold(Some(receiver), |receiver| async move {
+                let mut receiver = receiver?;
+                match receiver.recv().await {
+                    Ok(Some(update)) => Some((update, Some(receiver))),
+                    Ok(None) | Err(broadcast::error::RecvError::Closed) => None,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        Some((SharedUpdate::Lagged(skipped), Some(receiver)))
+                    }
+                }
+            })
+            .boxed()
+        };
+        Self { inner, shared }
+    }
+}
+
+impl Clone for SharedExecutionStream {
+    fn clone(&self) -> Self {
+        let receiver = self.shared.sender.subscribe();
+        Self::with_receiver(Arc::clone(&self.shared), receiver)
+    }
+}
+
+impl Stream for SharedExecutionStream {
+    type Item = SharedUpdate;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl WebSocketStream {
+    /// Share this stream between several consumers
+    ///
+    /// `capacity` is the number of updates each receiver may fall behind before
+    /// it starts skipping; see [`SharedExecutionStream`]. Must be called from
+    /// within a Tokio runtime.
+    pub fn shareable(self, capacity: usize) -> SharedExecutionStream {
+        SharedExecutionStream::spawn(self, capacity, 1)
+            .pop()
+            .expect("one receiver was subscribed")
+    }
+
+    /// Split this stream into `n` receivers with the default capacity
+    ///
+    /// Every receiver gets all updates, from the first one on.
+    pub fn fan_out(self, n: usize) -> Vec<SharedExecutionStream> {
+        SharedExecutionStream::spawn(self, DEFAULT_FAN_OUT_CAPACITY, n)
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{Error, Result};
+use futures_util::future::{self, BoxFuture, FutureExt};
+use std::future::Future;
+use std::sync::Arc;
+
+/// Environment variable that allows mutations under [`ConfirmationHook::allow_env`]
+pub const ALLOW_PROD_ENV: &str = "REPORUNNER_ALLOW_PROD";
+
+/// A mutating request about to be sent to a guarded environment
+#[derive(Debug, Clone)]
+pub struct Mutation {
+    /// Environment label of the client
+    pub environment: String,
+    pub method: String,
+    pub path: String,
+}
+
+type HookFn = dyn Fn(Mutation) -> BoxFuture<'static, Result<()>> + Send + Sync;
+
+/// Check run before every mutating request to a guarded environment
+///
+/// Returning an error vetoes the request; the error is returned to the caller
+/// and nothing is sent.
+#[derive(Clone)]
+pub struct ConfirmationHook(Arc<HookFn>);
+
+impl ConfirmationHook {
+    /// Create a hook from a synchronous check
+    pub fn new(hook: impl Fn(&Mutation) -> Result<()> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(move |mutation| {
+            future::ready(hook(&mutation)).boxed()
+        }))
+    }
+
+    /// Create a hook from an asynchronous check, e.g. an interactive prompt
+    pub fn new_async<F, Fut>(hook: F) -> Self
+    where
+        F: Fn(Mutation) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self(Arc::new(move |mutation| hook(mutation).boxed()))
+    }
+
+    /// Allow mutations only when `REPORUNNER_ALLOW_PROD=1` is set
+    pub fn allow_env() -> Self {
+        Self::new(|mutation| match std::env::var(ALLOW_PROD_ENV).as_deref() {
+            Ok("1") => Ok(()),
+            _ => Err(Error::Vetoed(format!(
+                "{} {} against {} requires {}=1",
+                mutation.method, mutation.path, mutation.environment, ALLOW_PROD_ENV
+            ))),
+        })
+    }
+
+    pub(crate) async fn check(&self, mutation: Mutation) -> Result<()> {
+        (self.0)(mutation).await
+    }
+}
+
+/// Environment labels whose mutations must pass a confirmation hook
+#[derive(Clone)]
+pub(crate) struct Guardrail {
+    pub labels: Vec<String>,
+    pub hook: ConfirmationHook,
+}
+
+impl Guardrail {
+    pub fn covers(&self, environment: &str) -> bool {
+        self.labels
+            .iter()
+            .any(|label| label.eq_ignore_ascii_case(environment))
+    }
+}
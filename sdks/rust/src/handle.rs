@@ -0,0 +1,170 @@
+use crate::client::Client;
+use crate::models::*;
+use crate::page::Page;
+use crate::wait::WaitOptions;
+use crate::watch::WatchOptions;
+use crate::websocket::WebSocketStream;
+use crate::Result;
+use futures_util::Stream;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Client scoped to a single workflow, created with [`Client::workflow`]
+///
+/// The fetched definition is memoized: repeated [`get`](Self::get) calls reuse it
+/// until [`refresh`](Self::refresh) is called or the handle itself changes the workflow.
+/// Clones of a handle share the memoized definition.
+#[derive(Clone)]
+pub struct WorkflowHandle {
+    client: Client,
+    workflow_id: String,
+    cached: Arc<Mutex<Option<WorkflowDefinition>>>,
+}
+
+/// Client scoped to a single execution, created with [`Client::execution`]
+#[derive(Clone)]
+pub struct ExecutionHandle {
+    client: Client,
+    execution_id: String,
+}
+
+impl Client {
+    /// Get a handle for calls against a single execution
+    pub fn execution(&self, execution_id: impl Into<String>) -> ExecutionHandle {
+        ExecutionHandle {
+            client: self.clone(),
+            execution_id: execution_id.into(),
+        }
+    }
+
+    /// Get a handle for calls against a single workflow
+    pub fn workflow(&self, workflow_id: impl Into<String>) -> WorkflowHandle {
+        WorkflowHandle {
+            client: self.clone(),
+            workflow_id: workflow_id.into(),
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl WorkflowHandle {
+    /// ID of the workflow this handle is scoped to
+    pub fn id(&self) -> &str {
+        &self.workflow_id
+    }
+
+    /// Get the workflow definition, fetching it only if not already memoized
+    pub async fn get(&self) -> Result<WorkflowDefinition> {
+        let mut cached = self.cached.lock().await;
+        if let Some(workflow) = cached.as_ref() {
+            return Ok(workflow.clone());
+        }
+        let workflow = self.client.get_workflow(&self.workflow_id).await?;
+        *cached = Some(workflow.clone());
+        Ok(workflow)
+    }
+
+    /// Refetch the workflow definition, replacing the memoized one
+    pub async fn refresh(&self) -> Result<WorkflowDefinition> {
+        let workflow = self.client.get_workflow(&self.workflow_id).await?;
+        *self.cached.lock().await = Some(workflow.clone());
+        Ok(workflow)
+    }
+
+    /// Update the workflow
+    pub async fn update(&self, request: UpdateWorkflowRequest) -> Result<WorkflowDefinition> {
+        let workflow = self
+            .client
+            .update_workflow(&self.workflow_id, request)
+            .await?;
+        *self.cached.lock().await = Some(workflow.clone());
+        Ok(workflow)
+    }
+
+    /// Delete the workflow
+    pub async fn delete(&self) -> Result<()> {
+        self.client.delete_workflow(&self.workflow_id).await?;
+        *self.cached.lock().await = None;
+        Ok(())
+    }
+
+    /// Activate the workflow
+    pub async fn activate(&self) -> Result<WorkflowDefinition> {
+        let workflow = self.client.activate_workflow(&self.workflow_id).await?;
+        *self.cached.lock().await = Some(workflow.clone());
+        Ok(workflow)
+    }
+
+    /// Execute the workflow
+    pub async fn execute(
+        &self,
+        input_data: FieldMap,
+        wait_for_completion: bool,
+    ) -> Result<ExecutionResult> {
+        self.client
+            .execute_workflow(&self.workflow_id, input_data, wait_for_completion)
+            .await
+    }
+
+    /// Get one page of the workflow's execution history
+    pub async fn history(
+        &self,
+        options: Option<ExecutionHistoryOptions>,
+    ) -> Result<Page<ExecutionResult>> {
+        self.client
+            .get_execution_history(&self.workflow_id, options)
+            .await
+    }
+
+    /// Get execution statistics for the workflow
+    pub async fn stats(&self) -> Result<ExecutionStatistics> {
+        self.client
+            .get_execution_statistics(Some(&self.workflow_id))
+            .await
+    }
+
+    /// Stream newly finished executions of the workflow
+    pub fn stream_executions(&self) -> impl Stream<Item = Result<ExecutionResult>> {
+        self.client
+            .watch_executions(&self.workflow_id, WatchOptions::default())
+    }
+}
+
+impl ExecutionHandle {
+    /// ID of the execution this handle is scoped to
+    pub fn id(&self) -> &str {
+        &self.execution_id
+    }
+
+    /// Get the current state of the execution
+    pub async fn get(&self) -> Result<ExecutionResult> {
+        self.client.get_execution(&self.execution_id).await
+    }
+
+    /// Wait for the execution to finish
+    ///
+    /// Concurrent waits on the same execution share one poller, see
+    /// [`Client::wait_stats`].
+    pub async fn wait(&self) -> Result<ExecutionResult> {
+        self.client.wait_for_execution(&self.execution_id).await
+    }
+
+    /// Wait for the execution to finish with its own poll interval, timeout and stall detection
+    ///
+    /// Polls by itself instead of joining other waits, see [`Client::wait_future`].
+    pub async fn wait_with(&self, options: WaitOptions) -> Result<ExecutionResult> {
+        self.client
+            .in_flight(self.client.wait_future(&self.execution_id, options))
+            .await
+    }
+
+    /// Cancel the execution
+    pub async fn cancel(&self) -> Result<()> {
+        self.client.cancel_execution(&self.execution_id).await
+    }
+
+    /// Stream real-time updates of the execution
+    pub async fn stream(&self) -> Result<WebSocketStream> {
+        self.client.stream_execution(&self.execution_id).await
+    }
+}
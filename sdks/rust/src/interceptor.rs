@@ -0,0 +1,124 @@
+use bytes::Bytes;
+use futures_util::future::{BoxFuture, FutureExt};
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::error;
+
+/// An API request about to be sent, as seen by a [`RequestInterceptor`]
+#[derive(Debug, Clone)]
+pub struct RequestParts {
+    method: Method,
+    path: String,
+    /// Headers to send; interceptors may add, replace or remove them
+    pub headers: HeaderMap,
+    body: Option<Bytes>,
+}
+
+impl RequestParts {
+    pub(crate) fn new(
+        method: Method,
+        path: String,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Self {
+        Self {
+            method,
+            path,
+            headers,
+            body,
+        }
+    }
+
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Path and query as sent, including the API version prefix
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The serialized JSON body, exactly as it will be sent
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+
+    pub(crate) fn into_headers(self) -> HeaderMap {
+        self.headers
+    }
+}
+
+/// Outcome of an API request, as seen by a [`RequestInterceptor`]
+#[derive(Debug, Clone)]
+pub struct ResponseMeta {
+    pub method: Method,
+    pub path: String,
+    /// `None` when no response was received
+    pub status: Option<StatusCode>,
+    pub headers: HeaderMap,
+    /// Time from sending the request to receiving the whole response or failing
+    pub elapsed: Duration,
+}
+
+/// Hook run around every API request, registered with [`Client::with_interceptor`](crate::Client::with_interceptor)
+///
+/// Interceptors run in registration order, each seeing the headers left by
+/// the ones before it. They run once per request sent, so a request retried
+/// by the client is intercepted again. An interceptor that panics is logged
+/// and skipped; the request and the client are unaffected.
+pub trait RequestInterceptor: Send + Sync {
+    /// Inspect the request and adjust its headers before it is sent
+    fn before<'a>(&'a self, request: &'a mut RequestParts) -> BoxFuture<'a, ()> {
+        let _ = request;
+        futures_util::future::ready(()).boxed()
+    }
+
+    /// Observe the outcome of the request
+    fn after<'a>(&'a self, response: &'a ResponseMeta) -> BoxFuture<'a, ()> {
+        let _ = response;
+        futures_util::future::ready(()).boxed()
+    }
+}
+
+/// Run the `before` hooks in order, skipping any that panic
+pub(crate) async fn run_before(
+    interceptors: &[Arc<dyn RequestInterceptor>],
+    request: &mut RequestParts,
+) {
+    for interceptor in interceptors {
+        let parts = &mut *request;
+        if guarded(move || interceptor.before(parts)).await.is_err() {
+            error!(
+                "Request interceptor panicked before {} {}",
+                request.method, request.path
+            );
+        }
+    }
+}
+
+/// Run the `after` hooks in order, skipping any that panic
+pub(crate) async fn run_after(
+    interceptors: &[Arc<dyn RequestInterceptor>],
+    response: &ResponseMeta,
+) {
+    for interceptor in interceptors {
+        if guarded(|| interceptor.after(response)).await.is_err() {
+            error!(
+                "Request interceptor panicked after {} {}",
+                response.method, response.path
+            );
+        }
+    }
+}
+
+/// Run a hook, catching panics both while creating its future and while polling it
+async fn guarded<'a>(hook: impl FnOnce() -> BoxFuture<'a, ()>) -> Result<(), ()> {
+    let future = std::panic::catch_unwind(AssertUnwindSafe(hook)).map_err(|_| ())?;
+    AssertUnwindSafe(future)
+        .catch_unwind()
+        .await
+        .map_err(|_| ())
+}
@@ -12,14 +12,15 @@
 //!
 //! ## Quick Start
 //!
-//! ```rust
-//! use klikkflow_sdk::{Client, CreateWorkflowRequest, NodeDefinition, Position};
-//! use std::collections::HashMap;
+//! ```rust,no_run
+//! use klikkflow_sdk::{Client, CreateWorkflowRequest, FieldMap, NodeDefinition, Position};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let client = Client::new("http://localhost:3001")
-//!         .with_api_key("your-api-key");
+//!     let client = Client::builder()
+//!         .base_url("http://localhost:3001")
+//!         .api_key("your-api-key")
+//!         .build()?;
 //!
 //!     let workflow = client.create_workflow(CreateWorkflowRequest {
 //!         name: "Rust Workflow".to_string(),
@@ -29,7 +30,7 @@
 //!             name: "Start".to_string(),
 //!             node_type: "trigger".to_string(),
 //!             position: Position { x: 100.0, y: 100.0 },
-//!             parameters: HashMap::new(),
+//!             parameters: FieldMap::new(),
 //!         }],
 //!         connections: vec![],
 //!         settings: None,
@@ -40,20 +41,172 @@
 //! }
 //! ```
 
-use std::collections::HashMap;
 use std::time::Duration;
 
+mod artifacts;
+mod builder;
+mod cancel;
+mod capabilities;
+mod circuit;
 mod client;
+mod clock;
+mod compare;
+mod compat;
+mod complexity;
+mod consistency;
+mod convert;
+mod credentials;
+mod dependencies;
+mod deploy;
+mod deprecation;
+mod diagnose;
+mod dns;
+mod env;
 mod error;
+mod failover;
+mod fanout;
+mod guard;
+mod handle;
+mod interceptor;
+mod limits;
 mod models;
+mod node_params;
+mod options;
+mod page;
+mod progress;
+mod proxy;
+mod quota;
+mod redact;
+mod registry;
+mod rerun;
+mod response_cache;
+mod retention;
+mod retry;
+mod run;
+mod scan;
+mod scheduler;
+mod schema_cache;
+mod search;
+mod settings;
+mod shutdown;
+mod snapshot;
+mod subscriptions;
+mod sync;
+mod tail;
+mod timeouts;
+mod timeseries;
+mod tls;
+mod traced;
+mod tracker;
+mod transport;
+mod unix;
+mod usage;
+mod wait;
+mod watch;
 mod websocket;
 
-pub use client::Client;
-pub use error::{Error, Result};
+pub mod lint;
+#[cfg(feature = "loadtest")]
+pub mod loadtest;
+pub mod ndjson;
+pub mod nodes;
+pub mod spec;
+
+pub use artifacts::ArtifactInfo;
+pub use builder::{IdStrategy, WorkflowBuilder};
+pub use capabilities::{Capabilities, Capability, CapabilitySource};
+pub use circuit::{CircuitBreakerConfig, CircuitState};
+pub use client::{ApiVersion, Client, ClientBuilder};
+pub use clock::DEFAULT_CLOCK_SKEW_WARNING;
+pub use compare::{CompareOptions, OutputDiff, ValueChange};
+pub use compat::{ResponseAdapter, ResponseAdapters, SERVER_VERSION_HEADER};
+pub use complexity::{ComplexityReport, ComplexityThresholds};
+pub use consistency::{ConsistencyOptions, CONSISTENCY_TOKEN_HEADER};
+pub use convert::CreateRequestOptions;
+pub use credentials::{
+    CreateCredentialRequest, Credential, CredentialRotation, CredentialTest, CredentialUsage,
+    RotationFailure, RotationOptions,
+};
+pub use dependencies::{Dependency, DependencyGraph, Resource};
+pub use deploy::{
+    AppliedStep, DeploymentPlan, DeploymentReport, DeploymentStep, RollbackFailure, StepFailure,
+};
+pub use deprecation::{Deprecation, API_VERSION_HEADER};
+pub use diagnose::{
+    CertificateSummary, CheckKind, CheckStatus, DiagnosticCheck, DiagnosticsReport,
+};
+pub use dns::DnsCacheOptions;
+pub use env::{API_KEY_ENV, BASE_URL_ENV, INSECURE_SKIP_TLS_VERIFY_ENV, TIMEOUT_SECS_ENV};
+pub use error::{
+    Error, ErrorCode, ErrorReport, FieldError, ProblemDetails, Result, TransportErrorKind,
+};
+pub use failover::DEFAULT_FAILBACK_INTERVAL;
+pub use fanout::{SharedExecutionStream, SharedUpdate, DEFAULT_FAN_OUT_CAPACITY};
+pub use guard::{ConfirmationHook, Mutation, ALLOW_PROD_ENV};
+pub use handle::{ExecutionHandle, WorkflowHandle};
+pub use interceptor::{RequestInterceptor, RequestParts, ResponseMeta};
+pub use limits::{JsonLimit, JsonLimits, MAX_JSON_DEPTH};
 pub use models::*;
+pub use node_params::merge_node_parameters;
+pub use options::{
+    RequestOptions, MAX_TAG_KEY_LEN, MAX_TAG_VALUE_LEN, PRIORITY_HEADER, REQUEST_TAG_HEADER_PREFIX,
+    SDK_INTERNAL_TAG, WORKSPACE_HEADER,
+};
+pub use page::Page;
+pub use progress::ExecutionSnapshot;
+pub use quota::QuotaStatus;
+pub use redact::{Redaction, RedactionAction, RedactionAudit, RedactionPolicy, DEFAULT_MASK};
+pub use registry::{ClientRegistry, InstanceHealth};
+pub use rerun::{is_transient_failure, ExecutionAttempts, ExecutionRetryPolicy};
+pub use response_cache::CachePolicy;
+pub use retention::{RetentionPolicy, RetentionReport, RetentionViolation};
+pub use retry::RetryPolicy;
+pub use run::RunOptions;
+pub use scan::{ScanCheckpoint, ScanOptions, Scanned};
+pub use scheduler::{ClassConfig, ClassStats, Priority, SchedulerConfig, SchedulerStats};
+pub use schema_cache::SchemaCacheOptions;
+pub use search::{ExecutionMatch, SearchOptions, SearchProgress};
+pub use settings::{
+    merge_settings, DataSavingChange, DataSavingPolicy, DefaultsChange, SettingChange,
+    WorkflowSettings,
+};
+pub use snapshot::SnapshotOptions;
+pub use subscriptions::{
+    verify_event_signature, DeliveredEvent, EventSubscription, EventType, ExecutionEventData,
+    SubscriptionPing, SubscriptionRequest, EVENT_SIGNATURE_HEADER, EVENT_TIMESTAMP_HEADER,
+};
+pub use sync::{SyncAction, SyncItem, SyncMatch, SyncOptions, SyncOutcome, SyncPlan, SyncReport};
+pub use tail::TailEvent;
+pub use timeouts::{OperationClass, TimeoutProfile};
+pub use timeseries::{BucketSize, TimeBucket};
+pub use tls::ClientIdentity;
+pub use traced::{TracedExecutionStream, DEFAULT_NODE_SPAN_TIMEOUT};
+pub use tracker::ExecutionTracker;
+#[cfg(feature = "test-util")]
+pub use transport::MemoryTransport;
+pub use transport::{ReqwestTransport, Transport, TransportRequest, TransportResponse};
+pub use usage::{UsageGroup, UsageGroupBy, UsageReport};
+pub use wait::{
+    StallAction, StallDecision, StallHook, StallPolicy, WaitFuture, WaitOptions, WaitStats,
+};
+pub use watch::{FailureWebhook, WatchOptions, DEFAULT_WATCH_INTERVAL};
+pub use websocket::{
+    ConnectionEvent, ConnectionEventStream, StreamEvent, WebSocketStream, DEFAULT_DEGRADED_RTT,
+};
+
+pub use tokio_util::sync::CancellationToken;
 
 /// Default timeout for HTTP requests
 pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Default timeout for establishing HTTP connections
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum serialized size of a workflow's static data accepted by the server
+pub const MAX_STATIC_DATA_BYTES: usize = 1024 * 1024;
+
 /// Default base URL for the KlikkFlow API
-pub const DEFAULT_BASE_URL: &str = "http://localhost:3001";
\ No newline at end of file
+pub const DEFAULT_BASE_URL: &str = "http://localhost:3001";
+
+/// `User-Agent` identifying this SDK, sent unless overridden
+pub const DEFAULT_USER_AGENT: &str = concat!("reporunner-rust-sdk/", env!("CARGO_PKG_VERSION"));
@@ -0,0 +1,108 @@
+use crate::{Error, Result};
+use std::fmt;
+
+/// Deepest nesting of arrays and objects that serde_json parses
+///
+/// A larger [`JsonLimits::max_depth`] has the effect of this one.
+pub const MAX_JSON_DEPTH: usize = 127;
+
+/// Limits enforced on response bodies and stream messages before they are deserialized
+///
+/// Execution results embed arbitrary node output, so a misbehaving workflow can
+/// produce pathologically nested or oversized JSON. Bodies are scanned
+/// iteratively against these limits first and rejected with
+/// [`Error::JsonLimitExceeded`] instead of being parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonLimits {
+    /// Maximum nesting depth of arrays and objects, at most [`MAX_JSON_DEPTH`]
+    pub max_depth: usize,
+    /// Maximum length of a single string, in bytes as encoded
+    pub max_string_len: usize,
+    /// Maximum size of the whole body in bytes
+    ///
+    /// The default transport enforces it while reading: a response whose
+    /// `Content-Length` is larger fails before its body is read, and a body
+    /// without one is read no further than the limit.
+    pub max_total_bytes: usize,
+}
+
+impl Default for JsonLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: MAX_JSON_DEPTH,
+            max_string_len: 64 * 1024 * 1024,
+            max_total_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/// The limit that a JSON document exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonLimit {
+    Depth,
+    StringLength,
+    TotalBytes,
+}
+
+impl fmt::Display for JsonLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            JsonLimit::Depth => "depth",
+            JsonLimit::StringLength => "string length",
+            JsonLimit::TotalBytes => "total bytes",
+        })
+    }
+}
+
+impl JsonLimits {
+    /// Check a JSON document against the limits without parsing it
+    pub(crate) fn check(&self, json: &[u8]) -> Result<()> {
+        if json.len() > self.max_total_bytes {
+            return Err(exceeded(JsonLimit::TotalBytes, self.max_total_bytes));
+        }
+
+        let max_depth = self.max_depth.min(MAX_JSON_DEPTH);
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut string_len = 0usize;
+
+        for &byte in json {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if byte == b'\\' {
+                    escaped = true;
+                } else if byte == b'"' {
+                    in_string = false;
+                    continue;
+                }
+                string_len += 1;
+                if string_len > self.max_string_len {
+                    return Err(exceeded(JsonLimit::StringLength, self.max_string_len));
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => {
+                    in_string = true;
+                    string_len = 0;
+                }
+                b'{' | b'[' => {
+                    depth += 1;
+                    if depth > max_depth {
+                        return Err(exceeded(JsonLimit::Depth, max_depth));
+                    }
+                }
+                b'}' | b']' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn exceeded(which: JsonLimit, limit: usize) -> Error {
+    Error::JsonLimitExceeded { which, limit }
+}
@@ -0,0 +1,481 @@
+//! Opinionated lints for workflow definitions
+//!
+//! A [`Linter`] runs a set of [`Rule`]s over a [`WorkflowDefinition`] and
+//! reports their findings with a configurable [`Severity`]. The built-in rules
+//! are enabled by default; custom rules implement [`Rule`] and are added with
+//! [`LinterBuilder::rule`].
+//!
+//! ```rust,no_run
+//! # #[tokio::main]
+//! # async fn main() -> klikkflow_sdk::Result<()> {
+//! use klikkflow_sdk::lint::{Linter, Severity};
+//!
+//! let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+//! let workflow = client.get_workflow("wf-1").await?;
+//! let linter = Linter::builder()
+//!     .severity("default-node-name", Severity::Error)
+//!     .build();
+//! for finding in linter.lint(&workflow) {
+//!     println!("{}", finding);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::models::{NodeDefinition, WorkflowDefinition};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+/// How serious a lint finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Problem reported by a rule, before the linter assigns a severity
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Node the problem was found on, if it concerns a single node
+    pub node_id: Option<String>,
+    pub message: String,
+}
+
+impl Violation {
+    /// Violation concerning the workflow as a whole
+    pub fn workflow(message: impl Into<String>) -> Self {
+        Self {
+            node_id: None,
+            message: message.into(),
+        }
+    }
+
+    /// Violation concerning a single node
+    pub fn node(node_id: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            node_id: Some(node_id.into()),
+            message: message.into(),
+        }
+    }
+}
+
+/// Lint finding reported by a [`Linter`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Name of the rule that produced the finding
+    pub rule: String,
+    pub severity: Severity,
+    pub node_id: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.node_id {
+            Some(node_id) => write!(
+                f,
+                "{} [{}] node {}: {}",
+                self.severity, self.rule, node_id, self.message
+            ),
+            None => write!(f, "{} [{}] {}", self.severity, self.rule, self.message),
+        }
+    }
+}
+
+/// A single lint check over a workflow definition
+pub trait Rule: Send + Sync {
+    /// Unique name used to configure the rule, e.g. `disconnected-node`
+    fn name(&self) -> &str;
+
+    /// Severity used unless overridden with [`LinterBuilder::severity`]
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    /// Check the workflow and return every violation found
+    fn check(&self, workflow: &WorkflowDefinition) -> Vec<Violation>;
+}
+
+/// Runs a configured set of rules over workflow definitions
+#[derive(Clone)]
+pub struct Linter {
+    rules: Vec<(Arc<dyn Rule>, Severity)>,
+}
+
+impl Linter {
+    /// Linter running every built-in rule with its default severity
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    /// Start configuring a linter, with every built-in rule enabled
+    pub fn builder() -> LinterBuilder {
+        LinterBuilder::default()
+    }
+
+    /// Names of the rules this linter runs
+    pub fn rules(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|(rule, _)| rule.name())
+    }
+
+    /// Lint a workflow; findings are ordered by severity, most severe first
+    pub fn lint(&self, workflow: &WorkflowDefinition) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = self
+            .rules
+            .iter()
+            .flat_map(|(rule, severity)| {
+                rule.check(workflow)
+                    .into_iter()
+                    .map(move |violation| Finding {
+                        rule: rule.name().to_string(),
+                        severity: *severity,
+                        node_id: violation.node_id,
+                        message: violation.message,
+                    })
+            })
+            .collect();
+        findings.sort_by_key(|finding| Reverse(finding.severity));
+        findings
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder selecting the rules and severities of a [`Linter`]
+pub struct LinterBuilder {
+    rules: Vec<Arc<dyn Rule>>,
+    disabled: HashSet<String>,
+    severities: HashMap<String, Severity>,
+}
+
+impl Default for LinterBuilder {
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                Arc::new(DefaultNodeName),
+                Arc::new(DisconnectedNode),
+                Arc::new(CredentialById),
+                Arc::new(OverlappingPositions::default()),
+                Arc::new(LongLinearChain::default()),
+            ],
+            disabled: HashSet::new(),
+            severities: HashMap::new(),
+        }
+    }
+}
+
+impl LinterBuilder {
+    /// Start from an empty rule set instead of the built-in rules
+    pub fn without_builtin_rules(mut self) -> Self {
+        self.rules.clear();
+        self
+    }
+
+    /// Add a rule, replacing any rule with the same name
+    pub fn rule(mut self, rule: impl Rule + 'static) -> Self {
+        self.rules.retain(|existing| existing.name() != rule.name());
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Enable a rule that was disabled
+    pub fn enable(mut self, name: &str) -> Self {
+        self.disabled.remove(name);
+        self
+    }
+
+    /// Disable a rule by name
+    pub fn disable(mut self, name: impl Into<String>) -> Self {
+        self.disabled.insert(name.into());
+        self
+    }
+
+    /// Report findings of a rule with the given severity
+    pub fn severity(mut self, name: impl Into<String>, severity: Severity) -> Self {
+        self.severities.insert(name.into(), severity);
+        self
+    }
+
+    pub fn build(self) -> Linter {
+        let rules = self
+            .rules
+            .into_iter()
+            .filter(|rule| !self.disabled.contains(rule.name()))
+            .map(|rule| {
+                let severity = self
+                    .severities
+                    .get(rule.name())
+                    .copied()
+                    .unwrap_or_else(|| rule.default_severity());
+                (rule, severity)
+            })
+            .collect();
+        Linter { rules }
+    }
+}
+
+/// Nodes still carrying an editor-generated name such as `HTTP Request 7`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultNodeName;
+
+impl Rule for DefaultNodeName {
+    fn name(&self) -> &str {
+        "default-node-name"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check(&self, workflow: &WorkflowDefinition) -> Vec<Violation> {
+        workflow
+            .nodes
+            .iter()
+            .filter(|node| !node.is_annotation())
+            .filter(|node| {
+                let base = node
+                    .name
+                    .trim_end_matches(|c: char| c.is_ascii_digit())
+                    .trim_end();
+                normalize(base) == normalize(&node.node_type)
+            })
+            .map(|node| {
+                Violation::node(
+                    &node.id,
+                    format!("node \"{}\" still has its default name", node.name),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Lowercase and drop separators so `HTTP Request` matches `http-request`
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Nodes without any incoming or outgoing connection in a multi-node workflow
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisconnectedNode;
+
+impl Rule for DisconnectedNode {
+    fn name(&self) -> &str {
+        "disconnected-node"
+    }
+
+    fn check(&self, workflow: &WorkflowDefinition) -> Vec<Violation> {
+        let nodes: Vec<&NodeDefinition> = workflow
+            .nodes
+            .iter()
+            .filter(|node| !node.is_annotation())
+            .collect();
+        if nodes.len() < 2 {
+            return Vec::new();
+        }
+        let connected: HashSet<&str> = workflow
+            .connections
+            .iter()
+            .flat_map(|c| [c.source.node_id.as_str(), c.destination.node_id.as_str()])
+            .collect();
+        nodes
+            .into_iter()
+            .filter(|node| !connected.contains(node.id.as_str()))
+            .map(|node| {
+                Violation::node(
+                    &node.id,
+                    format!("node \"{}\" is not connected to any other node", node.name),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Credentials referenced by id, which breaks when a workflow moves between instances
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CredentialById;
+
+impl Rule for CredentialById {
+    fn name(&self) -> &str {
+        "credential-by-id"
+    }
+
+    fn check(&self, workflow: &WorkflowDefinition) -> Vec<Violation> {
+        workflow
+            .nodes
+            .iter()
+            .filter(|node| {
+                node.parameters.contains_key("credentialId")
+                    || node
+                        .parameters
+                        .get("credential")
+                        .and_then(|value| value.as_str())
+                        .is_some_and(looks_like_id)
+            })
+            .map(|node| {
+                Violation::node(
+                    &node.id,
+                    format!(
+                        "node \"{}\" references its credential by id instead of name",
+                        node.name
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+fn looks_like_id(value: &str) -> bool {
+    uuid::Uuid::parse_str(value).is_ok()
+        || (!value.is_empty() && value.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Nodes placed on top of each other on the canvas
+///
+/// ```rust
+/// use klikkflow_sdk::lint::{Linter, OverlappingPositions};
+///
+/// let linter = Linter::builder()
+///     .rule(OverlappingPositions { min_distance: 40.0 })
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct OverlappingPositions {
+    /// Nodes closer than this distance on both axes are considered overlapping
+    pub min_distance: f64,
+}
+
+impl Default for OverlappingPositions {
+    fn default() -> Self {
+        Self { min_distance: 20.0 }
+    }
+}
+
+impl Rule for OverlappingPositions {
+    fn name(&self) -> &str {
+        "overlapping-positions"
+    }
+
+    fn check(&self, workflow: &WorkflowDefinition) -> Vec<Violation> {
+        // Notes and groups are placed around or over the nodes they document
+        let nodes: Vec<&NodeDefinition> = workflow
+            .nodes
+            .iter()
+            .filter(|node| !node.is_annotation())
+            .collect();
+        let mut violations = Vec::new();
+        for (i, node) in nodes.iter().enumerate() {
+            let overlapped = nodes[..i].iter().find(|other| {
+                (node.position.x - other.position.x).abs() < self.min_distance
+                    && (node.position.y - other.position.y).abs() < self.min_distance
+            });
+            if let Some(other) = overlapped {
+                violations.push(Violation::node(
+                    &node.id,
+                    format!("node \"{}\" overlaps node \"{}\"", node.name, other.name),
+                ));
+            }
+        }
+        violations
+    }
+}
+
+/// Long chains of nodes without branching, better extracted into a sub-workflow
+///
+/// ```rust
+/// use klikkflow_sdk::lint::{Linter, LongLinearChain};
+///
+/// let linter = Linter::builder()
+///     .rule(LongLinearChain { max_length: 8 })
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct LongLinearChain {
+    /// Longest chain length accepted without a finding
+    pub max_length: usize,
+}
+
+impl Default for LongLinearChain {
+    fn default() -> Self {
+        Self { max_length: 15 }
+    }
+}
+
+impl Rule for LongLinearChain {
+    fn name(&self) -> &str {
+        "long-linear-chain"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check(&self, workflow: &WorkflowDefinition) -> Vec<Violation> {
+        let mut outgoing: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut incoming: HashMap<&str, usize> = HashMap::new();
+        for connection in &workflow.connections {
+            outgoing
+                .entry(connection.source.node_id.as_str())
+                .or_default()
+                .push(connection.destination.node_id.as_str());
+            *incoming
+                .entry(connection.destination.node_id.as_str())
+                .or_default() += 1;
+        }
+        let is_link = |id: &str| {
+            incoming.get(id).copied().unwrap_or(0) == 1 && outgoing.get(id).map_or(0, Vec::len) <= 1
+        };
+        let mut violations = Vec::new();
+        for node in &workflow.nodes {
+            // Chains start at a node that is not itself a continuation of one
+            let single_successor = match outgoing.get(node.id.as_str()) {
+                Some(next) if next.len() == 1 => next[0],
+                _ => continue,
+            };
+            if is_link(&node.id) || !is_link(single_successor) {
+                continue;
+            }
+
+            let mut length = 1;
+            let mut seen = HashSet::from([node.id.as_str()]);
+            let mut current = single_successor;
+            while seen.insert(current) && is_link(current) {
+                length += 1;
+                match outgoing.get(current).and_then(|next| next.first()) {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+
+            if length > self.max_length {
+                violations.push(Violation::node(
+                    &node.id,
+                    format!(
+                        "chain of {} nodes starting at \"{}\" could be a sub-workflow",
+                        length, node.name
+                    ),
+                ));
+            }
+        }
+        violations
+    }
+}
@@ -0,0 +1,465 @@
+//! Load testing a server with synthetic executions
+//!
+//! Available with the `loadtest` feature. See [`LoadTest`].
+
+use crate::client::Client;
+use crate::models::{ExecuteOptions, ExecutionStatus, FieldMap};
+use crate::wait::WaitOptions;
+use crate::Error;
+use futures_util::future::{self, FutureExt};
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::{interval, Instant, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+/// How often the launch rate is re-evaluated
+const TICK: Duration = Duration::from_millis(10);
+
+/// How the launch rate approaches the [target](LoadTest::target_rps)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RampProfile {
+    /// The target rate from the start
+    #[default]
+    Constant,
+    /// From zero up to the target rate over `ramp_up`, then the target rate
+    Linear { ramp_up: Duration },
+    /// The target rate in `steps` equal increments, each held for `every`
+    Step { steps: u32, every: Duration },
+}
+
+impl RampProfile {
+    /// Executions per second to launch `elapsed` into the test
+    fn rate_at(&self, elapsed: Duration, target: f64) -> f64 {
+        match *self {
+            RampProfile::Constant => target,
+            RampProfile::Linear { ramp_up } if ramp_up.is_zero() => target,
+            RampProfile::Linear { ramp_up } => {
+                target * (elapsed.as_secs_f64() / ramp_up.as_secs_f64()).min(1.0)
+            }
+            RampProfile::Step { steps, every } if steps == 0 || every.is_zero() => target,
+            RampProfile::Step { steps, every } => {
+                let step = (elapsed.as_secs_f64() / every.as_secs_f64()).floor() as u32 + 1;
+                target * f64::from(step.min(steps)) / f64::from(steps)
+            }
+        }
+    }
+}
+
+type PayloadFn = dyn Fn(u64) -> FieldMap + Send + Sync;
+
+/// Synthetic executions of one workflow launched at a controlled rate
+///
+/// Executions are launched following the [ramp profile](Self::ramp) up to
+/// [`target_rps`](Self::target_rps) for [`duration`](Self::duration), with
+/// at most [`max_concurrency`](Self::max_concurrency) in flight; when the
+/// cap is reached, launching waits and the achieved rate drops below the
+/// target. The payload function receives the sequence number of each
+/// execution. Unless [fire-and-forget](Self::fire_and_forget), every
+/// execution is waited on to record its end-to-end latency.
+///
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use klikkflow_sdk::loadtest::{LoadTest, RampProfile};
+/// use std::time::Duration;
+///
+/// let client = klikkflow_sdk::Client::new("https://staging.klikkflow.example.com");
+/// let report = LoadTest::new(client, "wf-checkout", |n| {
+///     [("order".to_string(), serde_json::json!(n))].into_iter().collect()
+/// })
+/// .ramp(RampProfile::Linear { ramp_up: Duration::from_secs(60) })
+/// .target_rps(50.0)
+/// .duration(Duration::from_secs(300))
+/// .max_concurrency(200)
+/// .run()
+/// .await;
+/// println!("{} launched, {} succeeded", report.launched, report.succeeded);
+/// # }
+/// ```
+pub struct LoadTest {
+    client: Client,
+    workflow_id: String,
+    payload: Arc<PayloadFn>,
+    profile: RampProfile,
+    duration: Duration,
+    target_rps: f64,
+    max_concurrency: usize,
+    fire_and_forget: bool,
+    cancel_in_flight: bool,
+    wait_options: WaitOptions,
+}
+
+impl std::fmt::Debug for LoadTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoadTest")
+            .field("workflow_id", &self.workflow_id)
+            .field("profile", &self.profile)
+            .field("duration", &self.duration)
+            .field("target_rps", &self.target_rps)
+            .field("max_concurrency", &self.max_concurrency)
+            .field("fire_and_forget", &self.fire_and_forget)
+            .field("cancel_in_flight", &self.cancel_in_flight)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LoadTest {
+    /// Load test of `workflow_id`: 1 execution per second for a minute, at most 100 in flight
+    pub fn new<F>(client: Client, workflow_id: impl Into<String>, payload: F) -> Self
+    where
+        F: Fn(u64) -> FieldMap + Send + Sync + 'static,
+    {
+        Self {
+            client,
+            workflow_id: workflow_id.into(),
+            payload: Arc::new(payload),
+            profile: RampProfile::Constant,
+            duration: Duration::from_secs(60),
+            target_rps: 1.0,
+            max_concurrency: 100,
+            fire_and_forget: false,
+            cancel_in_flight: false,
+            wait_options: WaitOptions::default(),
+        }
+    }
+
+    pub fn ramp(mut self, profile: RampProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// How long executions are launched for
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Executions launched per second once the ramp is complete
+    pub fn target_rps(mut self, rps: f64) -> Self {
+        self.target_rps = rps.max(0.0);
+        self
+    }
+
+    /// Most executions submitted or waited on at the same time
+    pub fn max_concurrency(mut self, max: usize) -> Self {
+        self.max_concurrency = max.max(1);
+        self
+    }
+
+    /// Only submit executions, without waiting for them to finish
+    pub fn fire_and_forget(mut self, enabled: bool) -> Self {
+        self.fire_and_forget = enabled;
+        self
+    }
+
+    /// Cancel the executions still in flight when the test is stopped early
+    pub fn cancel_in_flight(mut self, enabled: bool) -> Self {
+        self.cancel_in_flight = enabled;
+        self
+    }
+
+    /// How executions are waited on, unless fire-and-forget
+    pub fn wait_options(mut self, options: WaitOptions) -> Self {
+        self.wait_options = options;
+        self
+    }
+
+    /// Run the whole test and wait for the executions in flight at its end
+    pub async fn run(self) -> LoadTestReport {
+        self.run_until(future::pending()).await
+    }
+
+    /// Run the test until it completes or `stop` resolves
+    ///
+    /// Once `stop` resolves no further execution is launched. The executions
+    /// in flight are still waited on, unless [`cancel_in_flight`](Self::cancel_in_flight)
+    /// is set: then waiting stops at once and the submitted ones are
+    /// cancelled on the server, counted in [`LoadTestReport::cancelled`].
+    pub async fn run_until(self, stop: impl Future<Output = ()>) -> LoadTestReport {
+        info!(
+            "Load testing workflow {} at up to {} executions/s for {:?}",
+            self.workflow_id, self.target_rps, self.duration
+        );
+        let mut stop = std::pin::pin!(stop.fuse());
+        let mut stopped = false;
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrency));
+        let in_flight: Arc<Mutex<HashSet<String>>> = Arc::default();
+        let mut tasks = JoinSet::new();
+        let mut samples = Samples::default();
+
+        let started = Instant::now();
+        let mut ticks = interval(TICK);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut last_tick = started;
+        let mut credit = 0.0;
+        let mut launched = 0;
+        'launch: while started.elapsed() < self.duration {
+            tokio::select! {
+                biased;
+                _ = &mut stop => {
+                    stopped = true;
+                    break 'launch;
+                }
+                _ = ticks.tick() => {}
+            }
+            let now = Instant::now();
+            let rate = self
+                .profile
+                .rate_at(now.duration_since(started), self.target_rps);
+            credit += rate * now.duration_since(last_tick).as_secs_f64();
+            last_tick = now;
+
+            while credit >= 1.0 {
+                let permit = tokio::select! {
+                    biased;
+                    _ = &mut stop => {
+                        stopped = true;
+                        break 'launch;
+                    }
+                    permit = Arc::clone(&semaphore).acquire_owned() => {
+                        permit.expect("load test semaphore is never closed")
+                    }
+                };
+                credit -= 1.0;
+                let execution = self.execution(launched, Arc::clone(&in_flight));
+                tasks.spawn(async move {
+                    let outcome = execution.await;
+                    drop(permit);
+                    outcome
+                });
+                launched += 1;
+                while let Some(outcome) = tasks.try_join_next() {
+                    samples.record(outcome);
+                }
+            }
+        }
+        debug!(
+            "Launched {} executions, waiting for those in flight",
+            launched
+        );
+
+        while !tasks.is_empty() {
+            if stopped && self.cancel_in_flight {
+                tasks.abort_all();
+                break;
+            }
+            tokio::select! {
+                biased;
+                _ = &mut stop, if !stopped => stopped = true,
+                Some(outcome) = tasks.join_next() => samples.record(outcome),
+            }
+        }
+        while let Some(outcome) = tasks.join_next().await {
+            samples.record(outcome);
+        }
+
+        let leftover: Vec<String> = in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain()
+            .collect();
+        if !leftover.is_empty() {
+            info!("Cancelling {} executions in flight", leftover.len());
+            let cancels = leftover
+                .iter()
+                .map(|execution_id| self.client.cancel_execution(execution_id));
+            for (execution_id, result) in leftover.iter().zip(future::join_all(cancels).await) {
+                if let Err(e) = result {
+                    warn!("Failed to cancel execution {}: {}", execution_id, e);
+                }
+            }
+        }
+
+        samples.report(launched, started.elapsed(), !self.fire_and_forget)
+    }
+
+    /// Submit execution `sequence` and, unless fire-and-forget, wait for it
+    fn execution(
+        &self,
+        sequence: u64,
+        in_flight: Arc<Mutex<HashSet<String>>>,
+    ) -> impl Future<Output = Outcome> + Send + 'static {
+        let client = self.client.clone();
+        let workflow_id = self.workflow_id.clone();
+        let payload = Arc::clone(&self.payload);
+        let fire_and_forget = self.fire_and_forget;
+        let wait_options = self.wait_options.clone();
+        async move {
+            let started = Instant::now();
+            let submitted = client
+                .execute_workflow_with_options(
+                    &workflow_id,
+                    payload(sequence),
+                    ExecuteOptions::new(),
+                )
+                .await;
+            let submit_latency = started.elapsed();
+            let execution = match submitted {
+                Ok(execution) => execution,
+                Err(e) => return Outcome::Failed(error_kind(&e)),
+            };
+            if fire_and_forget {
+                return Outcome::Submitted { submit_latency };
+            }
+
+            let finished = if execution.status.is_terminal() {
+                Ok(execution)
+            } else {
+                let lock = || in_flight.lock().unwrap_or_else(|e| e.into_inner());
+                lock().insert(execution.id.clone());
+                let finished = client.wait_future(&execution.id, wait_options).await;
+                lock().remove(&execution.id);
+                finished
+            };
+            let completion_latency = started.elapsed();
+            match finished {
+                Ok(execution) if execution.status == ExecutionStatus::Success => {
+                    Outcome::Completed {
+                        submit_latency,
+                        completion_latency,
+                    }
+                }
+                Ok(execution) => {
+                    Outcome::Failed(format!("execution_{}", execution.status.as_str()))
+                }
+                Err(e) => Outcome::Failed(error_kind(&e)),
+            }
+        }
+    }
+}
+
+/// Key of an error in [`LoadTestReport::errors`]
+fn error_kind(error: &Error) -> String {
+    error.code().as_str().to_string()
+}
+
+enum Outcome {
+    Submitted {
+        submit_latency: Duration,
+    },
+    Completed {
+        submit_latency: Duration,
+        completion_latency: Duration,
+    },
+    Failed(String),
+}
+
+#[derive(Default)]
+struct Samples {
+    submit: Vec<Duration>,
+    completion: Vec<Duration>,
+    succeeded: u64,
+    cancelled: u64,
+    errors: BTreeMap<String, u64>,
+}
+
+impl Samples {
+    fn record(&mut self, outcome: std::result::Result<Outcome, tokio::task::JoinError>) {
+        match outcome {
+            Ok(Outcome::Submitted { submit_latency }) => {
+                self.submit.push(submit_latency);
+                self.succeeded += 1;
+            }
+            Ok(Outcome::Completed {
+                submit_latency,
+                completion_latency,
+            }) => {
+                self.submit.push(submit_latency);
+                self.completion.push(completion_latency);
+                self.succeeded += 1;
+            }
+            Ok(Outcome::Failed(kind)) => *self.errors.entry(kind).or_default() += 1,
+            Err(e) if e.is_cancelled() => self.cancelled += 1,
+            Err(_) => *self.errors.entry("panic".to_string()).or_default() += 1,
+        }
+    }
+
+    fn report(self, launched: u64, elapsed: Duration, waited: bool) -> LoadTestReport {
+        LoadTestReport {
+            launched,
+            succeeded: self.succeeded,
+            cancelled: self.cancelled,
+            errors: self.errors,
+            elapsed,
+            submit_latency: LatencySummary::new(self.submit),
+            completion_latency: waited.then(|| LatencySummary::new(self.completion)),
+        }
+    }
+}
+
+/// Distribution of latencies measured by a [`LoadTest`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencySummary {
+    /// Number of measurements; all other fields are zero when there are none
+    pub count: usize,
+    pub min: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencySummary {
+    fn new(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort();
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * samples.len() as f64).ceil() as usize;
+            samples[rank.clamp(1, samples.len()) - 1]
+        };
+        Self {
+            count: samples.len(),
+            min: samples[0],
+            mean: samples.iter().sum::<Duration>() / samples.len() as u32,
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Result of a [`LoadTest`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadTestReport {
+    /// Executions launched, whatever became of them
+    pub launched: u64,
+    /// Executions submitted, and when waited on, finished successfully
+    pub succeeded: u64,
+    /// Executions given up on when the test was stopped early
+    pub cancelled: u64,
+    /// Failed executions by [error code](crate::ErrorCode), or by final
+    /// status as `execution_<status>` for those that ran and failed
+    pub errors: BTreeMap<String, u64>,
+    /// Time from the first launch until the last execution was accounted for
+    pub elapsed: Duration,
+    /// Time to submit each successful execution
+    pub submit_latency: LatencySummary,
+    /// Time from submitting to finishing of each successful execution;
+    /// `None` when fire-and-forget
+    pub completion_latency: Option<LatencySummary>,
+}
+
+impl LoadTestReport {
+    /// Executions launched per second over the whole test
+    pub fn achieved_rps(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+        self.launched as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Failed executions, of every kind
+    pub fn failed(&self) -> u64 {
+        self.errors.values().sum()
+    }
+}
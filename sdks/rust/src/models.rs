@@ -1,6 +1,48 @@
+use crate::rerun::{ExecutionAttempts, ExecutionRetryPolicy};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Node type of a sticky note: canvas documentation with no part in execution
+pub const STICKY_NOTE_NODE_TYPE: &str = "sticky-note";
+/// Node type of a frame grouping nodes on the canvas, with no part in execution
+pub const GROUP_NODE_TYPE: &str = "group";
+/// Node types that only annotate the canvas and are never executed
+pub const ANNOTATION_NODE_TYPES: &[&str] = &[STICKY_NOTE_NODE_TYPE, GROUP_NODE_TYPE];
+/// Request header identifying an execution request, so resending it starts no second execution
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Map of workflow settings, node parameters and execution data, by key
+///
+/// With the default `ordered-maps` feature this is an
+/// [`IndexMap`](indexmap::IndexMap): keys keep the order they were inserted
+/// or received in, through deserialization and serialization. Without it,
+/// it is a `HashMap` whose keys are serialized sorted, so equal maps still
+/// produce identical JSON.
+///
+/// ```rust
+/// use klikkflow_sdk::NodeDefinition;
+///
+/// let json = r#"{"id":"n1","name":"Fetch","type":"http-request","position":{"x":0.0,"y":0.0},"parameters":{"url":"https://example.com","method":"GET","body":null}}"#;
+/// let mut node: NodeDefinition = serde_json::from_str(json).unwrap();
+/// assert_eq!(serde_json::to_string(&node).unwrap(), json);
+///
+/// node.parameters.insert("auth".to_string(), "none".into());
+/// assert!(serde_json::to_string(&node).unwrap().ends_with(r#""body":null,"auth":"none"}}"#));
+/// ```
+#[cfg(feature = "ordered-maps")]
+pub type FieldMap<V = serde_json::Value> = indexmap::IndexMap<String, V>;
+/// Map of workflow settings, node parameters and execution data, by key
+///
+/// With the default `ordered-maps` feature this is an `IndexMap` keeping
+/// keys in insertion order. Without it, it is a
+/// [`HashMap`](std::collections::HashMap) whose keys are serialized sorted,
+/// so equal maps still produce identical JSON.
+#[cfg(not(feature = "ordered-maps"))]
+pub type FieldMap<V = serde_json::Value> = HashMap<String, V>;
 
 /// Workflow definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,7 +53,8 @@ pub struct WorkflowDefinition {
     pub active: bool,
     pub nodes: Vec<NodeDefinition>,
     pub connections: Vec<Connection>,
-    pub settings: HashMap<String, serde_json::Value>,
+    #[serde(serialize_with = "ordered")]
+    pub settings: FieldMap,
     #[serde(rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(rename = "updatedAt")]
@@ -26,7 +69,15 @@ pub struct NodeDefinition {
     #[serde(rename = "type")]
     pub node_type: String,
     pub position: Position,
-    pub parameters: HashMap<String, serde_json::Value>,
+    #[serde(serialize_with = "ordered")]
+    pub parameters: FieldMap,
+}
+
+impl NodeDefinition {
+    /// Whether the node is a sticky note or group rather than a step of the workflow
+    pub fn is_annotation(&self) -> bool {
+        ANNOTATION_NODE_TYPES.contains(&self.node_type.as_str())
+    }
 }
 
 /// Node position in the workflow canvas
@@ -65,18 +116,107 @@ pub struct ExecutionResult {
     pub started_at: DateTime<Utc>,
     #[serde(rename = "finishedAt")]
     pub finished_at: Option<DateTime<Utc>>,
-    #[serde(rename = "inputData")]
-    pub input_data: HashMap<String, serde_json::Value>,
-    #[serde(rename = "outputData")]
+    /// Empty when listed without data, see [`ExecutionHistoryOptions::include_data`]
+    #[serde(rename = "inputData", default, serialize_with = "ordered")]
+    pub input_data: FieldMap,
+    #[serde(rename = "outputData", default)]
     pub output_data: HashMap<String, serde_json::Value>,
     pub error: Option<String>,
-    #[serde(rename = "nodeResults")]
-    pub node_results: HashMap<String, serde_json::Value>,
+    #[serde(
+        rename = "nodeResults",
+        default,
+        serialize_with = "ordered",
+        deserialize_with = "lenient_node_results"
+    )]
+    pub node_results: FieldMap<NodeResult>,
     pub metadata: ExecutionMetadata,
+    /// Workflow definition as it was when the execution ran, if requested
+    #[serde(
+        rename = "workflowSnapshot",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub workflow_snapshot: Option<WorkflowDefinition>,
+    /// How the execution was started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ExecutionMode>,
+    /// What started the execution, and the execution it retries, if any
+    #[serde(
+        rename = "triggeredBy",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub triggered_by: Option<TriggerSource>,
+    /// Attempts made when executed with a retry policy; not sent by the server
+    #[serde(skip)]
+    pub attempts: Option<ExecutionAttempts>,
+    /// Idempotency key of the request that started the execution, as echoed by the server
+    #[serde(skip)]
+    pub idempotency_key: Option<String>,
+}
+
+/// How an execution was started
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionMode {
+    Manual,
+    Webhook,
+    Schedule,
+    Api,
+    /// Re-execution of a failed execution
+    Retry,
+    /// A mode this version of the SDK does not know
+    #[serde(other)]
+    Unknown,
+}
+
+impl ExecutionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionMode::Manual => "manual",
+            ExecutionMode::Webhook => "webhook",
+            ExecutionMode::Schedule => "schedule",
+            ExecutionMode::Api => "api",
+            ExecutionMode::Retry => "retry",
+            ExecutionMode::Unknown => "unknown",
+        }
+    }
+}
+
+/// What started an execution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriggerSource {
+    pub kind: TriggerKind,
+    /// Kind-specific detail, such as the webhook path or the cron expression
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Execution this one retries or was started by
+    #[serde(
+        rename = "parentExecutionId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub parent_execution_id: Option<String>,
+}
+
+/// Kind of [`TriggerSource`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerKind {
+    Manual,
+    Webhook,
+    Schedule,
+    Api,
+    Retry,
+    /// Another workflow running this one as a sub-workflow
+    Workflow,
+    /// A trigger this version of the SDK does not know
+    #[serde(other)]
+    Unknown,
 }
 
 /// Execution status
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum ExecutionStatus {
     Pending,
@@ -96,6 +236,229 @@ impl ExecutionStatus {
             ExecutionStatus::Cancelled => "cancelled",
         }
     }
+
+    /// Whether the execution has finished and will not change status again
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ExecutionStatus::Success | ExecutionStatus::Error | ExecutionStatus::Cancelled
+        )
+    }
+}
+
+/// Result of a single node within an execution
+///
+/// An entry of an execution's `nodeResults` that does not have this shape,
+/// such as one recorded by an older server, does not fail the execution: it
+/// is kept as received in [`raw`](Self::raw), with the other fields empty.
+///
+/// ```rust
+/// use klikkflow_sdk::{ExecutionResult, NodeStatus};
+/// use serde_json::json;
+///
+/// let execution: ExecutionResult = serde_json::from_value(json!({
+///     "id": "ex-1", "workflowId": "wf-1", "status": "success",
+///     "startedAt": "2024-01-01T00:00:00Z", "finishedAt": null,
+///     "inputData": {}, "outputData": {}, "error": null,
+///     "nodeResults": {
+///         "fetch": { "status": "success", "output": { "count": 2 } },
+///         "legacy": { "status": "success", "error": { "message": "boom" }, "startedAt": 1704067200 }
+///     },
+///     "metadata": { "totalNodes": 2, "completedNodes": 2, "failedNodes": 0, "retriedNodes": 0 }
+/// }))
+/// .unwrap();
+/// assert_eq!(execution.node_results["fetch"].status, Some(NodeStatus::Success));
+/// let legacy = &execution.node_results["legacy"];
+/// assert_eq!(legacy.status, None);
+/// assert_eq!(legacy.raw.as_ref().unwrap()["error"]["message"], "boom");
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeResult {
+    #[serde(default)]
+    pub status: Option<NodeStatus>,
+    #[serde(rename = "startedAt", default)]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(rename = "finishedAt", default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub output: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// The entry as received, when it did not have the shape of a node result
+    #[serde(skip)]
+    pub raw: Option<serde_json::Value>,
+}
+
+impl NodeResult {
+    /// Whether the node failed, either by status or by carrying an error
+    ///
+    /// For an entry kept [`raw`](Self::raw), its `status` and `error` fields
+    /// are checked as received.
+    pub fn is_failed(&self) -> bool {
+        if self.status == Some(NodeStatus::Error) || self.error.is_some() {
+            return true;
+        }
+        self.raw.as_ref().is_some_and(|raw| {
+            raw.get("status").and_then(serde_json::Value::as_str) == Some("error")
+                || raw.get("error").is_some_and(|error| !error.is_null())
+        })
+    }
+}
+
+/// Status of a single node within an execution
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeStatus {
+    Pending,
+    Running,
+    Success,
+    Error,
+    Skipped,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Node results of an execution bucketed by outcome, each in execution order
+#[derive(Debug, Clone, Default)]
+pub struct NodeResultGroups<'a> {
+    pub succeeded: Vec<(&'a str, &'a NodeResult)>,
+    pub failed: Vec<(&'a str, &'a NodeResult)>,
+    pub skipped: Vec<(&'a str, &'a NodeResult)>,
+    pub in_progress: Vec<(&'a str, &'a NodeResult)>,
+    /// Results without a recognized status, such as entries kept [`raw`](NodeResult::raw)
+    pub unknown: Vec<(&'a str, &'a NodeResult)>,
+    /// Workflow nodes that have no result at all
+    pub not_run: Vec<&'a NodeDefinition>,
+}
+
+impl ExecutionResult {
+    /// Execution this one retries or was started by, see [`Client::get_retry_chain`](crate::Client::get_retry_chain)
+    pub fn parent_execution_id(&self) -> Option<&str> {
+        self.triggered_by
+            .as_ref()
+            .and_then(|trigger| trigger.parent_execution_id.as_deref())
+    }
+
+    /// Node results sorted by node start time.
+    ///
+    /// Nodes that started at the same instant (or report no start time) are
+    /// ordered by their position in the workflow graph. Results are keyed by
+    /// node id, falling back to node name.
+    pub fn node_results_ordered<'a>(
+        &'a self,
+        workflow: &WorkflowDefinition,
+    ) -> Vec<(&'a str, &'a NodeResult)> {
+        let order = workflow.execution_order();
+        let rank: HashMap<&str, usize> = order
+            .iter()
+            .enumerate()
+            .flat_map(|(index, node)| [(node.id.as_str(), index), (node.name.as_str(), index)])
+            .collect();
+
+        let mut results: Vec<(&str, &NodeResult)> = self
+            .node_results
+            .iter()
+            .map(|(key, result)| (key.as_str(), result))
+            .collect();
+        results.sort_by(|(a_key, a), (b_key, b)| {
+            let a_start = a.started_at.map_or((1, None), |t| (0, Some(t)));
+            let b_start = b.started_at.map_or((1, None), |t| (0, Some(t)));
+            let a_rank = rank.get(a_key).copied().unwrap_or(usize::MAX);
+            let b_rank = rank.get(b_key).copied().unwrap_or(usize::MAX);
+            (a_start, a_rank, a_key).cmp(&(b_start, b_rank, b_key))
+        });
+        results
+    }
+
+    /// Node results bucketed by outcome, plus workflow nodes that never ran
+    pub fn group_by_status<'a>(&'a self, workflow: &'a WorkflowDefinition) -> NodeResultGroups<'a> {
+        let mut groups = NodeResultGroups::default();
+        for (key, result) in self.node_results_ordered(workflow) {
+            let bucket = if result.is_failed() {
+                &mut groups.failed
+            } else {
+                match result.status {
+                    Some(NodeStatus::Success) => &mut groups.succeeded,
+                    Some(NodeStatus::Skipped) => &mut groups.skipped,
+                    Some(NodeStatus::Error) => &mut groups.failed,
+                    Some(NodeStatus::Pending | NodeStatus::Running) => &mut groups.in_progress,
+                    Some(NodeStatus::Unknown) | None => &mut groups.unknown,
+                }
+            };
+            bucket.push((key, result));
+        }
+
+        groups.not_run = workflow
+            .execution_order()
+            .into_iter()
+            .filter(|node| {
+                !self.node_results.contains_key(&node.id)
+                    && !self.node_results.contains_key(&node.name)
+            })
+            .collect();
+        groups
+    }
+}
+
+impl WorkflowDefinition {
+    /// Nodes in graph order: a topological sort of the connections, breaking
+    /// ties by the order nodes are declared in. Nodes caught in a cycle are
+    /// appended in declaration order. Annotations are left out.
+    pub fn execution_order(&self) -> Vec<&NodeDefinition> {
+        let nodes: Vec<&NodeDefinition> = self
+            .nodes
+            .iter()
+            .filter(|node| !node.is_annotation())
+            .collect();
+        let index: HashMap<&str, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), i))
+            .collect();
+
+        let mut in_degree = vec![0usize; nodes.len()];
+        let mut edges: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+        for connection in &self.connections {
+            if let (Some(&from), Some(&to)) = (
+                index.get(connection.source.node_id.as_str()),
+                index.get(connection.destination.node_id.as_str()),
+            ) {
+                edges[from].push(to);
+                in_degree[to] += 1;
+            }
+        }
+
+        let mut ready: BTreeSet<usize> = (0..nodes.len()).filter(|&i| in_degree[i] == 0).collect();
+        let mut visited = vec![false; nodes.len()];
+        let mut order = Vec::with_capacity(nodes.len());
+        while let Some(current) = ready.pop_first() {
+            visited[current] = true;
+            order.push(nodes[current]);
+            for &next in &edges[current] {
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    ready.insert(next);
+                }
+            }
+        }
+
+        order.extend(
+            nodes
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !visited[*i])
+                .map(|(_, node)| *node),
+        );
+        order
+    }
+
+    /// Sticky notes and groups documenting the canvas, in declaration order
+    pub fn annotations(&self) -> Vec<&NodeDefinition> {
+        self.nodes
+            .iter()
+            .filter(|node| node.is_annotation())
+            .collect()
+    }
 }
 
 /// Execution metadata and statistics
@@ -111,6 +474,64 @@ pub struct ExecutionMetadata {
     pub retried_nodes: usize,
 }
 
+/// Aggregated execution statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecutionStatistics {
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub running: u64,
+    #[serde(default)]
+    pub completed: u64,
+    #[serde(default)]
+    pub failed: u64,
+    #[serde(default)]
+    pub cancelled: u64,
+    #[serde(rename = "successRate", default)]
+    pub success_rate: f64,
+    #[serde(rename = "failureRate", default)]
+    pub failure_rate: f64,
+    /// Average execution duration in milliseconds
+    #[serde(rename = "avgDuration", default)]
+    pub avg_duration: f64,
+}
+
+/// Description of a node type available on the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeTypeDescription {
+    /// Type identifier used in [`NodeDefinition::node_type`]
+    pub name: String,
+    #[serde(rename = "displayName", default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub group: Vec<String>,
+    /// Parameter definitions, as described by the server
+    #[serde(default)]
+    pub properties: Vec<serde_json::Value>,
+}
+
+/// Version information reported by the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerInfo {
+    #[serde(default)]
+    pub version: String,
+    #[serde(rename = "apiVersions", default)]
+    pub api_versions: Vec<String>,
+    /// Feature flags enabled on the server
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+impl ServerInfo {
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|enabled| enabled == feature)
+    }
+}
+
 /// Request to create a workflow
 #[derive(Debug, Clone, Serialize)]
 pub struct CreateWorkflowRequest {
@@ -118,63 +539,355 @@ pub struct CreateWorkflowRequest {
     pub description: String,
     pub nodes: Vec<NodeDefinition>,
     pub connections: Vec<Connection>,
-    pub settings: Option<HashMap<String, serde_json::Value>>,
+    #[serde(serialize_with = "ordered_opt")]
+    pub settings: Option<FieldMap>,
+}
+
+/// Change to a collection field of an [`UpdateWorkflowRequest`]
+///
+/// Servers treat a missing field and an empty one differently: a missing
+/// field is left unchanged, while an empty one removes everything in it.
+/// `Keep` omits the field, `Clear` sends an empty collection and `Set` sends
+/// the given value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum FieldUpdate<T> {
+    #[default]
+    Keep,
+    Clear,
+    Set(T),
+}
+
+impl<T> FieldUpdate<T> {
+    pub fn is_keep(&self) -> bool {
+        matches!(self, FieldUpdate::Keep)
+    }
+}
+
+impl<T> From<T> for FieldUpdate<T> {
+    fn from(value: T) -> Self {
+        FieldUpdate::Set(value)
+    }
+}
+
+impl<T: Serialize + Default> Serialize for FieldUpdate<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            // Only reached when the containing struct does not skip `Keep`
+            FieldUpdate::Keep => serializer.serialize_none(),
+            FieldUpdate::Clear => T::default().serialize(serializer),
+            FieldUpdate::Set(value) => value.serialize(serializer),
+        }
+    }
 }
 
 /// Request to update a workflow
-#[derive(Debug, Clone, Serialize)]
+///
+/// Fields left at their default are omitted, so the server keeps their
+/// current values:
+///
+/// ```rust
+/// use klikkflow_sdk::{FieldUpdate, UpdateWorkflowRequest};
+/// use serde_json::json;
+///
+/// let keep = UpdateWorkflowRequest {
+///     name: Some("Renamed".to_string()),
+///     ..Default::default()
+/// };
+/// assert_eq!(serde_json::to_value(&keep).unwrap(), json!({ "name": "Renamed" }));
+///
+/// let clear = UpdateWorkflowRequest {
+///     connections: FieldUpdate::Clear,
+///     settings: FieldUpdate::Clear,
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     serde_json::to_value(&clear).unwrap(),
+///     json!({ "connections": [], "settings": {} })
+/// );
+///
+/// let set = UpdateWorkflowRequest {
+///     active: Some(false),
+///     nodes: FieldUpdate::Set(Vec::new()),
+///     settings: [("timezone".to_string(), json!("UTC"))].into_iter().collect::<klikkflow_sdk::FieldMap>().into(),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     serde_json::to_value(&set).unwrap(),
+///     json!({ "active": false, "nodes": [], "settings": { "timezone": "UTC" } })
+/// );
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct UpdateWorkflowRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub active: Option<bool>,
-    pub nodes: Option<Vec<NodeDefinition>>,
-    pub connections: Option<Vec<Connection>>,
-    pub settings: Option<HashMap<String, serde_json::Value>>,
+    #[serde(skip_serializing_if = "FieldUpdate::is_keep")]
+    pub nodes: FieldUpdate<Vec<NodeDefinition>>,
+    #[serde(skip_serializing_if = "FieldUpdate::is_keep")]
+    pub connections: FieldUpdate<Vec<Connection>>,
+    #[serde(skip_serializing_if = "FieldUpdate::is_keep")]
+    pub settings: FieldUpdate<FieldMap>,
 }
 
 /// Request to execute a workflow
+///
+/// Fields may borrow from the caller so large input data is serialized in place.
 #[derive(Debug, Clone, Serialize)]
-pub struct ExecuteWorkflowRequest {
+pub struct ExecuteWorkflowRequest<'a> {
     #[serde(rename = "workflowId")]
-    pub workflow_id: String,
-    #[serde(rename = "inputData")]
-    pub input_data: HashMap<String, serde_json::Value>,
+    pub workflow_id: Cow<'a, str>,
+    #[serde(rename = "inputData", serialize_with = "ordered_cow")]
+    pub input_data: Cow<'a, FieldMap>,
+    #[serde(rename = "idempotencyKey", skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// Id of the failed execution this request retries
+    #[serde(rename = "retryOf", skip_serializing_if = "Option::is_none")]
+    pub retry_of: Option<String>,
+    /// Token of a pre-opened stream that should receive the execution's updates
+    #[serde(rename = "subscriptionToken", skip_serializing_if = "Option::is_none")]
+    pub subscription_token: Option<String>,
+}
+
+/// Problem with a node that prevents a workflow from being activated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeIssue {
+    #[serde(rename = "nodeId")]
+    pub node_id: String,
+    pub kind: NodeIssueKind,
+    pub message: String,
+}
+
+/// Category of a node issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NodeIssueKind {
+    Credentials,
+    Parameters,
+    TypeUnknown,
+    Execution,
+    #[serde(other)]
+    Other,
+}
+
+/// Body of a "workflow has issues" error or issues listing
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct NodeIssuesPayload {
+    #[serde(default)]
+    pub issues: Vec<NodeIssue>,
+}
+
+/// Workflow-level persistent state, e.g. polling cursors of trigger nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaticDataPayload {
+    #[serde(rename = "staticData", default)]
+    pub static_data: HashMap<String, serde_json::Value>,
+}
+
+/// How to handle an execution request while the workflow is already running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingletonPolicy {
+    /// Fail with `Error::AlreadyRunning`
+    Reject,
+    /// Wait for the active executions to finish, then execute
+    Queue,
+    /// Cancel the active executions, then execute
+    CancelPrevious,
+}
+
+/// Options for executing a workflow
+#[derive(Debug, Clone, Default)]
+pub struct ExecuteOptions {
+    /// Wait for the execution to reach a terminal status before returning
+    pub wait_for_completion: bool,
+    /// Guard against concurrent executions of the same workflow
+    pub singleton: Option<SingletonPolicy>,
+    /// Re-execute when the execution fails; implies waiting for completion
+    pub retry: Option<ExecutionRetryPolicy>,
+    /// Execute even if a client-side execution quota is exhausted
+    pub override_quota: bool,
+    /// ID of the failed execution this one retries, recorded as its parent
+    pub retry_of: Option<String>,
+    /// Key identifying the request, see [`idempotency_key`](Self::idempotency_key)
+    pub idempotency_key: Option<String>,
+    /// Token abandoning the call when cancelled, see [`cancellation`](Self::cancellation)
+    pub cancellation: Option<CancellationToken>,
+    /// Cancel the execution on the server when the call is abandoned
+    pub cancel_on_drop: bool,
+    /// Instant by which the call must complete, see [`deadline`](Self::deadline)
+    pub deadline: Option<Instant>,
+}
+
+impl ExecuteOptions {
+    /// Create options that return as soon as the execution was submitted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for the execution to finish before returning
+    pub fn wait_for_completion(mut self, wait: bool) -> Self {
+        self.wait_for_completion = wait;
+        self
+    }
+
+    /// Never run this execution concurrently with another one of the same workflow
+    pub fn singleton(mut self, policy: SingletonPolicy) -> Self {
+        self.singleton = Some(policy);
+        self
+    }
+
+    /// Re-execute with the same input when the execution fails with a retryable error
+    pub fn retry_on_failure(mut self, policy: ExecutionRetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Execute even if an execution quota is exhausted, for break-glass situations
+    ///
+    /// The execution still counts against the quota.
+    pub fn override_quota(mut self, override_quota: bool) -> Self {
+        self.override_quota = override_quota;
+        self
+    }
+
+    /// Identify the execution request by `key`, so sending it again starts no second execution
+    ///
+    /// The key is sent in the [`IDEMPOTENCY_KEY_HEADER`] and reused when the
+    /// request is retried under the client's [`RetryPolicy`](crate::RetryPolicy).
+    /// Without a key, one is generated when the client retries requests.
+    /// With [`retry_on_failure`](Self::retry_on_failure), each re-execution
+    /// is a new request and gets the key suffixed with its attempt number.
+    ///
+    /// When the server echoes the key in its response, it is recorded in
+    /// [`ExecutionResult::idempotency_key`].
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::{Client, ExecuteOptions, FieldMap};
+    ///
+    /// let client = Client::new("https://klikkflow.example.com");
+    /// // Safe to resend: a charge already started is returned rather than started again
+    /// let options = ExecuteOptions::new().idempotency_key("charge-42");
+    /// let execution = client
+    ///     .execute_workflow_with_options("wf-billing", FieldMap::new(), options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Abandon the call once `token` is cancelled
+    ///
+    /// The call stops submitting or polling at once and fails with
+    /// [`Error::Cancelled`](crate::Error::Cancelled). The execution keeps
+    /// running on the server unless [`cancel_on_drop`](Self::cancel_on_drop)
+    /// is set as well.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::{CancellationToken, Client, ExecuteOptions, FieldMap};
+    ///
+    /// let client = Client::new("https://klikkflow.example.com");
+    /// let token = CancellationToken::new();
+    /// let options = ExecuteOptions::new()
+    ///     .wait_for_completion(true)
+    ///     .cancellation(token.clone())
+    ///     .cancel_on_drop(true);
+    /// let export = client.execute_workflow_with_options("wf-export", FieldMap::new(), options);
+    /// // Elsewhere, e.g. on Ctrl-C: token.cancel()
+    /// export.await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Cancel the execution on the server when the call is abandoned before it finished
+    ///
+    /// Applies when the [`cancellation`](Self::cancellation) token is
+    /// cancelled, and when the call's future is dropped while it waits for
+    /// the execution; the cancel request is then sent in the background.
+    pub fn cancel_on_drop(mut self, cancel: bool) -> Self {
+        self.cancel_on_drop = cancel;
+        self
+    }
+
+    /// Record the execution as a retry of `execution_id`
+    ///
+    /// The server reports it as the parent in [`TriggerSource::parent_execution_id`],
+    /// so retry chains can be followed with [`Client::get_retry_chain`](crate::Client::get_retry_chain).
+    pub fn retry_of(mut self, execution_id: impl Into<String>) -> Self {
+        self.retry_of = Some(execution_id.into());
+        self
+    }
+
+    /// Fail the call with [`Error::DeadlineExceeded`](crate::Error::DeadlineExceeded) if it is still running at `deadline`
+    ///
+    /// The deadline covers the whole call: submitting the execution and its
+    /// retries, waiting for it to finish, and re-executing it under
+    /// [`retry_on_failure`](Self::retry_on_failure). It replaces the default
+    /// 5-minute limit of the wait, and takes precedence over the deadline of
+    /// the client's [`RequestOptions`](crate::RequestOptions). The execution
+    /// keeps running on the server.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::{Client, ExecuteOptions, FieldMap};
+    /// use std::time::Duration;
+    ///
+    /// let client = Client::new("https://klikkflow.example.com");
+    /// let options = ExecuteOptions::new()
+    ///     .wait_for_completion(true)
+    ///     .budget(Duration::from_secs(30));
+    /// client
+    ///     .execute_workflow_with_options("wf-export", FieldMap::new(), options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set the [`deadline`](Self::deadline) to `budget` from now
+    pub fn budget(self, budget: Duration) -> Self {
+        self.deadline(Instant::now() + budget)
+    }
 }
 
 /// Options for listing workflows
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ListWorkflowsOptions {
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    /// Cursor from a previous page's [`next_cursor`](crate::Page::next_cursor)
+    pub cursor: Option<String>,
     pub active_only: bool,
 }
 
-impl Default for ListWorkflowsOptions {
-    fn default() -> Self {
-        Self {
-            limit: None,
-            offset: None,
-            active_only: false,
-        }
-    }
-}
-
 /// Options for getting execution history
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ExecutionHistoryOptions {
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    /// Cursor from a previous page's [`next_cursor`](crate::Page::next_cursor)
+    pub cursor: Option<String>,
     pub status: Option<ExecutionStatus>,
-}
-
-impl Default for ExecutionHistoryOptions {
-    fn default() -> Self {
-        Self {
-            limit: None,
-            offset: None,
-            status: None,
-        }
-    }
+    /// Only executions started this way
+    pub mode: Option<ExecutionMode>,
+    /// Whether to return input and output data; without it, listings of many executions stay small
+    pub include_data: Option<bool>,
 }
 
 /// WebSocket update message
@@ -184,4 +897,119 @@ pub struct ExecutionUpdate {
     pub update_type: String,
     pub data: serde_json::Value,
     pub timestamp: DateTime<Utc>,
-}
\ No newline at end of file
+}
+
+/// Kind of a WebSocket update, derived from its `type` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateKind {
+    ExecutionStarted,
+    NodeStarted,
+    NodeCompleted,
+    ExecutionCompleted,
+    Other,
+}
+
+impl ExecutionUpdate {
+    /// Classify the update, accepting `nodeStarted`, `node_started` or `node:started` spellings
+    pub fn kind(&self) -> UpdateKind {
+        let normalized: String = self
+            .update_type
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match normalized.as_str() {
+            "executionstarted" => UpdateKind::ExecutionStarted,
+            "nodestarted" => UpdateKind::NodeStarted,
+            "nodecompleted" | "nodefinished" => UpdateKind::NodeCompleted,
+            "executioncompleted" | "executionfinished" => UpdateKind::ExecutionCompleted,
+            _ => UpdateKind::Other,
+        }
+    }
+
+    /// ID of the node the update refers to, if any
+    pub fn node_id(&self) -> Option<&str> {
+        self.data.get("nodeId")?.as_str()
+    }
+
+    /// Name of the node the update refers to, if any
+    pub fn node_name(&self) -> Option<&str> {
+        self.data.get("nodeName")?.as_str()
+    }
+
+    /// Status reported by the update, if any
+    pub fn status(&self) -> Option<&str> {
+        self.data.get("status")?.as_str()
+    }
+}
+
+/// Deserialize node results, keeping entries of an unexpected shape in [`NodeResult::raw`]
+///
+/// A `null` map is read as empty.
+fn lenient_node_results<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<FieldMap<NodeResult>, D::Error> {
+    let entries = Option::<FieldMap<serde_json::Value>>::deserialize(deserializer)?;
+    Ok(entries
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(node, entry)| {
+            let result = NodeResult::deserialize(&entry).unwrap_or_else(|_| NodeResult {
+                raw: Some(entry),
+                ..Default::default()
+            });
+            (node, result)
+        })
+        .collect())
+}
+
+/// Serialize a map with its keys in insertion order, see [`FieldMap`]
+#[cfg(feature = "ordered-maps")]
+fn ordered<S: serde::Serializer, V: Serialize>(
+    map: &FieldMap<V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.serialize(serializer)
+}
+
+/// Serialize a map with its keys sorted, so equal maps produce identical JSON
+#[cfg(not(feature = "ordered-maps"))]
+fn ordered<S: serde::Serializer, V: Serialize>(
+    map: &FieldMap<V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter()
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .serialize(serializer)
+}
+
+/// Remove a key from a [`FieldMap`], keeping the other keys in order
+#[cfg(feature = "ordered-maps")]
+pub(crate) fn remove_field<V>(map: &mut FieldMap<V>, key: &str) -> Option<V> {
+    map.shift_remove(key)
+}
+
+/// Remove a key from a [`FieldMap`]
+#[cfg(not(feature = "ordered-maps"))]
+pub(crate) fn remove_field<V>(map: &mut FieldMap<V>, key: &str) -> Option<V> {
+    map.remove(key)
+}
+
+fn ordered_opt<S: serde::Serializer>(
+    map: &Option<FieldMap>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match map {
+        Some(map) => ordered(map, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+// The signature is fixed by `serialize_with`
+#[allow(clippy::ptr_arg)]
+fn ordered_cow<S: serde::Serializer>(
+    map: &Cow<'_, FieldMap>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    ordered(map, serializer)
+}
@@ -0,0 +1,174 @@
+//! Execution updates as newline-delimited JSON
+//!
+//! [`Client::stream_execution_ndjson`] writes one JSON object per line. Keys
+//! are sorted and no whitespace is added, so the same events always produce
+//! the same bytes. Every line has these fields:
+//!
+//! | Field         | Value                                                  |
+//! |---------------|--------------------------------------------------------|
+//! | `v`           | Schema version, currently `1`                          |
+//! | `seq`         | Line number, starting at `0`                           |
+//! | `event`       | `connected`, `update`, `gap` or `closed`               |
+//! | `executionId` | The execution being streamed                           |
+//!
+//! `update` lines add the update's `type` as sent by the server, its `kind`
+//! (`execution_started`, `node_started`, `node_completed`,
+//! `execution_completed` or `other`), its `timestamp` and its `data`.
+//!
+//! `gap` lines mark updates that may be missing and add a `reason`:
+//! `undecodable` for a message that could not be read, with the message in
+//! `raw`, or `disconnected` when the connection dropped before the execution
+//! finished and is about to be reopened.
+//!
+//! The last line is always `closed`, with a `reason` of `completed` after the
+//! server reported completion, `finished` when the execution turned out to be
+//! finished after the connection dropped, or `disconnected` when reconnecting
+//! gave up. `status` holds the execution's final status when known.
+//!
+//! New fields may be added within a schema version; existing fields keep
+//! their meaning until `v` changes.
+
+use crate::client::Client;
+use crate::models::{ExecutionUpdate, UpdateKind};
+use crate::{Error, Result};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::{debug, warn};
+
+/// Version of the line schema, written as `v` on every line
+pub const NDJSON_SCHEMA_VERSION: u64 = 1;
+
+/// Times a dropped connection is reopened before giving up
+const MAX_RECONNECTS: u32 = 5;
+
+/// Writes the lines of one execution stream
+struct LineWriter<'a, W> {
+    writer: W,
+    execution_id: &'a str,
+    seq: u64,
+}
+
+impl<W: AsyncWrite + Unpin> LineWriter<'_, W> {
+    /// Write one line with the common fields added, then flush it
+    async fn write(&mut self, event: &str, mut fields: Value) -> Result<()> {
+        if let Value::Object(map) = &mut fields {
+            map.insert("v".to_string(), json!(NDJSON_SCHEMA_VERSION));
+            map.insert("seq".to_string(), json!(self.seq));
+            map.insert("event".to_string(), json!(event));
+            map.insert("executionId".to_string(), json!(self.execution_id));
+        }
+        let mut line =
+            serde_json::to_vec(&fields).map_err(|e| Error::Serialization(e.to_string()))?;
+        line.push(b'\n');
+        self.writer.write_all(&line).await.map_err(io_error)?;
+        self.writer.flush().await.map_err(io_error)?;
+        self.seq += 1;
+        Ok(())
+    }
+
+    async fn update(&mut self, update: &ExecutionUpdate) -> Result<()> {
+        let kind = match update.kind() {
+            UpdateKind::ExecutionStarted => "execution_started",
+            UpdateKind::NodeStarted => "node_started",
+            UpdateKind::NodeCompleted => "node_completed",
+            UpdateKind::ExecutionCompleted => "execution_completed",
+            UpdateKind::Other => "other",
+        };
+        let fields = json!({
+            "type": update.update_type,
+            "kind": kind,
+            "timestamp": update.timestamp,
+            "data": update.data,
+        });
+        self.write("update", fields).await
+    }
+
+    async fn closed(&mut self, reason: &str, status: Option<&str>) -> Result<()> {
+        let mut fields = json!({ "reason": reason });
+        if let Some(status) = status {
+            fields["status"] = json!(status);
+        }
+        self.write("closed", fields).await
+    }
+}
+
+impl Client {
+    /// Write the updates of an execution to `writer` as newline-delimited JSON
+    ///
+    /// Returns once the execution has finished, after writing a `closed`
+    /// line. Each line is flushed as soon as it is written, so the output can
+    /// be piped to tools such as `jq`. A dropped connection is reopened,
+    /// marked by a `gap` line, unless the execution finished meanwhile. See
+    /// the [module documentation](crate::ndjson) for the line schema.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// client.stream_execution_ndjson("ex-1", tokio::io::stdout()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stream_execution_ndjson<W>(&self, execution_id: &str, writer: W) -> Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = LineWriter {
+            writer,
+            execution_id,
+            seq: 0,
+        };
+        let mut reconnects = 0;
+        loop {
+            let mut stream = self.stream_execution(execution_id).await?;
+            lines.write("connected", json!({})).await?;
+            while let Some(item) = stream.next().await {
+                match item {
+                    Ok(update) => {
+                        lines.update(&update).await?;
+                        if update.kind() == UpdateKind::ExecutionCompleted {
+                            let _ = stream.close().await;
+                            return lines.closed("completed", update.status()).await;
+                        }
+                    }
+                    Err(Error::MessageDecode { raw, .. }) => {
+                        let fields = json!({ "reason": "undecodable", "raw": raw });
+                        lines.write("gap", fields).await?;
+                    }
+                    Err(e) => {
+                        debug!("Execution stream of {} failed: {}", execution_id, e);
+                        break;
+                    }
+                }
+            }
+
+            let execution = self.get_execution(execution_id).await?;
+            if execution.status.is_terminal() {
+                return lines
+                    .closed("finished", Some(execution.status.as_str()))
+                    .await;
+            }
+            if reconnects == MAX_RECONNECTS {
+                lines.closed("disconnected", None).await?;
+                return Err(Error::WebSocket(format!(
+                    "execution stream of {} dropped {} times",
+                    execution_id,
+                    reconnects + 1
+                )));
+            }
+            reconnects += 1;
+            warn!(
+                "Execution stream of {} dropped, reconnecting (attempt {})",
+                execution_id, reconnects
+            );
+            lines
+                .write("gap", json!({ "reason": "disconnected" }))
+                .await?;
+        }
+    }
+}
+
+fn io_error(error: std::io::Error) -> Error {
+    Error::Io(error.to_string())
+}
@@ -0,0 +1,174 @@
+use crate::client::{path_segment, Client};
+use crate::models::*;
+use crate::timeouts::OperationClass;
+use crate::{Error, Result};
+use reqwest::header::ETAG;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, info};
+
+#[derive(Serialize)]
+struct NodeParametersPatch<'a> {
+    parameters: &'a FieldMap,
+    merge: bool,
+}
+
+/// Apply parameter updates to a node's parameters
+///
+/// Keys absent from `updates` are always kept. With `merge == false` every
+/// key in `updates` replaces the existing value as a whole. With
+/// `merge == true` objects are merged recursively: nested keys absent from
+/// the update are kept, while arrays and scalars are replaced.
+///
+/// ```rust
+/// use klikkflow_sdk::{merge_node_parameters, FieldMap};
+/// use serde_json::json;
+///
+/// let existing: FieldMap = serde_json::from_value(json!({
+///     "url": "https://api.example.com",
+///     "options": { "timeout": 30, "retry": { "count": 3, "delay": 5 } }
+/// }))
+/// .unwrap();
+/// let updates: FieldMap =
+///     serde_json::from_value(json!({ "options": { "retry": { "count": 5 } } })).unwrap();
+///
+/// // Replace: the whole `options` object is swapped out
+/// let mut replaced = existing.clone();
+/// merge_node_parameters(&mut replaced, updates.clone(), false);
+/// assert_eq!(replaced["options"], json!({ "retry": { "count": 5 } }));
+/// assert_eq!(replaced["url"], "https://api.example.com");
+///
+/// // Deep merge: only `options.retry.count` changes
+/// let mut merged = existing.clone();
+/// merge_node_parameters(&mut merged, updates, true);
+/// assert_eq!(
+///     merged["options"],
+///     json!({ "timeout": 30, "retry": { "count": 5, "delay": 5 } })
+/// );
+/// ```
+pub fn merge_node_parameters(existing: &mut FieldMap, updates: FieldMap, merge: bool) {
+    for (key, update) in updates {
+        match existing.get_mut(&key) {
+            Some(current) if merge => deep_merge(current, update),
+            _ => {
+                existing.insert(key, update);
+            }
+        }
+    }
+}
+
+fn deep_merge(current: &mut Value, update: Value) {
+    match (current, update) {
+        (Value::Object(current), Value::Object(update)) => {
+            for (key, value) in update {
+                match current.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        current.insert(key, value);
+                    }
+                }
+            }
+        }
+        (current, update) => *current = update,
+    }
+}
+
+/// Whether a failed node-level update means the server lacks the endpoint
+///
+/// A `404` naming the workflow or node means that is missing instead.
+fn endpoint_unavailable(error: &Error) -> bool {
+    match error {
+        Error::NotFound { id, .. } => id.is_none(),
+        error => matches!(error.status(), Some(405 | 501)),
+    }
+}
+
+impl Client {
+    /// Update the parameters of a single node without resending the whole workflow
+    ///
+    /// Uses the server's node-level `PATCH` endpoint when available. When the
+    /// server answers it with `405` or `501`, or a `404` naming no workflow or
+    /// node, the workflow is fetched and written back with `If-Match` set to its
+    /// ETag, changing only this node, so a concurrent edit makes the update
+    /// fail with a `412` [`Error::Api`] instead of being overwritten. See
+    /// [`merge_node_parameters`] for the meaning of `merge`.
+    pub async fn update_node_parameters(
+        &self,
+        workflow_id: &str,
+        node_id: &str,
+        params: FieldMap,
+        merge: bool,
+    ) -> Result<NodeDefinition> {
+        info!(
+            "Updating parameters of node {} in workflow {}",
+            node_id, workflow_id
+        );
+        let path = format!(
+            "/api/workflows/{}/nodes/{}/parameters",
+            path_segment(workflow_id),
+            path_segment(node_id)
+        );
+        let patch = NodeParametersPatch {
+            parameters: &params,
+            merge,
+        };
+        match self
+            .make_request(OperationClass::Mutate, "PATCH", &path, Some(&patch))
+            .await
+        {
+            Err(e) if endpoint_unavailable(&e) => {
+                debug!("Node-level update unavailable, falling back to a conditional update");
+            }
+            result => return result,
+        }
+
+        let workflow_path = format!("/api/workflows/{}", path_segment(workflow_id));
+        let (mut workflow, headers): (WorkflowDefinition, _) = self
+            .make_request_with_headers(
+                OperationClass::Read,
+                "GET",
+                &workflow_path,
+                None::<&()>,
+                &[],
+            )
+            .await?;
+        let etag = headers
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                Error::Unsupported(
+                    "server returned no ETag for a conditional node update".to_string(),
+                )
+            })?;
+
+        let node = workflow
+            .nodes
+            .iter_mut()
+            .find(|node| node.id == node_id)
+            .ok_or_else(|| {
+                Error::InvalidInput(format!("workflow {} has no node {}", workflow_id, node_id))
+            })?;
+        merge_node_parameters(&mut node.parameters, params, merge);
+        let updated = node.clone();
+
+        let request = UpdateWorkflowRequest {
+            name: Some(workflow.name),
+            description: Some(workflow.description),
+            active: Some(workflow.active),
+            nodes: FieldUpdate::Set(workflow.nodes),
+            connections: FieldUpdate::Set(workflow.connections),
+            settings: FieldUpdate::Set(workflow.settings),
+        };
+        let _: (serde_json::Value, _) = self
+            .make_request_with_headers(
+                OperationClass::Mutate,
+                "PUT",
+                &workflow_path,
+                Some(&request),
+                &[("If-Match", etag)],
+            )
+            .await?;
+        Ok(updated)
+    }
+}
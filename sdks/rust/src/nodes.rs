@@ -0,0 +1,296 @@
+//! Builders for commonly created node types
+//!
+//! ```rust
+//! use klikkflow_sdk::nodes::{BodyMode, HttpRequestNode};
+//!
+//! let node = HttpRequestNode::new("Create user", "POST", "https://api.example.com/users")
+//!     .query("notify", "true")
+//!     .header("X-Source", "rust-sdk")
+//!     .json_body(&serde_json::json!({ "name": "Ada" }))
+//!     .unwrap()
+//!     .auth_credential("Example API")
+//!     .build();
+//!
+//! // Read it back, switch to a form body and rebuild
+//! let mut builder = HttpRequestNode::from_node(&node).unwrap();
+//! assert!(matches!(builder.body, BodyMode::Json(_)));
+//! builder = builder.form_field("name", "Ada");
+//! assert!(matches!(builder.body, BodyMode::Form(_)));
+//! let updated = builder.build();
+//! assert_eq!(updated.parameters["contentType"], "form-urlencoded");
+//! ```
+
+use crate::models::{remove_field, FieldMap, NodeDefinition, Position};
+use crate::{Error, Result};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// Node type identifier of the HTTP Request node
+pub const HTTP_REQUEST_NODE_TYPE: &str = "http-request";
+
+/// Parameters describing the body of an HTTP Request node, across all body modes
+const BODY_PARAMETERS: [&str; 5] = [
+    "contentType",
+    "jsonBody",
+    "bodyParameters",
+    "rawContentType",
+    "body",
+];
+
+/// Request body of an HTTP Request node
+#[derive(Debug, Clone, PartialEq)]
+pub enum BodyMode {
+    /// No request body
+    None,
+    /// JSON body
+    Json(Value),
+    /// `application/x-www-form-urlencoded` fields
+    Form(Vec<(String, String)>),
+    /// Raw body sent with an explicit content type
+    Raw { content_type: String, body: String },
+}
+
+/// Builder producing a correctly shaped HTTP Request [`NodeDefinition`]
+#[derive(Debug, Clone)]
+pub struct HttpRequestNode {
+    pub id: String,
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    pub position: Position,
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: BodyMode,
+    pub credential: Option<String>,
+    /// Parameters not managed by the builder, preserved when modifying an existing node
+    pub extra: FieldMap,
+}
+
+impl HttpRequestNode {
+    /// Create a builder for a request with the given method and URL
+    pub fn new(name: impl Into<String>, method: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.into(),
+            method: method.into().to_uppercase(),
+            url: url.into(),
+            position: Position { x: 0.0, y: 0.0 },
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: BodyMode::None,
+            credential: None,
+            extra: FieldMap::new(),
+        }
+    }
+
+    /// Read an existing HTTP Request node back into a builder for modification
+    pub fn from_node(node: &NodeDefinition) -> Result<Self> {
+        if node.node_type != HTTP_REQUEST_NODE_TYPE {
+            return Err(Error::InvalidInput(format!(
+                "node {} has type {}, expected {}",
+                node.id, node.node_type, HTTP_REQUEST_NODE_TYPE
+            )));
+        }
+
+        let mut params = node.parameters.clone();
+        let method = take_string(&mut params, "method").unwrap_or_else(|| "GET".to_string());
+        let url = take_string(&mut params, "url").unwrap_or_default();
+        remove_field(&mut params, "sendQuery");
+        remove_field(&mut params, "sendHeaders");
+        remove_field(&mut params, "sendBody");
+        remove_field(&mut params, "authentication");
+
+        let query = take_pairs(&mut params, "queryParameters");
+        let headers = take_pairs(&mut params, "headerParameters");
+        let credential = remove_field(&mut params, "credential")
+            .and_then(|value| value.as_str().map(str::to_string));
+
+        let body = match take_string(&mut params, "contentType").as_deref() {
+            Some("json") => {
+                BodyMode::Json(remove_field(&mut params, "jsonBody").unwrap_or(Value::Null))
+            }
+            Some("form-urlencoded") => BodyMode::Form(take_pairs(&mut params, "bodyParameters")),
+            Some("raw") => BodyMode::Raw {
+                content_type: take_string(&mut params, "rawContentType").unwrap_or_default(),
+                body: take_string(&mut params, "body").unwrap_or_default(),
+            },
+            _ => BodyMode::None,
+        };
+        // Leftovers of other body modes would be sent along with the current one
+        for key in BODY_PARAMETERS {
+            remove_field(&mut params, key);
+        }
+
+        Ok(Self {
+            id: node.id.clone(),
+            name: node.name.clone(),
+            method,
+            url,
+            position: node.position.clone(),
+            query,
+            headers,
+            body,
+            credential,
+            extra: params,
+        })
+    }
+
+    /// Use an explicit node id instead of a generated one
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Set the node position on the canvas
+    pub fn position(mut self, x: f64, y: f64) -> Self {
+        self.position = Position { x, y };
+        self
+    }
+
+    /// Add a query parameter
+    pub fn query(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    /// Add a request header
+    pub fn header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// Send a JSON body, replacing any previous body
+    pub fn json_body(mut self, body: &impl Serialize) -> Result<Self> {
+        let value = serde_json::to_value(body).map_err(|e| Error::Serialization(e.to_string()))?;
+        self.body = BodyMode::Json(value);
+        Ok(self)
+    }
+
+    /// Add a form field, switching to a form body if another body mode was set
+    pub fn form_field(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        match &mut self.body {
+            BodyMode::Form(fields) => fields.push((key.into(), value.into())),
+            _ => self.body = BodyMode::Form(vec![(key.into(), value.into())]),
+        }
+        self
+    }
+
+    /// Send a raw body with the given content type, replacing any previous body
+    pub fn raw_body(mut self, content_type: impl Into<String>, body: impl Into<String>) -> Self {
+        self.body = BodyMode::Raw {
+            content_type: content_type.into(),
+            body: body.into(),
+        };
+        self
+    }
+
+    /// Remove the request body
+    pub fn no_body(mut self) -> Self {
+        self.body = BodyMode::None;
+        self
+    }
+
+    /// Authenticate using the credential with the given name
+    pub fn auth_credential(mut self, name: impl Into<String>) -> Self {
+        self.credential = Some(name.into());
+        self
+    }
+
+    /// Produce the node definition
+    ///
+    /// With the default `ordered-maps` feature, the parameters keep the
+    /// order of [`extra`](Self::extra), followed by those the builder
+    /// manages in a fixed order, so building twice gives identical JSON.
+    pub fn build(self) -> NodeDefinition {
+        let mut parameters = self.extra;
+        parameters.insert("method".to_string(), json!(self.method));
+        parameters.insert("url".to_string(), json!(self.url));
+
+        parameters.insert("sendQuery".to_string(), json!(!self.query.is_empty()));
+        if !self.query.is_empty() {
+            parameters.insert("queryParameters".to_string(), pairs_value(&self.query));
+        }
+
+        parameters.insert("sendHeaders".to_string(), json!(!self.headers.is_empty()));
+        if !self.headers.is_empty() {
+            parameters.insert("headerParameters".to_string(), pairs_value(&self.headers));
+        }
+
+        match self.credential {
+            Some(credential) => {
+                parameters.insert("authentication".to_string(), json!("credential"));
+                parameters.insert("credential".to_string(), json!(credential));
+            }
+            None => {
+                parameters.insert("authentication".to_string(), json!("none"));
+            }
+        }
+
+        parameters.insert("sendBody".to_string(), json!(self.body != BodyMode::None));
+        match self.body {
+            BodyMode::None => {}
+            BodyMode::Json(body) => {
+                parameters.insert("contentType".to_string(), json!("json"));
+                parameters.insert("jsonBody".to_string(), body);
+            }
+            BodyMode::Form(fields) => {
+                parameters.insert("contentType".to_string(), json!("form-urlencoded"));
+                parameters.insert("bodyParameters".to_string(), pairs_value(&fields));
+            }
+            BodyMode::Raw { content_type, body } => {
+                parameters.insert("contentType".to_string(), json!("raw"));
+                parameters.insert("rawContentType".to_string(), json!(content_type));
+                parameters.insert("body".to_string(), json!(body));
+            }
+        }
+
+        NodeDefinition {
+            id: self.id,
+            name: self.name,
+            node_type: HTTP_REQUEST_NODE_TYPE.to_string(),
+            position: self.position,
+            parameters,
+        }
+    }
+}
+
+/// Encode name/value pairs as `{ "parameters": [{ "name", "value" }] }`
+fn pairs_value(pairs: &[(String, String)]) -> Value {
+    let parameters: Vec<Value> = pairs
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect();
+    json!({ "parameters": parameters })
+}
+
+fn take_pairs(params: &mut FieldMap, key: &str) -> Vec<(String, String)> {
+    let Some(value) = remove_field(params, key) else {
+        return Vec::new();
+    };
+    value
+        .get("parameters")
+        .and_then(Value::as_array)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(Value::as_object)
+                .map(|entry| (pair_field(entry, "name"), pair_field(entry, "value")))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn pair_field(entry: &Map<String, Value>, field: &str) -> String {
+    match entry.get(field) {
+        Some(Value::String(text)) => text.clone(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    }
+}
+
+fn take_string(params: &mut FieldMap, key: &str) -> Option<String> {
+    match remove_field(params, key)? {
+        Value::String(text) => Some(text),
+        other => Some(other.to_string()),
+    }
+}
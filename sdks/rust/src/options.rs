@@ -0,0 +1,188 @@
+use crate::retry::RetryPolicy;
+use crate::scheduler::Priority;
+use crate::{Error, Result};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Request header selecting the workspace of a multi-tenant deployment
+pub const WORKSPACE_HEADER: &str = "X-Reporunner-Workspace";
+
+/// Request header carrying the [`Priority`] class of the request
+pub const PRIORITY_HEADER: &str = "X-Request-Priority";
+
+/// Prefix of the request headers carrying tags, followed by the tag key
+pub const REQUEST_TAG_HEADER_PREFIX: &str = "X-Request-Tag-";
+
+/// Tag the SDK puts on requests it sends on its own
+///
+/// Its value names the traffic: `wait-poll` for the polls of a wait on an
+/// execution, `tail-poll`, `watch-poll` and `summary-poll` for those of
+/// [`Client::tail_latest_execution`](crate::Client::tail_latest_execution),
+/// [`Client::watch_executions`](crate::Client::watch_executions) and
+/// [`Client::poll_summaries`](crate::Client::poll_summaries), and
+/// `failback-probe` for the probes of a failed primary base URL, see
+/// [`Client::with_fallback_urls`](crate::Client::with_fallback_urls).
+///
+/// ```rust
+/// use klikkflow_sdk::{REQUEST_TAG_HEADER_PREFIX, SDK_INTERNAL_TAG};
+///
+/// // What a server sees on the polls of a wait
+/// let header = format!("{}{}", REQUEST_TAG_HEADER_PREFIX, SDK_INTERNAL_TAG);
+/// assert_eq!(header, "X-Request-Tag-sdk-internal");
+/// ```
+pub const SDK_INTERNAL_TAG: &str = "sdk-internal";
+
+/// Maximum length of a tag key
+pub const MAX_TAG_KEY_LEN: usize = 64;
+
+/// Maximum length of a tag value
+pub const MAX_TAG_VALUE_LEN: usize = 256;
+
+/// Options for the requests of a scoped client, see [`Client::with_request_options`](crate::Client::with_request_options)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// Scheduling class, used when the client has a scheduler and sent in the [`PRIORITY_HEADER`]
+    pub priority: Priority,
+    /// Timeout of each request, replacing the client's timeout and timeout profile
+    pub timeout: Option<Duration>,
+    /// Workspace of each request, replacing the client's workspace
+    pub workspace: Option<String>,
+    /// Retry requests of any method under the retry policy
+    pub idempotent: bool,
+    /// Retry policy of each request, replacing the client's one
+    pub retry: Option<RetryPolicy>,
+    /// Instant by which each call must complete, retries and polling included
+    pub deadline: Option<Instant>,
+    /// Tags of each request by lowercase key, added to the client's default tags
+    pub tags: BTreeMap<String, String>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn workspace(mut self, workspace: impl Into<String>) -> Self {
+        self.workspace = Some(workspace.into());
+        self
+    }
+
+    /// Declare the requests safe to send more than once, so POSTs are retried too
+    pub fn idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = idempotent;
+        self
+    }
+
+    /// Retry requests under `policy`, or not at all with [`RetryPolicy::disabled`]
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Fail calls still running at `deadline` with [`Error::DeadlineExceeded`](crate::Error::DeadlineExceeded)
+    ///
+    /// Unlike [`timeout`](Self::timeout), which bounds each request, the
+    /// deadline bounds the whole call: every retry under the retry policy and,
+    /// for calls waiting on an execution, the polling as well, replacing the
+    /// default 5-minute limit of the wait. A retry whose backoff would end
+    /// past the deadline is not attempted; the call fails right away.
+    ///
+    /// ```rust
+    /// use klikkflow_sdk::{Client, RequestOptions};
+    /// use std::time::Duration;
+    ///
+    /// let client = Client::new("https://klikkflow.example.com");
+    /// let bounded = client.with_request_options(RequestOptions::new().budget(Duration::from_secs(2)));
+    /// ```
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set the [`deadline`](Self::deadline) to `budget` from now
+    pub fn budget(self, budget: Duration) -> Self {
+        self.deadline(Instant::now() + budget)
+    }
+
+    /// Tag each request with `key`, sent as the `X-Request-Tag-{key}` header
+    ///
+    /// Tags let the server attribute load, e.g. to the feature making the
+    /// calls. Keys are case-insensitive; tagging a key again replaces its
+    /// value, and a tag replaces a default tag of the client with the same
+    /// key. Keys are up to [`MAX_TAG_KEY_LEN`] ASCII letters, digits and
+    /// dashes; values up to [`MAX_TAG_VALUE_LEN`] printable ASCII characters
+    /// without surrounding spaces. Requests with an invalid tag fail with
+    /// [`Error::InvalidInput`] before being sent.
+    ///
+    /// ```rust
+    /// use klikkflow_sdk::{Client, Priority, RequestOptions};
+    ///
+    /// let client = Client::new("https://klikkflow.example.com").with_tag("team", "platform")?;
+    /// let checkout = client.with_request_options(
+    ///     RequestOptions::new()
+    ///         .priority(Priority::Background)
+    ///         .tag("feature", "checkout"),
+    /// );
+    /// # Ok::<(), klikkflow_sdk::Error>(())
+    /// ```
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags
+            .insert(key.into().to_ascii_lowercase(), value.into());
+        self
+    }
+}
+
+/// Check a tag against the rules of [`RequestOptions::tag`], returning what is wrong with it
+pub(crate) fn check_tag(key: &str, value: &str) -> std::result::Result<(), String> {
+    if key.is_empty() || key.len() > MAX_TAG_KEY_LEN {
+        return Err(format!("key must be 1 to {} characters", MAX_TAG_KEY_LEN));
+    }
+    if !key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+        return Err("key may only contain ASCII letters, digits and dashes".to_string());
+    }
+    if value.is_empty() || value.len() > MAX_TAG_VALUE_LEN {
+        return Err(format!(
+            "value must be 1 to {} characters",
+            MAX_TAG_VALUE_LEN
+        ));
+    }
+    if !value.bytes().all(|b| (b' '..=b'~').contains(&b)) {
+        return Err("value may only contain printable ASCII characters".to_string());
+    }
+    if value.trim() != value {
+        return Err("value must not start or end with a space".to_string());
+    }
+    Ok(())
+}
+
+/// Add the headers of `defaults` and `tags` to `headers`, `tags` taking precedence
+pub(crate) fn insert_tag_headers(
+    headers: &mut HeaderMap,
+    defaults: &BTreeMap<String, String>,
+    tags: &BTreeMap<String, String>,
+) -> Result<()> {
+    let defaults = defaults.iter().filter(|(key, _)| !tags.contains_key(*key));
+    for (key, value) in defaults.chain(tags) {
+        check_tag(key, value).map_err(|reason| {
+            Error::InvalidInput(format!("invalid request tag {}: {}", key, reason))
+        })?;
+        let name = format!("{}{}", REQUEST_TAG_HEADER_PREFIX, key);
+        // Both are checked to be header-safe above
+        headers.insert(
+            HeaderName::from_bytes(name.as_bytes()).expect("valid tag header name"),
+            HeaderValue::from_str(value).expect("valid tag header value"),
+        );
+    }
+    Ok(())
+}
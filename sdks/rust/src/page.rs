@@ -0,0 +1,89 @@
+use crate::Result;
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+
+/// One page of results from a list endpoint
+///
+/// Servers may leave out any of the envelope fields. A missing `has_more` is
+/// inferred from `next_cursor`, `total` or whether the page came back full.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items across all pages, if the server reports it
+    #[serde(default)]
+    pub total: Option<u64>,
+    /// Cursor to pass to fetch the next page, for cursor-paginated endpoints
+    #[serde(rename = "nextCursor", default)]
+    pub next_cursor: Option<String>,
+    /// Whether more pages follow this one
+    #[serde(rename = "hasMore", default)]
+    pub has_more: bool,
+}
+
+impl<T> Page<T> {
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl<T> IntoIterator for Page<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+/// Position of a page to fetch
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PageRequest {
+    pub offset: usize,
+    pub cursor: Option<String>,
+}
+
+impl PageRequest {
+    /// Request for the page following `page`, if there is one
+    pub fn after<T>(&self, page: &Page<T>) -> Option<Self> {
+        if !page.has_more || page.is_empty() {
+            return None;
+        }
+        Some(Self {
+            offset: self.offset + page.len(),
+            cursor: page.next_cursor.clone(),
+        })
+    }
+}
+
+/// Stream every item of a paginated endpoint, fetching pages with `fetch` as needed
+///
+/// Follows the server's cursor when it sends one and advances the offset
+/// otherwise. Ends after the first error.
+pub(crate) fn paginate<T, F, Fut>(start: PageRequest, fetch: F) -> impl Stream<Item = Result<T>>
+where
+    F: FnMut(PageRequest) -> Fut,
+    Fut: Future<Output = Result<Page<T>>>,
+{
+    let state = (fetch, Some(start), VecDeque::new());
+    stream::unfold(state, |(mut fetch, mut next, mut buffered)| async move {
+        loop {
+            if let Some(item) = buffered.pop_front() {
+                return Some((Ok(item), (fetch, next, buffered)));
+            }
+            let request = next.take()?;
+            match fetch(request.clone()).await {
+                Ok(page) => {
+                    next = request.after(&page);
+                    buffered.extend(page.items);
+                }
+                Err(e) => return Some((Err(e), (fetch, None, buffered))),
+            }
+        }
+    })
+}
@@ -0,0 +1,199 @@
+use crate::capabilities::Capability;
+use crate::client::{path_segment, Client};
+use crate::models::*;
+use crate::timeouts::OperationClass;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{debug, warn};
+
+/// Progress of an execution as reported by the status endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionSnapshot {
+    pub id: String,
+    pub status: ExecutionStatus,
+    #[serde(rename = "startedAt")]
+    pub started_at: DateTime<Utc>,
+    #[serde(rename = "finishedAt", default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Nodes that have finished, successfully or not
+    #[serde(rename = "completedNodes", default)]
+    pub completed_nodes: usize,
+    /// Nodes in the workflow, if known
+    #[serde(rename = "totalNodes", default)]
+    pub total_nodes: Option<usize>,
+    /// Name of the node that finished most recently
+    #[serde(rename = "lastNode", default)]
+    pub last_node: Option<String>,
+}
+
+impl ExecutionSnapshot {
+    /// Time the execution has been running, or ran for if it finished
+    pub fn elapsed(&self) -> Duration {
+        let end = self.finished_at.unwrap_or_else(Utc::now);
+        (end - self.started_at).to_std().unwrap_or_default()
+    }
+
+    /// One-line progress summary for terminal output
+    ///
+    /// The format is stable and locale-independent:
+    /// `<Status> · <completed>/<total> nodes · <elapsed> · last: <node>`, where
+    /// the total is left out when unknown, elapsed time is written like `42s`,
+    /// `3m05s` or `1h02m03s`, and the last part is left out before any node
+    /// finished. Use [`summary_json`](Self::summary_json) for a machine-readable form.
+    ///
+    /// ```rust
+    /// use klikkflow_sdk::ExecutionSnapshot;
+    ///
+    /// let snapshot: ExecutionSnapshot = serde_json::from_str(r#"{
+    ///     "id": "ex-1", "status": "success",
+    ///     "startedAt": "2024-01-01T00:00:00Z", "finishedAt": "2024-01-01T00:03:05Z",
+    ///     "completedNodes": 50, "totalNodes": 50, "lastNode": "Transform Data"
+    /// }"#).unwrap();
+    /// assert_eq!(
+    ///     snapshot.summary_line(),
+    ///     "Success · 50/50 nodes · 3m05s · last: Transform Data"
+    /// );
+    /// assert_eq!(snapshot.summary_json()["elapsedSeconds"], 185);
+    /// ```
+    pub fn summary_line(&self) -> String {
+        let status = match self.status {
+            ExecutionStatus::Pending => "Pending",
+            ExecutionStatus::Running => "Running",
+            ExecutionStatus::Success => "Success",
+            ExecutionStatus::Error => "Error",
+            ExecutionStatus::Cancelled => "Cancelled",
+        };
+        let nodes = match self.total_nodes {
+            Some(total) => format!("{}/{} nodes", self.completed_nodes, total),
+            None => format!("{} nodes", self.completed_nodes),
+        };
+        let mut line = format!(
+            "{} · {} · {}",
+            status,
+            nodes,
+            format_elapsed(self.elapsed())
+        );
+        if let Some(node) = &self.last_node {
+            line.push_str(" · last: ");
+            line.push_str(node);
+        }
+        line
+    }
+
+    /// Machine-readable counterpart of [`summary_line`](Self::summary_line)
+    pub fn summary_json(&self) -> Value {
+        json!({
+            "executionId": self.id,
+            "status": self.status.as_str(),
+            "completedNodes": self.completed_nodes,
+            "totalNodes": self.total_nodes,
+            "elapsedSeconds": self.elapsed().as_secs(),
+            "lastNode": self.last_node,
+        })
+    }
+}
+
+impl From<&ExecutionResult> for ExecutionSnapshot {
+    fn from(execution: &ExecutionResult) -> Self {
+        let workflow = execution.workflow_snapshot.as_ref();
+        let last_node = execution
+            .node_results
+            .iter()
+            .filter_map(|(id, result)| result.finished_at.map(|at| (at, id)))
+            .max()
+            .map(|(_, id)| {
+                workflow
+                    .and_then(|w| w.nodes.iter().find(|node| &node.id == id))
+                    .map_or_else(|| id.clone(), |node| node.name.clone())
+            });
+        Self {
+            id: execution.id.clone(),
+            status: execution.status.clone(),
+            started_at: execution.started_at,
+            finished_at: execution.finished_at,
+            completed_nodes: execution.metadata.completed_nodes,
+            total_nodes: Some(execution.metadata.total_nodes),
+            last_node,
+        }
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m{:02}s", m, s),
+        (h, m, s) => format!("{}h{:02}m{:02}s", h, m, s),
+    }
+}
+
+impl Client {
+    /// Get the progress of an execution
+    ///
+    /// Uses the lightweight status endpoint, falling back to the full
+    /// execution on servers that do not have it.
+    pub async fn get_execution_snapshot(&self, execution_id: &str) -> Result<ExecutionSnapshot> {
+        let fallback = || async {
+            debug!("No status endpoint, fetching execution {}", execution_id);
+            let execution = self.get_execution_with_workflow(execution_id).await?;
+            Ok(ExecutionSnapshot::from(&execution))
+        };
+        if self
+            .manifest_capabilities()
+            .is_some_and(|capabilities| !capabilities.supports(Capability::ExecutionStatus))
+        {
+            return fallback().await;
+        }
+
+        let path = format!("/api/executions/{}/status", path_segment(execution_id));
+        match self
+            .make_request(OperationClass::Read, "GET", &path, None::<&()>)
+            .await
+        {
+            Err(e) if matches!(e.status(), Some(404 | 405 | 501)) => {
+                self.capability_unavailable(Capability::ExecutionStatus);
+                fallback().await
+            }
+            other => other,
+        }
+    }
+
+    /// Poll an execution every `interval`, yielding a [`summary_line`](ExecutionSnapshot::summary_line) each time
+    ///
+    /// The stream ends after the line for the terminal status. Retryable
+    /// errors are yielded without ending the stream; any other error ends it.
+    pub fn poll_summaries(
+        &self,
+        execution_id: &str,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<String>> {
+        let state = (
+            self.internal("summary-poll"),
+            execution_id.to_string(),
+            false,
+        );
+        stream::unfold(Some(state), move |state| async move {
+            let (client, execution_id, polled) = state?;
+            if polled {
+                sleep(interval).await;
+            }
+            match client.get_execution_snapshot(&execution_id).await {
+                Ok(snapshot) => {
+                    let next =
+                        (!snapshot.status.is_terminal()).then_some((client, execution_id, true));
+                    Some((Ok(snapshot.summary_line()), next))
+                }
+                Err(e) => {
+                    warn!("Failed to poll execution {}: {}", execution_id, e);
+                    let next = e.is_retryable().then_some((client, execution_id, true));
+                    Some((Err(e), next))
+                }
+            }
+        })
+    }
+}
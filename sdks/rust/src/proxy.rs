@@ -0,0 +1,148 @@
+use crate::{Error, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use percent_encoding::percent_decode_str;
+use std::fmt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tracing::debug;
+use url::Url;
+
+/// Largest proxy response to a `CONNECT` accepted before giving up
+const MAX_CONNECT_RESPONSE: usize = 8 * 1024;
+
+/// Proxy configured with [`Client::with_proxy`](crate::Client::with_proxy)
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct Proxy {
+    url: Url,
+}
+
+/// Whether requests go through a proxy
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) enum ProxyMode {
+    /// Proxies from the `HTTP_PROXY` family of environment variables, for API requests only
+    #[default]
+    System,
+    /// No proxy, even if the environment configures one
+    Disabled,
+    /// This proxy, for API requests and execution streams
+    Explicit(Proxy),
+}
+
+impl Proxy {
+    pub fn parse(url: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::InvalidProxy(format!("{}: {}", redacted(url), reason));
+        let url = Url::parse(url).map_err(|e| invalid(&e.to_string()))?;
+        match url.scheme() {
+            "http" | "https" => {}
+            "socks5" | "socks5h" => {
+                return Err(invalid("SOCKS proxies are not supported by this build"))
+            }
+            scheme => return Err(invalid(&format!("unsupported scheme {}", scheme))),
+        }
+        if url.host_str().is_none() {
+            return Err(invalid("no host"));
+        }
+        Ok(Self { url })
+    }
+
+    /// The proxy for reqwest, which takes the credentials from the URL
+    pub fn to_reqwest(&self) -> Result<reqwest::Proxy> {
+        reqwest::Proxy::all(self.url.as_str())
+            .map_err(|e| Error::InvalidProxy(format!("{}: {}", self, e)))
+    }
+
+    /// `Proxy-Authorization` value for the credentials in the URL, if any
+    fn basic_auth(&self) -> Option<String> {
+        if self.url.username().is_empty() {
+            return None;
+        }
+        let decode = |part: &str| percent_decode_str(part).decode_utf8_lossy().into_owned();
+        let username = decode(self.url.username());
+        let password = decode(self.url.password().unwrap_or_default());
+        Some(format!(
+            "Basic {}",
+            BASE64.encode(format!("{}:{}", username, password))
+        ))
+    }
+
+    /// Open a connection to `host:port` tunnelled through the proxy with `CONNECT`
+    pub async fn tunnel(&self, host: &str, port: u16) -> Result<TcpStream> {
+        if self.url.scheme() != "http" {
+            return Err(Error::Unsupported(format!(
+                "execution streams through {} proxies",
+                self.url.scheme()
+            )));
+        }
+        let proxy_host = self.url.host_str().unwrap_or_default();
+        let proxy_port = self.url.port_or_known_default().unwrap_or(80);
+        let failed = |e: std::io::Error| Error::WebSocket(format!("proxy {}: {}", self, e));
+        let mut stream = TcpStream::connect((proxy_host, proxy_port))
+            .await
+            .map_err(failed)?;
+
+        let target = if host.contains(':') {
+            format!("[{}]:{}", host, port)
+        } else {
+            format!("{}:{}", host, port)
+        };
+        let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+        if let Some(auth) = self.basic_auth() {
+            request.push_str(&format!("Proxy-Authorization: {}\r\n", auth));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await.map_err(failed)?;
+
+        // Read byte by byte so nothing after the proxy's response is consumed
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            if response.len() >= MAX_CONNECT_RESPONSE {
+                return Err(Error::WebSocket(format!(
+                    "proxy {} sent an oversized response",
+                    self
+                )));
+            }
+            let byte = stream.read_u8().await.map_err(failed)?;
+            response.push(byte);
+        }
+        let response = String::from_utf8_lossy(&response);
+        let status_line = response.lines().next().unwrap_or_default();
+        if status_line.split_whitespace().nth(1) != Some("200") {
+            return Err(Error::WebSocket(format!(
+                "proxy {} refused to connect to {}: {}",
+                self, target, status_line
+            )));
+        }
+        debug!("Tunnelled to {} through proxy {}", target, self);
+        Ok(stream)
+    }
+}
+
+/// Shows the proxy URL without its password
+impl fmt::Display for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&redacted(self.url.as_str()))
+    }
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Proxy").field(&self.to_string()).finish()
+    }
+}
+
+/// `url` with any password replaced, for errors and logs
+fn redacted(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some("***"));
+            parsed.to_string()
+        }
+        Ok(parsed) => parsed.to_string(),
+        // Unparsable, so the password cannot be located; keep only the scheme
+        Err(_) => match url.split_once("://") {
+            Some((scheme, _)) => format!("{}://...", scheme),
+            None => "...".to_string(),
+        },
+    }
+}
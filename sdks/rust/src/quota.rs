@@ -0,0 +1,192 @@
+use crate::client::Client;
+use crate::redact::glob_match;
+use crate::{Error, Result};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Window over which execution quotas are counted
+const QUOTA_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Executions of one workflow counted against a quota, see [`Client::quota_status`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaStatus {
+    /// Pattern of the quota, as passed to [`ClientBuilder::execution_quota`](crate::ClientBuilder::execution_quota)
+    pub pattern: String,
+    pub workflow_id: String,
+    /// Executions started in the last hour
+    pub used: u32,
+    pub max_per_hour: u32,
+    /// Time until the oldest counted execution leaves the window
+    pub resets_in: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct QuotaRule {
+    pub pattern: String,
+    pub max_per_hour: u32,
+}
+
+/// Sliding-window execution counters, shared by all clones of a client
+#[derive(Debug)]
+pub(crate) struct QuotaTracker {
+    rules: Vec<QuotaRule>,
+    /// Start times of recent executions per rule index and workflow
+    started: Mutex<HashMap<(usize, String), VecDeque<Instant>>>,
+}
+
+impl QuotaTracker {
+    pub fn new(rules: Vec<QuotaRule>) -> Self {
+        Self {
+            rules,
+            started: Mutex::default(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<(usize, String), VecDeque<Instant>>> {
+        self.started.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Count an execution of `workflow_id` against every quota matching it
+    ///
+    /// Fails with [`Error::QuotaExceeded`] if one of them is exhausted, unless
+    /// `bypass` is set. The returned reservation gives the slot back when
+    /// dropped without being [committed](Reservation::commit), e.g. because
+    /// the execution could not be submitted.
+    pub fn reserve(self: &Arc<Self>, workflow_id: &str, bypass: bool) -> Result<Reservation> {
+        let now = Instant::now();
+        let mut started = self.lock();
+        let matching: Vec<usize> = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| glob_match(&rule.pattern, workflow_id))
+            .map(|(index, _)| index)
+            .collect();
+
+        for &index in &matching {
+            let window = started.entry((index, workflow_id.to_string())).or_default();
+            while window
+                .front()
+                .is_some_and(|&at| now.duration_since(at) >= QUOTA_WINDOW)
+            {
+                window.pop_front();
+            }
+            let rule = &self.rules[index];
+            if window.len() >= rule.max_per_hour as usize {
+                let resets_in = window.front().map_or(Duration::ZERO, |&oldest| {
+                    QUOTA_WINDOW.saturating_sub(now.duration_since(oldest))
+                });
+                if !bypass {
+                    return Err(Error::QuotaExceeded {
+                        workflow_id: workflow_id.to_string(),
+                        resets_in,
+                    });
+                }
+                warn!(
+                    "Bypassing exhausted execution quota {} for workflow {}",
+                    rule.pattern, workflow_id
+                );
+            }
+        }
+
+        for &index in &matching {
+            if let Some(window) = started.get_mut(&(index, workflow_id.to_string())) {
+                window.push_back(now);
+            }
+        }
+        Ok(Reservation {
+            tracker: Some(Arc::clone(self)),
+            workflow_id: workflow_id.to_string(),
+            rules: matching,
+            at: now,
+            committed: false,
+        })
+    }
+
+    fn status(&self) -> Vec<QuotaStatus> {
+        let now = Instant::now();
+        let started = self.lock();
+        let mut status: Vec<QuotaStatus> = started
+            .iter()
+            .filter_map(|((index, workflow_id), window)| {
+                let recent: Vec<Instant> = window
+                    .iter()
+                    .copied()
+                    .filter(|&at| now.duration_since(at) < QUOTA_WINDOW)
+                    .collect();
+                let oldest = *recent.first()?;
+                let rule = &self.rules[*index];
+                Some(QuotaStatus {
+                    pattern: rule.pattern.clone(),
+                    workflow_id: workflow_id.clone(),
+                    used: recent.len() as u32,
+                    max_per_hour: rule.max_per_hour,
+                    resets_in: QUOTA_WINDOW.saturating_sub(now.duration_since(oldest)),
+                })
+            })
+            .collect();
+        status.sort_by(|a, b| (&a.pattern, &a.workflow_id).cmp(&(&b.pattern, &b.workflow_id)));
+        status
+    }
+}
+
+/// An execution counted against quotas before it was submitted
+pub(crate) struct Reservation {
+    /// `None` for clients without quotas
+    tracker: Option<Arc<QuotaTracker>>,
+    workflow_id: String,
+    rules: Vec<usize>,
+    at: Instant,
+    committed: bool,
+}
+
+impl Reservation {
+    /// Keep the execution counted
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        let Some(tracker) = self.tracker.as_ref().filter(|_| !self.committed) else {
+            return;
+        };
+        let mut started = tracker.lock();
+        for &index in &self.rules {
+            if let Some(window) = started.get_mut(&(index, self.workflow_id.clone())) {
+                if let Some(position) = window.iter().rposition(|&at| at == self.at) {
+                    window.remove(position);
+                }
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Executions counted against each quota in the last hour, per workflow
+    ///
+    /// Only workflows that started an execution within the window are listed.
+    /// Empty when the client has no execution quotas.
+    pub fn quota_status(&self) -> Vec<QuotaStatus> {
+        self.quota_tracker()
+            .map(|tracker| tracker.status())
+            .unwrap_or_default()
+    }
+
+    /// Reserve a quota slot for an execution of `workflow_id`
+    pub(crate) fn reserve_execution(&self, workflow_id: &str, bypass: bool) -> Result<Reservation> {
+        match self.quota_tracker() {
+            Some(tracker) => tracker.reserve(workflow_id, bypass),
+            None => Ok(Reservation {
+                tracker: None,
+                workflow_id: workflow_id.to_string(),
+                rules: Vec::new(),
+                at: Instant::now(),
+                committed: false,
+            }),
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use crate::models::FieldMap;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::sync::Arc;
+
+/// Replacement for masked values unless configured otherwise
+pub const DEFAULT_MASK: &str = "[REDACTED]";
+
+/// What happens to a value selected by a redaction rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactionAction {
+    /// Remove the value, or the array element, entirely
+    Drop,
+    /// Replace the value with the policy's mask
+    Mask,
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+    /// JSON pointer segments; `*` matches any key or index
+    Pointer(Vec<String>),
+    /// Lowercased glob matched against object keys at any depth
+    Key(String),
+}
+
+impl Selector {
+    fn matches(&self, path: &[String]) -> bool {
+        match self {
+            Selector::Pointer(segments) => {
+                segments.len() == path.len()
+                    && segments
+                        .iter()
+                        .zip(path)
+                        .all(|(segment, part)| segment == "*" || segment == part)
+            }
+            Selector::Key(pattern) => path
+                .last()
+                .is_some_and(|key| glob_match(pattern, &key.to_lowercase())),
+        }
+    }
+}
+
+/// A value removed or masked from execution input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redaction {
+    /// JSON pointer of the value within the input data
+    pub pointer: String,
+    pub action: RedactionAction,
+}
+
+/// What a policy redacted from the input of one execution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedactionAudit {
+    pub workflow_id: String,
+    pub redactions: Vec<Redaction>,
+}
+
+type AuditCallback = Arc<dyn Fn(&RedactionAudit) + Send + Sync>;
+
+/// Fields to drop or mask from execution input before it is sent
+///
+/// Rules select values either by JSON pointer into the input data, where a
+/// `*` segment matches any key or array index, or by a key pattern matched
+/// case-insensitively against object keys at any depth, including objects
+/// inside arrays. A key pattern may contain `*` wildcards. The first matching
+/// rule wins, and redacted values are not searched further.
+///
+/// ```rust
+/// use klikkflow_sdk::{FieldMap, RedactionPolicy};
+/// use serde_json::json;
+///
+/// let policy = RedactionPolicy::new()
+///     .mask_keys("*token*")
+///     .drop_pointer("/users/*/email");
+/// let input: FieldMap = serde_json::from_value(json!({
+///     "apiToken": "secret",
+///     "users": [
+///         { "name": "Ada", "email": "ada@example.com" },
+///         { "name": "Bob", "email": "bob@example.com", "refresh_token": "x" }
+///     ]
+/// }))
+/// .unwrap();
+///
+/// let (redacted, redactions) = policy.redact(&input);
+/// assert_eq!(
+///     serde_json::to_value(&redacted).unwrap(),
+///     json!({
+///         "apiToken": "[REDACTED]",
+///         "users": [
+///             { "name": "Ada" },
+///             { "name": "Bob", "refresh_token": "[REDACTED]" }
+///         ]
+///     })
+/// );
+/// assert_eq!(redactions.len(), 4);
+/// // The caller's data is untouched
+/// assert_eq!(input["apiToken"], "secret");
+/// ```
+#[derive(Clone)]
+pub struct RedactionPolicy {
+    rules: Vec<(Selector, RedactionAction)>,
+    mask: Value,
+    audit: Option<AuditCallback>,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RedactionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedactionPolicy")
+            .field("rules", &self.rules)
+            .field("mask", &self.mask)
+            .field("audit", &self.audit.is_some())
+            .finish()
+    }
+}
+
+impl RedactionPolicy {
+    /// Policy without rules
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            mask: Value::String(DEFAULT_MASK.to_string()),
+            audit: None,
+        }
+    }
+
+    /// Drop the values at a JSON pointer such as `/customer/email` or `/items/*/token`
+    pub fn drop_pointer(self, pointer: &str) -> Self {
+        self.pointer_rule(pointer, RedactionAction::Drop)
+    }
+
+    /// Mask the values at a JSON pointer
+    pub fn mask_pointer(self, pointer: &str) -> Self {
+        self.pointer_rule(pointer, RedactionAction::Mask)
+    }
+
+    /// Drop the values of all keys matching `pattern`, e.g. `email` or `*_token`
+    pub fn drop_keys(self, pattern: &str) -> Self {
+        self.key_rule(pattern, RedactionAction::Drop)
+    }
+
+    /// Mask the values of all keys matching `pattern`
+    pub fn mask_keys(self, pattern: &str) -> Self {
+        self.key_rule(pattern, RedactionAction::Mask)
+    }
+
+    /// Replace masked values with `mask` instead of [`DEFAULT_MASK`]
+    pub fn mask_with(mut self, mask: impl Into<Value>) -> Self {
+        self.mask = mask.into();
+        self
+    }
+
+    /// Call `callback` for every execution whose input had values redacted
+    pub fn on_redact<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&RedactionAudit) + Send + Sync + 'static,
+    {
+        self.audit = Some(Arc::new(callback));
+        self
+    }
+
+    fn pointer_rule(mut self, pointer: &str, action: RedactionAction) -> Self {
+        let segments = pointer
+            .split('/')
+            .skip(1)
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect();
+        self.rules.push((Selector::Pointer(segments), action));
+        self
+    }
+
+    fn key_rule(mut self, pattern: &str, action: RedactionAction) -> Self {
+        self.rules
+            .push((Selector::Key(pattern.to_lowercase()), action));
+        self
+    }
+
+    /// Redacted copy of `input`, with the list of redacted values
+    pub fn redact(&self, input: &FieldMap) -> (FieldMap, Vec<Redaction>) {
+        let mut redactions = Vec::new();
+        let mut path = Vec::new();
+        let mut object: Map<String, Value> = input
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        self.redact_object(&mut object, &mut path, &mut redactions);
+        // Back in the order of the input, less dropped keys
+        let redacted = input
+            .keys()
+            .filter_map(|key| Some((key.clone(), object.remove(key)?)))
+            .collect();
+        (redacted, redactions)
+    }
+
+    /// Redact `input` for an execution of `workflow_id`, reporting to the audit callback
+    pub(crate) fn apply(&self, workflow_id: &str, input: &FieldMap) -> FieldMap {
+        let (redacted, redactions) = self.redact(input);
+        if let (Some(audit), false) = (&self.audit, redactions.is_empty()) {
+            audit(&RedactionAudit {
+                workflow_id: workflow_id.to_string(),
+                redactions,
+            });
+        }
+        redacted
+    }
+
+    /// Action of the first rule selecting `path`; key patterns never select array elements
+    fn action_for(&self, path: &[String], array_element: bool) -> Option<RedactionAction> {
+        self.rules
+            .iter()
+            .filter(|(selector, _)| !(array_element && matches!(selector, Selector::Key(_))))
+            .find(|(selector, _)| selector.matches(path))
+            .map(|(_, action)| *action)
+    }
+
+    fn redact_object(
+        &self,
+        object: &mut Map<String, Value>,
+        path: &mut Vec<String>,
+        redactions: &mut Vec<Redaction>,
+    ) {
+        let keys: Vec<String> = object.keys().cloned().collect();
+        for key in keys {
+            path.push(key.clone());
+            match self.action_for(path, false) {
+                Some(action) => {
+                    match action {
+                        RedactionAction::Drop => {
+                            object.remove(&key);
+                        }
+                        RedactionAction::Mask => {
+                            object.insert(key, self.mask.clone());
+                        }
+                    }
+                    redactions.push(Redaction {
+                        pointer: to_pointer(path),
+                        action,
+                    });
+                }
+                None => {
+                    if let Some(value) = object.get_mut(&key) {
+                        self.redact_value(value, path, redactions);
+                    }
+                }
+            }
+            path.pop();
+        }
+    }
+
+    fn redact_value(
+        &self,
+        value: &mut Value,
+        path: &mut Vec<String>,
+        redactions: &mut Vec<Redaction>,
+    ) {
+        match value {
+            Value::Object(object) => self.redact_object(object, path, redactions),
+            Value::Array(items) => {
+                let mut index = 0;
+                items.retain_mut(|item| {
+                    path.push(index.to_string());
+                    index += 1;
+                    let keep = match self.action_for(path, true) {
+                        Some(action) => {
+                            redactions.push(Redaction {
+                                pointer: to_pointer(path),
+                                action,
+                            });
+                            if action == RedactionAction::Mask {
+                                *item = self.mask.clone();
+                            }
+                            action == RedactionAction::Mask
+                        }
+                        None => {
+                            self.redact_value(item, path, redactions);
+                            true
+                        }
+                    };
+                    path.pop();
+                    keep
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn to_pointer(path: &[String]) -> String {
+    path.iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+/// `secret` for logs and `Debug` output: its last four characters, and only
+/// for secrets long enough that they give little away
+pub(crate) fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() < 12 {
+        return "****".to_string();
+    }
+    let last: String = chars[chars.len() - 4..].iter().collect();
+    format!("****{}", last)
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, rest) = parts.split_first().expect("split yields at least one part");
+    let Some(mut remaining) = text.strip_prefix(first) else {
+        return false;
+    };
+    let Some((last, middle)) = rest.split_last() else {
+        return remaining.is_empty();
+    };
+    for part in middle {
+        match remaining.find(part) {
+            Some(position) => remaining = &remaining[position + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
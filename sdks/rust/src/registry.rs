@@ -0,0 +1,174 @@
+use crate::client::Client;
+use crate::models::*;
+use crate::redact::mask_secret;
+use crate::{Error, Result};
+use futures_util::future::{self, join_all};
+use futures_util::TryStreamExt;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// Profile file layout: one `[instances.<name>]` table per instance
+#[derive(Debug, Deserialize)]
+struct ProfileFile {
+    #[serde(default)]
+    instances: BTreeMap<String, InstanceConfig>,
+}
+
+/// Connection settings for a single named instance
+#[derive(Deserialize)]
+struct InstanceConfig {
+    base_url: String,
+    api_key: Option<String>,
+    timeout_secs: Option<u64>,
+}
+
+impl std::fmt::Debug for InstanceConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InstanceConfig")
+            .field("base_url", &self.base_url)
+            .field("api_key", &self.api_key.as_deref().map(mask_secret))
+            .field("timeout_secs", &self.timeout_secs)
+            .finish()
+    }
+}
+
+/// Health of a single instance as reported by [`ClientRegistry::health`]
+#[derive(Debug, Clone)]
+pub struct InstanceHealth {
+    pub healthy: bool,
+    pub latency: Duration,
+    pub error: Option<String>,
+}
+
+/// Named set of clients for talking to several KlikkFlow instances
+#[derive(Clone, Default)]
+pub struct ClientRegistry {
+    clients: BTreeMap<String, Client>,
+}
+
+impl ClientRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load instances from a TOML profile file.
+    ///
+    /// Each instance is declared in its own table:
+    ///
+    /// ```toml
+    /// [instances.eu]
+    /// base_url = "https://eu.klikkflow.example.com"
+    /// api_key = "..."
+    /// timeout_secs = 30
+    /// ```
+    pub fn from_profile(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("failed to read {}: {}", path.display(), e)))?;
+        Self::from_profile_str(&contents)
+    }
+
+    /// Load instances from the contents of a TOML profile file
+    pub fn from_profile_str(contents: &str) -> Result<Self> {
+        let profile: ProfileFile = toml::from_str(contents)
+            .map_err(|e| Error::Config(format!("invalid profile file: {}", e)))?;
+
+        let mut registry = Self::new();
+        for (name, config) in profile.instances {
+            let mut builder = Client::builder().base_url(config.base_url);
+            if let Some(api_key) = config.api_key {
+                builder = builder.api_key(api_key);
+            }
+            if let Some(secs) = config.timeout_secs {
+                builder = builder.timeout(Duration::from_secs(secs));
+            }
+            registry.insert(name, builder.build()?);
+        }
+        Ok(registry)
+    }
+
+    /// Register a client under the given instance name, replacing any existing one
+    pub fn insert(&mut self, name: impl Into<String>, client: Client) {
+        self.clients.insert(name.into(), client);
+    }
+
+    /// Get the client for an instance
+    pub fn client(&self, name: &str) -> Option<&Client> {
+        self.clients.get(name)
+    }
+
+    /// Names of all registered instances
+    pub fn instances(&self) -> impl Iterator<Item = &str> {
+        self.clients.keys().map(String::as_str)
+    }
+
+    fn require(&self, name: &str) -> Result<&Client> {
+        self.client(name)
+            .ok_or_else(|| Error::Config(format!("unknown instance: {}", name)))
+    }
+
+    /// Find workflows with the given name on every instance
+    pub async fn find_workflow(&self, name: &str) -> Result<Vec<(String, WorkflowDefinition)>> {
+        debug!("Searching all instances for workflow: {}", name);
+        let lookups = self.clients.iter().map(|(instance, client)| {
+            client
+                .stream_workflows(ListWorkflowsOptions::default())
+                .try_filter(move |workflow| future::ready(workflow.name == name))
+                .map_ok(move |workflow| (instance.clone(), workflow))
+                .try_collect::<Vec<_>>()
+        });
+
+        let mut found = Vec::new();
+        for result in join_all(lookups).await {
+            found.extend(result?);
+        }
+        Ok(found)
+    }
+
+    /// Execute a workflow on a specific instance
+    pub async fn execute_on(
+        &self,
+        instance: &str,
+        workflow_id: &str,
+        input_data: FieldMap,
+        wait_for_completion: bool,
+    ) -> Result<ExecutionResult> {
+        info!(
+            "Executing workflow {} on instance {}",
+            workflow_id, instance
+        );
+        self.require(instance)?
+            .execute_workflow(workflow_id, input_data, wait_for_completion)
+            .await
+    }
+
+    /// Check the health of every instance concurrently
+    pub async fn health(&self) -> BTreeMap<String, InstanceHealth> {
+        let checks = self.clients.iter().map(|(instance, client)| async move {
+            let started = Instant::now();
+            let result = client.health_check().await;
+            let health = InstanceHealth {
+                healthy: result.is_ok(),
+                latency: started.elapsed(),
+                error: result.err().map(|e| e.to_string()),
+            };
+            (instance.clone(), health)
+        });
+        join_all(checks).await.into_iter().collect()
+    }
+}
+
+impl Client {
+    /// Create a client for one named instance of a TOML profile file.
+    ///
+    /// See [`ClientRegistry::from_profile`] for the file layout.
+    pub fn from_profile(path: impl AsRef<Path>, instance: &str) -> Result<Client> {
+        ClientRegistry::from_profile(path)?
+            .require(instance)
+            .cloned()
+    }
+}
@@ -0,0 +1,187 @@
+use crate::client::Client;
+use crate::models::{ExecutionHistoryOptions, ExecutionMode, ExecutionResult, ExecutionStatus};
+use crate::Result;
+use futures_util::TryStreamExt;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// HTTP statuses that indicate a transient downstream failure, matched as whole words
+const TRANSIENT_STATUSES: &[&str] = &["429", "502", "503", "504"];
+
+/// Phrases of an error message that indicate a transient downstream failure
+const TRANSIENT_MARKERS: &[&str] = &[
+    "too many requests",
+    "bad gateway",
+    "service unavailable",
+    "gateway timeout",
+    "request timeout",
+    "temporarily unavailable",
+    "timed out",
+    "etimedout",
+    "econnreset",
+    "econnrefused",
+    "connection reset",
+];
+
+/// Whether a failed execution looks like it was caused by a transient error
+///
+/// Checks the execution error and the errors of failed nodes for HTTP
+/// 429/502/503/504 statuses, timeouts and connection resets. Statuses only
+/// count as words of their own, so `order 15030` is not a `503`.
+pub fn is_transient_failure(execution: &ExecutionResult) -> bool {
+    if execution.status != ExecutionStatus::Error {
+        return false;
+    }
+    let node_errors = execution
+        .node_results
+        .values()
+        .filter_map(|result| result.error.as_deref());
+    execution
+        .error
+        .as_deref()
+        .into_iter()
+        .chain(node_errors)
+        .any(is_transient_message)
+}
+
+fn is_transient_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    TRANSIENT_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+        || message
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|word| TRANSIENT_STATUSES.contains(&word))
+}
+
+type RetryPredicate = dyn Fn(&ExecutionResult) -> bool + Send + Sync;
+
+/// Policy for re-executing a workflow whose execution failed
+///
+/// Attempts are linked: every attempt is submitted with an idempotency key
+/// derived from a key shared by the whole chain, and with the id of the
+/// execution it retries.
+#[derive(Clone)]
+pub struct ExecutionRetryPolicy {
+    /// Total number of executions, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every further retry
+    pub backoff: Duration,
+    retry_if: Arc<RetryPredicate>,
+}
+
+impl ExecutionRetryPolicy {
+    /// Retry transient failures up to `max_attempts` executions in total
+    pub fn new(max_attempts: u32, backoff: Duration) -> Self {
+        Self {
+            max_attempts,
+            backoff,
+            retry_if: Arc::new(is_transient_failure),
+        }
+    }
+
+    /// Decide which failed executions are retried instead of [`is_transient_failure`]
+    pub fn retry_if(
+        mut self,
+        predicate: impl Fn(&ExecutionResult) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_if = Arc::new(predicate);
+        self
+    }
+
+    pub(crate) fn should_retry(&self, execution: &ExecutionResult, attempt: u32) -> bool {
+        attempt < self.max_attempts
+            && execution.status == ExecutionStatus::Error
+            && (self.retry_if)(execution)
+    }
+
+    /// Delay before the retry following the given attempt
+    pub(crate) fn delay_after(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+impl Default for ExecutionRetryPolicy {
+    fn default() -> Self {
+        Self::new(3, Duration::from_secs(2))
+    }
+}
+
+impl fmt::Debug for ExecutionRetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecutionRetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Attempts made to obtain an execution under an [`ExecutionRetryPolicy`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionAttempts {
+    /// Number of executions submitted, including the returned one
+    pub count: u32,
+    /// Ids of the earlier executions that failed and were retried, oldest first
+    pub failed_execution_ids: Vec<String>,
+}
+
+impl Client {
+    /// All executions in the retry chain of an execution, oldest first
+    ///
+    /// Follows [`parent_execution_id`](ExecutionResult::parent_execution_id)
+    /// back to the original execution, then looks up the later retries among
+    /// the workflow's executions in [`ExecutionMode::Retry`]. The chain
+    /// includes `execution_id` itself; an execution that was never retried
+    /// and retries nothing is a chain of one.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// for execution in client.get_retry_chain("ex-2").await? {
+    ///     println!("{} {:?}", execution.id, execution.status);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_retry_chain(&self, execution_id: &str) -> Result<Vec<ExecutionResult>> {
+        debug!("Getting retry chain of execution: {}", execution_id);
+        let mut chain = vec![self.get_execution(execution_id).await?];
+        let mut seen: HashSet<String> = HashSet::from([execution_id.to_string()]);
+        while let Some(parent) = chain.last().and_then(ExecutionResult::parent_execution_id) {
+            if !seen.insert(parent.to_string()) {
+                warn!("Retry chain of execution {} has a cycle", execution_id);
+                break;
+            }
+            let parent = self.get_execution(parent).await?;
+            chain.push(parent);
+        }
+        chain.reverse();
+
+        let options = ExecutionHistoryOptions {
+            mode: Some(ExecutionMode::Retry),
+            ..Default::default()
+        };
+        let mut retries: HashMap<String, ExecutionResult> = self
+            .stream_execution_history(&chain[0].workflow_id, options)
+            .try_filter_map(|execution| async move {
+                Ok(execution
+                    .parent_execution_id()
+                    .map(str::to_string)
+                    .map(|parent| (parent, execution)))
+            })
+            .try_collect()
+            .await?;
+        while let Some(retry) = retries.remove(&chain[chain.len() - 1].id) {
+            if !seen.insert(retry.id.clone()) {
+                break;
+            }
+            chain.push(retry);
+        }
+        Ok(chain)
+    }
+}
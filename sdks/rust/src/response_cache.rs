@@ -0,0 +1,217 @@
+use crate::client::Client;
+use crate::timeouts::OperationClass;
+use crate::{Error, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::task::AbortHandle;
+use tracing::{debug, warn};
+
+pub(crate) const NODE_TYPES_PATH: &str = "/api/node-types";
+
+/// How [`Client::list_node_types`] and [`Client::get_workflow`] use cached responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CachePolicy {
+    /// Always ask the server; only the schema cache, if configured, is consulted
+    #[default]
+    Fresh,
+    /// Return a cached response at most `max_stale` old immediately and
+    /// refresh it in the background
+    ///
+    /// Older responses are refetched before returning. Writes through the
+    /// client drop the cached responses of the resources they touch.
+    StaleWhileRevalidate { max_stale: Duration },
+}
+
+pub(crate) type RefreshCallback = Arc<dyn Fn(&str, &Value) + Send + Sync>;
+
+struct Entry {
+    body: Value,
+    fetched_at: Instant,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<String, Entry>,
+    revalidating: HashMap<String, AbortHandle>,
+    /// Bumped by every invalidation, so fetches that raced with a write are not stored
+    generation: u64,
+}
+
+/// In-memory cache behind [`CachePolicy::StaleWhileRevalidate`]
+///
+/// Background revalidations only hold a weak reference to the cache and are
+/// aborted when it is dropped along with the last clone of the client.
+pub(crate) struct ResponseCache {
+    max_stale: Duration,
+    state: Mutex<State>,
+}
+
+impl ResponseCache {
+    pub fn new(max_stale: Duration) -> Self {
+        Self {
+            max_stale,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn max_stale(&self) -> Duration {
+        self.max_stale
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Drop the cached responses of `resource`, of resources below it and of those containing it
+    pub fn invalidate(&self, resource: &str) {
+        let related =
+            |key: &str| key == resource || is_below(key, resource) || is_below(resource, key);
+        let mut state = self.lock();
+        state.generation += 1;
+        state.entries.retain(|key, _| !related(key));
+        state.revalidating.retain(|key, task| {
+            if related(key) {
+                task.abort();
+            }
+            !related(key)
+        });
+    }
+
+    /// Invalidate `resource` now and again when the returned guard is dropped
+    ///
+    /// Held across a write, so responses fetched while it was in flight are discarded.
+    pub fn invalidate_around<'a>(&'a self, resource: &'a str) -> Invalidation<'a> {
+        self.invalidate(resource);
+        Invalidation {
+            cache: self,
+            resource,
+        }
+    }
+}
+
+impl Drop for ResponseCache {
+    fn drop(&mut self) {
+        for task in self.lock().revalidating.values() {
+            task.abort();
+        }
+    }
+}
+
+pub(crate) struct Invalidation<'a> {
+    cache: &'a ResponseCache,
+    resource: &'a str,
+}
+
+impl Drop for Invalidation<'_> {
+    fn drop(&mut self) {
+        self.cache.invalidate(self.resource);
+    }
+}
+
+fn is_below(path: &str, parent: &str) -> bool {
+    path.strip_prefix(parent)
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+impl Client {
+    /// GET `path`, going through the response cache when one is configured
+    pub(crate) async fn revalidating_get(&self, path: &str) -> Result<Value> {
+        let Some(cache) = self.response_cache() else {
+            return self.fetch_cacheable(path).await;
+        };
+
+        let generation = {
+            let state = cache.lock();
+            if let Some(entry) = state.entries.get(path) {
+                if entry.fetched_at.elapsed() <= cache.max_stale {
+                    debug!("Serving cached {}, revalidating", path);
+                    let body = entry.body.clone();
+                    drop(state);
+                    self.spawn_revalidation(cache, path);
+                    return Ok(body);
+                }
+            }
+            state.generation
+        };
+
+        let body = self.fetch_cacheable(path).await?;
+        let mut state = cache.lock();
+        if state.generation == generation {
+            state.entries.insert(
+                path.to_string(),
+                Entry {
+                    body: body.clone(),
+                    fetched_at: Instant::now(),
+                },
+            );
+        }
+        Ok(body)
+    }
+
+    fn spawn_revalidation(&self, cache: &Arc<ResponseCache>, path: &str) {
+        let mut state = cache.lock();
+        if state.revalidating.contains_key(path) {
+            return;
+        }
+
+        let weak = Arc::downgrade(cache);
+        // The task must not keep the cache alive, so it uses a client without one
+        let client = self.without_response_cache();
+        let callback = self.cache_refresh_callback();
+        let generation = state.generation;
+        let key = path.to_string();
+        let task = tokio::spawn(async move {
+            let result = client.fetch_cacheable(&key).await;
+            let Some(cache) = weak.upgrade() else {
+                return;
+            };
+            let mut state = cache.lock();
+            state.revalidating.remove(&key);
+            let body = match result {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!("Failed to revalidate {}: {}", key, e);
+                    return;
+                }
+            };
+            if state.generation != generation {
+                debug!("Discarding revalidation of {} after invalidation", key);
+                return;
+            }
+            let changed = state
+                .entries
+                .get(&key)
+                .is_some_and(|entry| entry.body != body);
+            state.entries.insert(
+                key.clone(),
+                Entry {
+                    body: body.clone(),
+                    fetched_at: Instant::now(),
+                },
+            );
+            drop(state);
+            if let (true, Some(callback)) = (changed, callback) {
+                callback(&key, &body);
+            }
+        });
+        state
+            .revalidating
+            .insert(path.to_string(), task.abort_handle());
+    }
+
+    /// Fetch a cacheable resource from the schema cache or the server
+    async fn fetch_cacheable(&self, path: &str) -> Result<Value> {
+        if path == NODE_TYPES_PATH {
+            return self.cached_get(path).await;
+        }
+        self.make_request(OperationClass::Read, "GET", path, None::<&()>)
+            .await
+    }
+}
+
+/// Deserialize a response served through the response cache
+pub(crate) fn from_cached<T: serde::de::DeserializeOwned>(body: Value) -> Result<T> {
+    serde_json::from_value(body).map_err(|e| Error::Serialization(e.to_string()))
+}
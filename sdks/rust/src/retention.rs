@@ -0,0 +1,327 @@
+use crate::client::{path_segment, Client};
+use crate::models::*;
+use crate::timeouts::OperationClass;
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+const RETENTION_PATH: &str = "/api/settings/retention";
+/// Page size used when scanning execution history for old executions
+const HISTORY_PAGE_SIZE: usize = 200;
+
+/// How long the server keeps execution data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Age after which execution data is purged
+    pub max_age: Duration,
+    /// Keep the data of successful executions regardless of age
+    pub keep_successful: bool,
+    /// Keep the data of failed executions regardless of age
+    pub keep_failed: bool,
+}
+
+impl RetentionPolicy {
+    /// Whether the data of an execution that started at `started_at` must be purged by now
+    pub fn requires_purge(&self, status: &ExecutionStatus, started_at: DateTime<Utc>) -> bool {
+        self.requires_purge_before(status, started_at, self.cutoff())
+    }
+
+    /// [`requires_purge`](Self::requires_purge) against a given cutoff
+    pub(crate) fn requires_purge_before(
+        &self,
+        status: &ExecutionStatus,
+        started_at: DateTime<Utc>,
+        cutoff: DateTime<Utc>,
+    ) -> bool {
+        let kept = match status {
+            ExecutionStatus::Success => self.keep_successful,
+            ExecutionStatus::Error => self.keep_failed,
+            ExecutionStatus::Cancelled => false,
+            // Unfinished executions have not produced their data yet
+            ExecutionStatus::Pending | ExecutionStatus::Running => true,
+        };
+        !kept && started_at < cutoff
+    }
+
+    /// Start time before which execution data must be purged
+    pub fn cutoff(&self) -> DateTime<Utc> {
+        self.cutoff_at(Utc::now())
+    }
+
+    /// Start time before which execution data must be purged, as of `now`
+    pub(crate) fn cutoff_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        chrono::Duration::from_std(self.max_age)
+            .ok()
+            .and_then(|max_age| now.checked_sub_signed(max_age))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RetentionPayload {
+    #[serde(rename = "maxAgeSeconds")]
+    max_age_seconds: u64,
+    #[serde(rename = "keepSuccessful", default)]
+    keep_successful: bool,
+    #[serde(rename = "keepFailed", default)]
+    keep_failed: bool,
+}
+
+impl From<RetentionPayload> for RetentionPolicy {
+    fn from(payload: RetentionPayload) -> Self {
+        Self {
+            max_age: Duration::from_secs(payload.max_age_seconds),
+            keep_successful: payload.keep_successful,
+            keep_failed: payload.keep_failed,
+        }
+    }
+}
+
+/// The fields of a history entry needed to pick executions to check
+#[derive(Deserialize)]
+struct ExecutionSummary {
+    id: String,
+    status: ExecutionStatus,
+    #[serde(rename = "startedAt")]
+    started_at: DateTime<Utc>,
+}
+
+/// An execution whose data should have been purged but was not
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionViolation {
+    pub execution_id: String,
+    pub workflow_id: String,
+    pub started_at: DateTime<Utc>,
+    /// Names of the fields that still hold data, e.g. `inputData`
+    pub fields: Vec<&'static str>,
+}
+
+/// Result of [`Client::verify_retention`]
+#[derive(Debug, Clone)]
+pub struct RetentionReport {
+    pub policy: RetentionPolicy,
+    /// Executions that started before this time were expected to be purged
+    pub cutoff: DateTime<Utc>,
+    /// Number of executions checked
+    pub checked: usize,
+    pub violations: Vec<RetentionViolation>,
+}
+
+impl RetentionReport {
+    /// Whether every checked execution was purged
+    ///
+    /// A report that checked nothing is compliant but proves little; see [`checked`](Self::checked).
+    pub fn is_compliant(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl Client {
+    /// Get the server's execution data retention policy
+    ///
+    /// Fails with [`Error::Unsupported`] if the server has no retention settings.
+    pub async fn get_retention_policy(&self) -> Result<RetentionPolicy> {
+        debug!("Getting retention policy");
+        self.make_request::<RetentionPayload, ()>(OperationClass::Read, "GET", RETENTION_PATH, None)
+            .await
+            .map(RetentionPolicy::from)
+            .map_err(unsupported_retention)
+    }
+
+    /// Replace the server's execution data retention policy
+    ///
+    /// Fails with [`Error::Unsupported`] if the server has no retention settings.
+    pub async fn set_retention_policy(&self, policy: RetentionPolicy) -> Result<RetentionPolicy> {
+        info!("Setting retention policy: {:?}", policy);
+        let payload = RetentionPayload {
+            max_age_seconds: policy.max_age.as_secs(),
+            keep_successful: policy.keep_successful,
+            keep_failed: policy.keep_failed,
+        };
+        self.make_request::<RetentionPayload, _>(
+            OperationClass::Mutate,
+            "PUT",
+            RETENTION_PATH,
+            Some(&payload),
+        )
+        .await
+        .map(RetentionPolicy::from)
+        .map_err(unsupported_retention)
+    }
+
+    /// Check that old executions had their data purged according to the retention policy
+    ///
+    /// Picks up to `sample_size` executions that the policy requires to be
+    /// purged, spread across every page of workflows, and reports every one
+    /// that still holds input, output or node data.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let report = client.verify_retention(50).await?;
+    /// for violation in &report.violations {
+    ///     eprintln!("{} kept {:?}", violation.execution_id, violation.fields);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_retention(&self, sample_size: usize) -> Result<RetentionReport> {
+        let policy = self.get_retention_policy().await?;
+        // Execution start times are by the server's clock
+        let cutoff = policy.cutoff_at(self.server_now());
+        info!(
+            "Verifying retention of executions started before {}",
+            cutoff
+        );
+
+        let mut candidates: Vec<VecDeque<(String, ExecutionSummary)>> = Vec::new();
+        let workflows = self.stream_workflows(ListWorkflowsOptions::default());
+        futures_util::pin_mut!(workflows);
+        while let Some(workflow) = workflows.try_next().await? {
+            let found = self
+                .purge_candidates(&workflow.id, &policy, cutoff, sample_size)
+                .await?;
+            if !found.is_empty() {
+                candidates.push(
+                    found
+                        .into_iter()
+                        .map(|e| (workflow.id.clone(), e))
+                        .collect(),
+                );
+            }
+        }
+
+        // Take candidates round-robin so every workflow is represented
+        let mut sample = Vec::with_capacity(sample_size);
+        while sample.len() < sample_size && candidates.iter().any(|c| !c.is_empty()) {
+            for workflow in candidates.iter_mut() {
+                if sample.len() == sample_size {
+                    break;
+                }
+                if let Some(candidate) = workflow.pop_front() {
+                    sample.push(candidate);
+                }
+            }
+        }
+
+        let mut violations = Vec::new();
+        let mut checked = 0;
+        for (workflow_id, summary) in sample {
+            // Fetched untyped, since purged executions may lack the data fields entirely
+            let path = format!("/api/executions/{}", path_segment(&summary.id));
+            let execution: serde_json::Value = match self
+                .make_request(OperationClass::Read, "GET", &path, None::<&()>)
+                .await
+            {
+                Ok(execution) => execution,
+                // Deleting whole executions satisfies the policy as well
+                Err(Error::NotFound { .. }) => {
+                    checked += 1;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            checked += 1;
+            let fields = retained_fields(&execution);
+            if !fields.is_empty() {
+                warn!(
+                    "Execution {} still holds data after its retention period",
+                    summary.id
+                );
+                violations.push(RetentionViolation {
+                    execution_id: summary.id,
+                    workflow_id,
+                    started_at: summary.started_at,
+                    fields,
+                });
+            }
+        }
+
+        Ok(RetentionReport {
+            policy,
+            cutoff,
+            checked,
+            violations,
+        })
+    }
+
+    /// Up to `limit` executions of a workflow whose data the policy requires to be purged
+    async fn purge_candidates(
+        &self,
+        workflow_id: &str,
+        policy: &RetentionPolicy,
+        cutoff: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<ExecutionSummary>> {
+        let mut found = Vec::new();
+        let mut skip = 0;
+        while found.len() < limit {
+            let path = format!(
+                "/api/workflows/{}/executions?limit={}&offset={}&includeData=false",
+                path_segment(workflow_id),
+                HISTORY_PAGE_SIZE,
+                skip
+            );
+            let response: serde_json::Value = self
+                .make_request(OperationClass::Read, "GET", &path, None::<&()>)
+                .await?;
+            let page: Vec<ExecutionSummary> = self.list_items(response, "executions")?;
+            let page_len = page.len();
+            found.extend(
+                page.into_iter()
+                    .filter(|e| policy.requires_purge_before(&e.status, e.started_at, cutoff)),
+            );
+            if page_len < HISTORY_PAGE_SIZE {
+                break;
+            }
+            skip += page_len;
+        }
+        found.truncate(limit);
+        Ok(found)
+    }
+}
+
+/// Names of the data fields of an execution that are not empty
+fn retained_fields(execution: &serde_json::Value) -> Vec<&'static str> {
+    let holds_data = |value: Option<&serde_json::Value>| match value {
+        None | Some(serde_json::Value::Null) => false,
+        Some(serde_json::Value::Object(map)) => !map.is_empty(),
+        Some(serde_json::Value::Array(items)) => !items.is_empty(),
+        Some(_) => true,
+    };
+
+    let mut fields = Vec::new();
+    if holds_data(execution.get("inputData")) {
+        fields.push("inputData");
+    }
+    if holds_data(execution.get("outputData")) {
+        fields.push("outputData");
+    }
+    if execution
+        .get("nodeResults")
+        .and_then(serde_json::Value::as_object)
+        .is_some_and(|results| {
+            results
+                .values()
+                .any(|result| holds_data(result.get("output")))
+        })
+    {
+        fields.push("nodeResults");
+    }
+    fields
+}
+
+/// Report a missing retention endpoint as [`Error::Unsupported`]
+fn unsupported_retention(error: Error) -> Error {
+    match error {
+        error if matches!(error.status(), Some(404 | 405 | 501)) => {
+            Error::Unsupported("server has no execution retention policy settings".to_string())
+        }
+        other => other,
+    }
+}
@@ -0,0 +1,266 @@
+use crate::deprecation::parse_http_date;
+use crate::error::ErrorCode;
+use crate::Error;
+use chrono::Utc;
+use reqwest::header::{HeaderMap, DATE, RETRY_AFTER};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Policy for resending requests that failed transiently, see [`Client::with_retry`](crate::Client::with_retry)
+///
+/// GET and DELETE requests are retried after responses with one of the
+/// `retryable_statuses` (by default 408, 500, 502, 503 and 504), connection
+/// failures and timeouts. Other methods are only retried when flagged
+/// idempotent with [`RequestOptions::idempotent`](crate::RequestOptions::idempotent),
+/// or when the connection could not be established, so nothing was sent.
+/// [`retry_if`](Self::retry_if) replaces these rules with a predicate.
+/// The delay before each retry grows exponentially from `initial_backoff`
+/// up to `max_backoff`, with the `jitter` fraction of it random, by default
+/// half.
+///
+/// Requests of any method rejected with [`Error::RateLimited`] are retried
+/// after the wait the server asked for, or the usual delay when it did not
+/// say.
+///
+/// ```rust
+/// use klikkflow_sdk::{Client, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new()
+///     .max_attempts(5)
+///     .initial_backoff(Duration::from_millis(200))
+///     .max_backoff(Duration::from_secs(10));
+/// let client = Client::new("https://klikkflow.example.com").with_retry(policy);
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every further retry
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Fraction of each delay that is random, from `0.0` for none to `1.0`
+    ///
+    /// Values outside that range are clamped, and a value that is not a
+    /// number counts as `0.0`.
+    pub jitter: f64,
+    /// Response statuses after which a request is retried
+    pub retryable_statuses: BTreeSet<u16>,
+    /// Time after the first attempt beyond which no retry is started
+    pub budget: Duration,
+    jitter_seed: Option<u64>,
+    retry_if: Option<Arc<RetryPredicate>>,
+}
+
+type RetryPredicate = dyn Fn(&Error, u32) -> bool + Send + Sync;
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.5,
+            retryable_statuses: BTreeSet::from([408, 500, 502, 503, 504]),
+            budget: Duration::from_secs(30),
+            jitter_seed: None,
+            retry_if: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Policy that never retries, to turn retries off for some calls with [`RequestOptions::retry`](crate::RequestOptions::retry)
+    pub fn disabled() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts;
+        self
+    }
+
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Randomize this fraction of each delay, clamped to `0.0..=1.0`
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = clamp_jitter(jitter);
+        self
+    }
+
+    /// Derive the random part of delays from `seed`, making them reproducible
+    ///
+    /// Meant for tests: every request then waits the same delays.
+    pub fn jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_seed = Some(seed);
+        self
+    }
+
+    /// Replace the statuses after which requests are retried
+    pub fn retryable_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    pub fn budget(mut self, budget: Duration) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Decide which failures are retried instead of the method and status rules
+    ///
+    /// `predicate` is given the error and the number of the attempt that
+    /// failed, starting from 1. `max_attempts` and `budget` still apply.
+    pub fn retry_if(
+        mut self,
+        predicate: impl Fn(&Error, u32) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_if = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Whether a request that failed with `error` on the given attempt is sent again
+    ///
+    /// `idempotent` marks a request safe to repeat regardless of its method,
+    /// see [`RequestOptions::idempotent`](crate::RequestOptions::idempotent).
+    /// Failures before the request was sent, such as connect timeouts and
+    /// refused connections, are retried whatever the method.
+    pub fn should_retry(
+        &self,
+        method: &str,
+        idempotent: bool,
+        error: &Error,
+        attempt: u32,
+    ) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+        if let Some(retry_if) = &self.retry_if {
+            return retry_if(error, attempt);
+        }
+        match error {
+            Error::ConnectTimeout(_) | Error::RateLimited { .. } => return true,
+            Error::Transport { kind, .. } if kind.is_before_send() => return true,
+            _ => {}
+        }
+        let idempotent = idempotent || matches!(method, "GET" | "DELETE");
+        idempotent
+            && match error.status() {
+                Some(status) => self.retryable_statuses.contains(&status),
+                None => matches!(error.code(), ErrorCode::Transport | ErrorCode::Timeout),
+            }
+    }
+
+    /// Delay before retrying the given attempt, which failed with `error`
+    pub(crate) fn delay_for(&self, error: &Error, attempt: u32) -> Duration {
+        match error {
+            Error::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            } => *retry_after,
+            _ => self.delay_after(attempt),
+        }
+    }
+
+    /// Delay before the retry following the given attempt, jittered
+    pub fn delay_after(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        let random = match self.jitter_seed {
+            Some(seed) => splitmix64(seed ^ u64::from(attempt)),
+            None => uuid::Uuid::new_v4().as_u128() as u64,
+        };
+        let fraction = (random >> 11) as f64 / (1u64 << 53) as f64;
+        // The field is public, so it is clamped here too
+        let jitter = clamp_jitter(self.jitter);
+        backoff.mul_f64(1.0 - jitter + jitter * fraction)
+    }
+}
+
+impl PartialEq for RetryPolicy {
+    fn eq(&self, other: &Self) -> bool {
+        self.max_attempts == other.max_attempts
+            && self.initial_backoff == other.initial_backoff
+            && self.max_backoff == other.max_backoff
+            && self.jitter.to_bits() == other.jitter.to_bits()
+            && self.retryable_statuses == other.retryable_statuses
+            && self.budget == other.budget
+            && self.jitter_seed == other.jitter_seed
+            && match (&self.retry_if, &other.retry_if) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
+impl Eq for RetryPolicy {}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("jitter", &self.jitter)
+            .field("retryable_statuses", &self.retryable_statuses)
+            .field("budget", &self.budget)
+            .field("jitter_seed", &self.jitter_seed)
+            .field("retry_if", &self.retry_if.is_some())
+            .finish()
+    }
+}
+
+/// `jitter` within `0.0..=1.0`, or `0.0` if it is not a number
+fn clamp_jitter(jitter: f64) -> f64 {
+    if jitter.is_nan() {
+        0.0
+    } else {
+        jitter.clamp(0.0, 1.0)
+    }
+}
+
+/// Mix a seed into a well-distributed 64-bit value
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Wait asked for by a `Retry-After` header, in seconds or as an HTTP date
+///
+/// A date is by the server's clock, so it is compared with the `Date` of the
+/// response when there is one.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+    let value = header(RETRY_AFTER)?;
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let until = parse_http_date(value)?;
+    let now = header(DATE)
+        .and_then(parse_http_date)
+        .unwrap_or_else(Utc::now);
+    Some((until - now).to_std().unwrap_or_default())
+}
@@ -0,0 +1,182 @@
+use crate::client::Client;
+use crate::models::*;
+use crate::wait::WaitOptions;
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use tracing::debug;
+
+/// Options for [`Client::run`]
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// How to execute the workflow; it is waited for regardless of `wait_for_completion`
+    pub execute: ExecuteOptions,
+    pub wait: WaitOptions,
+    /// Node, by ID or name, whose output is returned instead of the terminal node's
+    pub output_node: Option<String>,
+}
+
+impl RunOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn execute(mut self, options: ExecuteOptions) -> Self {
+        self.execute = options;
+        self
+    }
+
+    pub fn wait(mut self, options: WaitOptions) -> Self {
+        self.wait = options;
+        self
+    }
+
+    pub fn output_node(mut self, node: impl Into<String>) -> Self {
+        self.output_node = Some(node.into());
+        self
+    }
+}
+
+impl Client {
+    /// Execute a workflow, wait for it and deserialize its output
+    ///
+    /// `input` must serialize to a JSON object. An execution that does not
+    /// succeed fails with [`Error::ExecutionFailed`].
+    ///
+    /// The output is that of the terminal node: the single node without
+    /// outgoing connections in the execution's workflow snapshot or, without
+    /// a snapshot, the node that finished last. When there is no single such
+    /// node, the execution's whole `outputData` is deserialized instead. Set
+    /// [`RunOptions::output_node`] to pick the node.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::RunOptions;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Order {
+    ///     sku: String,
+    ///     quantity: u32,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Quote {
+    ///     total: f64,
+    /// }
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let order = Order { sku: "A-1".to_string(), quantity: 3 };
+    /// let quote: Quote = client.run("wf-quote", &order, RunOptions::new()).await?;
+    /// println!("total: {}", quote.total);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run<T: DeserializeOwned>(
+        &self,
+        workflow_id: &str,
+        input: impl Serialize,
+        options: RunOptions,
+    ) -> Result<T> {
+        let input_data = match serde_json::to_value(input) {
+            Ok(Value::Object(input)) => input.into_iter().collect(),
+            Ok(other) => {
+                return Err(Error::InvalidInput(format!(
+                    "workflow input must be a JSON object, got {}",
+                    other
+                )))
+            }
+            Err(e) => return Err(Error::Serialization(e.to_string())),
+        };
+
+        let mut execution = self
+            .execute_workflow_with_options(workflow_id, input_data, options.execute)
+            .await?;
+        if !execution.status.is_terminal() {
+            debug!("Waiting for execution completion: {}", execution.id);
+            execution = self
+                .in_flight(self.wait_future(&execution.id, options.wait))
+                .await?;
+        }
+        if execution.status != ExecutionStatus::Success {
+            return Err(Error::ExecutionFailed {
+                node: execution
+                    .node_results
+                    .iter()
+                    .filter(|(_, result)| result.is_failed())
+                    .min_by_key(|(id, result)| (result.finished_at, *id))
+                    .map(|(id, _)| id.clone()),
+                execution_id: execution.id,
+                status: execution.status,
+                error: execution.error,
+            });
+        }
+
+        let output = match &options.output_node {
+            Some(node) => node_output(&execution, node).ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "execution {} has no output for node {}",
+                    execution.id, node
+                ))
+            })?,
+            None => terminal_output(&execution)
+                .unwrap_or_else(|| Value::Object(execution.output_data.into_iter().collect())),
+        };
+        serde_json::from_value(output).map_err(|e| Error::Serialization(e.to_string()))
+    }
+}
+
+/// Output of the node with ID or name `node`
+fn node_output(execution: &ExecutionResult, node: &str) -> Option<Value> {
+    let id = execution
+        .workflow_snapshot
+        .as_ref()
+        .and_then(|workflow| workflow.nodes.iter().find(|n| n.name == node))
+        .map_or(node, |n| n.id.as_str());
+    execution
+        .node_results
+        .get(id)
+        .or_else(|| execution.node_results.get(node))?
+        .output
+        .clone()
+}
+
+/// Output of the one terminal node, if there is exactly one
+fn terminal_output(execution: &ExecutionResult) -> Option<Value> {
+    let terminal: Vec<&String> = match &execution.workflow_snapshot {
+        Some(workflow) => {
+            let sources: HashSet<&str> = workflow
+                .connections
+                .iter()
+                .map(|connection| connection.source.node_id.as_str())
+                .collect();
+            workflow
+                .nodes
+                .iter()
+                .filter(|node| !node.is_annotation() && !sources.contains(node.id.as_str()))
+                .filter_map(|node| execution.node_results.get_key_value(&node.id))
+                .map(|(id, _)| id)
+                .collect()
+        }
+        None => {
+            let finished: HashMap<&String, _> = execution
+                .node_results
+                .iter()
+                .filter_map(|(id, result)| result.finished_at.map(|at| (id, at)))
+                .collect();
+            let last = finished.values().max();
+            finished
+                .iter()
+                .filter(|(_, at)| Some(*at) == last)
+                .map(|(id, _)| *id)
+                .collect()
+        }
+    };
+    match terminal[..] {
+        [id] => execution.node_results[id].output.clone(),
+        _ => None,
+    }
+}
@@ -0,0 +1,279 @@
+use crate::client::Client;
+use crate::deprecation::parse_http_date;
+use crate::models::*;
+use crate::page::{Page, PageRequest};
+use crate::Result;
+use futures_util::stream::{self, Stream};
+use reqwest::header::{HeaderMap, DATE};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+const RATE_LIMIT_REMAINING: &str = "x-ratelimit-remaining";
+const RATE_LIMIT_LIMIT: &str = "x-ratelimit-limit";
+const RATE_LIMIT_RESET: &str = "x-ratelimit-reset";
+
+/// Interval between pages used by adaptive pacing when no rate is set
+const ADAPTIVE_INTERVAL: Duration = Duration::from_millis(100);
+/// Largest factor by which adaptive pacing stretches the interval
+const MAX_SLOWDOWN: f64 = 16.0;
+/// Longest wait for the rate limit to reset once it is exhausted
+const MAX_RESET_WAIT: Duration = Duration::from_secs(60);
+
+/// Pacing of the page fetches of a scan
+///
+/// With `adaptive`, the interval between pages grows once the server's
+/// `X-RateLimit-Remaining` drops below half of `X-RateLimit-Limit` (or of
+/// the highest remaining count seen, if the server sends no limit), up to
+/// 16 times the base interval. When nothing remains, the scan waits for
+/// `X-RateLimit-Reset`, for at most a minute.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScanOptions {
+    /// Most pages fetched per second, or `None` for no fixed pacing
+    pub pages_per_second: Option<f64>,
+    /// Slow down further as the server's rate limit runs low
+    pub adaptive: bool,
+}
+
+/// Position in a scan from which it can be resumed
+///
+/// Serializable so it can be stored between runs. Taken from
+/// [`Scanned::checkpoint`], it resumes after that item.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    offset: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
+    /// Items of the page already seen
+    #[serde(default)]
+    skip: usize,
+}
+
+/// Item yielded by a scan, with the checkpoint to resume after it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scanned<T> {
+    pub item: T,
+    pub checkpoint: ScanCheckpoint,
+}
+
+/// Waits between page fetches
+#[derive(Debug)]
+struct Pacer {
+    options: ScanOptions,
+    /// Highest remaining request count seen, standing in for a missing limit
+    capacity: u64,
+    delay: Duration,
+}
+
+impl Pacer {
+    fn new(options: ScanOptions) -> Self {
+        Self {
+            options,
+            capacity: 0,
+            delay: Duration::ZERO,
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        self.options
+            .pages_per_second
+            .filter(|rate| rate.is_finite() && *rate > 0.0)
+            .map(|rate| Duration::from_secs_f64(1.0 / rate))
+            .unwrap_or_default()
+    }
+
+    /// Work out the delay before the next page from the last response
+    fn observe(&mut self, headers: &HeaderMap) {
+        let interval = self.interval();
+        self.delay = interval;
+        if !self.options.adaptive {
+            return;
+        }
+        let Some(remaining) = header_u64(headers, RATE_LIMIT_REMAINING) else {
+            return;
+        };
+        self.capacity = self.capacity.max(remaining);
+        let limit = header_u64(headers, RATE_LIMIT_LIMIT).unwrap_or(self.capacity);
+        let base = interval.max(ADAPTIVE_INTERVAL);
+        if remaining == 0 {
+            self.delay = header_u64(headers, RATE_LIMIT_RESET)
+                .map(|reset| reset_delay(reset, headers))
+                .unwrap_or_else(|| base.mul_f64(MAX_SLOWDOWN))
+                .min(MAX_RESET_WAIT);
+        } else if remaining * 2 < limit {
+            let slowdown = (limit as f64 / (2 * remaining) as f64).min(MAX_SLOWDOWN);
+            self.delay = base.mul_f64(slowdown);
+        }
+        if self.delay > interval {
+            debug!(
+                "{} requests left of {}, waiting {:?} between pages",
+                remaining, limit, self.delay
+            );
+        }
+    }
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Time until a reset given as seconds from now or as a Unix timestamp
+///
+/// A timestamp is by the server's clock, so it is compared with the `Date`
+/// of the response when there is one.
+fn reset_delay(reset: u64, headers: &HeaderMap) -> Duration {
+    // Larger values cannot be a delay in seconds anyone would send
+    if reset > 1_000_000_000 {
+        let server_now = headers
+            .get(DATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_http_date(value.trim()))
+            .and_then(|date| u64::try_from(date.timestamp()).ok());
+        let now = server_now.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        });
+        Duration::from_secs(reset.saturating_sub(now))
+    } else {
+        Duration::from_secs(reset)
+    }
+}
+
+/// Stream items of a paginated endpoint with paced page fetches and checkpoints
+fn scan<T, F, Fut>(
+    resume: ScanCheckpoint,
+    options: ScanOptions,
+    fetch: F,
+) -> impl Stream<Item = Result<Scanned<T>>>
+where
+    F: FnMut(PageRequest) -> Fut,
+    Fut: Future<Output = Result<(Page<T>, HeaderMap)>>,
+{
+    let start = PageRequest {
+        offset: resume.offset,
+        cursor: resume.cursor,
+    };
+    let state = (
+        fetch,
+        Some(start),
+        resume.skip,
+        Pacer::new(options),
+        true,
+        VecDeque::new(),
+    );
+    stream::unfold(
+        state,
+        |(mut fetch, mut next, mut skip, mut pacer, mut first, mut buffered)| async move {
+            loop {
+                if let Some(item) = buffered.pop_front() {
+                    return Some((Ok(item), (fetch, next, skip, pacer, first, buffered)));
+                }
+                let request = next.take()?;
+                if !first && !pacer.delay.is_zero() {
+                    tokio::time::sleep(pacer.delay).await;
+                }
+                first = false;
+                match fetch(request.clone()).await {
+                    Ok((page, headers)) => {
+                        pacer.observe(&headers);
+                        next = request.after(&page);
+                        let skipped = std::mem::take(&mut skip);
+                        buffered.extend(page.items.into_iter().enumerate().skip(skipped).map(
+                            |(index, item)| Scanned {
+                                item,
+                                checkpoint: checkpoint_after(&request, index),
+                            },
+                        ));
+                    }
+                    Err(e) => {
+                        return Some((Err(e), (fetch, None, skip, pacer, first, buffered)));
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Checkpoint resuming after the item at `index` of the page fetched with `request`
+///
+/// Points at the start of the page rather than the next item, since cursors
+/// only address whole pages.
+fn checkpoint_after(request: &PageRequest, index: usize) -> ScanCheckpoint {
+    ScanCheckpoint {
+        offset: request.offset,
+        cursor: request.cursor.clone(),
+        skip: index + 1,
+    }
+}
+
+impl Client {
+    /// Stream every workflow like [`stream_workflows`](Self::stream_workflows), pacing page fetches
+    ///
+    /// Each workflow comes with a checkpoint; pass it as `resume` to continue
+    /// an interrupted scan after that workflow. `options.offset` and
+    /// `options.cursor` are ignored, the scan starts at `resume` or at the
+    /// beginning.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use futures_util::TryStreamExt;
+    /// use klikkflow_sdk::{ListWorkflowsOptions, ScanOptions};
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let scan = ScanOptions { pages_per_second: Some(2.0), adaptive: true };
+    /// let mut workflows = Box::pin(client.scan_workflows(ListWorkflowsOptions::default(), scan, None));
+    /// while let Some(scanned) = workflows.try_next().await? {
+    ///     // Saved so that an interrupted scan can pick up from here
+    ///     let checkpoint = serde_json::to_string(&scanned.checkpoint).unwrap();
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scan_workflows(
+        &self,
+        options: ListWorkflowsOptions,
+        scan_options: ScanOptions,
+        resume: Option<ScanCheckpoint>,
+    ) -> impl Stream<Item = Result<Scanned<WorkflowDefinition>>> {
+        let client = self.clone();
+        scan(resume.unwrap_or_default(), scan_options, move |request| {
+            let client = client.clone();
+            let options = ListWorkflowsOptions {
+                offset: request.cursor.is_none().then_some(request.offset),
+                cursor: request.cursor,
+                ..options.clone()
+            };
+            async move { client.list_workflows_page(options).await }
+        })
+    }
+
+    /// Stream a workflow's execution history like
+    /// [`stream_execution_history`](Self::stream_execution_history), pacing page fetches
+    ///
+    /// See [`scan_workflows`](Self::scan_workflows) for checkpoints.
+    pub fn scan_execution_history(
+        &self,
+        workflow_id: &str,
+        options: ExecutionHistoryOptions,
+        scan_options: ScanOptions,
+        resume: Option<ScanCheckpoint>,
+    ) -> impl Stream<Item = Result<Scanned<ExecutionResult>>> {
+        let client = self.clone();
+        let workflow_id = workflow_id.to_string();
+        scan(resume.unwrap_or_default(), scan_options, move |request| {
+            let client = client.clone();
+            let workflow_id = workflow_id.clone();
+            let options = ExecutionHistoryOptions {
+                offset: request.cursor.is_none().then_some(request.offset),
+                cursor: request.cursor,
+                ..options.clone()
+            };
+            async move { client.execution_history_page(&workflow_id, options).await }
+        })
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tracing::debug;
+
+/// Scheduling class of a request, set with [`RequestOptions::priority`](crate::RequestOptions::priority)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Priority {
+    /// User-facing calls, admitted first
+    #[default]
+    Interactive,
+    /// Bulk work that may wait
+    Background,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Interactive => "interactive",
+            Priority::Background => "background",
+        }
+    }
+}
+
+/// How a [`Priority`] class is admitted when requests of several classes are waiting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassConfig {
+    /// Admissions of this class per round while other classes are waiting
+    pub weight: u32,
+    /// Waiting longer than this admits a request ahead of every other class
+    pub max_wait: Option<Duration>,
+}
+
+/// Configuration of the client-side request scheduler
+///
+/// At most `max_in_flight` requests are sent at once; the rest queue per
+/// class. Free slots go to the classes in weighted rounds, in priority
+/// order within a round, so background work keeps moving under sustained
+/// interactive load. A request that waited past its class's `max_wait` is
+/// admitted first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulerConfig {
+    pub max_in_flight: usize,
+    /// Classes missing here use weight 1 and no `max_wait`
+    pub classes: HashMap<Priority, ClassConfig>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self::new(8)
+    }
+}
+
+impl SchedulerConfig {
+    /// Four interactive admissions per background one, and no background
+    /// request waiting more than 5 seconds for a slot
+    pub fn new(max_in_flight: usize) -> Self {
+        let classes = HashMap::from([
+            (
+                Priority::Interactive,
+                ClassConfig {
+                    weight: 4,
+                    max_wait: None,
+                },
+            ),
+            (
+                Priority::Background,
+                ClassConfig {
+                    weight: 1,
+                    max_wait: Some(Duration::from_secs(5)),
+                },
+            ),
+        ]);
+        Self {
+            max_in_flight,
+            classes,
+        }
+    }
+
+    pub fn class(mut self, priority: Priority, config: ClassConfig) -> Self {
+        self.classes.insert(priority, config);
+        self
+    }
+
+    fn class_config(&self, priority: Priority) -> ClassConfig {
+        self.classes.get(&priority).copied().unwrap_or(ClassConfig {
+            weight: 1,
+            max_wait: None,
+        })
+    }
+}
+
+/// Queue metrics of one [`Priority`] class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClassStats {
+    /// Requests currently waiting for a slot
+    pub queued: usize,
+    /// Requests admitted so far
+    pub admitted: u64,
+    /// Time admitted requests spent waiting, in total
+    pub total_wait: Duration,
+    /// Longest time an admitted request waited
+    pub max_wait: Duration,
+}
+
+impl ClassStats {
+    /// Average time admitted requests spent waiting
+    pub fn mean_wait(&self) -> Duration {
+        match u32::try_from(self.admitted) {
+            Ok(0) => Duration::ZERO,
+            Ok(admitted) => self.total_wait / admitted,
+            Err(_) => Duration::from_secs_f64(self.total_wait.as_secs_f64() / self.admitted as f64),
+        }
+    }
+}
+
+/// Snapshot of the request scheduler, from [`Client::scheduler_stats`](crate::Client::scheduler_stats)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchedulerStats {
+    pub in_flight: usize,
+    pub classes: BTreeMap<Priority, ClassStats>,
+}
+
+struct Waiter {
+    enqueued: Instant,
+    admit: oneshot::Sender<Permit>,
+}
+
+#[derive(Default)]
+struct State {
+    in_flight: usize,
+    queues: BTreeMap<Priority, VecDeque<Waiter>>,
+    /// Admissions left per class in the current round
+    credits: HashMap<Priority, u32>,
+    stats: BTreeMap<Priority, ClassStats>,
+}
+
+/// Admission control for requests, shared by all clones of a client
+pub(crate) struct Scheduler {
+    config: SchedulerConfig,
+    state: Mutex<State>,
+}
+
+/// A slot for one request, released when dropped
+pub(crate) struct Permit {
+    scheduler: Arc<Scheduler>,
+    /// Whether dropping the permit releases its slot
+    armed: bool,
+}
+
+impl Permit {
+    fn new(scheduler: &Arc<Scheduler>) -> Self {
+        Self {
+            scheduler: Arc::clone(scheduler),
+            armed: true,
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if self.armed {
+            self.scheduler.release();
+        }
+    }
+}
+
+impl Scheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::default(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait for a slot for a request of class `priority`
+    pub async fn acquire(self: &Arc<Self>, priority: Priority) -> Permit {
+        let admitted = {
+            let mut state = self.lock();
+            let queued = state.queues.values().any(|queue| !queue.is_empty());
+            if !queued && state.in_flight < self.config.max_in_flight.max(1) {
+                state.in_flight += 1;
+                record_admission(&mut state, priority, Duration::ZERO);
+                None
+            } else {
+                let (admit, admitted) = oneshot::channel();
+                state.queues.entry(priority).or_default().push_back(Waiter {
+                    enqueued: Instant::now(),
+                    admit,
+                });
+                debug!(
+                    "Queued {:?} request behind {} in flight",
+                    priority, state.in_flight
+                );
+                Some(admitted)
+            }
+        };
+        match admitted {
+            // The sender only goes away with the scheduler, which this call keeps alive
+            Some(admitted) => admitted.await.expect("scheduler dropped a queued request"),
+            None => Permit::new(self),
+        }
+    }
+
+    fn release(self: &Arc<Self>) {
+        let mut state = self.lock();
+        state.in_flight -= 1;
+        while state.in_flight < self.config.max_in_flight.max(1) {
+            let Some(priority) = self.next_class(&mut state) else {
+                break;
+            };
+            let Some(waiter) = state
+                .queues
+                .get_mut(&priority)
+                .and_then(VecDeque::pop_front)
+            else {
+                break;
+            };
+            state.in_flight += 1;
+            match waiter.admit.send(Permit::new(self)) {
+                Ok(()) => record_admission(&mut state, priority, waiter.enqueued.elapsed()),
+                Err(mut permit) => {
+                    // The caller gave up waiting; reuse the slot without releasing it
+                    permit.armed = false;
+                    state.in_flight -= 1;
+                }
+            }
+        }
+    }
+
+    /// Class whose oldest waiting request goes next
+    fn next_class(&self, state: &mut State) -> Option<Priority> {
+        let waiting: Vec<(Priority, Instant)> = state
+            .queues
+            .iter()
+            .filter_map(|(priority, queue)| queue.front().map(|w| (*priority, w.enqueued)))
+            .collect();
+        if waiting.is_empty() {
+            return None;
+        }
+
+        let overdue = waiting
+            .iter()
+            .filter(|(priority, enqueued)| {
+                self.config
+                    .class_config(*priority)
+                    .max_wait
+                    .is_some_and(|max_wait| enqueued.elapsed() >= max_wait)
+            })
+            .min_by_key(|(_, enqueued)| *enqueued);
+        if let Some((priority, _)) = overdue {
+            return Some(*priority);
+        }
+
+        if waiting
+            .iter()
+            .all(|(priority, _)| state.credits.get(priority).copied().unwrap_or(0) == 0)
+        {
+            for (priority, _) in &waiting {
+                let weight = self.config.class_config(*priority).weight.max(1);
+                state.credits.insert(*priority, weight);
+            }
+        }
+        let (priority, _) = waiting
+            .iter()
+            .find(|(priority, _)| state.credits.get(priority).copied().unwrap_or(0) > 0)?;
+        if let Some(credits) = state.credits.get_mut(priority) {
+            *credits -= 1;
+        }
+        Some(*priority)
+    }
+
+    pub fn stats(&self) -> SchedulerStats {
+        let state = self.lock();
+        let mut classes = state.stats.clone();
+        for (priority, queue) in &state.queues {
+            classes.entry(*priority).or_default().queued = queue.len();
+        }
+        SchedulerStats {
+            in_flight: state.in_flight,
+            classes,
+        }
+    }
+}
+
+fn record_admission(state: &mut State, priority: Priority, waited: Duration) {
+    let stats = state.stats.entry(priority).or_default();
+    stats.admitted += 1;
+    stats.total_wait += waited;
+    stats.max_wait = stats.max_wait.max(waited);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancelled_queued_requests_do_not_keep_the_scheduler_alive() {
+        let scheduler = Arc::new(Scheduler::new(SchedulerConfig::new(1)));
+        let held = scheduler.acquire(Priority::Interactive).await;
+        for priority in [Priority::Interactive, Priority::Background] {
+            let queued = scheduler.acquire(priority);
+            // Poll once so the request queues, then give up on it
+            assert!(tokio::time::timeout(Duration::from_millis(10), queued)
+                .await
+                .is_err());
+        }
+        drop(held);
+
+        let stats = scheduler.stats();
+        assert_eq!(stats.in_flight, 0);
+        assert!(stats.classes.values().all(|class| class.queued == 0));
+        let weak = Arc::downgrade(&scheduler);
+        drop(scheduler);
+        assert!(weak.upgrade().is_none());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::client::Client;
+use crate::compat::Version;
+use crate::models::*;
+use crate::page::Page;
+use crate::response_cache::NODE_TYPES_PATH;
+use crate::timeouts::OperationClass;
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Options of the on-disk cache for node types and the workflow schema
+#[derive(Debug, Clone)]
+pub struct SchemaCacheOptions {
+    /// Directory holding the cache entries, created if missing
+    pub dir: PathBuf,
+    /// Age beyond which an entry is refetched from the server
+    pub max_age: Duration,
+}
+
+impl SchemaCacheOptions {
+    /// Cache in `dir` with a `max_age` of one day
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_age: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    #[serde(rename = "serverVersion")]
+    server_version: String,
+    resource: String,
+    #[serde(rename = "storedAt")]
+    stored_at: DateTime<Utc>,
+    body: Value,
+}
+
+/// Cache of server responses that only change with the server version
+///
+/// Entries are files named after a hash of the server version and the
+/// resource. They are written to a unique temporary file and renamed into
+/// place, so processes sharing the directory never observe partial entries.
+/// Unreadable or mismatching entries count as misses.
+#[derive(Debug)]
+pub(crate) struct SchemaCache {
+    options: SchemaCacheOptions,
+}
+
+impl SchemaCache {
+    pub fn new(options: SchemaCacheOptions) -> Result<Self> {
+        std::fs::create_dir_all(&options.dir).map_err(|e| {
+            Error::Config(format!(
+                "failed to create schema cache directory {}: {}",
+                options.dir.display(),
+                e
+            ))
+        })?;
+        Ok(Self { options })
+    }
+
+    fn entry_path(&self, server_version: &str, resource: &str) -> PathBuf {
+        let key = fnv1a(format!("{}\0{}", server_version, resource).as_bytes());
+        self.options.dir.join(format!("{:016x}.json", key))
+    }
+
+    /// Cached body of `resource`, if present and younger than `max_age`
+    pub fn load(&self, server_version: &str, resource: &str) -> Option<Value> {
+        let path = self.entry_path(server_version, resource);
+        let contents = std::fs::read(&path).ok()?;
+        let entry: Entry = match serde_json::from_slice(&contents) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!(
+                    "Ignoring corrupt schema cache entry {}: {}",
+                    path.display(),
+                    e
+                );
+                return None;
+            }
+        };
+        if entry.server_version != server_version || entry.resource != resource {
+            return None;
+        }
+        let age = (Utc::now() - entry.stored_at).to_std().unwrap_or_default();
+        if age > self.options.max_age {
+            debug!("Schema cache entry for {} expired", resource);
+            return None;
+        }
+        Some(entry.body)
+    }
+
+    /// Store `body`; failures are logged since the cache is only an optimization
+    pub fn store(&self, server_version: &str, resource: &str, body: &Value) {
+        let path = self.entry_path(server_version, resource);
+        let entry = Entry {
+            server_version: server_version.to_string(),
+            resource: resource.to_string(),
+            stored_at: Utc::now(),
+            body: body.clone(),
+        };
+        if let Err(e) = write_atomically(&path, &entry) {
+            warn!(
+                "Failed to write schema cache entry {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn write_atomically(path: &Path, entry: &Entry) -> std::io::Result<()> {
+    let contents = serde_json::to_vec(entry)?;
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&temporary, contents)?;
+    std::fs::rename(&temporary, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temporary);
+    })
+}
+
+/// 64-bit FNV-1a, used for entry names because it is stable across Rust releases
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl Client {
+    /// Get the server's version information
+    ///
+    /// Fetched once and shared by clones of the client; failures are not
+    /// cached. The version is remembered to select [response adapters](Self::with_response_adapters)
+    /// for responses that do not name the version of the replica that sent them.
+    pub async fn server_info(&self) -> Result<ServerInfo> {
+        let info = self
+            .server_info_cell()
+            .get_or_try_init(|| async {
+                let info: ServerInfo = self
+                    .make_request(OperationClass::Read, "GET", "/api/version", None::<&()>)
+                    .await?;
+                self.response_normalizer().record_version(&info.version);
+                Ok::<_, Error>(info)
+            })
+            .await?;
+        Ok(info.clone())
+    }
+
+    /// Fail with [`Error::IncompatibleServer`] unless the server is at least `min_version`
+    ///
+    /// Guards against servers too old to understand the requests an
+    /// application sends, which would otherwise fail with an opaque `400`.
+    /// Versions compare as `major.minor.patch`; a server that reports no
+    /// parseable version is considered incompatible.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// // Fails with `Error::IncompatibleServer` on servers older than 2.4
+    /// let server = client.assert_compatible("2.4").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn assert_compatible(&self, min_version: &str) -> Result<ServerInfo> {
+        let required = Version::parse(min_version)
+            .ok_or_else(|| Error::InvalidInput(format!("invalid version: {}", min_version)))?;
+        let info = self.server_info().await?;
+        if Version::parse(&info.version).is_some_and(|version| version >= required) {
+            return Ok(info);
+        }
+        Err(Error::IncompatibleServer {
+            server_version: if info.version.is_empty() {
+                "unknown".to_string()
+            } else {
+                info.version
+            },
+            min_version: min_version.to_string(),
+        })
+    }
+
+    /// List the node types available on the server
+    ///
+    /// Served from the schema cache when one is configured, and from the
+    /// response cache under [`CachePolicy::StaleWhileRevalidate`](crate::CachePolicy).
+    pub async fn list_node_types(&self) -> Result<Page<NodeTypeDescription>> {
+        debug!("Listing node types");
+        let response = self.revalidating_get(NODE_TYPES_PATH).await?;
+        self.list_page(response, "nodeTypes", None, None)
+    }
+
+    /// List the node types available on the server, returning only the items of the page
+    #[deprecated(note = "use `list_node_types`, whose `items` hold the node types")]
+    pub async fn list_node_types_items(&self) -> Result<Vec<NodeTypeDescription>> {
+        Ok(self.list_node_types().await?.items)
+    }
+
+    /// Get the JSON schema of workflow definitions
+    ///
+    /// Served from the schema cache when one is configured.
+    pub async fn get_workflow_schema(&self) -> Result<Value> {
+        debug!("Getting workflow schema");
+        self.cached_get("/api/workflows/schema").await
+    }
+
+    /// GET `path`, going through the schema cache keyed by the server version
+    pub(crate) async fn cached_get(&self, path: &str) -> Result<Value> {
+        let Some(cache) = self.schema_cache() else {
+            return self
+                .make_request(OperationClass::Read, "GET", path, None::<&()>)
+                .await;
+        };
+
+        let version = match self.server_info().await {
+            Ok(info) if !info.version.is_empty() => info.version,
+            Ok(_) | Err(Error::NotFound { .. }) => {
+                debug!("Server reports no version, bypassing schema cache");
+                return self
+                    .make_request(OperationClass::Read, "GET", path, None::<&()>)
+                    .await;
+            }
+            Err(e) => return Err(e),
+        };
+        // Responses differ between API versions, so they are part of the key
+        let resource = format!("{} {}", self.api_version().as_str(), path);
+        if let Some(body) = cache.load(&version, &resource) {
+            debug!("Schema cache hit for {}", path);
+            return Ok(body);
+        }
+
+        let body: Value = self
+            .make_request(OperationClass::Read, "GET", path, None::<&()>)
+            .await?;
+        cache.store(&version, &resource, &body);
+        Ok(body)
+    }
+}
@@ -0,0 +1,325 @@
+use crate::client::{path_segment, Client};
+use crate::models::{ExecutionResult, ExecutionStatus};
+use crate::timeouts::OperationClass;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream, StreamExt};
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+const HISTORY_PAGE_SIZE: usize = 200;
+
+type ProgressCallback = Arc<dyn Fn(SearchProgress) + Send + Sync>;
+
+/// Options of [`Client::search_execution_errors`]
+#[derive(Clone)]
+pub struct SearchOptions {
+    concurrency: usize,
+    context: usize,
+    progress: Option<ProgressCallback>,
+}
+
+impl fmt::Debug for SearchOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchOptions")
+            .field("concurrency", &self.concurrency)
+            .field("context", &self.context)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchOptions {
+    /// Fetch 4 executions at a time and keep 40 characters of context
+    pub fn new() -> Self {
+        Self {
+            concurrency: 4,
+            context: 40,
+            progress: None,
+        }
+    }
+
+    /// Most executions whose details are fetched at the same time
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Characters of the error kept on either side of a match
+    pub fn context(mut self, context: usize) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Call `callback` whenever the search makes progress
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(SearchProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+}
+
+/// How far an execution error search has got
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchProgress {
+    /// Executions of the history read so far
+    pub scanned: u64,
+    /// Executions in the history, if the server reports it
+    pub total: Option<u64>,
+    /// Failed executions in the range whose errors were searched
+    pub checked: u64,
+    pub matches: u64,
+}
+
+/// An error matching the pattern of [`Client::search_execution_errors`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionMatch {
+    pub execution_id: String,
+    pub started_at: DateTime<Utc>,
+    pub status: ExecutionStatus,
+    /// Node whose error matched, or `None` for the execution's own error
+    pub node_id: Option<String>,
+    /// The text matched by the pattern
+    pub matched: String,
+    /// The match with the surrounding text of the error, `…` marking cut-off ends
+    pub context: String,
+}
+
+/// Execution history row, read without data
+#[derive(Deserialize)]
+struct ExecutionSummary {
+    id: String,
+    status: ExecutionStatus,
+    #[serde(rename = "startedAt")]
+    started_at: DateTime<Utc>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+struct Progress {
+    state: Mutex<SearchProgress>,
+    callback: Option<ProgressCallback>,
+}
+
+impl Progress {
+    fn update(&self, update: impl FnOnce(&mut SearchProgress)) {
+        let progress = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            update(&mut state);
+            *state
+        };
+        if let Some(callback) = &self.callback {
+            callback(progress);
+        }
+    }
+}
+
+impl Client {
+    /// Find the failed executions of a workflow in `range` whose errors match `pattern`
+    ///
+    /// The history is read newest first, a page at a time and without
+    /// execution data. Only executions that started in `range` and failed or
+    /// carry an error are fetched in full, at most
+    /// [`concurrency`](SearchOptions::concurrency) at a time, and their error
+    /// and node errors are searched. Each matching error is yielded with its
+    /// surrounding text, in history order. Memory use is bounded by one page
+    /// of history and the executions being fetched.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use chrono::{Duration, Utc};
+    /// use futures_util::TryStreamExt;
+    /// use klikkflow_sdk::SearchOptions;
+    /// use regex::Regex;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let pattern = Regex::new("ECONN(RESET|REFUSED)").unwrap();
+    /// let last_week = Utc::now() - Duration::weeks(1)..Utc::now();
+    /// let matches: Vec<_> = client
+    ///     .search_execution_errors("wf-1", &pattern, last_week, SearchOptions::new())
+    ///     .try_collect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn search_execution_errors(
+        &self,
+        workflow_id: &str,
+        pattern: &Regex,
+        range: Range<DateTime<Utc>>,
+        options: SearchOptions,
+    ) -> impl Stream<Item = Result<ExecutionMatch>> {
+        let progress = Arc::new(Progress {
+            state: Mutex::new(SearchProgress::default()),
+            callback: options.progress.clone(),
+        });
+        let candidates = self.error_candidates(workflow_id, range, Arc::clone(&progress));
+
+        let client = self.clone();
+        let pattern = pattern.clone();
+        let context = options.context;
+        candidates
+            .map(move |candidate| {
+                let client = client.clone();
+                let pattern = pattern.clone();
+                let progress = Arc::clone(&progress);
+                async move {
+                    let execution = client.get_execution(&candidate?.id).await?;
+                    let matches = error_matches(&execution, &pattern, context);
+                    progress.update(|p| {
+                        p.checked += 1;
+                        p.matches += matches.len() as u64;
+                    });
+                    Ok(matches)
+                }
+            })
+            .buffered(options.concurrency)
+            .flat_map(|matches: Result<Vec<ExecutionMatch>>| {
+                stream::iter(match matches {
+                    Ok(matches) => matches.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                })
+            })
+    }
+
+    /// History rows in `range` whose errors need searching, read a page at a time
+    fn error_candidates(
+        &self,
+        workflow_id: &str,
+        range: Range<DateTime<Utc>>,
+        progress: Arc<Progress>,
+    ) -> impl Stream<Item = Result<ExecutionSummary>> {
+        let client = self.clone();
+        let workflow_id = workflow_id.to_string();
+        let pages = stream::unfold(Some(0), move |offset| {
+            let client = client.clone();
+            let workflow_id = workflow_id.clone();
+            let range = range.clone();
+            let progress = Arc::clone(&progress);
+            async move {
+                let offset = offset?;
+                let path = format!(
+                    "/api/workflows/{}/executions?limit={}&offset={}&includeData=false",
+                    path_segment(&workflow_id),
+                    HISTORY_PAGE_SIZE,
+                    offset
+                );
+                let response = match client
+                    .make_request(OperationClass::Read, "GET", &path, None::<&()>)
+                    .await
+                {
+                    Ok(response) => response,
+                    Err(e) => return Some((vec![Err(e)], None)),
+                };
+                let page = match client.list_page::<ExecutionSummary>(
+                    response,
+                    "executions",
+                    Some(HISTORY_PAGE_SIZE),
+                    Some(offset),
+                ) {
+                    Ok(page) => page,
+                    Err(e) => return Some((vec![Err(e)], None)),
+                };
+                progress.update(|p| {
+                    p.scanned += page.items.len() as u64;
+                    p.total = page.total.or(p.total);
+                });
+                debug!(
+                    "Searching page of {} executions of workflow {}",
+                    page.items.len(),
+                    workflow_id
+                );
+
+                // History is newest first, so a page ending before the range ends the search
+                let reached_start = page
+                    .items
+                    .last()
+                    .is_some_and(|execution| execution.started_at < range.start);
+                let next = (page.has_more && !page.items.is_empty() && !reached_start)
+                    .then_some(offset + page.items.len());
+                let candidates = page
+                    .items
+                    .into_iter()
+                    .filter(|execution| {
+                        range.contains(&execution.started_at)
+                            && (execution.status == ExecutionStatus::Error
+                                || execution.error.is_some())
+                    })
+                    .map(Ok)
+                    .collect();
+                Some((candidates, next))
+            }
+        });
+        pages.flat_map(stream::iter)
+    }
+}
+
+/// The first match of `pattern` in the execution's error and in each node error
+fn error_matches(
+    execution: &ExecutionResult,
+    pattern: &Regex,
+    context: usize,
+) -> Vec<ExecutionMatch> {
+    let mut node_errors: Vec<(&String, &String)> = execution
+        .node_results
+        .iter()
+        .filter_map(|(node_id, result)| Some((node_id, result.error.as_ref()?)))
+        .collect();
+    node_errors.sort();
+    let errors = execution.error.iter().map(|error| (None, error)).chain(
+        node_errors
+            .into_iter()
+            .map(|(node_id, error)| (Some(node_id), error)),
+    );
+
+    errors
+        .filter_map(|(node_id, error)| {
+            let found = pattern.find(error)?;
+            Some(ExecutionMatch {
+                execution_id: execution.id.clone(),
+                started_at: execution.started_at,
+                status: execution.status.clone(),
+                node_id: node_id.cloned(),
+                matched: found.as_str().to_string(),
+                context: surrounding(error, found.start(), found.end(), context),
+            })
+        })
+        .collect()
+}
+
+/// `text[start..end]` with up to `context` characters on either side
+fn surrounding(text: &str, start: usize, end: usize, context: usize) -> String {
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .take(context)
+        .last()
+        .map_or(start, |(index, _)| index);
+    let to = text[end..]
+        .char_indices()
+        .nth(context)
+        .map_or(text.len(), |(index, _)| end + index);
+
+    let mut surrounding = String::new();
+    if from > 0 {
+        surrounding.push('…');
+    }
+    surrounding.push_str(&text[from..to]);
+    if to < text.len() {
+        surrounding.push('…');
+    }
+    surrounding
+}
@@ -0,0 +1,314 @@
+use crate::client::Client;
+use crate::models::*;
+use crate::{Error, Result};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::{info, warn};
+
+/// Typed view of the workflow settings most commonly managed from code
+///
+/// Settings not covered by a typed field are kept in `extra`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// ID of the workflow run when this workflow fails
+    #[serde(rename = "errorWorkflow", skip_serializing_if = "Option::is_none")]
+    pub error_workflow: Option<String>,
+    /// Maximum execution time in seconds
+    #[serde(rename = "executionTimeout", skip_serializing_if = "Option::is_none")]
+    pub execution_timeout: Option<u64>,
+    /// Keep the data of successful executions; the server defaults to `true`
+    #[serde(
+        rename = "saveSuccessfulExecutions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub save_execution_data_on_success: Option<bool>,
+    /// Keep the data of failed executions; the server defaults to `true`
+    #[serde(
+        rename = "saveFailedExecutions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub save_execution_data_on_error: Option<bool>,
+    /// Keep manually started executions; the server defaults to `true`
+    #[serde(
+        rename = "saveManualExecutions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub save_manual_executions: Option<bool>,
+    #[serde(flatten)]
+    pub extra: FieldMap,
+}
+
+impl WorkflowSettings {
+    /// Convert into the untyped settings map used by requests and definitions
+    pub fn to_map(&self) -> Result<FieldMap> {
+        match serde_json::to_value(self).map_err(|e| Error::Serialization(e.to_string()))? {
+            Value::Object(map) => Ok(map.into_iter().collect()),
+            _ => Ok(FieldMap::new()),
+        }
+    }
+}
+
+/// Merge default settings into explicit ones.
+///
+/// Explicit values always win. When both sides hold a JSON object under the
+/// same key, the objects are merged recursively with the same rule, so a
+/// default nested key is only added when the explicit object lacks it.
+/// Arrays and scalars are never merged: an explicit value replaces the
+/// default entirely. Returns the JSON pointers of the values taken from the
+/// defaults.
+///
+/// ```rust
+/// use klikkflow_sdk::{merge_settings, FieldMap};
+/// use serde_json::json;
+///
+/// let mut settings: FieldMap = [
+///     ("timezone".to_string(), json!("Europe/Oslo")),
+///     ("retry".to_string(), json!({ "maxAttempts": 5 })),
+/// ]
+/// .into_iter()
+/// .collect();
+/// let defaults: FieldMap = [
+///     ("timezone".to_string(), json!("UTC")),
+///     ("retry".to_string(), json!({ "maxAttempts": 3, "backoff": "exponential" })),
+///     ("tags".to_string(), json!(["managed"])),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// let added = merge_settings(&mut settings, &defaults);
+/// assert_eq!(added, vec!["/retry/backoff", "/tags"]);
+/// assert_eq!(settings["timezone"], json!("Europe/Oslo"));
+/// assert_eq!(settings["retry"], json!({ "maxAttempts": 5, "backoff": "exponential" }));
+/// ```
+pub fn merge_settings(settings: &mut FieldMap, defaults: &FieldMap) -> Vec<String> {
+    let mut added = Vec::new();
+    for (key, default) in defaults {
+        let pointer = format!("/{}", key);
+        match settings.get_mut(key) {
+            Some(Value::Object(explicit)) => {
+                if let Value::Object(default) = default {
+                    merge_objects(explicit, default, &pointer, &mut added);
+                }
+            }
+            Some(_) => {}
+            None => {
+                settings.insert(key.clone(), default.clone());
+                added.push(pointer);
+            }
+        }
+    }
+    added.sort();
+    added
+}
+
+fn merge_objects(
+    explicit: &mut Map<String, Value>,
+    defaults: &Map<String, Value>,
+    pointer: &str,
+    added: &mut Vec<String>,
+) {
+    for (key, default) in defaults {
+        let child = format!("{}/{}", pointer, key);
+        match explicit.get_mut(key) {
+            Some(Value::Object(nested)) => {
+                if let Value::Object(default) = default {
+                    merge_objects(nested, default, &child, added);
+                }
+            }
+            Some(_) => {}
+            None => {
+                explicit.insert(key.clone(), default.clone());
+                added.push(child);
+            }
+        }
+    }
+}
+
+/// Settings added to an existing workflow by [`Client::apply_defaults_to_existing`]
+#[derive(Debug, Clone)]
+pub struct DefaultsChange {
+    pub workflow_id: String,
+    pub workflow_name: String,
+    /// JSON pointers of the settings filled in from the defaults
+    pub added: Vec<String>,
+}
+
+/// Which execution data the server keeps, set with [`Client::set_data_saving_policy`]
+///
+/// Settings left `None` are not changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DataSavingPolicy {
+    pub save_execution_data_on_success: Option<bool>,
+    pub save_execution_data_on_error: Option<bool>,
+    pub save_manual_executions: Option<bool>,
+}
+
+impl DataSavingPolicy {
+    /// Setting keys and values the policy sets
+    fn settings(&self) -> Vec<(&'static str, bool)> {
+        [
+            (
+                "saveSuccessfulExecutions",
+                self.save_execution_data_on_success,
+            ),
+            ("saveFailedExecutions", self.save_execution_data_on_error),
+            ("saveManualExecutions", self.save_manual_executions),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?)))
+        .collect()
+    }
+}
+
+/// A data-saving setting changed by [`Client::set_data_saving_policy`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingChange {
+    /// Setting key, e.g. `saveFailedExecutions`
+    pub key: &'static str,
+    /// Value before the change, `None` when the server default applied
+    pub from: Option<bool>,
+    pub to: bool,
+}
+
+/// A workflow whose data-saving settings differ from the policy
+#[derive(Debug, Clone)]
+pub struct DataSavingChange {
+    pub workflow_id: String,
+    pub workflow_name: String,
+    pub changes: Vec<SettingChange>,
+}
+
+impl Client {
+    /// Apply a data-saving policy to every workflow matching `filter`
+    ///
+    /// Pages through all matching workflows and updates those whose settings
+    /// differ from the policy, leaving their other settings untouched.
+    /// Returns one entry per workflow that differs; with `dry_run`, nothing is
+    /// updated. Disabling the saving of failed executions is logged as a
+    /// warning, since it leaves nothing to debug failures with.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::DataSavingPolicy;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let policy = DataSavingPolicy {
+    ///     save_execution_data_on_success: Some(false),
+    ///     ..Default::default()
+    /// };
+    /// // Review the plan first, then apply it
+    /// for change in client.set_data_saving_policy(None, policy, true).await? {
+    ///     println!("{}: {:?}", change.workflow_id, change.changes);
+    /// }
+    /// client.set_data_saving_policy(None, policy, false).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_data_saving_policy(
+        &self,
+        filter: Option<ListWorkflowsOptions>,
+        policy: DataSavingPolicy,
+        dry_run: bool,
+    ) -> Result<Vec<DataSavingChange>> {
+        if policy.save_execution_data_on_error == Some(false) {
+            warn!("Disabling saving of failed executions; failures will be hard to debug");
+        }
+        let wanted = policy.settings();
+
+        let workflows: Vec<WorkflowDefinition> = self
+            .stream_workflows(filter.unwrap_or_default())
+            .try_collect()
+            .await?;
+        let mut changed = Vec::new();
+        for workflow in workflows {
+            let changes: Vec<SettingChange> = wanted
+                .iter()
+                .filter_map(|&(key, to)| {
+                    let from = workflow.settings.get(key).and_then(Value::as_bool);
+                    (from != Some(to)).then_some(SettingChange { key, from, to })
+                })
+                .collect();
+            if changes.is_empty() {
+                continue;
+            }
+
+            info!(
+                "Workflow {} needs {} data-saving setting(s) changed{}",
+                workflow.id,
+                changes.len(),
+                if dry_run { " (dry run)" } else { "" }
+            );
+            if !dry_run {
+                let mut settings = workflow.settings.clone();
+                for change in &changes {
+                    settings.insert(change.key.to_string(), Value::Bool(change.to));
+                }
+                let request = UpdateWorkflowRequest {
+                    settings: FieldUpdate::Set(settings),
+                    ..Default::default()
+                };
+                self.update_workflow(&workflow.id, request).await?;
+            }
+
+            changed.push(DataSavingChange {
+                workflow_id: workflow.id,
+                workflow_name: workflow.name,
+                changes,
+            });
+        }
+        Ok(changed)
+    }
+
+    /// Patch existing workflows that are missing some of the configured default settings
+    ///
+    /// Returns one entry per workflow that lacks defaults. With `dry_run`, nothing
+    /// is updated and the result only describes what would change.
+    pub async fn apply_defaults_to_existing(
+        &self,
+        filter: Option<ListWorkflowsOptions>,
+        dry_run: bool,
+    ) -> Result<Vec<DefaultsChange>> {
+        let Some(defaults) = self.workflow_defaults() else {
+            return Ok(Vec::new());
+        };
+        let defaults = defaults.to_map()?;
+
+        let workflows: Vec<WorkflowDefinition> = self
+            .stream_workflows(filter.unwrap_or_default())
+            .try_collect()
+            .await?;
+        let mut changes = Vec::new();
+        for workflow in workflows {
+            let mut settings = workflow.settings.clone();
+            let added = merge_settings(&mut settings, &defaults);
+            if added.is_empty() {
+                continue;
+            }
+
+            info!(
+                "Workflow {} is missing {} default setting(s){}",
+                workflow.id,
+                added.len(),
+                if dry_run { " (dry run)" } else { "" }
+            );
+            if !dry_run {
+                let request = UpdateWorkflowRequest {
+                    settings: FieldUpdate::Set(settings),
+                    ..Default::default()
+                };
+                self.update_workflow(&workflow.id, request).await?;
+            }
+
+            changes.push(DefaultsChange {
+                workflow_id: workflow.id,
+                workflow_name: workflow.name,
+                added,
+            });
+        }
+        Ok(changes)
+    }
+}
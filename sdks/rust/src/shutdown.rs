@@ -0,0 +1,134 @@
+use crate::client::Client;
+use crate::{Error, Result};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+tokio::task_local! {
+    /// Set while a call admitted before [`Client::shutdown`] runs, so the
+    /// requests it makes internally are admitted too
+    static ADMITTED: ();
+}
+
+/// Open or closed state of a client, shared by all its clones
+pub(crate) struct Lifecycle {
+    closed: AtomicBool,
+    in_flight: watch::Sender<usize>,
+    /// Set once the grace period is over; pending calls abort and streams close
+    terminated: watch::Sender<bool>,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self {
+            closed: AtomicBool::new(false),
+            in_flight: watch::channel(0).0,
+            terminated: watch::channel(false).0,
+        }
+    }
+}
+
+/// A call counted as in flight until dropped
+struct InFlight<'a>(&'a Lifecycle);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.send_modify(|count| *count -= 1);
+    }
+}
+
+impl Lifecycle {
+    fn enter(&self) -> Result<InFlight<'_>> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(Error::ClientClosed);
+        }
+        self.in_flight.send_modify(|count| *count += 1);
+        Ok(InFlight(self))
+    }
+
+    /// Resolves once the client has shut down; never, if it is dropped first
+    pub fn terminated(&self) -> BoxFuture<'static, ()> {
+        let mut terminated = self.terminated.subscribe();
+        async move {
+            while !*terminated.borrow_and_update() {
+                if terminated.changed().await.is_err() {
+                    futures_util::future::pending::<()>().await;
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+impl Client {
+    /// Run `call` as in flight, so [`shutdown`](Self::shutdown) waits for it or aborts it
+    ///
+    /// Fails with [`Error::ClientClosed`] once the client is closed, unless
+    /// made from within a call that was admitted before, which already counts.
+    pub(crate) async fn in_flight<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        if ADMITTED.try_with(|_| ()).is_ok() {
+            return call.await;
+        }
+        let lifecycle = self.lifecycle();
+        let _in_flight = lifecycle.enter()?;
+        tokio::select! {
+            result = ADMITTED.scope((), call) => result,
+            _ = lifecycle.terminated() => Err(Error::ClientClosed),
+        }
+    }
+
+    /// Whether [`shutdown`](Self::shutdown) was called on this client or a clone of it
+    pub fn is_closed(&self) -> bool {
+        self.lifecycle().closed.load(Ordering::SeqCst)
+    }
+
+    /// Stop making calls, wait up to `grace` for the ones in flight, then abort the rest
+    ///
+    /// Calls started afterwards, on this client or any clone, fail with
+    /// [`Error::ClientClosed`]. Calls already in flight keep going, including
+    /// the polling of [`ExecutionHandle::wait`](crate::ExecutionHandle::wait)
+    /// and waiting [`execute_workflow`](Self::execute_workflow) calls; those
+    /// still running when the grace period ends fail with
+    /// [`Error::ClientClosed`]. Execution streams opened by the client are then
+    /// sent a close frame and end, once their consumer polls them.
+    ///
+    /// Returns the number of calls that were aborted.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use std::time::Duration;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// // On SIGTERM
+    /// let aborted = client.shutdown(Duration::from_secs(10)).await;
+    /// println!("aborted {} waits", aborted);
+    /// # }
+    /// ```
+    pub async fn shutdown(&self, grace: Duration) -> usize {
+        let lifecycle = self.lifecycle();
+        lifecycle.closed.store(true, Ordering::SeqCst);
+        let mut in_flight = lifecycle.in_flight.subscribe();
+        info!(
+            "Shutting down client with {} call(s) in flight",
+            *in_flight.borrow()
+        );
+
+        let drained = async {
+            // The sender lives in `lifecycle`, so this never fails
+            while *in_flight.borrow_and_update() > 0 && in_flight.changed().await.is_ok() {}
+        };
+        let _ = tokio::time::timeout(grace, drained).await;
+
+        let aborted = *lifecycle.in_flight.borrow();
+        if aborted > 0 {
+            warn!("Aborting {} call(s) still in flight", aborted);
+        }
+        lifecycle.terminated.send_replace(true);
+        aborted
+    }
+}
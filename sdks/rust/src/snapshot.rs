@@ -0,0 +1,173 @@
+use crate::models::*;
+use crate::redact::{glob_match, DEFAULT_MASK};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Options for [`WorkflowDefinition::canonical_snapshot_with`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotOptions {
+    /// Parameter keys whose values are replaced with [`DEFAULT_MASK`]; `*` matches any run of characters
+    pub redacted_parameters: Vec<String>,
+}
+
+impl SnapshotOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mask values under parameter keys matching `pattern`, ignoring case, at any depth
+    pub fn redact_parameter(mut self, pattern: impl Into<String>) -> Self {
+        self.redacted_parameters.push(pattern.into());
+        self
+    }
+
+    fn redacts(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.redacted_parameters
+            .iter()
+            .any(|pattern| glob_match(&pattern.to_lowercase(), &key))
+    }
+}
+
+impl WorkflowDefinition {
+    /// Normalized JSON of the definition for snapshot tests
+    ///
+    /// The workflow ID and timestamps are left out, object keys are sorted,
+    /// nodes are ordered by name, type and position and renumbered `node-1`,
+    /// `node-2`, ... in that order, and connections refer to the new IDs and
+    /// are sorted. Two definitions that differ only in generated IDs,
+    /// timestamps or ordering produce the same snapshot.
+    ///
+    /// The format is stable across patch releases of the SDK: a snapshot
+    /// only changes when the workflow does. The format may change in minor
+    /// releases.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::SnapshotOptions;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let workflow = client.get_workflow("wf-1").await?;
+    /// let options = SnapshotOptions::new().redact_parameter("token");
+    /// std::fs::write("workflows/wf-1.json", workflow.canonical_snapshot_with(&options))
+    ///     .expect("snapshot is writable");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn canonical_snapshot(&self) -> String {
+        self.canonical_snapshot_with(&SnapshotOptions::default())
+    }
+
+    /// [`canonical_snapshot`](Self::canonical_snapshot) with parameter values redacted
+    pub fn canonical_snapshot_with(&self, options: &SnapshotOptions) -> String {
+        let (nodes, connections) = canonical_graph(&self.nodes, &self.connections, options);
+        render(json!({
+            "name": self.name,
+            "description": self.description,
+            "active": self.active,
+            "nodes": nodes,
+            "connections": connections,
+            "settings": self.settings,
+        }))
+    }
+}
+
+impl CreateWorkflowRequest {
+    /// Normalized JSON of the request for snapshot tests, see [`WorkflowDefinition::canonical_snapshot`]
+    pub fn canonical_snapshot(&self) -> String {
+        self.canonical_snapshot_with(&SnapshotOptions::default())
+    }
+
+    /// [`canonical_snapshot`](Self::canonical_snapshot) with parameter values redacted
+    pub fn canonical_snapshot_with(&self, options: &SnapshotOptions) -> String {
+        let (nodes, connections) = canonical_graph(&self.nodes, &self.connections, options);
+        render(json!({
+            "name": self.name,
+            "description": self.description,
+            "nodes": nodes,
+            "connections": connections,
+            "settings": self.settings,
+        }))
+    }
+}
+
+/// Nodes and connections in canonical order, with stable node IDs
+pub(crate) fn canonical_graph(
+    nodes: &[NodeDefinition],
+    connections: &[Connection],
+    options: &SnapshotOptions,
+) -> (Vec<Value>, Vec<Value>) {
+    let mut ordered: Vec<(&NodeDefinition, Value)> = nodes
+        .iter()
+        .map(|node| {
+            let mut parameters = serde_json::to_value(&node.parameters).unwrap_or(Value::Null);
+            redact(&mut parameters, options);
+            (node, parameters)
+        })
+        .collect();
+    // Parameters break ties, so only nodes identical but for their ID compare equal
+    ordered.sort_by(|(a, a_parameters), (b, b_parameters)| {
+        (&a.name, &a.node_type)
+            .cmp(&(&b.name, &b.node_type))
+            .then(a.position.x.total_cmp(&b.position.x))
+            .then(a.position.y.total_cmp(&b.position.y))
+            .then_with(|| a_parameters.to_string().cmp(&b_parameters.to_string()))
+    });
+
+    let ids: HashMap<&str, String> = ordered
+        .iter()
+        .enumerate()
+        .map(|(index, (node, _))| (node.id.as_str(), format!("node-{}", index + 1)))
+        .collect();
+    let stable_id = |id: &str| ids.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+    let nodes = ordered
+        .into_iter()
+        .map(|(node, parameters)| {
+            json!({
+                "id": stable_id(&node.id),
+                "name": node.name,
+                "type": node.node_type,
+                "position": node.position,
+                "parameters": parameters,
+            })
+        })
+        .collect();
+
+    let mut connections: Vec<Value> = connections
+        .iter()
+        .map(|connection| {
+            let mut connection = connection.clone();
+            connection.source.node_id = stable_id(&connection.source.node_id);
+            connection.destination.node_id = stable_id(&connection.destination.node_id);
+            serde_json::to_value(connection).unwrap_or(Value::Null)
+        })
+        .collect();
+    connections.sort_by_cached_key(Value::to_string);
+    (nodes, connections)
+}
+
+/// Replace the values under redacted keys, at any depth
+fn redact(value: &mut Value, options: &SnapshotOptions) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if options.redacts(key) {
+                    *value = Value::String(DEFAULT_MASK.to_string());
+                } else {
+                    redact(value, options);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(item, options)),
+        _ => {}
+    }
+}
+
+/// Pretty JSON with keys sorted, ending with a newline
+fn render(value: Value) -> String {
+    let mut rendered = serde_json::to_string_pretty(&value).unwrap_or_default();
+    rendered.push('\n');
+    rendered
+}
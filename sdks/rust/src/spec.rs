@@ -0,0 +1,480 @@
+//! Declarative workflow specs compiled into workflow definitions
+//!
+//! A [`WorkflowSpec`] lists steps, each naming the kind of node it `uses`,
+//! its parameters (`with`) and the steps it `needs`. Compiling a spec against
+//! a [`NodeTypeRegistry`] produces a [`CreateWorkflowRequest`] with one node
+//! per step, named after the step id, connections from each needed step and
+//! nodes laid out left to right by dependency depth.
+//!
+//! ```rust
+//! use klikkflow_sdk::spec::{NodeTypeRegistry, WorkflowSpec};
+//!
+//! let spec = WorkflowSpec::parse(r#"
+//! name = "Sync users"
+//!
+//! [[steps]]
+//! id = "fetch"
+//! uses = "http"
+//! with = { method = "GET", url = "https://api.example.com/users" }
+//!
+//! [[steps]]
+//! id = "active"
+//! uses = "filter"
+//! with = { condition = "{{ $json.active }}" }
+//! needs = ["fetch"]
+//!
+//! [[steps]]
+//! id = "inactive"
+//! uses = "filter"
+//! with = { condition = "{{ !$json.active }}" }
+//! needs = ["fetch"]
+//!
+//! [[steps]]
+//! id = "upsert"
+//! name = "Upsert active users"
+//! uses = "http"
+//! with = { method = "POST", url = "https://crm.example.com/users" }
+//! needs = ["active"]
+//!
+//! [[steps]]
+//! id = "report"
+//! uses = "http"
+//! with = { method = "POST", url = "https://chat.example.com/hooks/sync" }
+//! needs = ["upsert", "inactive"]
+//! "#)
+//! .unwrap();
+//!
+//! let registry = NodeTypeRegistry::new().alias("filter", "filter");
+//! let request = spec.compile(&registry).unwrap();
+//! assert_eq!(request.nodes.len(), 5);
+//! assert_eq!(request.connections.len(), 5);
+//! assert_eq!(request.nodes[3].name, "Upsert active users");
+//! assert_eq!(request.nodes[0].node_type, "http-request");
+//! // Branches share a column and are stacked vertically
+//! assert_eq!(request.nodes[1].position.x, request.nodes[2].position.x);
+//! assert_ne!(request.nodes[1].position.y, request.nodes[2].position.y);
+//! assert!(request.nodes[4].position.x > request.nodes[3].position.x);
+//!
+//! // The same spec as JSON compiles to the same workflow
+//! let json = WorkflowSpec::parse(&spec.to_json().unwrap()).unwrap();
+//! assert_eq!(
+//!     serde_json::to_value(json.compile(&registry).unwrap()).unwrap(),
+//!     serde_json::to_value(&request).unwrap(),
+//! );
+//! ```
+//!
+//! Problems are reported together, with their position in the source:
+//!
+//! ```rust
+//! use klikkflow_sdk::spec::{NodeTypeRegistry, WorkflowSpec};
+//! use klikkflow_sdk::Error;
+//!
+//! let spec = WorkflowSpec::parse(r#"{
+//!   "name": "Broken",
+//!   "steps": [
+//!     { "id": "fetch", "uses": "ftp" },
+//!     { "id": "store", "uses": "http", "needs": ["fetc"] }
+//!   ]
+//! }"#)
+//! .unwrap();
+//!
+//! match spec.compile(&NodeTypeRegistry::new()) {
+//!     Err(Error::InvalidSpec { issues }) => {
+//!         let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+//!         assert_eq!(messages, [
+//!             "4:30: step `fetch` uses unknown node kind `ftp`",
+//!             "5:48: step `store` needs unknown step `fetc`",
+//!         ]);
+//!     }
+//!     other => panic!("unexpected result: {:?}", other),
+//! }
+//! ```
+
+use crate::models::{
+    Connection, ConnectionPoint, CreateWorkflowRequest, NodeDefinition, NodeTypeDescription,
+    Position,
+};
+use crate::nodes::HTTP_REQUEST_NODE_TYPE;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use toml::Spanned;
+
+/// Canvas position of the first column of nodes
+const ORIGIN: Position = Position { x: 250.0, y: 300.0 };
+/// Horizontal distance between dependency levels
+const COLUMN_WIDTH: f64 = 250.0;
+/// Vertical distance between nodes of the same level
+const ROW_HEIGHT: f64 = 150.0;
+
+/// Maps the node kinds used in specs to server node types
+///
+/// `http` is registered as an alias of the HTTP Request node. A kind that is
+/// not an alias resolves only if it is a known node type itself.
+#[derive(Debug, Clone)]
+pub struct NodeTypeRegistry {
+    aliases: HashMap<String, String>,
+    node_types: HashSet<String>,
+}
+
+impl Default for NodeTypeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodeTypeRegistry {
+    /// Registry with the built-in aliases
+    pub fn new() -> Self {
+        Self {
+            aliases: HashMap::new(),
+            node_types: HashSet::new(),
+        }
+        .alias("http", HTTP_REQUEST_NODE_TYPE)
+    }
+
+    /// Registry with the built-in aliases and the node types available on a server
+    ///
+    /// The node types are usually obtained with [`Client::list_node_types`](crate::Client::list_node_types).
+    pub fn from_node_types(node_types: &[NodeTypeDescription]) -> Self {
+        let mut registry = Self::new();
+        registry
+            .node_types
+            .extend(node_types.iter().map(|node_type| node_type.name.clone()));
+        registry
+    }
+
+    /// Let specs refer to `node_type` as `kind`
+    pub fn alias(mut self, kind: impl Into<String>, node_type: impl Into<String>) -> Self {
+        let node_type = node_type.into();
+        self.node_types.insert(node_type.clone());
+        self.aliases.insert(kind.into(), node_type);
+        self
+    }
+
+    /// Node type a spec kind stands for
+    pub fn resolve(&self, kind: &str) -> Option<&str> {
+        match self.aliases.get(kind) {
+            Some(node_type) => Some(node_type),
+            None => self.node_types.get(kind).map(String::as_str),
+        }
+    }
+}
+
+/// One step of a [`WorkflowSpec`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepSpec {
+    /// Unique id, also used as the node id
+    pub id: String,
+    /// Node name; defaults to the id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Node kind, resolved with a [`NodeTypeRegistry`]
+    pub uses: String,
+    /// Node parameters
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub with: Map<String, Value>,
+    /// Ids of the steps whose output feeds this step
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub needs: Vec<String>,
+}
+
+/// Declarative description of a workflow, written in TOML or JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowSpec {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub settings: Map<String, Value>,
+    pub steps: Vec<StepSpec>,
+    /// Document the spec was parsed from, used to locate issues
+    #[serde(skip)]
+    source: Option<String>,
+    /// Where each step's values are in the source
+    #[serde(skip)]
+    locations: Vec<StepOffsets>,
+}
+
+/// Byte offsets in the source of the values of one step
+#[derive(Debug, Clone, Default)]
+struct StepOffsets {
+    id: usize,
+    uses: usize,
+    needs: Vec<usize>,
+}
+
+/// The steps of a spec with only their located values, `T` carrying the location
+#[derive(Deserialize)]
+struct SpecLocations<T> {
+    steps: Vec<StepLocations<T>>,
+}
+
+#[derive(Deserialize)]
+struct StepLocations<T> {
+    id: T,
+    uses: T,
+    #[serde(default = "Vec::new")]
+    needs: Vec<T>,
+}
+
+impl<T> SpecLocations<T> {
+    fn offsets(self, offset: impl Fn(&T) -> usize) -> Vec<StepOffsets> {
+        self.steps
+            .into_iter()
+            .map(|step| StepOffsets {
+                id: offset(&step.id),
+                uses: offset(&step.uses),
+                needs: step.needs.iter().map(&offset).collect(),
+            })
+            .collect()
+    }
+}
+
+/// Problem found in a spec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecIssue {
+    /// 1-based line and column in the source document, when known
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl fmt::Display for SpecIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "{}:{}: {}", line, column, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl WorkflowSpec {
+    /// Spec built in code rather than parsed; its issues carry no positions
+    pub fn new(name: impl Into<String>, steps: Vec<StepSpec>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            settings: Map::new(),
+            steps,
+            source: None,
+            locations: Vec::new(),
+        }
+    }
+
+    /// Parse a spec from a JSON document, or from TOML if it does not start with `{`
+    pub fn parse(source: &str) -> Result<Self> {
+        let parsed = if source.trim_start().starts_with('{') {
+            serde_json::from_str::<Self>(source)
+                .and_then(|spec| {
+                    // Borrowed raw values are slices of the source
+                    let located: SpecLocations<&RawValue> = serde_json::from_str(source)?;
+                    let start = source.as_ptr() as usize;
+                    let offsets = located.offsets(|raw| raw.get().as_ptr() as usize - start);
+                    Ok((spec, offsets))
+                })
+                .map_err(|e| SpecIssue {
+                    position: Some((e.line(), e.column())),
+                    message: e.to_string(),
+                })
+        } else {
+            toml::from_str::<Self>(source)
+                .and_then(|spec| {
+                    let located: SpecLocations<Spanned<String>> = toml::from_str(source)?;
+                    Ok((spec, located.offsets(|value| value.span().start)))
+                })
+                .map_err(|e| SpecIssue {
+                    position: e.span().map(|span| line_column(source, span.start)),
+                    message: e.message().to_string(),
+                })
+        };
+        let (mut spec, locations) = parsed.map_err(|issue| Error::InvalidSpec {
+            issues: vec![issue],
+        })?;
+        spec.source = Some(source.to_string());
+        spec.locations = locations;
+        Ok(spec)
+    }
+
+    /// Serialize the spec as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| Error::Serialization(e.to_string()))
+    }
+
+    /// Expand the steps into nodes and connections
+    ///
+    /// Fails with [`Error::InvalidSpec`] listing every duplicate step id,
+    /// unknown node kind, unresolved `needs` reference and dependency cycle.
+    pub fn compile(&self, registry: &NodeTypeRegistry) -> Result<CreateWorkflowRequest> {
+        let mut issues = Vec::new();
+        let location = |index: usize| self.locations.get(index);
+
+        let mut seen = HashSet::new();
+        for (index, step) in self.steps.iter().enumerate() {
+            if !seen.insert(step.id.as_str()) {
+                let at = location(index).map(|offsets| offsets.id);
+                issues.push(self.issue(at, format!("duplicate step id `{}`", step.id)));
+            }
+        }
+
+        let mut node_types = Vec::with_capacity(self.steps.len());
+        for (index, step) in self.steps.iter().enumerate() {
+            let node_type = registry.resolve(&step.uses);
+            if node_type.is_none() {
+                let at = location(index).map(|offsets| offsets.uses);
+                issues.push(self.issue(
+                    at,
+                    format!("step `{}` uses unknown node kind `{}`", step.id, step.uses),
+                ));
+            }
+            node_types.push(node_type.unwrap_or_default());
+
+            for (need_index, need) in step.needs.iter().enumerate() {
+                if !seen.contains(need.as_str()) {
+                    let at = location(index).and_then(|offsets| offsets.needs.get(need_index));
+                    issues.push(self.issue(
+                        at.copied(),
+                        format!("step `{}` needs unknown step `{}`", step.id, need),
+                    ));
+                }
+            }
+        }
+
+        let depths = match self.depths() {
+            Ok(depths) => depths,
+            Err(index) => {
+                let step = &self.steps[index];
+                issues.push(self.issue(
+                    location(index).map(|offsets| offsets.id),
+                    format!("step `{}` is part of a dependency cycle", step.id),
+                ));
+                Vec::new()
+            }
+        };
+
+        if !issues.is_empty() {
+            return Err(Error::InvalidSpec { issues });
+        }
+
+        let mut rows: HashMap<usize, usize> = HashMap::new();
+        let nodes = self
+            .steps
+            .iter()
+            .zip(node_types)
+            .zip(&depths)
+            .map(|((step, node_type), &depth)| {
+                let row = rows.entry(depth).or_default();
+                let position = Position {
+                    x: ORIGIN.x + depth as f64 * COLUMN_WIDTH,
+                    y: ORIGIN.y + *row as f64 * ROW_HEIGHT,
+                };
+                *row += 1;
+                NodeDefinition {
+                    id: step.id.clone(),
+                    name: step.name.clone().unwrap_or_else(|| step.id.clone()),
+                    node_type: node_type.to_string(),
+                    position,
+                    parameters: step.with.clone().into_iter().collect(),
+                }
+            })
+            .collect();
+
+        let connections = self
+            .steps
+            .iter()
+            .flat_map(|step| {
+                step.needs.iter().map(|need| Connection {
+                    source: ConnectionPoint {
+                        node_id: need.clone(),
+                        output_index: Some(0),
+                        input_index: None,
+                    },
+                    destination: ConnectionPoint {
+                        node_id: step.id.clone(),
+                        output_index: None,
+                        input_index: Some(0),
+                    },
+                })
+            })
+            .collect();
+
+        Ok(CreateWorkflowRequest {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            nodes,
+            connections,
+            settings: (!self.settings.is_empty())
+                .then(|| self.settings.clone().into_iter().collect()),
+        })
+    }
+
+    /// Length of the longest `needs` chain leading to each step
+    ///
+    /// Fails with the index of a step on a cycle. Unknown needs are ignored.
+    fn depths(&self) -> std::result::Result<Vec<usize>, usize> {
+        let index: HashMap<&str, usize> = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| (step.id.as_str(), i))
+            .collect();
+        let mut depths: Vec<Option<usize>> = vec![None; self.steps.len()];
+        // Steps whose depth is being computed, to detect cycles
+        let mut visiting = vec![false; self.steps.len()];
+
+        fn visit(
+            i: usize,
+            spec: &WorkflowSpec,
+            index: &HashMap<&str, usize>,
+            depths: &mut Vec<Option<usize>>,
+            visiting: &mut Vec<bool>,
+        ) -> std::result::Result<usize, usize> {
+            if let Some(depth) = depths[i] {
+                return Ok(depth);
+            }
+            if visiting[i] {
+                return Err(i);
+            }
+            visiting[i] = true;
+            let mut depth = 0;
+            for need in &spec.steps[i].needs {
+                if let Some(&j) = index.get(need.as_str()) {
+                    depth = depth.max(visit(j, spec, index, depths, visiting)? + 1);
+                }
+            }
+            visiting[i] = false;
+            depths[i] = Some(depth);
+            Ok(depth)
+        }
+
+        (0..self.steps.len())
+            .map(|i| visit(i, self, &index, &mut depths, &mut visiting))
+            .collect()
+    }
+
+    fn issue(&self, offset: Option<usize>, message: String) -> SpecIssue {
+        SpecIssue {
+            position: self
+                .source
+                .as_deref()
+                .zip(offset)
+                .map(|(source, offset)| line_column(source, offset)),
+            message,
+        }
+    }
+}
+
+/// 1-based line and column of a byte offset
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before.len(), |i| before.len() - i - 1)
+        + 1;
+    (line, column)
+}
@@ -0,0 +1,251 @@
+use crate::client::{path_segment, Client};
+use crate::models::ExecutionStatus;
+use crate::page::Page;
+use crate::timeouts::OperationClass;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use tracing::{debug, info};
+
+/// Header carrying the hex HMAC-SHA256 of a delivered event body
+pub const EVENT_SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+/// Header carrying the time the server sent an event delivery
+pub const EVENT_TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+
+/// Kind of an event delivered to a subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EventType {
+    #[serde(rename = "execution.started")]
+    ExecutionStarted,
+    #[serde(rename = "execution.completed")]
+    ExecutionCompleted,
+    #[serde(rename = "execution.failed")]
+    ExecutionFailed,
+    #[serde(rename = "execution.cancelled")]
+    ExecutionCancelled,
+    #[serde(rename = "node.failed")]
+    NodeFailed,
+    /// An event type this version of the SDK does not know
+    #[serde(other)]
+    Unknown,
+}
+
+/// Request to subscribe an endpoint to execution events
+#[derive(Clone, Serialize)]
+pub struct SubscriptionRequest {
+    /// Endpoint the server POSTs events to
+    pub url: String,
+    pub events: Vec<EventType>,
+    /// Workflows to receive events for; empty for all workflows
+    #[serde(rename = "workflowIds", skip_serializing_if = "Vec::is_empty")]
+    pub workflow_ids: Vec<String>,
+    /// Secret deliveries are signed with, see [`verify_event_signature`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl std::fmt::Debug for SubscriptionRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubscriptionRequest")
+            .field("url", &self.url)
+            .field("events", &self.events)
+            .field("workflow_ids", &self.workflow_ids)
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// A registered event subscription; the secret is never returned
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventSubscription {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<EventType>,
+    /// Empty when the subscription covers all workflows
+    #[serde(rename = "workflowIds", default)]
+    pub workflow_ids: Vec<String>,
+    #[serde(default = "default_active")]
+    pub active: bool,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+/// Result of [`Client::ping_event_subscription`]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SubscriptionPing {
+    /// Whether the endpoint answered with a 2xx status
+    pub delivered: bool,
+    /// Status the endpoint answered with, if it answered
+    #[serde(rename = "statusCode", default)]
+    pub status_code: Option<u16>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// An event as delivered to a subscribed endpoint
+///
+/// Deserialize the request body into this after checking it with
+/// [`verify_event_signature`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveredEvent {
+    /// Delivery ID, the same across redeliveries of one event
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    #[serde(rename = "subscriptionId")]
+    pub subscription_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub data: ExecutionEventData,
+}
+
+/// Execution the delivered event is about
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionEventData {
+    #[serde(rename = "executionId")]
+    pub execution_id: String,
+    #[serde(rename = "workflowId")]
+    pub workflow_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ExecutionStatus>,
+    /// Node the event is about, for node events
+    #[serde(rename = "nodeId", default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Output data, for completed executions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+}
+
+impl Client {
+    /// Subscribe an endpoint to execution events
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::{EventType, SubscriptionRequest};
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let subscription = client
+    ///     .create_event_subscription(SubscriptionRequest {
+    ///         url: "https://hooks.example.com/klikkflow".to_string(),
+    ///         events: vec![EventType::ExecutionFailed],
+    ///         workflow_ids: Vec::new(),
+    ///         secret: Some("s3cret".to_string()),
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_event_subscription(
+        &self,
+        request: SubscriptionRequest,
+    ) -> Result<EventSubscription> {
+        info!("Creating event subscription for {}", request.url);
+        self.make_request(
+            OperationClass::Mutate,
+            "POST",
+            "/api/event-subscriptions",
+            Some(&request),
+        )
+        .await
+    }
+
+    /// List event subscriptions
+    pub async fn list_event_subscriptions(&self) -> Result<Page<EventSubscription>> {
+        debug!("Listing event subscriptions");
+        let response: Value = self
+            .make_request(
+                OperationClass::Read,
+                "GET",
+                "/api/event-subscriptions",
+                None::<&()>,
+            )
+            .await?;
+        self.list_page(response, "subscriptions", None, None)
+    }
+
+    /// Delete an event subscription
+    pub async fn delete_event_subscription(&self, subscription_id: &str) -> Result<()> {
+        info!("Deleting event subscription: {}", subscription_id);
+        let path = format!("/api/event-subscriptions/{}", path_segment(subscription_id));
+        let _: Value = self
+            .make_request(OperationClass::Mutate, "DELETE", &path, None::<&()>)
+            .await?;
+        Ok(())
+    }
+
+    /// Ask the server to deliver a test event to a subscription's endpoint
+    pub async fn ping_event_subscription(&self, subscription_id: &str) -> Result<SubscriptionPing> {
+        debug!("Pinging event subscription: {}", subscription_id);
+        let path = format!(
+            "/api/event-subscriptions/{}/ping",
+            path_segment(subscription_id)
+        );
+        self.make_request(OperationClass::Mutate, "POST", &path, None::<&()>)
+            .await
+    }
+}
+
+/// Check that an event delivery was signed with `secret`
+///
+/// The server signs the raw request body with HMAC-SHA256 and sends the
+/// hex digest in the [`EVENT_SIGNATURE_HEADER`], optionally prefixed with
+/// `sha256=`. Pass the body exactly as received, before parsing it. The
+/// comparison takes the same time wherever the signatures differ.
+///
+/// ```rust
+/// use klikkflow_sdk::{verify_event_signature, DeliveredEvent, EventType};
+/// use reqwest::header::HeaderMap;
+///
+/// let body = br#"{"id":"dl-1","type":"execution.failed","subscriptionId":"sub-1","timestamp":"2024-01-01T00:00:00Z","data":{"executionId":"ex-1","workflowId":"wf-1","status":"error","error":"boom"}}"#;
+/// let mut headers = HeaderMap::new();
+/// headers.insert(
+///     "x-webhook-signature",
+///     "sha256=9a9728c2df005ed846e5995ab0720119a5a32881b8434e7fe54d361763fa9cf8".parse().unwrap(),
+/// );
+/// assert!(verify_event_signature("s3cret", &headers, body));
+/// assert!(!verify_event_signature("another-secret", &headers, body));
+///
+/// let event: DeliveredEvent = serde_json::from_slice(body).unwrap();
+/// assert_eq!(event.event_type, EventType::ExecutionFailed);
+/// assert_eq!(event.data.error.as_deref(), Some("boom"));
+/// ```
+pub fn verify_event_signature(secret: &str, headers: &HeaderMap, body: &[u8]) -> bool {
+    let Some(signature) = headers
+        .get(EVENT_SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let signature = signature.trim();
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+    let Some(signature) = decode_hex(signature) else {
+        return false;
+    };
+
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Bytes of a hex string in either case, or `None` if it is not hex
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
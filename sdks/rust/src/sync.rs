@@ -0,0 +1,427 @@
+use crate::client::Client;
+use crate::models::*;
+use crate::redact::glob_match;
+use crate::snapshot::{canonical_graph, SnapshotOptions};
+use crate::{Error, Result};
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// How files are matched to workflows on the server by [`Client::sync_workflows`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncMatch {
+    /// By workflow name; names must be unique on both sides
+    #[default]
+    Name,
+    /// By the `id` field of the file; files without one are created
+    IdFile,
+}
+
+/// Options of [`Client::sync_workflows`]
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    /// Delete workflows on the server that have no file
+    pub delete_extraneous: bool,
+    /// Only compute the plan, changing nothing
+    pub dry_run: bool,
+    pub match_by: SyncMatch,
+    /// Workflow names, or `*` patterns, never deleted even with `delete_extraneous`
+    pub protected: Vec<String>,
+    /// Most changes applied at the same time
+    pub concurrency: usize,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            delete_extraneous: false,
+            dry_run: false,
+            match_by: SyncMatch::default(),
+            protected: Vec::new(),
+            concurrency: 4,
+        }
+    }
+}
+
+/// What [`Client::sync_workflows`] does with one workflow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncAction {
+    /// The file has no workflow on the server
+    Create,
+    /// The workflow differs from its file in the listed fields
+    Update { fields: Vec<&'static str> },
+    /// The workflow matches its file
+    Unchanged,
+    /// The workflow has no file and is deleted
+    Delete,
+    /// The workflow has no file but is kept, being protected or `delete_extraneous` unset
+    Retain,
+}
+
+/// One workflow of a [`SyncPlan`]
+#[derive(Debug, Clone)]
+pub struct SyncItem {
+    pub name: String,
+    /// The workflow on the server, `None` for a create
+    pub workflow_id: Option<String>,
+    /// The file of the workflow, `None` for workflows without one
+    pub path: Option<PathBuf>,
+    pub action: SyncAction,
+}
+
+/// Changes needed to make the server match a directory, in file name order
+/// followed by the workflows without a file
+#[derive(Debug, Clone, Default)]
+pub struct SyncPlan {
+    pub items: Vec<SyncItem>,
+    /// Requests to send, by index into `items`
+    changes: Vec<(usize, Change)>,
+}
+
+#[derive(Debug, Clone)]
+enum Change {
+    /// The request, and whether to activate the workflow once created
+    Create(CreateWorkflowRequest, bool),
+    Update(String, UpdateWorkflowRequest),
+    Delete(String),
+}
+
+impl SyncPlan {
+    /// Whether the server already matches the directory
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for SyncPlan {
+    /// One line per workflow: `+` create, `~` update, `-` delete, `=` unchanged, `!` retained
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for item in &self.items {
+            match &item.action {
+                SyncAction::Create => writeln!(f, "+ {}", item.name)?,
+                SyncAction::Update { fields } => {
+                    writeln!(f, "~ {} ({})", item.name, fields.join(", "))?
+                }
+                SyncAction::Unchanged => writeln!(f, "= {}", item.name)?,
+                SyncAction::Delete => writeln!(f, "- {}", item.name)?,
+                SyncAction::Retain => writeln!(f, "! {} (no file, kept)", item.name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Result of applying one change of a [`SyncPlan`]
+#[derive(Debug)]
+pub struct SyncOutcome {
+    /// Index of the item in [`SyncPlan::items`]
+    pub item: usize,
+    /// ID of the workflow created, updated or deleted
+    pub result: Result<String>,
+}
+
+/// Result of [`Client::sync_workflows`]
+#[derive(Debug)]
+pub struct SyncReport {
+    pub plan: SyncPlan,
+    /// One outcome per change, in plan order; empty for a dry run
+    pub outcomes: Vec<SyncOutcome>,
+}
+
+impl SyncReport {
+    /// Whether every change was applied
+    pub fn is_success(&self) -> bool {
+        self.outcomes.iter().all(|outcome| outcome.result.is_ok())
+    }
+}
+
+/// A workflow file; server-managed fields other than `id` are ignored
+#[derive(Debug, Deserialize)]
+struct WorkflowFile {
+    #[serde(default)]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    active: Option<bool>,
+    #[serde(default)]
+    nodes: Vec<NodeDefinition>,
+    #[serde(default)]
+    connections: Vec<Connection>,
+    #[serde(default)]
+    settings: FieldMap,
+}
+
+impl Client {
+    /// Make the workflows on the server match a directory of workflow files
+    ///
+    /// Every `*.json` file of `dir` holds one workflow, as exported by the
+    /// server or written by hand. Files are matched to workflows according to
+    /// [`match_by`](SyncOptions::match_by); workflows without a file are
+    /// created, and activated if the file sets `active`, and those differing
+    /// from their file are updated. Definitions
+    /// are compared after normalization, ignoring node and connection order,
+    /// timestamps, and `active` unless the file sets it. Workflows with no
+    /// file are only deleted with [`delete_extraneous`](SyncOptions::delete_extraneous),
+    /// and never when their name is [protected](SyncOptions::protected).
+    ///
+    /// The plan is computed before anything changes and returned in the
+    /// report; print it for a summary. Changes are then applied
+    /// [`concurrency`](SyncOptions::concurrency) at a time, carrying on
+    /// past failures, which are reported per item.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::SyncOptions;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let options = SyncOptions {
+    ///     delete_extraneous: true,
+    ///     protected: vec!["Legacy".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// let report = client.sync_workflows("workflows/", options).await?;
+    /// print!("{}", report.plan);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sync_workflows(
+        &self,
+        dir: impl AsRef<Path>,
+        options: SyncOptions,
+    ) -> Result<SyncReport> {
+        let plan = self.plan_workflow_sync(dir.as_ref(), &options).await?;
+        info!(
+            "Workflow sync plan for {}: {} change(s)\n{}",
+            dir.as_ref().display(),
+            plan.changes.len(),
+            plan
+        );
+        if options.dry_run {
+            return Ok(SyncReport {
+                plan,
+                outcomes: Vec::new(),
+            });
+        }
+
+        let items = &plan.items;
+        let outcomes = stream::iter(plan.changes.clone())
+            .map(|(item, change)| async move {
+                let result = match change {
+                    Change::Create(request, activate) => {
+                        self.create_and_activate(request, activate).await
+                    }
+                    Change::Update(workflow_id, request) => self
+                        .update_workflow(&workflow_id, request)
+                        .await
+                        .map(|workflow| workflow.id),
+                    Change::Delete(workflow_id) => self
+                        .delete_workflow(&workflow_id)
+                        .await
+                        .map(|()| workflow_id),
+                };
+                if let Err(e) = &result {
+                    warn!("Failed to sync workflow {}: {}", items[item].name, e);
+                }
+                SyncOutcome { item, result }
+            })
+            .buffered(options.concurrency.max(1))
+            .collect()
+            .await;
+        Ok(SyncReport { plan, outcomes })
+    }
+
+    /// Create a workflow, activating it if its file asks for it
+    async fn create_and_activate(
+        &self,
+        request: CreateWorkflowRequest,
+        activate: bool,
+    ) -> Result<String> {
+        let workflow = self.create_workflow(request).await?;
+        if activate {
+            self.activate_workflow(&workflow.id).await?;
+        }
+        Ok(workflow.id)
+    }
+
+    /// Compare the files of `dir` with the workflows on the server
+    async fn plan_workflow_sync(&self, dir: &Path, options: &SyncOptions) -> Result<SyncPlan> {
+        let files = read_workflow_files(dir)?;
+        let remote: Vec<WorkflowDefinition> = self
+            .stream_workflows(ListWorkflowsOptions::default())
+            .try_collect()
+            .await?;
+
+        let key = |id: &str, name: &str| match options.match_by {
+            SyncMatch::Name => name.to_string(),
+            SyncMatch::IdFile => id.to_string(),
+        };
+        let mut by_key: HashMap<String, &WorkflowDefinition> = HashMap::new();
+        for workflow in &remote {
+            if by_key
+                .insert(key(&workflow.id, &workflow.name), workflow)
+                .is_some()
+            {
+                return Err(Error::InvalidInput(format!(
+                    "several workflows on the server are named {}",
+                    workflow.name
+                )));
+            }
+        }
+
+        let mut plan = SyncPlan::default();
+        let mut seen = HashSet::new();
+        for (path, file) in files {
+            let file_key = match options.match_by {
+                SyncMatch::Name => Some(file.name.clone()),
+                SyncMatch::IdFile => file.id.clone(),
+            };
+            if let Some(file_key) = &file_key {
+                if !seen.insert(file_key.clone()) {
+                    return Err(Error::InvalidInput(format!(
+                        "{} matches the same workflow as another file",
+                        path.display()
+                    )));
+                }
+            }
+
+            let index = plan.items.len();
+            let existing = file_key.and_then(|file_key| by_key.get(&file_key).copied());
+            let (action, change) = match existing {
+                None => (
+                    SyncAction::Create,
+                    Some(Change::Create(
+                        CreateWorkflowRequest {
+                            name: file.name.clone(),
+                            description: file.description.clone(),
+                            nodes: file.nodes.clone(),
+                            connections: file.connections.clone(),
+                            settings: Some(file.settings.clone()),
+                        },
+                        file.active == Some(true),
+                    )),
+                ),
+                Some(workflow) => {
+                    let fields = changed_fields(&file, workflow);
+                    if fields.is_empty() {
+                        (SyncAction::Unchanged, None)
+                    } else {
+                        let request = update_request(&file);
+                        (
+                            SyncAction::Update { fields },
+                            Some(Change::Update(workflow.id.clone(), request)),
+                        )
+                    }
+                }
+            };
+            if let Some(change) = change {
+                plan.changes.push((index, change));
+            }
+            plan.items.push(SyncItem {
+                name: file.name,
+                workflow_id: existing.map(|workflow| workflow.id.clone()),
+                path: Some(path),
+                action,
+            });
+        }
+
+        for workflow in &remote {
+            if seen.contains(&key(&workflow.id, &workflow.name)) {
+                continue;
+            }
+            let protected = options
+                .protected
+                .iter()
+                .any(|pattern| glob_match(pattern, &workflow.name));
+            let action = if options.delete_extraneous && !protected {
+                plan.changes
+                    .push((plan.items.len(), Change::Delete(workflow.id.clone())));
+                SyncAction::Delete
+            } else {
+                SyncAction::Retain
+            };
+            plan.items.push(SyncItem {
+                name: workflow.name.clone(),
+                workflow_id: Some(workflow.id.clone()),
+                path: None,
+                action,
+            });
+        }
+        Ok(plan)
+    }
+}
+
+/// Parse every `*.json` file of `dir`, sorted by file name
+fn read_workflow_files(dir: &Path) -> Result<Vec<(PathBuf, WorkflowFile)>> {
+    let read_error = |path: &Path, e: std::io::Error| {
+        Error::Io(format!("failed to read {}: {}", path.display(), e))
+    };
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| read_error(dir, e))? {
+        let path = entry.map_err(|e| read_error(dir, e))?.path();
+        if path.is_file()
+            && path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let contents = std::fs::read(&path).map_err(|e| read_error(&path, e))?;
+            let file = serde_json::from_slice(&contents).map_err(|e| {
+                Error::InvalidInput(format!("invalid workflow file {}: {}", path.display(), e))
+            })?;
+            Ok((path, file))
+        })
+        .collect()
+}
+
+/// Fields of `workflow` that differ from `file` after normalization
+fn changed_fields(file: &WorkflowFile, workflow: &WorkflowDefinition) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if file.name != workflow.name {
+        fields.push("name");
+    }
+    if file.description != workflow.description {
+        fields.push("description");
+    }
+    if file.active.is_some_and(|active| active != workflow.active) {
+        fields.push("active");
+    }
+    // Normalized like snapshots, so sync and snapshots agree on what changed
+    let options = SnapshotOptions::default();
+    let (file_nodes, file_connections) = canonical_graph(&file.nodes, &file.connections, &options);
+    let (nodes, connections) = canonical_graph(&workflow.nodes, &workflow.connections, &options);
+    if file_nodes != nodes {
+        fields.push("nodes");
+    }
+    if file_connections != connections {
+        fields.push("connections");
+    }
+    if serde_json::to_value(&file.settings).ok() != serde_json::to_value(&workflow.settings).ok() {
+        fields.push("settings");
+    }
+    fields
+}
+
+/// Request replacing the whole definition with the file's
+fn update_request(file: &WorkflowFile) -> UpdateWorkflowRequest {
+    UpdateWorkflowRequest {
+        name: Some(file.name.clone()),
+        description: Some(file.description.clone()),
+        active: file.active,
+        nodes: file.nodes.clone().into(),
+        connections: file.connections.clone().into(),
+        settings: file.settings.clone().into(),
+    }
+}
@@ -0,0 +1,213 @@
+use crate::client::{path_segment, Client};
+use crate::models::*;
+use crate::websocket::WebSocketStream;
+use crate::Result;
+use futures_util::stream::{self, Stream};
+use futures_util::StreamExt;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+use tracing::{debug, info};
+
+/// Interval at which a followed tail looks for a newer execution
+const TAIL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Executions fetched to find the most recent one
+const TAIL_HISTORY_PAGE: usize = 10;
+
+/// Item of a stream from [`Client::tail_latest_execution`]
+#[derive(Debug, Clone)]
+pub enum TailEvent {
+    /// An update of the execution being tailed
+    Update {
+        execution_id: String,
+        update: ExecutionUpdate,
+    },
+    /// A newer execution started and the tail moved on to it
+    SwitchedExecution { from: String, to: String },
+}
+
+struct TailState {
+    client: Client,
+    workflow_id: String,
+    follow: bool,
+    current: Option<(String, WebSocketStream)>,
+    /// Whether the current execution's stream ended
+    drained: bool,
+    // Created on first use, since timers need a running runtime
+    ticker: Option<Interval>,
+    pending: VecDeque<TailEvent>,
+}
+
+impl TailState {
+    fn ticker(&mut self) -> &mut Interval {
+        self.ticker.get_or_insert_with(|| {
+            let mut ticker = interval(TAIL_POLL_INTERVAL);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ticker
+        })
+    }
+
+    /// The most recently started execution of the workflow, if it has any
+    async fn latest(&self) -> Result<Option<ExecutionResult>> {
+        let options = ExecutionHistoryOptions {
+            limit: Some(TAIL_HISTORY_PAGE),
+            ..Default::default()
+        };
+        let history = self
+            .client
+            .get_execution_history(&self.workflow_id, Some(options))
+            .await?;
+        Ok(history
+            .into_iter()
+            .max_by_key(|execution| execution.started_at))
+    }
+
+    /// Stream an execution's updates, replaying those already sent
+    async fn open(&mut self, execution_id: String) -> Result<()> {
+        debug!("Tailing execution {}", execution_id);
+        let stream = self
+            .client
+            .connect_stream(&format!(
+                "/ws/execution/{}?replay=true",
+                path_segment(&execution_id)
+            ))
+            .await?;
+        if let Some((from, _)) = self.current.replace((execution_id.clone(), stream)) {
+            info!(
+                "Execution {} started, switching from {}",
+                execution_id, from
+            );
+            self.pending.push_back(TailEvent::SwitchedExecution {
+                from,
+                to: execution_id,
+            });
+        }
+        self.drained = false;
+        self.ticker().reset();
+        Ok(())
+    }
+
+    /// Switch to the most recent execution if it is not the one tailed
+    async fn check_latest(&mut self) -> Result<bool> {
+        let Some(latest) = self.latest().await? else {
+            return Ok(false);
+        };
+        if self.current.as_ref().map(|(id, _)| id) == Some(&latest.id) {
+            return Ok(false);
+        }
+        self.open(latest.id).await?;
+        Ok(true)
+    }
+
+    async fn next(&mut self) -> Option<Result<TailEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.current.is_none() {
+                match self.check_latest().await {
+                    Ok(true) => continue,
+                    Ok(false) if !self.follow => return None,
+                    Ok(false) => {
+                        self.ticker().tick().await;
+                        continue;
+                    }
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+
+            self.ticker();
+            let (Some((execution_id, stream)), Some(ticker)) =
+                (&mut self.current, &mut self.ticker)
+            else {
+                return None;
+            };
+            let update = if self.drained {
+                None
+            } else if self.follow {
+                tokio::select! {
+                    update = stream.next() => Some(update),
+                    _ = ticker.tick() => None,
+                }
+            } else {
+                Some(stream.next().await)
+            };
+            match update {
+                Some(Some(Ok(update))) => {
+                    return Some(Ok(TailEvent::Update {
+                        execution_id: execution_id.clone(),
+                        update,
+                    }))
+                }
+                Some(Some(Err(e))) => return Some(Err(e)),
+                Some(None) if !self.follow => return None,
+                Some(None) => {
+                    debug!("Stream of execution {} ended", execution_id);
+                    self.drained = true;
+                }
+                None => {
+                    // A finished execution is left as soon as a newer one is seen
+                    if self.drained {
+                        self.ticker().tick().await;
+                    }
+                    if let Err(e) = self.check_latest().await {
+                        return Some(Err(e));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Stream the updates of a workflow's most recent execution, running or not
+    ///
+    /// The execution's earlier updates are replayed first, where the server
+    /// keeps them. Without `follow`, the stream ends with the execution's
+    /// stream. With `follow`, the workflow's executions are checked every
+    /// second, also while updates arrive: once a newer execution starts, the
+    /// tail yields [`TailEvent::SwitchedExecution`] and continues with its
+    /// updates, and a workflow without executions is waited on until one
+    /// starts. Errors are yielded without ending a followed tail.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use futures_util::StreamExt;
+    /// use klikkflow_sdk::TailEvent;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let mut tail = Box::pin(client.tail_latest_execution("wf-ingest", true));
+    /// while let Some(event) = tail.next().await {
+    ///     match event? {
+    ///         TailEvent::Update { execution_id, update } => {
+    ///             println!("{}: {}", execution_id, update.update_type)
+    ///         }
+    ///         TailEvent::SwitchedExecution { to, .. } => println!("now tailing {}", to),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tail_latest_execution(
+        &self,
+        workflow_id: &str,
+        follow: bool,
+    ) -> impl Stream<Item = Result<TailEvent>> {
+        info!("Tailing latest execution of workflow: {}", workflow_id);
+        let state = TailState {
+            client: self.internal("tail-poll"),
+            workflow_id: workflow_id.to_string(),
+            follow,
+            current: None,
+            drained: false,
+            ticker: None,
+            pending: VecDeque::new(),
+        };
+        stream::unfold(state, |mut state| async move {
+            let event = state.next().await?;
+            Some((event, state))
+        })
+    }
+}
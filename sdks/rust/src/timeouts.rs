@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+/// Class of an API operation, used to pick its timeout
+///
+/// Every client method is tagged with the class matching its expected cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationClass {
+    /// Cheap lookups and listings that should fail fast
+    Read,
+    /// Requests that create, change or delete resources
+    Mutate,
+    /// Operations that legitimately take minutes, such as usage and time
+    /// series reports aggregated by the server over long date ranges
+    LongRunning,
+    /// Establishing a streaming connection
+    Stream,
+}
+
+/// Timeouts per operation class
+///
+/// When set on a client, overrides the single timeout configured with
+/// [`Client::with_timeout`](crate::Client::with_timeout).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutProfile {
+    pub read: Duration,
+    pub mutate: Duration,
+    pub long_running: Duration,
+    pub stream: Duration,
+}
+
+impl Default for TimeoutProfile {
+    fn default() -> Self {
+        Self {
+            read: Duration::from_secs(5),
+            mutate: Duration::from_secs(30),
+            long_running: Duration::from_secs(600),
+            stream: Duration::from_secs(10),
+        }
+    }
+}
+
+impl TimeoutProfile {
+    /// Timeout applied to operations of the given class
+    pub fn timeout_for(&self, class: OperationClass) -> Duration {
+        match class {
+            OperationClass::Read => self.read,
+            OperationClass::Mutate => self.mutate,
+            OperationClass::LongRunning => self.long_running,
+            OperationClass::Stream => self.stream,
+        }
+    }
+}
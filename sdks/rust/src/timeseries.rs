@@ -0,0 +1,239 @@
+use crate::client::{path_segment, Client};
+use crate::models::{ExecutionHistoryOptions, ExecutionStatus};
+use crate::timeouts::OperationClass;
+use crate::{Error, Result};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, FixedOffset, SecondsFormat, Timelike, Utc,
+};
+use futures_util::TryStreamExt;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Page size used when bucketing the execution history client-side
+const HISTORY_PAGE_SIZE: usize = 200;
+
+/// Width of the buckets of an execution time series
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketSize {
+    Hour,
+    Day,
+    /// Weeks starting on Monday
+    Week,
+}
+
+impl BucketSize {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BucketSize::Hour => "hour",
+            BucketSize::Day => "day",
+            BucketSize::Week => "week",
+        }
+    }
+
+    fn width(&self) -> ChronoDuration {
+        match self {
+            BucketSize::Hour => ChronoDuration::hours(1),
+            BucketSize::Day => ChronoDuration::days(1),
+            BucketSize::Week => ChronoDuration::weeks(1),
+        }
+    }
+
+    /// Start of the bucket containing `instant`, with boundaries at local time in `offset`
+    fn floor(&self, instant: DateTime<Utc>, offset: FixedOffset) -> DateTime<Utc> {
+        let local = instant.with_timezone(&offset);
+        let date = match self {
+            BucketSize::Week => {
+                local.date_naive()
+                    - ChronoDuration::days(local.weekday().num_days_from_monday().into())
+            }
+            _ => local.date_naive(),
+        };
+        let hour = if *self == BucketSize::Hour {
+            local.hour()
+        } else {
+            0
+        };
+        let naive = date.and_hms_opt(hour, 0, 0).expect("valid bucket start");
+        (naive - offset).and_utc()
+    }
+}
+
+/// Executions that started within one bucket of a time series
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeBucket {
+    pub start: DateTime<Utc>,
+    pub counts_by_status: HashMap<ExecutionStatus, u64>,
+    /// Average duration of the bucket's finished executions
+    pub avg_duration: Option<Duration>,
+}
+
+impl TimeBucket {
+    /// Total number of executions in the bucket
+    pub fn total(&self) -> u64 {
+        self.counts_by_status.values().sum()
+    }
+}
+
+#[derive(Deserialize)]
+struct TimeseriesResponse {
+    buckets: Vec<ServerBucket>,
+}
+
+#[derive(Deserialize)]
+struct ServerBucket {
+    start: DateTime<Utc>,
+    #[serde(rename = "countsByStatus", default)]
+    counts_by_status: HashMap<ExecutionStatus, u64>,
+    /// Milliseconds
+    #[serde(rename = "avgDuration", default)]
+    avg_duration: Option<f64>,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    counts_by_status: HashMap<ExecutionStatus, u64>,
+    total_duration: Duration,
+    finished: u32,
+}
+
+impl Client {
+    /// Count a workflow's executions per bucket and status over `range`, with UTC bucket boundaries
+    pub async fn get_execution_timeseries(
+        &self,
+        workflow_id: &str,
+        range: Range<DateTime<Utc>>,
+        bucket: BucketSize,
+    ) -> Result<Vec<TimeBucket>> {
+        self.get_execution_timeseries_in(
+            workflow_id,
+            range,
+            bucket,
+            FixedOffset::east_opt(0).expect("zero offset"),
+        )
+        .await
+    }
+
+    /// Count a workflow's executions per bucket and status over `range`
+    ///
+    /// Bucket boundaries fall on local midnight (or the local hour) at
+    /// `offset`; bucket starts are returned in UTC. Every bucket overlapping
+    /// the range is returned, including empty ones. Uses the server's
+    /// aggregation endpoint when available and otherwise buckets the
+    /// execution history page by page, without execution data.
+    pub async fn get_execution_timeseries_in(
+        &self,
+        workflow_id: &str,
+        range: Range<DateTime<Utc>>,
+        bucket: BucketSize,
+        offset: FixedOffset,
+    ) -> Result<Vec<TimeBucket>> {
+        if range.start >= range.end {
+            return Err(Error::InvalidInput(
+                "time series range must not be empty".to_string(),
+            ));
+        }
+        debug!(
+            "Getting {} execution time series for workflow: {}",
+            bucket.as_str(),
+            workflow_id
+        );
+
+        let path = format!(
+            "/api/workflows/{}/executions/timeseries?from={}&to={}&bucket={}&utcOffset={}",
+            path_segment(workflow_id),
+            range.start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            range.end.to_rfc3339_opts(SecondsFormat::Secs, true),
+            bucket.as_str(),
+            offset_param(offset)
+        );
+        match self
+            .make_request::<TimeseriesResponse, ()>(OperationClass::LongRunning, "GET", &path, None)
+            .await
+        {
+            Ok(response) => {
+                return Ok(response
+                    .buckets
+                    .into_iter()
+                    .map(|b| TimeBucket {
+                        start: b.start,
+                        counts_by_status: b.counts_by_status,
+                        avg_duration: b
+                            .avg_duration
+                            .map(|ms| Duration::from_secs_f64(ms.max(0.0) / 1000.0)),
+                    })
+                    .collect())
+            }
+            Err(Error::NotFound { .. }) => {
+                info!("Server has no time series endpoint, bucketing execution history");
+            }
+            Err(e) => return Err(e),
+        }
+
+        let first = bucket.floor(range.start, offset);
+        let mut starts = Vec::new();
+        let mut start = first;
+        while start < range.end {
+            starts.push(start);
+            start += bucket.width();
+        }
+        let mut accumulators: Vec<Accumulator> =
+            starts.iter().map(|_| Accumulator::default()).collect();
+
+        // New executions shift offset pages while they are read, so one may be seen twice
+        let mut seen = HashSet::new();
+        let options = ExecutionHistoryOptions {
+            limit: Some(HISTORY_PAGE_SIZE),
+            include_data: Some(false),
+            ..Default::default()
+        };
+        let history = self.stream_execution_history(workflow_id, options);
+        futures_util::pin_mut!(history);
+        while let Some(execution) = history.try_next().await? {
+            // History is newest first, so an execution before the range ends the scan
+            if execution.started_at < range.start {
+                break;
+            }
+            if execution.started_at >= range.end || !seen.insert(execution.id) {
+                continue;
+            }
+            let index = ((execution.started_at - first).num_seconds()
+                / bucket.width().num_seconds()) as usize;
+            let Some(accumulator) = accumulators.get_mut(index) else {
+                continue;
+            };
+            *accumulator
+                .counts_by_status
+                .entry(execution.status)
+                .or_default() += 1;
+            if let Some(duration) = execution
+                .finished_at
+                .and_then(|finished| (finished - execution.started_at).to_std().ok())
+            {
+                accumulator.total_duration += duration;
+                accumulator.finished += 1;
+            }
+        }
+
+        Ok(starts
+            .into_iter()
+            .zip(accumulators)
+            .map(|(start, accumulator)| TimeBucket {
+                start,
+                counts_by_status: accumulator.counts_by_status,
+                avg_duration: (accumulator.finished > 0)
+                    .then(|| accumulator.total_duration / accumulator.finished),
+            })
+            .collect())
+    }
+}
+
+/// Format an offset as `+HH:MM` for use in a query string
+fn offset_param(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { "-" } else { "%2B" };
+    let minutes = seconds.abs() / 60;
+    format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
@@ -0,0 +1,270 @@
+use crate::{Error, Result};
+use std::fmt;
+use tokio_tungstenite::Connector;
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+const PEM_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+const PEM_END: &str = "-----END CERTIFICATE-----";
+
+/// Client certificate for mutual TLS, see [`ClientBuilder::identity`](crate::ClientBuilder::identity)
+#[derive(Clone)]
+pub struct ClientIdentity(IdentityKind);
+
+#[derive(Clone)]
+#[cfg_attr(not(any(feature = "native-tls", feature = "rustls")), allow(dead_code))]
+enum IdentityKind {
+    // Only the native-tls backend reads PKCS #12 archives
+    #[cfg_attr(not(feature = "native-tls"), allow(dead_code))]
+    Pkcs12 { der: Vec<u8>, password: String },
+    Pem {
+        certificate_chain: Vec<u8>,
+        private_key: Vec<u8>,
+    },
+}
+
+impl ClientIdentity {
+    /// DER-encoded PKCS #12 archive holding the certificate and its private key
+    ///
+    /// Requires the `native-tls` feature; the `rustls` backend only reads PEM.
+    pub fn pkcs12(der: impl Into<Vec<u8>>, password: impl Into<String>) -> Self {
+        Self(IdentityKind::Pkcs12 {
+            der: der.into(),
+            password: password.into(),
+        })
+    }
+
+    /// PEM certificate chain and PEM PKCS #8 private key
+    pub fn pem(certificate_chain: impl Into<Vec<u8>>, private_key: impl Into<Vec<u8>>) -> Self {
+        Self(IdentityKind::Pem {
+            certificate_chain: certificate_chain.into(),
+            private_key: private_key.into(),
+        })
+    }
+}
+
+/// Never shows the key material
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = match self.0 {
+            IdentityKind::Pkcs12 { .. } => "pkcs12",
+            IdentityKind::Pem { .. } => "pem",
+        };
+        f.debug_tuple("ClientIdentity").field(&format).finish()
+    }
+}
+
+/// Trust and identity settings shared by API requests and execution streams
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TlsOptions {
+    /// PEM certificates, each possibly a bundle, trusted besides the system roots
+    pub root_certificates: Vec<Vec<u8>>,
+    pub identity: Option<ClientIdentity>,
+    pub accept_invalid_certs: bool,
+}
+
+impl TlsOptions {
+    pub fn is_default(&self) -> bool {
+        self.root_certificates.is_empty() && self.identity.is_none() && !self.accept_invalid_certs
+    }
+
+    /// Apply the settings to the HTTP client
+    ///
+    /// Uses the same TLS backend as [`websocket_connector`](Self::websocket_connector),
+    /// so API requests and execution streams accept the same settings.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn apply(&self, mut builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder> {
+        #[cfg(not(feature = "native-tls"))]
+        if self.accept_invalid_certs {
+            return Err(invalid_certs_unsupported());
+        }
+        for pem in &self.root_certificates {
+            for block in pem_blocks(pem)? {
+                let certificate = reqwest::Certificate::from_pem(block)
+                    .map_err(|e| invalid("root certificate", e))?;
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        if let Some(ClientIdentity(identity)) = &self.identity {
+            builder = builder.identity(http_identity(identity)?);
+        }
+        Ok(builder.danger_accept_invalid_certs(self.accept_invalid_certs))
+    }
+
+    /// Apply the settings to the HTTP client
+    #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+    pub fn apply(&self, builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder> {
+        if self.is_default() {
+            return Ok(builder);
+        }
+        Err(Error::Config(
+            "TLS settings require the native-tls or rustls feature".to_string(),
+        ))
+    }
+
+    /// Connector for `wss://` streams, or `None` to use the defaults
+    #[cfg(feature = "native-tls")]
+    pub fn websocket_connector(&self) -> Result<Option<Connector>> {
+        if self.is_default() {
+            return Ok(None);
+        }
+        let mut builder = native_tls::TlsConnector::builder();
+        for pem in &self.root_certificates {
+            for block in pem_blocks(pem)? {
+                let certificate = native_tls::Certificate::from_pem(block)
+                    .map_err(|e| invalid("root certificate", e))?;
+                builder.add_root_certificate(certificate);
+            }
+        }
+        if let Some(ClientIdentity(identity)) = &self.identity {
+            let identity = match identity {
+                IdentityKind::Pkcs12 { der, password } => {
+                    native_tls::Identity::from_pkcs12(der, password)
+                }
+                IdentityKind::Pem {
+                    certificate_chain,
+                    private_key,
+                } => native_tls::Identity::from_pkcs8(certificate_chain, private_key),
+            }
+            .map_err(|e| invalid("client identity", e))?;
+            builder.identity(identity);
+        }
+        builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        let connector = builder.build().map_err(|e| invalid("TLS settings", e))?;
+        Ok(Some(Connector::NativeTls(connector)))
+    }
+
+    /// Connector for `wss://` streams, or `None` to use the defaults
+    #[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+    pub fn websocket_connector(&self) -> Result<Option<Connector>> {
+        use std::sync::Arc;
+
+        if self.is_default() {
+            return Ok(None);
+        }
+        if self.accept_invalid_certs {
+            return Err(invalid_certs_unsupported());
+        }
+        let mut roots = rustls::RootCertStore::empty();
+        let native = rustls_native_certs::load_native_certs()
+            .map_err(|e| invalid("system root certificates", e))?;
+        for certificate in native {
+            // Unparsable system certificates are skipped, as rustls itself does
+            let _ = roots.add(&rustls::Certificate(certificate.0));
+        }
+        for pem in &self.root_certificates {
+            let certificates = rustls_pemfile::certs(&mut pem.as_slice())
+                .map_err(|e| invalid("root certificate", e))?;
+            for der in certificates {
+                roots
+                    .add(&rustls::Certificate(der))
+                    .map_err(|e| invalid("root certificate", e))?;
+            }
+        }
+
+        let config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots);
+        let config = match &self.identity {
+            None => config.with_no_client_auth(),
+            Some(ClientIdentity(IdentityKind::Pem {
+                certificate_chain,
+                private_key,
+            })) => {
+                let chain = rustls_pemfile::certs(&mut certificate_chain.as_slice())
+                    .map_err(|e| invalid("client identity", e))?
+                    .into_iter()
+                    .map(rustls::Certificate)
+                    .collect();
+                let key = rustls_pemfile::pkcs8_private_keys(&mut private_key.as_slice())
+                    .map_err(|e| invalid("client identity", e))?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| {
+                        Error::Config("invalid client identity: no PKCS #8 key".to_string())
+                    })?;
+                config
+                    .with_client_auth_cert(chain, rustls::PrivateKey(key))
+                    .map_err(|e| invalid("client identity", e))?
+            }
+            Some(ClientIdentity(IdentityKind::Pkcs12 { .. })) => return Err(pkcs12_unsupported()),
+        };
+        Ok(Some(Connector::Rustls(Arc::new(config))))
+    }
+
+    /// Connector for `wss://` streams, or `None` to use the defaults
+    #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+    pub fn websocket_connector(&self) -> Result<Option<Connector>> {
+        if self.is_default() {
+            return Ok(None);
+        }
+        Err(Error::Config(
+            "TLS settings require the native-tls or rustls feature".to_string(),
+        ))
+    }
+}
+
+/// Client identity for API requests
+#[cfg(feature = "native-tls")]
+fn http_identity(identity: &IdentityKind) -> Result<reqwest::Identity> {
+    match identity {
+        IdentityKind::Pkcs12 { der, password } => reqwest::Identity::from_pkcs12_der(der, password),
+        IdentityKind::Pem {
+            certificate_chain,
+            private_key,
+        } => reqwest::Identity::from_pkcs8_pem(certificate_chain, private_key),
+    }
+    .map_err(|e| invalid("client identity", e))
+}
+
+/// Client identity for API requests
+#[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+fn http_identity(identity: &IdentityKind) -> Result<reqwest::Identity> {
+    match identity {
+        IdentityKind::Pkcs12 { .. } => Err(pkcs12_unsupported()),
+        // rustls reads the key and the chain from a single PEM buffer
+        IdentityKind::Pem {
+            certificate_chain,
+            private_key,
+        } => reqwest::Identity::from_pem(&[&private_key[..], b"\n", certificate_chain].concat())
+            .map_err(|e| invalid("client identity", e)),
+    }
+}
+
+#[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+fn invalid_certs_unsupported() -> Error {
+    Error::Config("accepting invalid certificates requires the native-tls feature".to_string())
+}
+
+#[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+fn pkcs12_unsupported() -> Error {
+    Error::Config("PKCS #12 identities require the native-tls feature".to_string())
+}
+
+/// Split PEM text into its certificates
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+fn pem_blocks(pem: &[u8]) -> Result<Vec<&[u8]>> {
+    let text = std::str::from_utf8(pem)
+        .map_err(|_| Error::Config("invalid root certificate: not PEM text".to_string()))?;
+    let mut blocks = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(PEM_BEGIN) {
+        let Some(length) = rest[start..].find(PEM_END) else {
+            break;
+        };
+        let end = start + length + PEM_END.len();
+        blocks.push(&rest.as_bytes()[start..end]);
+        rest = &rest[end..];
+    }
+    if blocks.is_empty() {
+        return Err(Error::Config(
+            "invalid root certificate: no PEM certificate found".to_string(),
+        ));
+    }
+    Ok(blocks)
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+fn invalid(what: &str, error: impl fmt::Display) -> Error {
+    Error::Config(format!("invalid {}: {}", what, error))
+}
@@ -0,0 +1,186 @@
+use crate::client::Client;
+use crate::models::*;
+use crate::websocket::WebSocketStream;
+use crate::Result;
+use futures_util::{ready, Stream, StreamExt};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::time::{interval, Interval, MissedTickBehavior};
+use tracing::field::Empty;
+use tracing::{info, info_span, warn, Span};
+
+/// Default time after which a node span without a completion event is closed
+pub const DEFAULT_NODE_SPAN_TIMEOUT: Duration = Duration::from_secs(600);
+
+struct OpenNodeSpan {
+    span: Span,
+    started: Instant,
+}
+
+/// Execution update stream that mirrors the execution into tracing spans
+///
+/// An `execution` span covers the whole stream and every node gets a child
+/// `node` span opened on its start event and closed on its completion event,
+/// with `status` and `duration_ms` recorded. A completion event that arrives
+/// before its start event closes the node span as soon as it is opened. Node
+/// spans that never receive a completion event are closed with status
+/// `abandoned` once they exceed the node timeout, and all spans are closed
+/// when the stream ends.
+pub struct TracedExecutionStream {
+    inner: WebSocketStream,
+    execution_span: Span,
+    started: Instant,
+    node_spans: HashMap<String, OpenNodeSpan>,
+    /// Status of nodes whose completion event came before their start event
+    completed_early: HashMap<String, String>,
+    node_timeout: Duration,
+    sweep: Interval,
+}
+
+impl TracedExecutionStream {
+    fn new(inner: WebSocketStream, execution_id: &str, node_timeout: Duration) -> Self {
+        let execution_span = info_span!(
+            "execution",
+            execution_id = %execution_id,
+            status = Empty,
+            duration_ms = Empty
+        );
+        Self {
+            inner,
+            execution_span,
+            started: Instant::now(),
+            node_spans: HashMap::new(),
+            completed_early: HashMap::new(),
+            node_timeout,
+            sweep: sweep_interval(node_timeout),
+        }
+    }
+
+    /// Change how long a node span may stay open without a completion event
+    pub fn node_timeout(mut self, timeout: Duration) -> Self {
+        self.node_timeout = timeout;
+        self.sweep = sweep_interval(timeout);
+        self
+    }
+
+    fn record(&mut self, update: &ExecutionUpdate) {
+        let node_key = update.node_id().or(update.node_name()).map(str::to_string);
+        match (update.kind(), node_key) {
+            (UpdateKind::NodeStarted, Some(key)) => {
+                let span = info_span!(
+                    parent: &self.execution_span,
+                    "node",
+                    node_id = %key,
+                    node_name = update.node_name().unwrap_or_default(),
+                    status = Empty,
+                    duration_ms = Empty
+                );
+                if let Some(status) = self.completed_early.remove(&key) {
+                    close_span(&span, &status, Instant::now());
+                    return;
+                }
+                let replaced = self.node_spans.insert(
+                    key,
+                    OpenNodeSpan {
+                        span,
+                        started: Instant::now(),
+                    },
+                );
+                if let Some(previous) = replaced {
+                    close_span(&previous.span, "restarted", previous.started);
+                }
+            }
+            (UpdateKind::NodeCompleted, Some(key)) => {
+                let status = update.status().unwrap_or("completed");
+                match self.node_spans.remove(&key) {
+                    Some(open) => close_span(&open.span, status, open.started),
+                    None => {
+                        self.completed_early.insert(key, status.to_string());
+                    }
+                }
+            }
+            (UpdateKind::ExecutionCompleted, _) => {
+                self.finish(update.status().unwrap_or("completed"));
+            }
+            _ => {}
+        }
+    }
+
+    fn finalize_stale(&mut self) {
+        let timeout = self.node_timeout;
+        self.node_spans.retain(|key, open| {
+            if open.started.elapsed() < timeout {
+                return true;
+            }
+            warn!("No completion event for node {}, closing its span", key);
+            close_span(&open.span, "abandoned", open.started);
+            false
+        });
+    }
+
+    fn finish(&mut self, status: &str) {
+        for (_, open) in self.node_spans.drain() {
+            close_span(&open.span, "abandoned", open.started);
+        }
+        self.completed_early.clear();
+        close_span(&self.execution_span, status, self.started);
+        self.execution_span = Span::none();
+    }
+}
+
+fn sweep_interval(node_timeout: Duration) -> Interval {
+    let mut sweep = interval((node_timeout / 4).max(Duration::from_secs(1)));
+    sweep.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    sweep
+}
+
+fn close_span(span: &Span, status: &str, started: Instant) {
+    let duration_ms = started.elapsed().as_millis() as u64;
+    span.record("status", status);
+    span.record("duration_ms", duration_ms);
+    info!(parent: span, status, duration_ms, "span closed");
+}
+
+impl Stream for TracedExecutionStream {
+    type Item = Result<ExecutionUpdate>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        while this.sweep.poll_tick(cx).is_ready() {
+            this.finalize_stale();
+        }
+
+        match ready!(this.inner.poll_next_unpin(cx)) {
+            Some(Ok(update)) => {
+                this.record(&update);
+                Poll::Ready(Some(Ok(update)))
+            }
+            Some(Err(e)) => Poll::Ready(Some(Err(e))),
+            None => {
+                if !this.execution_span.is_none() {
+                    this.finish("disconnected");
+                }
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Stream execution updates while mirroring them into tracing spans
+    ///
+    /// See [`TracedExecutionStream`] for the span layout.
+    pub async fn stream_execution_traced(
+        &self,
+        execution_id: &str,
+    ) -> Result<TracedExecutionStream> {
+        let stream = self.stream_execution(execution_id).await?;
+        Ok(TracedExecutionStream::new(
+            stream,
+            execution_id,
+            DEFAULT_NODE_SPAN_TIMEOUT,
+        ))
+    }
+}
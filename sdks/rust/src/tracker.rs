@@ -0,0 +1,255 @@
+use crate::cancel::PendingExecution;
+use crate::client::Client;
+use crate::models::*;
+use crate::wait::WaitOptions;
+use crate::{Error, Result};
+use futures_util::future::join_all;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Interval between status checks while draining
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tracks the executions a process started so they can finish before it exits
+///
+/// Executions started through [`execute`](Self::execute) are recorded until
+/// they reach a terminal status. [`drain`](Self::drain) stops new executions
+/// from being started and waits for the recorded ones. With
+/// [`with_persistence`](Self::with_persistence) the tracked set is kept in a
+/// file, so a restarted process picks up draining where the previous one left
+/// off; failures to write the file are logged and do not fail the call.
+/// Clones share the tracked set.
+#[derive(Clone)]
+pub struct ExecutionTracker {
+    client: Client,
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    tracked: Mutex<BTreeSet<String>>,
+    draining: AtomicBool,
+    /// Number of `execute` calls in progress
+    submitting: AtomicUsize,
+    /// Notified when the last `execute` call in progress ends
+    settled: Notify,
+    path: Option<PathBuf>,
+}
+
+/// An `execute` call in progress, counted until dropped
+struct Submission<'a>(&'a Inner);
+
+impl<'a> Submission<'a> {
+    fn start(inner: &'a Inner) -> Self {
+        inner.submitting.fetch_add(1, Ordering::SeqCst);
+        Self(inner)
+    }
+}
+
+impl Drop for Submission<'_> {
+    fn drop(&mut self) {
+        if self.0.submitting.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.settled.notify_waiters();
+        }
+    }
+}
+
+impl ExecutionTracker {
+    /// Track executions in memory only
+    pub fn new(client: Client) -> Self {
+        Self::with_tracked(client, BTreeSet::new(), None)
+    }
+
+    /// Track executions in a JSON file at `path`, resuming any ids already stored there
+    pub fn with_persistence(client: Client, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let tracked = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|e| {
+                Error::Config(format!("invalid tracker file {}: {}", path.display(), e))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(e) => {
+                return Err(Error::Config(format!(
+                    "failed to read {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        if !tracked.is_empty() {
+            info!("Resuming {} tracked execution(s)", tracked.len());
+        }
+        Ok(Self::with_tracked(client, tracked, Some(path)))
+    }
+
+    fn with_tracked(client: Client, tracked: BTreeSet<String>, path: Option<PathBuf>) -> Self {
+        Self {
+            client,
+            inner: Arc::new(Inner {
+                tracked: Mutex::new(tracked),
+                draining: AtomicBool::new(false),
+                submitting: AtomicUsize::new(0),
+                settled: Notify::new(),
+                path,
+            }),
+        }
+    }
+
+    /// Execute a workflow and track the execution until it finishes
+    ///
+    /// Fails with [`Error::Draining`] once [`drain`](Self::drain) was called.
+    /// When waiting for completion, the execution is tracked from the moment
+    /// it starts, and every attempt under a retry policy is.
+    pub async fn execute(
+        &self,
+        workflow_id: &str,
+        input_data: FieldMap,
+        options: ExecuteOptions,
+    ) -> Result<ExecutionResult> {
+        // Counted before the check, so a drain either refuses this call or waits for it
+        let _submission = Submission::start(&self.inner);
+        if self.is_draining() {
+            return Err(Error::Draining);
+        }
+        let tracker = self.clone();
+        let pending = PendingExecution::new(&self.client, options.cancel_on_drop)
+            .on_started(move |execution_id| tracker.track(execution_id));
+        let execution = self
+            .client
+            .execute_observed(workflow_id, input_data, options, pending)
+            .await?;
+
+        let retried = execution
+            .attempts
+            .iter()
+            .flat_map(|attempts| attempts.failed_execution_ids.iter());
+        if execution.status.is_terminal() {
+            let finished: Vec<&String> = retried.chain([&execution.id]).collect();
+            self.update(|tracked| {
+                for id in finished {
+                    tracked.remove(id);
+                }
+            });
+        } else {
+            self.track(&execution.id);
+        }
+        Ok(execution)
+    }
+
+    /// Record a started execution
+    fn track(&self, execution_id: &str) {
+        debug!("Tracking execution {}", execution_id);
+        self.update(|tracked| {
+            tracked.insert(execution_id.to_string());
+        });
+    }
+
+    /// Whether [`drain`](Self::drain) was called
+    pub fn is_draining(&self) -> bool {
+        self.inner.draining.load(Ordering::SeqCst)
+    }
+
+    /// IDs of the executions that have not been seen finishing yet
+    pub fn tracked(&self) -> Vec<String> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// Stop starting executions and wait up to `timeout` for the tracked ones to finish
+    ///
+    /// Executions being submitted when the drain begins are waited for too.
+    /// Returns the IDs of the executions still running at the deadline, which
+    /// remain tracked. Executions the server no longer knows are dropped.
+    /// Waits share their polls with any other wait on the same execution.
+    pub async fn drain(&self, timeout: Duration) -> Result<Vec<String>> {
+        self.inner.draining.store(true, Ordering::SeqCst);
+        let deadline = Instant::now() + timeout;
+        if tokio::time::timeout_at(deadline, self.submissions_settled())
+            .await
+            .is_err()
+        {
+            warn!("Executions still being submitted at drain deadline");
+        }
+
+        let ids = self.tracked();
+        info!("Draining {} tracked execution(s)", ids.len());
+        let options = WaitOptions::new()
+            .poll_interval(DRAIN_POLL_INTERVAL)
+            .timeout(deadline.saturating_duration_since(Instant::now()));
+        let waits = ids
+            .iter()
+            .map(|id| self.client.wait_coalesced(id, options.clone()));
+        let results = join_all(waits).await;
+        let finished: Vec<&String> = ids
+            .iter()
+            .zip(results)
+            .filter_map(|(id, result)| match result {
+                Ok(_) => Some(id),
+                Err(Error::NotFound { .. }) => {
+                    warn!("Tracked execution {} no longer exists", id);
+                    Some(id)
+                }
+                Err(Error::WaitTimeout { .. } | Error::DeadlineExceeded { .. }) => None,
+                Err(e) => {
+                    warn!("Failed to wait for tracked execution {}: {}", id, e);
+                    None
+                }
+            })
+            .collect();
+        self.update(|tracked| {
+            for id in finished {
+                tracked.remove(id);
+            }
+        });
+
+        let remaining = self.tracked();
+        if !remaining.is_empty() {
+            warn!(
+                "{} execution(s) still running at drain deadline",
+                remaining.len()
+            );
+        }
+        Ok(remaining)
+    }
+
+    /// Wait until no `execute` call is in progress
+    async fn submissions_settled(&self) {
+        loop {
+            let settled = self.inner.settled.notified();
+            if self.inner.submitting.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            settled.await;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeSet<String>> {
+        self.inner.tracked.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Modify the tracked set and write it through to the persistence file
+    fn update(&self, change: impl FnOnce(&mut BTreeSet<String>)) {
+        let mut tracked = self.lock();
+        change(&mut tracked);
+        if let Some(path) = &self.inner.path {
+            if let Err(e) = persist(path, &tracked) {
+                warn!("Failed to persist tracked executions: {}", e);
+            }
+        }
+    }
+}
+
+/// Replace the file at `path` with `tracked`, via a rename so readers never see a partial file
+fn persist(path: &Path, tracked: &BTreeSet<String>) -> Result<()> {
+    let contents =
+        serde_json::to_vec_pretty(tracked).map_err(|e| Error::Serialization(e.to_string()))?;
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    std::fs::write(&temporary, contents)
+        .and_then(|_| std::fs::rename(&temporary, path))
+        .map_err(|e| Error::Config(format!("failed to write {}: {}", path.display(), e)))
+}
@@ -0,0 +1,375 @@
+use crate::error::TransportErrorKind;
+use crate::limits::JsonLimit;
+use crate::{Error, Result};
+use bytes::{Bytes, BytesMut};
+use futures_util::future::{BoxFuture, FutureExt};
+use reqwest::header::HeaderMap;
+use reqwest::{Client as HttpClient, Method, StatusCode};
+use std::error::Error as _;
+use std::time::Duration;
+use tracing::error;
+
+/// A request the client hands to its [`Transport`]
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    pub method: Method,
+    /// Absolute URL: the client's base URL followed by the API path and query
+    pub url: String,
+    /// Headers to send, including authorization and the client's default headers
+    pub headers: HeaderMap,
+    pub body: Option<Bytes>,
+    /// Time allowed for the whole request; `None` leaves it to the transport
+    pub timeout: Option<Duration>,
+    /// Largest response body the client accepts, see [`JsonLimits::max_total_bytes`](crate::JsonLimits::max_total_bytes)
+    ///
+    /// A transport should stop reading a longer body and fail with
+    /// [`Error::JsonLimitExceeded`] instead of buffering it. The client
+    /// checks the size of the returned body either way.
+    pub max_response_bytes: Option<usize>,
+}
+
+impl TransportRequest {
+    /// Path of the URL, without scheme, host or query
+    pub fn path(&self) -> &str {
+        let rest = self
+            .url
+            .split_once("://")
+            .map_or(self.url.as_str(), |(_, rest)| rest);
+        let rest = rest.find('/').map_or("/", |start| &rest[start..]);
+        rest.split(['?', '#']).next().unwrap_or(rest)
+    }
+
+    /// Query string of the URL, without the leading `?`
+    pub fn query(&self) -> Option<&str> {
+        let (_, query) = self.url.split_once('?')?;
+        Some(query.split('#').next().unwrap_or(query))
+    }
+}
+
+/// A complete response returned by a [`Transport`]
+///
+/// Unsuccessful statuses are returned as responses too; the client turns
+/// them into [`Error::Api`].
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl TransportResponse {
+    /// Response with `status` and `body` and no headers
+    pub fn new(status: StatusCode, body: impl Into<Bytes>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Response with `status` and `body` serialized as JSON
+    pub fn json(status: StatusCode, body: &impl serde::Serialize) -> Self {
+        let body = serde_json::to_vec(body).expect("response body serializes to JSON");
+        let mut response = Self::new(status, body);
+        response.headers.insert(
+            reqwest::header::CONTENT_TYPE,
+            reqwest::header::HeaderValue::from_static("application/json"),
+        );
+        response
+    }
+}
+
+/// The HTTP layer of a [`Client`](crate::Client)
+///
+/// Every API request made through `make_request` is sent with
+/// [`execute`](Self::execute), so a custom transport set with
+/// [`ClientBuilder::transport`](crate::ClientBuilder::transport) can tunnel
+/// requests through a custom authentication layer or answer them in memory.
+/// Execution streams and streaming artifact downloads do not use the
+/// transport; webhooks of execution watches do.
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() -> klikkflow_sdk::Result<()> {
+/// use futures_util::future::{BoxFuture, FutureExt};
+/// use klikkflow_sdk::{Client, Transport, TransportRequest, TransportResponse};
+/// use reqwest::StatusCode;
+/// use std::sync::Arc;
+///
+/// /// Answers every health check and nothing else
+/// struct Healthy;
+///
+/// impl Transport for Healthy {
+///     fn execute(&self, request: TransportRequest) -> BoxFuture<'_, klikkflow_sdk::Result<TransportResponse>> {
+///         let status = match request.path() {
+///             "/health" => StatusCode::OK,
+///             _ => StatusCode::NOT_FOUND,
+///         };
+///         async move { Ok(TransportResponse::new(status, "{}")) }.boxed()
+///     }
+/// }
+///
+/// let client = Client::builder()
+///     .base_url("https://klikkflow.example.com")
+///     .transport(Arc::new(Healthy))
+///     .build()?;
+/// client.health_check().await?;
+/// assert!(client.get_workflow("wf-1").await.is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub trait Transport: Send + Sync {
+    /// Send a request and return the response, whatever its status
+    fn execute(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse>>;
+}
+
+/// The default transport, sending requests with a reqwest client
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: HttpClient,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: HttpClient) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn execute(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse>> {
+        async move {
+            let method = request.method.clone();
+            let path = request.path().to_string();
+            let limit = request.max_response_bytes;
+            let mut builder = self
+                .client
+                .request(request.method, &request.url)
+                .headers(request.headers);
+            if let Some(timeout) = request.timeout {
+                builder = builder.timeout(timeout);
+            }
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            let response = builder
+                .send()
+                .await
+                .map_err(|e| transport_error(e, &method, &path))?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = read_body(response, limit, &method, &path).await?;
+            Ok(TransportResponse {
+                status,
+                headers,
+                body,
+            })
+        }
+        .boxed()
+    }
+}
+
+/// Read a response body, giving up as soon as it is known to exceed `limit` bytes
+///
+/// A `Content-Length` over the limit fails before anything is read; a body
+/// without one is read chunk by chunk and fails once the limit is passed.
+async fn read_body(
+    mut response: reqwest::Response,
+    limit: Option<usize>,
+    method: &Method,
+    path: &str,
+) -> Result<Bytes> {
+    let read_error = |e| {
+        error!("Failed to read response body: {}", e);
+        transport_error(e, method, path)
+    };
+    let Some(limit) = limit else {
+        return response.bytes().await.map_err(read_error);
+    };
+    let too_large = || Error::JsonLimitExceeded {
+        which: JsonLimit::TotalBytes,
+        limit,
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Err(too_large());
+    }
+    let mut body = BytesMut::new();
+    while let Some(chunk) = response.chunk().await.map_err(read_error)? {
+        if body.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+/// Classify a failed reqwest request, telling connect timeouts from request timeouts
+pub(crate) fn transport_error(error: reqwest::Error, method: &Method, path: &str) -> Error {
+    if error.is_timeout() && error.is_connect() {
+        Error::ConnectTimeout(format!("{} {}: {}", method, path, error))
+    } else if error.is_timeout() {
+        Error::Timeout(format!("{} {} timed out", method, path))
+    } else {
+        Error::Transport {
+            kind: transport_kind(&error),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// What failed in a reqwest request that did not time out
+fn transport_kind(error: &reqwest::Error) -> TransportErrorKind {
+    if error.is_body() || error.is_decode() {
+        return TransportErrorKind::Body;
+    }
+    if !error.is_connect() {
+        return TransportErrorKind::Request;
+    }
+    // The connector reports resolver failures as "dns error: ..."
+    let mut source = error.source();
+    while let Some(cause) = source {
+        if is_tls_error(cause) {
+            return TransportErrorKind::Tls;
+        }
+        if cause.to_string().starts_with("dns error") {
+            return TransportErrorKind::Dns;
+        }
+        source = cause.source();
+    }
+    TransportErrorKind::Connect
+}
+
+/// Whether `cause` comes from the TLS library of an enabled backend
+fn is_tls_error(cause: &(dyn std::error::Error + 'static)) -> bool {
+    #[cfg(feature = "native-tls")]
+    if cause.is::<native_tls::Error>() {
+        return true;
+    }
+    // hyper-rustls reports handshake failures as I/O errors wrapping the cause
+    #[cfg(feature = "rustls")]
+    if cause.is::<rustls::Error>()
+        || cause
+            .downcast_ref::<std::io::Error>()
+            .and_then(|e| e.get_ref())
+            .is_some_and(|inner| inner.is::<rustls::Error>())
+    {
+        return true;
+    }
+    #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+    let _ = cause;
+    false
+}
+
+#[cfg(feature = "test-util")]
+pub use memory::MemoryTransport;
+
+#[cfg(feature = "test-util")]
+mod memory {
+    use super::*;
+    use std::sync::Mutex;
+
+    type Handler = dyn Fn(&TransportRequest) -> TransportResponse + Send + Sync;
+
+    /// Transport answering requests in memory with registered handlers
+    ///
+    /// Handlers are matched by method and URL path, ignoring the query; the
+    /// most recently registered match wins. Unmatched requests get a 404.
+    /// Every request is recorded for later assertions. Available with the
+    /// `test-util` feature.
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::{ApiVersion, Client, MemoryTransport, TransportResponse};
+    /// use reqwest::StatusCode;
+    /// use std::sync::Arc;
+    ///
+    /// let transport = Arc::new(MemoryTransport::new().handle("DELETE", "/api/workflows/wf-1", |_| {
+    ///     TransportResponse::json(StatusCode::OK, &serde_json::json!({}))
+    /// }));
+    /// let client = Client::builder()
+    ///     .base_url("https://klikkflow.example.com")
+    ///     .api_key("your-api-key")
+    ///     .transport(transport.clone())
+    ///     // Pinned, so no version negotiation is among the requests
+    ///     .api_version(ApiVersion::V1)
+    ///     .build()?;
+    ///
+    /// client.delete_workflow("wf-1").await?;
+    /// assert!(client.delete_workflow("wf-2").await.is_err());
+    ///
+    /// let requests = transport.requests();
+    /// assert_eq!(requests.len(), 2);
+    /// assert_eq!(requests[0].headers["authorization"], "Bearer your-api-key");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[derive(Default)]
+    pub struct MemoryTransport {
+        handlers: Vec<(Method, String, Box<Handler>)>,
+        requests: Mutex<Vec<TransportRequest>>,
+    }
+
+    impl MemoryTransport {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Answer `method` requests for `path` with `handler`
+        ///
+        /// Panics if `method` is not a valid HTTP method.
+        pub fn handle(
+            mut self,
+            method: &str,
+            path: impl Into<String>,
+            handler: impl Fn(&TransportRequest) -> TransportResponse + Send + Sync + 'static,
+        ) -> Self {
+            let method = Method::from_bytes(method.as_bytes()).expect("valid HTTP method");
+            self.handlers.push((method, path.into(), Box::new(handler)));
+            self
+        }
+
+        /// Requests received so far, oldest first
+        pub fn requests(&self) -> Vec<TransportRequest> {
+            self.requests
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone()
+        }
+    }
+
+    impl Transport for MemoryTransport {
+        fn execute(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse>> {
+            let response = match self
+                .handlers
+                .iter()
+                .rev()
+                .find(|(method, path, _)| *method == request.method && path == request.path())
+            {
+                Some((_, _, handler)) => handler(&request),
+                None => TransportResponse::json(
+                    StatusCode::NOT_FOUND,
+                    &serde_json::json!({
+                        "error": format!("no handler for {} {}", request.method, request.path())
+                    }),
+                ),
+            };
+            self.requests
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(request);
+            futures_util::future::ready(Ok(response)).boxed()
+        }
+    }
+
+    impl std::fmt::Debug for MemoryTransport {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("MemoryTransport")
+                .field("handlers", &self.handlers.len())
+                .finish_non_exhaustive()
+        }
+    }
+}
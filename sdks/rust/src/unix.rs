@@ -0,0 +1,201 @@
+//! HTTP transport over a Unix domain socket, used for `unix://` base URLs
+
+use crate::transport::{Transport, TransportRequest, TransportResponse};
+use crate::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// Scheme prefix selecting the Unix domain socket transport
+pub(crate) const UNIX_SCHEME: &str = "unix://";
+
+/// Socket path of a `unix://` base URL
+pub(crate) fn socket_path(base_url: &str) -> Option<PathBuf> {
+    base_url.strip_prefix(UNIX_SCHEME).map(PathBuf::from)
+}
+
+#[cfg(unix)]
+pub(crate) use imp::UnixTransport;
+
+#[cfg(unix)]
+mod imp {
+    use super::*;
+    use futures_util::future::BoxFuture;
+    use hyper::client::connect::{Connected, Connection};
+    use hyper::service::Service;
+    use hyper::{Body, Request, Uri};
+    use std::io;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+    use tokio::net::UnixStream;
+
+    /// Connector that ignores the request URI and always dials the socket
+    #[derive(Clone)]
+    struct UnixConnector(Arc<PathBuf>);
+
+    impl Service<Uri> for UnixConnector {
+        type Response = UnixConnection;
+        type Error = io::Error;
+        type Future = BoxFuture<'static, io::Result<UnixConnection>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Uri) -> Self::Future {
+            let path = Arc::clone(&self.0);
+            Box::pin(async move {
+                UnixStream::connect(path.as_path())
+                    .await
+                    .map(UnixConnection)
+                    .map_err(|e| connect_error(&path, e))
+            })
+        }
+    }
+
+    fn connect_error(path: &Path, error: io::Error) -> io::Error {
+        let message = match error.kind() {
+            io::ErrorKind::NotFound => {
+                format!("Unix socket {} does not exist", path.display())
+            }
+            io::ErrorKind::PermissionDenied => format!(
+                "permission denied connecting to Unix socket {}",
+                path.display()
+            ),
+            _ => format!(
+                "failed to connect to Unix socket {}: {}",
+                path.display(),
+                error
+            ),
+        };
+        io::Error::new(error.kind(), message)
+    }
+
+    struct UnixConnection(UnixStream);
+
+    impl Connection for UnixConnection {
+        fn connected(&self) -> Connected {
+            Connected::new()
+        }
+    }
+
+    impl AsyncRead for UnixConnection {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for UnixConnection {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.0).poll_write(cx, buf)
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_flush(cx)
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_shutdown(cx)
+        }
+    }
+
+    /// HTTP/1 client talking to a server listening on a Unix domain socket
+    #[derive(Clone)]
+    pub(crate) struct UnixTransport {
+        client: hyper::Client<UnixConnector, Body>,
+        /// The `unix://` base URL, stripped from request URLs to get the path
+        base_url: String,
+    }
+
+    impl UnixTransport {
+        pub fn new(path: &Path) -> Self {
+            let connector = UnixConnector(Arc::new(path.to_path_buf()));
+            Self {
+                client: hyper::Client::builder().build(connector),
+                base_url: format!("{}{}", UNIX_SCHEME, path.display()),
+            }
+        }
+
+        async fn send(&self, request: TransportRequest) -> Result<TransportResponse> {
+            let path = request
+                .url
+                .strip_prefix(self.base_url.as_str())
+                .unwrap_or(request.url.as_str());
+            let mut builder = Request::builder()
+                .method(request.method.clone())
+                .uri(format!("http://localhost{}", path));
+            if let Some(headers) = builder.headers_mut() {
+                headers.extend(request.headers.clone());
+            }
+            let http_request = builder
+                .body(
+                    request
+                        .body
+                        .clone()
+                        .map(Body::from)
+                        .unwrap_or_else(Body::empty),
+                )
+                .map_err(|e| Error::Http(e.to_string()))?;
+
+            let send = async {
+                let response = self
+                    .client
+                    .request(http_request)
+                    .await
+                    .map_err(|e| Error::Http(e.to_string()))?;
+                let (parts, body) = response.into_parts();
+                let body = hyper::body::to_bytes(body)
+                    .await
+                    .map_err(|e| Error::Http(e.to_string()))?;
+                Ok(TransportResponse {
+                    status: parts.status,
+                    headers: parts.headers,
+                    body,
+                })
+            };
+            let limit = request.timeout.unwrap_or(crate::DEFAULT_TIMEOUT);
+            tokio::time::timeout(limit, send)
+                .await
+                .map_err(|_| Error::Timeout(format!("{} {} timed out", request.method, path)))?
+        }
+    }
+
+    impl Transport for UnixTransport {
+        fn execute(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse>> {
+            Box::pin(self.send(request))
+        }
+    }
+}
+
+#[cfg(not(unix))]
+#[derive(Clone)]
+pub(crate) struct UnixTransport;
+
+#[cfg(not(unix))]
+impl UnixTransport {
+    pub fn new(_: &Path) -> Self {
+        Self
+    }
+}
+
+#[cfg(not(unix))]
+impl Transport for UnixTransport {
+    fn execute(
+        &self,
+        _: TransportRequest,
+    ) -> futures_util::future::BoxFuture<'_, Result<TransportResponse>> {
+        Box::pin(async {
+            Err(Error::Unsupported(
+                "Unix domain sockets are not available on this platform".to_string(),
+            ))
+        })
+    }
+}
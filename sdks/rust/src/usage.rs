@@ -0,0 +1,161 @@
+use crate::client::Client;
+use crate::timeouts::OperationClass;
+use crate::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::ops::Range;
+use tracing::{debug, warn};
+
+/// Dimension usage is grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UsageGroupBy {
+    ApiKey,
+    Workflow,
+    Project,
+}
+
+impl UsageGroupBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageGroupBy::ApiKey => "apiKey",
+            UsageGroupBy::Workflow => "workflow",
+            UsageGroupBy::Project => "project",
+        }
+    }
+}
+
+/// Usage of a single API key, workflow or project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageGroup {
+    /// Id of the API key, workflow or project
+    pub key: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(rename = "executionCount")]
+    pub execution_count: u64,
+    #[serde(rename = "executionSeconds")]
+    pub execution_seconds: f64,
+}
+
+/// Execution usage over a time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    /// Start of the range actually covered by the report
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    #[serde(rename = "groupBy")]
+    pub group_by: UsageGroupBy,
+    #[serde(default)]
+    pub groups: Vec<UsageGroup>,
+    /// Whether part of the requested range lies beyond the server's retention
+    #[serde(default)]
+    pub partial: bool,
+}
+
+impl UsageReport {
+    /// Total number of executions across all groups
+    pub fn total_executions(&self) -> u64 {
+        self.groups.iter().map(|group| group.execution_count).sum()
+    }
+
+    /// Total execution time in seconds across all groups
+    pub fn total_execution_seconds(&self) -> f64 {
+        self.groups
+            .iter()
+            .map(|group| group.execution_seconds)
+            .sum()
+    }
+
+    /// Write the report as CSV with one row per group
+    ///
+    /// ```rust
+    /// # use klikkflow_sdk::{UsageGroup, UsageGroupBy, UsageReport};
+    /// let report = UsageReport {
+    ///     from: "2024-01-01T00:00:00Z".parse().unwrap(),
+    ///     to: "2024-02-01T00:00:00Z".parse().unwrap(),
+    ///     group_by: UsageGroupBy::Workflow,
+    ///     groups: vec![UsageGroup {
+    ///         key: "wf-1".to_string(),
+    ///         name: Some("Sync, nightly".to_string()),
+    ///         execution_count: 31,
+    ///         execution_seconds: 93.5,
+    ///     }],
+    ///     partial: false,
+    /// };
+    ///
+    /// let mut csv = Vec::new();
+    /// report.write_csv(&mut csv).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(csv).unwrap(),
+    ///     "workflow,name,executions,execution_seconds,from,to,partial\n\
+    ///      wf-1,\"Sync, nightly\",31,93.5,2024-01-01T00:00:00Z,2024-02-01T00:00:00Z,false\n"
+    /// );
+    /// ```
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "{},name,executions,execution_seconds,from,to,partial",
+            self.group_by.as_str()
+        )?;
+        let from = self.from.to_rfc3339_opts(SecondsFormat::Secs, true);
+        let to = self.to.to_rfc3339_opts(SecondsFormat::Secs, true);
+        for group in &self.groups {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{}",
+                csv_field(&group.key),
+                csv_field(group.name.as_deref().unwrap_or_default()),
+                group.execution_count,
+                group.execution_seconds,
+                from,
+                to,
+                self.partial
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl Client {
+    /// Get execution counts and execution time over `range`, grouped by `group_by`
+    ///
+    /// If the server no longer retains data for the start of the range, the
+    /// report covers the retained part only and is flagged as partial.
+    pub async fn get_usage(
+        &self,
+        range: Range<DateTime<Utc>>,
+        group_by: UsageGroupBy,
+    ) -> Result<UsageReport> {
+        debug!("Getting usage grouped by {}", group_by.as_str());
+        let path = format!(
+            "/api/usage?from={}&to={}&groupBy={}",
+            range.start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            range.end.to_rfc3339_opts(SecondsFormat::Secs, true),
+            group_by.as_str()
+        );
+        let mut report: UsageReport = self
+            .make_request(OperationClass::LongRunning, "GET", &path, None::<&()>)
+            .await?;
+
+        if report.from > range.start {
+            report.partial = true;
+        }
+        if report.partial {
+            warn!(
+                "Usage report covers {} to {} only, requested {} to {}",
+                report.from, report.to, range.start, range.end
+            );
+        }
+        Ok(report)
+    }
+}
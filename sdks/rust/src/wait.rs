@@ -0,0 +1,652 @@
+use crate::client::Client;
+use crate::models::*;
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::time::{sleep, Sleep};
+use tracing::{debug, warn};
+
+/// Options for waiting on an execution to finish
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WaitOptions {
+    /// Interval between status polls
+    pub poll_interval: Duration,
+    /// Time after which waiting fails with [`Error::WaitTimeout`]
+    pub timeout: Duration,
+    /// What to do about an execution that stops making progress
+    pub stall_detection: Option<StallPolicy>,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            timeout: Duration::from_secs(300),
+            stall_detection: None,
+        }
+    }
+}
+
+impl WaitOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Act on an execution still running without progress for a while
+    ///
+    /// Servers can lose track of an execution, e.g. when a worker crashes,
+    /// leaving it running forever. An execution progresses when its status,
+    /// its metadata counters or any of its node results change between polls;
+    /// once none did for [`no_progress_after`](StallPolicy::no_progress_after),
+    /// the policy's [`action`](StallPolicy::action) is taken.
+    ///
+    /// ```rust
+    /// use klikkflow_sdk::{StallAction, StallPolicy, WaitOptions};
+    /// use std::time::Duration;
+    ///
+    /// let options = WaitOptions::new().stall_detection(StallPolicy {
+    ///     no_progress_after: Duration::from_secs(600),
+    ///     action: StallAction::Cancel,
+    /// });
+    /// ```
+    pub fn stall_detection(mut self, policy: StallPolicy) -> Self {
+        self.stall_detection = Some(policy);
+        self
+    }
+}
+
+/// When an execution counts as stalled and what to do about it, see [`WaitOptions::stall_detection`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StallPolicy {
+    /// Time without progress after which the execution counts as stalled
+    pub no_progress_after: Duration,
+    pub action: StallAction,
+}
+
+/// Decides about a stalled execution, given it and when it last made progress
+pub type StallHook = dyn Fn(&ExecutionResult, DateTime<Utc>) -> StallDecision + Send + Sync;
+
+/// What a wait does about a stalled execution
+#[derive(Clone)]
+pub enum StallAction {
+    /// Fail with [`Error::ExecutionStalled`]
+    Fail,
+    /// Cancel the execution on the server, then fail with [`Error::ExecutionStalled`]
+    Cancel,
+    /// Let a hook decide, see [`StallAction::hook`]
+    Hook(Arc<StallHook>),
+}
+
+impl StallAction {
+    /// Ask `hook` each time the execution has gone without progress for the policy's period
+    ///
+    /// To re-execute a stalled execution, have the hook cancel it and re-run
+    /// the workflow with [`ExecuteOptions::retry_of`](crate::ExecuteOptions::retry_of)
+    /// set to its ID once the wait fails.
+    ///
+    /// ```rust
+    /// use klikkflow_sdk::{StallAction, StallDecision};
+    ///
+    /// // Keep waiting on long imports, give up on anything else
+    /// let action = StallAction::hook(|execution, _last_progress_at| {
+    ///     if execution.workflow_id == "wf-import" {
+    ///         StallDecision::KeepWaiting
+    ///     } else {
+    ///         StallDecision::Cancel
+    ///     }
+    /// });
+    /// ```
+    pub fn hook(
+        hook: impl Fn(&ExecutionResult, DateTime<Utc>) -> StallDecision + Send + Sync + 'static,
+    ) -> Self {
+        StallAction::Hook(Arc::new(hook))
+    }
+}
+
+impl std::fmt::Debug for StallAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StallAction::Fail => f.write_str("Fail"),
+            StallAction::Cancel => f.write_str("Cancel"),
+            StallAction::Hook(_) => f.write_str("Hook(..)"),
+        }
+    }
+}
+
+impl PartialEq for StallAction {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (StallAction::Hook(a), StallAction::Hook(b)) => Arc::ptr_eq(a, b),
+            (StallAction::Fail, StallAction::Fail) | (StallAction::Cancel, StallAction::Cancel) => {
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for StallAction {}
+
+/// Decision of a [`StallAction::hook`] about a stalled execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StallDecision {
+    /// Keep waiting; the hook is asked again after another period without progress
+    KeepWaiting,
+    /// Fail with [`Error::ExecutionStalled`]
+    Fail,
+    /// Cancel the execution on the server, then fail with [`Error::ExecutionStalled`]
+    Cancel,
+}
+
+/// What changes as an execution makes progress
+#[derive(Debug, PartialEq)]
+struct Progress {
+    status: ExecutionStatus,
+    counters: (usize, usize, usize, usize),
+    nodes: BTreeMap<String, NodeProgress>,
+}
+
+type NodeProgress = (
+    Option<NodeStatus>,
+    Option<DateTime<Utc>>,
+    Option<DateTime<Utc>>,
+    bool,
+);
+
+impl Progress {
+    fn of(execution: &ExecutionResult) -> Self {
+        let metadata = &execution.metadata;
+        Self {
+            status: execution.status.clone(),
+            counters: (
+                metadata.total_nodes,
+                metadata.completed_nodes,
+                metadata.failed_nodes,
+                metadata.retried_nodes,
+            ),
+            nodes: execution
+                .node_results
+                .iter()
+                .map(|(node, result)| {
+                    let progress = (
+                        result.status,
+                        result.started_at,
+                        result.finished_at,
+                        result.error.is_some(),
+                    );
+                    (node.clone(), progress)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Progress last seen by a wait with stall detection
+struct StallWatch {
+    progress: Progress,
+    /// When the progress last changed, reported in [`Error::ExecutionStalled`]
+    last_progress_at: DateTime<Utc>,
+    /// Start of the current period without progress
+    since: Instant,
+}
+
+enum WaitState {
+    Fetching(BoxFuture<'static, Result<ExecutionResult>>),
+    Sleeping(Pin<Box<Sleep>>),
+    /// Cancelling a stalled execution before failing with the error
+    Cancelling(BoxFuture<'static, ()>, Option<Error>),
+    Done,
+}
+
+/// Future resolving once an execution reaches a terminal status, created with [`Client::wait_future`]
+///
+/// Polls the execution until it finishes or the timeout elapses. Dropping the
+/// future stops waiting, so it can sit in a `select!` next to shutdown
+/// signals or in a struct field, and be inspected between polls.
+///
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() -> klikkflow_sdk::Result<()> {
+/// use klikkflow_sdk::WaitOptions;
+///
+/// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+/// let mut wait = client.wait_future("ex-1", WaitOptions::default());
+/// tokio::select! {
+///     result = &mut wait => println!("finished: {:?}", result?.status),
+///     _ = tokio::signal::ctrl_c() => {
+///         println!("interrupted after {} polls", wait.polls());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct WaitFuture {
+    client: Client,
+    execution_id: String,
+    options: WaitOptions,
+    state: WaitState,
+    // Created on first poll, since timers need a running runtime
+    deadline: Option<Pin<Box<Sleep>>>,
+    last_seen: Option<ExecutionResult>,
+    polls: u32,
+    stall: Option<StallWatch>,
+}
+
+impl WaitFuture {
+    /// Execution being waited on
+    pub fn execution_id(&self) -> &str {
+        &self.execution_id
+    }
+
+    /// The execution as of the most recent completed poll
+    pub fn last_seen(&self) -> Option<&ExecutionResult> {
+        self.last_seen.as_ref()
+    }
+
+    /// Number of completed polls
+    pub fn polls(&self) -> u32 {
+        self.polls
+    }
+
+    /// Check a running execution for progress, returning the action due if it stalled
+    fn check_stall(&mut self, execution: &ExecutionResult) -> Option<StallDecision> {
+        let policy = self.options.stall_detection.as_ref()?;
+        let progress = Progress::of(execution);
+        let watch = match &mut self.stall {
+            Some(watch) if watch.progress == progress => watch,
+            _ => {
+                self.stall = Some(StallWatch {
+                    progress,
+                    last_progress_at: Utc::now(),
+                    since: Instant::now(),
+                });
+                return None;
+            }
+        };
+        if watch.since.elapsed() < policy.no_progress_after {
+            return None;
+        }
+        let decision = match &policy.action {
+            StallAction::Fail => StallDecision::Fail,
+            StallAction::Cancel => StallDecision::Cancel,
+            StallAction::Hook(hook) => hook(execution, watch.last_progress_at),
+        };
+        if decision == StallDecision::KeepWaiting {
+            watch.since = Instant::now();
+            return None;
+        }
+        warn!(
+            "Execution {} made no progress since {}",
+            self.execution_id, watch.last_progress_at
+        );
+        Some(decision)
+    }
+
+    fn stalled_error(&self) -> Error {
+        Error::ExecutionStalled {
+            execution_id: self.execution_id.clone(),
+            last_progress_at: self
+                .stall
+                .as_ref()
+                .map_or_else(Utc::now, |watch| watch.last_progress_at),
+        }
+    }
+
+    fn fetch(&self) -> WaitState {
+        let client = self.client.clone();
+        let execution_id = self.execution_id.clone();
+        WaitState::Fetching(Box::pin(async move {
+            client.get_execution(&execution_id).await
+        }))
+    }
+}
+
+impl Future for WaitFuture {
+    type Output = Result<ExecutionResult>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let timeout = this.options.timeout;
+        let deadline = this
+            .deadline
+            .get_or_insert_with(|| Box::pin(sleep(timeout)));
+        if deadline.as_mut().poll(cx).is_ready() {
+            this.state = WaitState::Done;
+            return Poll::Ready(Err(Error::WaitTimeout {
+                execution_id: this.execution_id.clone(),
+                waited: timeout,
+            }));
+        }
+
+        loop {
+            match &mut this.state {
+                WaitState::Fetching(fetch) => {
+                    let result = ready!(fetch.as_mut().poll(cx));
+                    this.polls += 1;
+                    let execution = match result {
+                        Ok(execution) => execution,
+                        Err(e) => {
+                            this.state = WaitState::Done;
+                            return Poll::Ready(Err(e));
+                        }
+                    };
+                    this.last_seen = Some(execution.clone());
+                    if execution.status.is_terminal() {
+                        this.state = WaitState::Done;
+                        return Poll::Ready(Ok(execution));
+                    }
+                    match this.check_stall(&execution) {
+                        Some(StallDecision::Cancel) => {
+                            let client = this.client.clone();
+                            let execution_id = this.execution_id.clone();
+                            let cancel = Box::pin(async move {
+                                if let Err(e) = client.cancel_execution(&execution_id).await {
+                                    warn!(
+                                        "Failed to cancel stalled execution {}: {}",
+                                        execution_id, e
+                                    );
+                                }
+                            });
+                            this.state = WaitState::Cancelling(cancel, Some(this.stalled_error()));
+                            continue;
+                        }
+                        Some(_) => {
+                            this.state = WaitState::Done;
+                            return Poll::Ready(Err(this.stalled_error()));
+                        }
+                        None => {}
+                    }
+                    debug!("Execution {} still running, waiting...", this.execution_id);
+                    this.state = WaitState::Sleeping(Box::pin(sleep(this.options.poll_interval)));
+                }
+                WaitState::Sleeping(delay) => {
+                    ready!(delay.as_mut().poll(cx));
+                    this.state = this.fetch();
+                }
+                WaitState::Cancelling(cancel, error) => {
+                    ready!(cancel.as_mut().poll(cx));
+                    let error = error.take().unwrap_or_else(|| this.stalled_error());
+                    this.state = WaitState::Done;
+                    return Poll::Ready(Err(error));
+                }
+                WaitState::Done => panic!("WaitFuture polled after completion"),
+            }
+        }
+    }
+}
+
+/// Snapshot of the executions being waited on, from [`Client::wait_stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WaitStats {
+    /// Executions currently polled on behalf of waiters
+    pub pollers: usize,
+    /// Waits started so far
+    pub waits: u64,
+    /// Waits that joined a poller already running for the same execution
+    pub coalesced: u64,
+}
+
+/// Workspace and ID of an execution being waited on
+type PollerKey = (Option<String>, String);
+
+/// Publishes the terminal state of one execution to all its waiters
+type Poller = Arc<watch::Sender<Option<ExecutionResult>>>;
+
+/// Shared pollers of the executions being waited on, shared by all clones of a client
+#[derive(Default)]
+pub(crate) struct WaitRegistry {
+    pollers: Mutex<HashMap<PollerKey, Poller>>,
+    waits: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+impl WaitRegistry {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PollerKey, Poller>> {
+        self.pollers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Remove a poller unless another one replaced it
+    fn unregister(&self, key: &PollerKey, poller: &Poller) {
+        let mut pollers = self.lock();
+        if pollers.get(key).is_some_and(|p| Arc::ptr_eq(p, poller)) {
+            pollers.remove(key);
+        }
+    }
+
+    /// Remove a poller nobody waits on any more, returning whether it was
+    ///
+    /// Checked under the lock, so no wait joins a poller about to stop.
+    fn unregister_unused(&self, key: &PollerKey, poller: &Poller) -> bool {
+        let mut pollers = self.lock();
+        if poller.receiver_count() > 0 {
+            return false;
+        }
+        if pollers.get(key).is_some_and(|p| Arc::ptr_eq(p, poller)) {
+            pollers.remove(key);
+        }
+        true
+    }
+
+    pub fn stats(&self) -> WaitStats {
+        WaitStats {
+            pollers: self.lock().len(),
+            waits: self.waits.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Client {
+    /// Wait for an execution, sharing a single poller with other waits on it
+    ///
+    /// Concurrent waits on the same execution, from this client or its
+    /// clones, join one background poller instead of polling each on their
+    /// own; each still fails with [`Error::WaitTimeout`] after its own timeout,
+    /// or with [`Error::DeadlineExceeded`] at the deadline of the client's
+    /// request options if that comes first. When the shared poller fails,
+    /// each wait carries on polling by itself, so it reports its own error.
+    pub(crate) async fn wait_coalesced(
+        &self,
+        execution_id: &str,
+        mut options: WaitOptions,
+    ) -> Result<ExecutionResult> {
+        let started = Instant::now();
+        if let Some(deadline) = self.deadline() {
+            options.timeout = options
+                .timeout
+                .min(deadline.saturating_duration_since(started));
+        }
+        let mut receiver = self.join_poller(execution_id, options.poll_interval);
+        let finished = |execution: &Option<ExecutionResult>| {
+            execution
+                .as_ref()
+                .is_some_and(|execution| execution.status.is_terminal())
+        };
+        let shared = tokio::time::timeout(options.timeout, receiver.wait_for(finished))
+            .await
+            .map(|result| result.ok().and_then(|execution| execution.clone()));
+        match shared {
+            Ok(Some(execution)) => Ok(execution),
+            Ok(None) => {
+                debug!(
+                    "Shared poller of execution {} stopped, polling directly",
+                    execution_id
+                );
+                let timeout = options.timeout.saturating_sub(started.elapsed());
+                let mut wait = self.wait_future(execution_id, options.timeout(timeout));
+                match (&mut wait).await {
+                    Err(Error::WaitTimeout { .. }) => {
+                        let last_status = wait
+                            .last_seen()
+                            .or(receiver.borrow().as_ref())
+                            .map(|execution| execution.status.clone());
+                        Err(self.wait_expired(execution_id, started, last_status))
+                    }
+                    result => result,
+                }
+            }
+            Err(_) => {
+                let last_status = receiver
+                    .borrow()
+                    .as_ref()
+                    .map(|execution| execution.status.clone());
+                Err(self.wait_expired(execution_id, started, last_status))
+            }
+        }
+    }
+
+    /// Error of a wait that timed out, [`Error::DeadlineExceeded`] if the deadline passed
+    fn wait_expired(
+        &self,
+        execution_id: &str,
+        started: Instant,
+        last_status: Option<ExecutionStatus>,
+    ) -> Error {
+        match self.deadline() {
+            Some(deadline) if deadline <= Instant::now() => Error::DeadlineExceeded {
+                execution_id: Some(execution_id.to_string()),
+                last_status,
+            },
+            _ => Error::WaitTimeout {
+                execution_id: execution_id.to_string(),
+                waited: started.elapsed(),
+            },
+        }
+    }
+
+    /// Subscribe to the poller of an execution, starting one if none is running
+    fn join_poller(
+        &self,
+        execution_id: &str,
+        poll_interval: Duration,
+    ) -> watch::Receiver<Option<ExecutionResult>> {
+        let registry = self.wait_registry();
+        registry.waits.fetch_add(1, Ordering::Relaxed);
+        let key = (
+            self.workspace().map(str::to_string),
+            execution_id.to_string(),
+        );
+        let mut pollers = registry.lock();
+        if let Some(poller) = pollers.get(&key) {
+            registry.coalesced.fetch_add(1, Ordering::Relaxed);
+            debug!("Joining the running wait on execution {}", execution_id);
+            return poller.subscribe();
+        }
+
+        let (sender, receiver) = watch::channel(None);
+        let poller = Arc::new(sender);
+        pollers.insert(key.clone(), Arc::clone(&poller));
+        let client = self.clone();
+        tokio::spawn(async move {
+            client.run_poller(key, poller, poll_interval).await;
+        });
+        receiver
+    }
+
+    /// Poll until the execution finishes, fails to be fetched, or nobody waits any more
+    async fn run_poller(&self, key: PollerKey, poller: Poller, poll_interval: Duration) {
+        let registry = self.wait_registry();
+        let client = self.internal("wait-poll");
+        loop {
+            match client.get_execution(&key.1).await {
+                Ok(execution) if execution.status.is_terminal() => {
+                    // Unregistered first, so a wait that finds no poller sees the result
+                    registry.unregister(&key, &poller);
+                    poller.send_replace(Some(execution));
+                    return;
+                }
+                Ok(execution) => {
+                    debug!("Execution {} still running, waiting...", key.1);
+                    poller.send_replace(Some(execution));
+                }
+                Err(e) => {
+                    debug!("Shared poll of execution {} failed: {}", key.1, e);
+                    return registry.unregister(&key, &poller);
+                }
+            }
+            sleep(poll_interval).await;
+            if registry.unregister_unused(&key, &poller) {
+                debug!("No waits left on execution {}, stopping", key.1);
+                return;
+            }
+        }
+    }
+
+    /// Executions being waited on and how many waits were coalesced
+    ///
+    /// [`ExecutionHandle::wait`](crate::ExecutionHandle::wait) and waiting
+    /// [`execute_workflow`](Self::execute_workflow) calls on an execution
+    /// already waited on, by this client or a clone, join the running poller
+    /// instead of starting another. [`wait_future`](Self::wait_future) always
+    /// polls by itself.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let handle = client.execution("ex-1");
+    /// // One poller answers both
+    /// let (first, second) = tokio::join!(handle.wait(), handle.wait());
+    /// let stats = client.wait_stats();
+    /// println!("{} waits, {} coalesced", stats.waits, stats.coalesced);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_stats(&self) -> WaitStats {
+        self.wait_registry().stats()
+    }
+
+    /// Future that resolves once the execution reaches a terminal status
+    ///
+    /// Fails with [`Error::WaitTimeout`] when the execution is still running
+    /// after the timeout.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use klikkflow_sdk::{Error, WaitOptions};
+    /// use std::time::Duration;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let options = WaitOptions::new().timeout(Duration::from_secs(60));
+    /// match client.wait_future("ex-1", options).await {
+    ///     Ok(execution) => println!("{:?}", execution.status),
+    ///     Err(Error::WaitTimeout { waited, .. }) => println!("still running after {:?}", waited),
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_future(&self, execution_id: &str, options: WaitOptions) -> WaitFuture {
+        let mut future = WaitFuture {
+            client: self.internal("wait-poll"),
+            execution_id: execution_id.to_string(),
+            options,
+            state: WaitState::Done,
+            deadline: None,
+            last_seen: None,
+            polls: 0,
+            stall: None,
+        };
+        future.state = future.fetch();
+        future
+    }
+}
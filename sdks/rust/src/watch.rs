@@ -0,0 +1,286 @@
+use crate::client::Client;
+use crate::models::*;
+use crate::Result;
+use futures_util::stream::{self, Stream, StreamExt};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
+
+/// Default interval between execution history polls
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+type FailureCallback = Arc<dyn Fn(&ExecutionResult) + Send + Sync>;
+
+/// Webhook notified with a JSON payload when a watched execution fails
+#[derive(Debug, Clone)]
+pub struct FailureWebhook {
+    /// URL the payload is POSTed to
+    pub url: String,
+    /// Optional payload template. String values may contain the placeholders
+    /// `{{execution_id}}`, `{{workflow_id}}`, `{{status}}` and `{{error}}`.
+    /// When `None`, a default payload describing the execution is sent.
+    pub template: Option<Value>,
+}
+
+impl FailureWebhook {
+    /// Create a webhook using the default payload
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            template: None,
+        }
+    }
+
+    /// Use a custom payload template
+    pub fn with_template(mut self, template: Value) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    fn payload(&self, execution: &ExecutionResult) -> Value {
+        match &self.template {
+            Some(template) => render_template(template, execution),
+            None => json!({
+                "event": "execution.failed",
+                "executionId": execution.id,
+                "workflowId": execution.workflow_id,
+                "status": execution.status.as_str(),
+                "error": execution.error,
+                "startedAt": execution.started_at,
+                "finishedAt": execution.finished_at,
+            }),
+        }
+    }
+}
+
+fn render_template(template: &Value, execution: &ExecutionResult) -> Value {
+    match template {
+        Value::String(text) => Value::String(
+            text.replace("{{execution_id}}", &execution.id)
+                .replace("{{workflow_id}}", &execution.workflow_id)
+                .replace("{{status}}", execution.status.as_str())
+                .replace("{{error}}", execution.error.as_deref().unwrap_or_default()),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_template(item, execution))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), render_template(value, execution)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Options for watching a workflow's executions
+#[derive(Clone)]
+pub struct WatchOptions {
+    /// Interval between execution history polls
+    pub poll_interval: Duration,
+    /// Page size of the history reads; a poll reads further pages until it
+    /// reaches the executions seen by the previous poll
+    pub page_size: usize,
+    /// Minimum time between failure alerts for the same workflow
+    pub failure_debounce: Option<Duration>,
+    /// Webhook notified for every (non-debounced) failure
+    pub failure_webhook: Option<FailureWebhook>,
+    on_failure: Vec<FailureCallback>,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_WATCH_INTERVAL,
+            page_size: 50,
+            failure_debounce: None,
+            failure_webhook: None,
+            on_failure: Vec::new(),
+        }
+    }
+}
+
+impl WatchOptions {
+    /// Create watch options with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the interval between execution history polls
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Register a callback invoked for every execution reaching `Error` status.
+    ///
+    /// Panics raised by the callback are caught and logged so the watch keeps running.
+    pub fn on_failure(
+        mut self,
+        callback: impl Fn(&ExecutionResult) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_failure.push(Arc::new(callback));
+        self
+    }
+
+    /// Fire failure alerts at most once per workflow within the given window
+    pub fn debounce_failures(mut self, window: Duration) -> Self {
+        self.failure_debounce = Some(window);
+        self
+    }
+
+    /// POST a JSON payload to the given webhook for every failure
+    pub fn failure_webhook(mut self, webhook: FailureWebhook) -> Self {
+        self.failure_webhook = Some(webhook);
+        self
+    }
+}
+
+struct WatchState {
+    client: Client,
+    workflow_id: String,
+    options: WatchOptions,
+    /// Terminal executions on the pages read by the last poll
+    seen: Option<HashSet<String>>,
+    /// Executions still running at the last poll
+    running: HashSet<String>,
+    polled: bool,
+    pending: VecDeque<ExecutionResult>,
+    last_alert: HashMap<String, Instant>,
+}
+
+impl WatchState {
+    /// Read the history, newest first, until it reaches what the last poll saw
+    ///
+    /// Pages are read until one holds an execution that was already finished
+    /// at the last poll and every execution running then has been found
+    /// again, so no execution is missed however many finished in between.
+    async fn poll(&mut self) -> Result<()> {
+        let page_size = self.options.page_size.max(1);
+        let mut history = Box::pin(self.client.stream_execution_history(
+            &self.workflow_id,
+            ExecutionHistoryOptions {
+                limit: Some(page_size),
+                ..Default::default()
+            },
+        ));
+
+        let mut finished = HashSet::new();
+        let mut running = HashSet::new();
+        let mut missing = self.running.clone();
+        let mut reached_seen = false;
+        let mut fresh = Vec::new();
+        let first_poll = self.seen.is_none();
+        let mut read = 0;
+        while let Some(execution) = history.next().await {
+            let execution = execution?;
+            read += 1;
+            missing.remove(&execution.id);
+            if !execution.status.is_terminal() {
+                running.insert(execution.id.clone());
+            } else {
+                finished.insert(execution.id.clone());
+                match &self.seen {
+                    Some(seen) if seen.contains(&execution.id) => reached_seen = true,
+                    Some(_) => fresh.push(execution),
+                    // The first poll only records what finished before the watch started
+                    None => {}
+                }
+            }
+            if read % page_size == 0 && (first_poll || reached_seen) && missing.is_empty() {
+                break;
+            }
+        }
+
+        fresh.sort_by_key(|execution| execution.finished_at.unwrap_or(execution.started_at));
+        self.pending.extend(fresh);
+        self.seen = Some(finished);
+        self.running = running;
+        Ok(())
+    }
+
+    async fn notify_failure(&mut self, execution: &ExecutionResult) {
+        if let Some(window) = self.options.failure_debounce {
+            if let Some(last) = self.last_alert.get(&execution.workflow_id) {
+                if last.elapsed() < window {
+                    debug!("Suppressing failure alert for execution {}", execution.id);
+                    return;
+                }
+            }
+            self.last_alert
+                .insert(execution.workflow_id.clone(), Instant::now());
+        }
+
+        info!("Execution {} failed, notifying listeners", execution.id);
+        for callback in &self.options.on_failure {
+            if panic::catch_unwind(AssertUnwindSafe(|| callback(execution))).is_err() {
+                warn!(
+                    "on_failure callback panicked for execution {}",
+                    execution.id
+                );
+            }
+        }
+
+        if let Some(webhook) = &self.options.failure_webhook {
+            let payload = webhook.payload(execution);
+            if let Err(e) = self.client.post_json_to(&webhook.url, &payload).await {
+                warn!("Failure webhook {} failed: {}", webhook.url, e);
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Watch a workflow for newly finished executions.
+    ///
+    /// The returned stream polls the execution history and yields every execution
+    /// that reaches a terminal status after the watch started, oldest first. Poll
+    /// errors are yielded without ending the stream. Failures additionally trigger
+    /// the `on_failure` callbacks and webhook configured on `options`.
+    pub fn watch_executions(
+        &self,
+        workflow_id: &str,
+        options: WatchOptions,
+    ) -> impl Stream<Item = Result<ExecutionResult>> {
+        info!("Watching executions for workflow: {}", workflow_id);
+
+        let state = WatchState {
+            client: self.internal("watch-poll"),
+            workflow_id: workflow_id.to_string(),
+            options,
+            seen: None,
+            running: HashSet::new(),
+            polled: false,
+            pending: VecDeque::new(),
+            last_alert: HashMap::new(),
+        };
+
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(execution) = state.pending.pop_front() {
+                    if execution.status == ExecutionStatus::Error {
+                        state.notify_failure(&execution).await;
+                    }
+                    return Some((Ok(execution), state));
+                }
+
+                if state.polled {
+                    sleep(state.options.poll_interval).await;
+                }
+                state.polled = true;
+
+                if let Err(e) = state.poll().await {
+                    warn!("Failed to poll executions for {}: {}", state.workflow_id, e);
+                    return Some((Err(e), state));
+                }
+            }
+        })
+    }
+}
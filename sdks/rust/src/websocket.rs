@@ -0,0 +1,652 @@
+use crate::dns::Resolver;
+use crate::limits::JsonLimits;
+use crate::models::ExecutionUpdate;
+use crate::proxy::Proxy;
+use crate::{Error, Result};
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, BoxStream};
+use futures_util::{ready, FutureExt, SinkExt, Stream, StreamExt};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::{Request, Response};
+use tokio_tungstenite::tungstenite::http::HeaderMap;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{Connector, MaybeTlsStream};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+
+type InnerStream = tokio_tungstenite::WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Round trip time above which a connection is reported as degraded
+pub const DEFAULT_DEGRADED_RTT: Duration = Duration::from_secs(1);
+
+/// Delay before the first reconnect attempt, doubled for each further one
+const RECONNECT_BACKOFF: Duration = Duration::from_millis(250);
+/// Longest delay between reconnect attempts
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(8);
+/// Connection events buffered for each [`WebSocketStream::health_events`] receiver
+const HEALTH_EVENT_CAPACITY: usize = 32;
+
+/// Change in the health of an execution stream's connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// Connected, or recovered after being degraded or reconnecting
+    Connected,
+    /// A keepalive ping took longer than the threshold to be answered, or is still unanswered
+    Degraded { rtt: Duration },
+    /// The connection dropped and is being reopened
+    Reconnecting { attempt: u32 },
+    /// The stream ended and will not reconnect
+    Disconnected { reason: String },
+}
+
+/// Item of a stream from [`WebSocketStream::with_connection_events`]
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    Update(ExecutionUpdate),
+    Connection(ConnectionEvent),
+}
+
+/// Where and how to open the connection, kept for reconnecting
+struct Endpoint {
+    url: String,
+    headers: HeaderMap,
+    resolver: Option<Arc<Resolver>>,
+    proxy: Option<Proxy>,
+    connector: Option<Connector>,
+}
+
+/// Keepalive pings, whose answers measure the round trip time
+struct Keepalive {
+    interval: Interval,
+    next_id: u64,
+    /// Ping waiting to be sent
+    queued: Option<u64>,
+    /// Ping sent but not answered yet
+    outstanding: Option<(u64, Instant)>,
+}
+
+struct Reconnect {
+    max_attempts: u32,
+    attempt: u32,
+    pending: Option<BoxFuture<'static, Result<InnerStream>>>,
+}
+
+/// Stream of real-time execution updates received over WebSocket
+///
+/// A message that cannot be decoded is yielded as [`Error::MessageDecode`], or
+/// as [`Error::JsonLimitExceeded`] when it exceeds the client's
+/// [`JsonLimits`], and the stream keeps reading subsequent frames, unless
+/// strict mode is enabled.
+///
+/// Connection health is reported as [`ConnectionEvent`]s, either through
+/// [`health_events`](Self::health_events) or inline with
+/// [`with_connection_events`](Self::with_connection_events). Round trip times
+/// come from the pings enabled with [`keepalive`](Self::keepalive), and
+/// dropped connections are only reopened after [`reconnect`](Self::reconnect).
+pub struct WebSocketStream {
+    inner: InnerStream,
+    endpoint: Arc<Endpoint>,
+    finished: bool,
+    strict: bool,
+    decode_failures: Arc<AtomicU64>,
+    json_limits: JsonLimits,
+    keepalive: Option<Keepalive>,
+    degraded_after: Duration,
+    reconnect: Reconnect,
+    events: broadcast::Sender<ConnectionEvent>,
+    /// Last event sent, the current health
+    health: ConnectionEvent,
+    /// Resolves when the client shuts down, see [`Client::shutdown`](crate::Client::shutdown)
+    shutdown: Option<BoxFuture<'static, ()>>,
+    /// Resolves when the token passed to [`cancel_on`](Self::cancel_on) is cancelled
+    cancelled: Option<BoxFuture<'static, ()>>,
+    /// Sending the close frame after the client shut down or the stream was
+    /// cancelled, with the reason reported on disconnecting
+    closing: Option<&'static str>,
+}
+
+impl Endpoint {
+    async fn open(&self) -> Result<InnerStream> {
+        let mut request = self
+            .url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| Error::WebSocket(e.to_string()))?;
+
+        request.headers_mut().extend(self.headers.clone());
+
+        let target = || {
+            let uri = request.uri();
+            let host = uri
+                .host()
+                .ok_or_else(|| Error::WebSocket(format!("no host in URL: {}", self.url)))?
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_string();
+            let port = uri
+                .port_u16()
+                .unwrap_or(if uri.scheme_str() == Some("wss") {
+                    443
+                } else {
+                    80
+                });
+            Ok::<_, Error>((host, port))
+        };
+        let connected = match (&self.proxy, &self.resolver) {
+            (Some(proxy), _) => {
+                let (host, port) = target()?;
+                let stream = proxy.tunnel(&host, port).await?;
+                handshake(request, stream, self.connector.clone()).await
+            }
+            (None, Some(resolver)) => {
+                let (host, port) = target()?;
+                let addrs: Vec<SocketAddr> = resolver
+                    .lookup(&host)
+                    .await
+                    .map_err(|e| Error::WebSocket(e.to_string()))?
+                    .into_iter()
+                    .map(|addr| SocketAddr::new(addr.ip(), port))
+                    .collect();
+                let stream = TcpStream::connect(&addrs[..])
+                    .await
+                    .map_err(|e| Error::WebSocket(e.to_string()))?;
+                handshake(request, stream, self.connector.clone()).await
+            }
+            (None, None) => {
+                let (host, port) = target()?;
+                let stream = TcpStream::connect((host.as_str(), port))
+                    .await
+                    .map_err(|e| Error::WebSocket(e.to_string()))?;
+                handshake(request, stream, self.connector.clone()).await
+            }
+        };
+        let (inner, _) = connected.map_err(|e| {
+            error!("WebSocket connection failed: {}", e);
+            Error::WebSocket(e.to_string())
+        })?;
+        debug!("WebSocket connected: {}", self.url);
+        Ok(inner)
+    }
+}
+
+impl WebSocketStream {
+    /// Connect to the given WebSocket URL with additional handshake headers
+    ///
+    /// Host names are resolved with `resolver` when given, so DNS overrides and
+    /// caching configured on the client apply to streams as well. With a
+    /// `proxy`, the connection is tunnelled through it instead. `wss://` URLs
+    /// use `connector` for TLS when given.
+    pub(crate) async fn connect(
+        url: &str,
+        headers: HeaderMap,
+        resolver: Option<Arc<Resolver>>,
+        proxy: Option<Proxy>,
+        connector: Option<Connector>,
+    ) -> Result<Self> {
+        let endpoint = Arc::new(Endpoint {
+            url: url.to_string(),
+            headers,
+            resolver,
+            proxy,
+            connector,
+        });
+        let inner = endpoint.open().await?;
+        let (events, _) = broadcast::channel(HEALTH_EVENT_CAPACITY);
+
+        Ok(Self {
+            inner,
+            endpoint,
+            finished: false,
+            strict: false,
+            decode_failures: Arc::new(AtomicU64::new(0)),
+            json_limits: JsonLimits::default(),
+            keepalive: None,
+            degraded_after: DEFAULT_DEGRADED_RTT,
+            reconnect: Reconnect {
+                max_attempts: 0,
+                attempt: 0,
+                pending: None,
+            },
+            events,
+            health: ConnectionEvent::Connected,
+            shutdown: None,
+            cancelled: None,
+            closing: None,
+        })
+    }
+
+    /// Send a close frame and end the stream once `shutdown` resolves
+    pub(crate) fn close_on(mut self, shutdown: BoxFuture<'static, ()>) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Reject messages exceeding `limits` instead of decoding them
+    pub(crate) fn json_limits(mut self, limits: JsonLimits) -> Self {
+        self.json_limits = limits;
+        self
+    }
+
+    /// Close the connection and end the stream once `token` is cancelled
+    ///
+    /// The close frame is sent when the stream is next polled, as on
+    /// [`Client::shutdown`](crate::Client::shutdown). A stream reconnecting
+    /// at that point gives up.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use futures_util::StreamExt;
+    /// use klikkflow_sdk::CancellationToken;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let token = CancellationToken::new();
+    /// let mut updates = client.stream_execution("ex-1").await?.cancel_on(token.clone());
+    /// tokio::spawn(async move {
+    ///     tokio::signal::ctrl_c().await.ok();
+    ///     token.cancel();
+    /// });
+    /// while let Some(update) = updates.next().await {
+    ///     println!("{}", update?.update_type);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.cancelled = Some(token.cancelled_owned().boxed());
+        self
+    }
+
+    /// End the stream after the first undecodable message instead of skipping it
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Number of messages that failed to decode so far
+    pub fn decode_failures(&self) -> u64 {
+        self.decode_failures.load(Ordering::Relaxed)
+    }
+
+    /// Shared decode failure counter, readable while the stream is consumed elsewhere
+    pub fn decode_failure_counter(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.decode_failures)
+    }
+
+    /// Send a ping every `interval` to keep the connection open and measure its round trip time
+    pub fn keepalive(mut self, interval: Duration) -> Self {
+        let mut ticks = tokio::time::interval_at(Instant::now() + interval, interval);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        self.keepalive = Some(Keepalive {
+            interval: ticks,
+            next_id: 0,
+            queued: None,
+            outstanding: None,
+        });
+        self
+    }
+
+    /// Round trip time above which the connection is reported as degraded,
+    /// [`DEFAULT_DEGRADED_RTT`] by default
+    pub fn degraded_after(mut self, rtt: Duration) -> Self {
+        self.degraded_after = rtt;
+        self
+    }
+
+    /// Reopen a dropped connection up to `max_attempts` times in a row, with exponential backoff
+    ///
+    /// Updates sent while the connection was down are lost unless the server
+    /// replays them. A connection closed by the server is not reopened.
+    pub fn reconnect(mut self, max_attempts: u32) -> Self {
+        self.reconnect.max_attempts = max_attempts;
+        self
+    }
+
+    /// Connection events, starting with the current health
+    ///
+    /// Events are produced while this stream is being consumed. The returned
+    /// stream ends when this one is dropped; a receiver that falls behind
+    /// skips the events it missed.
+    pub fn health_events(&self) -> BoxStream<'static, ConnectionEvent> {
+        let receiver = self.events.subscribe();
+        let current = self.health.clone();
+        stream::once(async move { current })
+            .chain(stream::unfold(receiver, |mut receiver| async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) => return Some((event, receiver)),
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }))
+            .boxed()
+    }
+
+    /// Interleave connection events with the updates, starting with the current health
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> klikkflow_sdk::Result<()> {
+    /// use futures_util::StreamExt;
+    /// use klikkflow_sdk::StreamEvent;
+    ///
+    /// let client = klikkflow_sdk::Client::new("https://klikkflow.example.com");
+    /// let mut events = client.stream_execution("ex-1").await?.reconnect(3).with_connection_events();
+    /// while let Some(event) = events.next().await {
+    ///     match event? {
+    ///         StreamEvent::Update(update) => println!("{}", update.update_type),
+    ///         StreamEvent::Connection(connection) => eprintln!("connection: {:?}", connection),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_connection_events(self) -> ConnectionEventStream {
+        ConnectionEventStream {
+            receiver: self.events.subscribe(),
+            current: Some(self.health.clone()),
+            pending: None,
+            inner: self,
+        }
+    }
+
+    fn emit(&mut self, event: ConnectionEvent) {
+        debug!("Execution stream connection: {:?}", event);
+        self.health = event.clone();
+        // Fails only when nobody listens
+        let _ = self.events.send(event);
+    }
+
+    /// Send due keepalive pings and report unanswered ones
+    fn poll_keepalive(&mut self, cx: &mut Context<'_>) {
+        let Some(keepalive) = &mut self.keepalive else {
+            return;
+        };
+        let mut overdue = None;
+        while keepalive.interval.poll_tick(cx).is_ready() {
+            match keepalive.outstanding {
+                Some((_, sent)) => overdue = Some(sent.elapsed()),
+                None if keepalive.queued.is_none() => {
+                    keepalive.queued = Some(keepalive.next_id);
+                    keepalive.next_id += 1;
+                }
+                None => {}
+            }
+        }
+        if let Some(id) = keepalive.queued {
+            if let Poll::Ready(Ok(())) = self.inner.poll_ready_unpin(cx) {
+                let payload = id.to_be_bytes().to_vec();
+                if self.inner.start_send_unpin(Message::Ping(payload)).is_ok() {
+                    keepalive.queued = None;
+                    keepalive.outstanding = Some((id, Instant::now()));
+                }
+            }
+        }
+        // Write errors surface when reading
+        let _ = self.inner.poll_flush_unpin(cx);
+        // Reported once while the ping stays unanswered, again with its round trip time
+        let degraded = matches!(self.health, ConnectionEvent::Degraded { .. });
+        if let Some(rtt) = overdue.filter(|rtt| *rtt >= self.degraded_after && !degraded) {
+            self.emit(ConnectionEvent::Degraded { rtt });
+        }
+    }
+
+    fn pong(&mut self, payload: &[u8]) {
+        let Some(keepalive) = &mut self.keepalive else {
+            return;
+        };
+        let Some((id, sent)) = keepalive.outstanding else {
+            return;
+        };
+        if payload != id.to_be_bytes() {
+            return;
+        }
+        keepalive.outstanding = None;
+        let rtt = sent.elapsed();
+        if rtt >= self.degraded_after {
+            self.emit(ConnectionEvent::Degraded { rtt });
+        } else if self.health != ConnectionEvent::Connected {
+            self.emit(ConnectionEvent::Connected);
+        }
+    }
+
+    /// Start reopening the connection, if attempts are left
+    fn start_reconnect(&mut self) -> bool {
+        if self.reconnect.attempt >= self.reconnect.max_attempts {
+            return false;
+        }
+        self.reconnect.attempt += 1;
+        let attempt = self.reconnect.attempt;
+        let backoff = RECONNECT_BACKOFF
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(MAX_RECONNECT_BACKOFF);
+        warn!(
+            "Execution stream dropped, reconnecting in {:?} (attempt {})",
+            backoff, attempt
+        );
+        self.emit(ConnectionEvent::Reconnecting { attempt });
+        let endpoint = Arc::clone(&self.endpoint);
+        self.reconnect.pending = Some(
+            async move {
+                tokio::time::sleep(backoff).await;
+                endpoint.open().await
+            }
+            .boxed(),
+        );
+        true
+    }
+
+    /// Count a message that could not be decoded, ending the stream in strict mode
+    fn undecodable(&mut self, error: Error) -> Error {
+        self.decode_failures.fetch_add(1, Ordering::Relaxed);
+        warn!("Failed to decode stream message: {}", error);
+        if self.strict {
+            self.disconnected("undecodable message in strict mode".to_string());
+        }
+        error
+    }
+
+    fn disconnected(&mut self, reason: String) {
+        self.finished = true;
+        self.emit(ConnectionEvent::Disconnected { reason });
+    }
+
+    /// Read the next text message without decoding it as an update
+    pub(crate) async fn next_text(&mut self) -> Result<Option<String>> {
+        loop {
+            return match self.inner.next().await {
+                Some(Ok(Message::Text(text))) => Ok(Some(text)),
+                Some(Ok(Message::Binary(bytes))) => {
+                    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+                }
+                Some(Ok(Message::Close(_))) | None => {
+                    self.finished = true;
+                    Ok(None)
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    self.finished = true;
+                    Err(Error::WebSocket(e.to_string()))
+                }
+            };
+        }
+    }
+
+    /// Close the underlying WebSocket connection
+    pub async fn close(&mut self) -> Result<()> {
+        self.disconnected("closed by client".to_string());
+        self.inner
+            .close(None)
+            .await
+            .map_err(|e| Error::WebSocket(e.to_string()))
+    }
+}
+
+/// Run the WebSocket handshake over `stream`, with TLS for `wss://` requests
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+async fn handshake(
+    request: Request,
+    stream: TcpStream,
+    connector: Option<Connector>,
+) -> std::result::Result<(InnerStream, Response), tokio_tungstenite::tungstenite::Error> {
+    tokio_tungstenite::client_async_tls_with_config(request, stream, None, connector).await
+}
+
+/// Run the WebSocket handshake over `stream`; without a TLS feature only `ws://` works
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+async fn handshake(
+    request: Request,
+    stream: TcpStream,
+    _connector: Option<Connector>,
+) -> std::result::Result<(InnerStream, Response), tokio_tungstenite::tungstenite::Error> {
+    tokio_tungstenite::client_async(request, MaybeTlsStream::Plain(stream)).await
+}
+
+impl Stream for WebSocketStream {
+    type Item = Result<ExecutionUpdate>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.finished {
+            return Poll::Ready(None);
+        }
+        if let Some(shutdown) = &mut self.shutdown {
+            if shutdown.poll_unpin(cx).is_ready() {
+                self.shutdown = None;
+                self.reconnect.pending = None;
+                self.closing = Some("client shut down");
+            }
+        }
+        if let Some(cancelled) = &mut self.cancelled {
+            if cancelled.poll_unpin(cx).is_ready() {
+                self.cancelled = None;
+                self.reconnect.pending = None;
+                self.closing.get_or_insert("cancelled");
+            }
+        }
+        if let Some(reason) = self.closing {
+            // A connection that is already broken just ends the stream
+            let _ = ready!(self.inner.poll_close_unpin(cx));
+            self.disconnected(reason.to_string());
+            return Poll::Ready(None);
+        }
+
+        loop {
+            if let Some(pending) = &mut self.reconnect.pending {
+                match ready!(pending.poll_unpin(cx)) {
+                    Ok(inner) => {
+                        self.inner = inner;
+                        self.reconnect.pending = None;
+                        self.reconnect.attempt = 0;
+                        if let Some(keepalive) = &mut self.keepalive {
+                            keepalive.outstanding = None;
+                        }
+                        self.emit(ConnectionEvent::Connected);
+                    }
+                    Err(e) => {
+                        self.reconnect.pending = None;
+                        if !self.start_reconnect() {
+                            self.disconnected(e.to_string());
+                            return Poll::Ready(Some(Err(e)));
+                        }
+                        continue;
+                    }
+                }
+            }
+
+            self.poll_keepalive(cx);
+            let raw = match ready!(self.inner.poll_next_unpin(cx)) {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Binary(bytes))) => String::from_utf8_lossy(&bytes).into_owned(),
+                Some(Ok(Message::Pong(payload))) => {
+                    self.pong(&payload);
+                    continue;
+                }
+                Some(Ok(Message::Close(_))) | None => {
+                    self.disconnected("closed by server".to_string());
+                    return Poll::Ready(None);
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    if self.start_reconnect() {
+                        continue;
+                    }
+                    self.disconnected(e.to_string());
+                    return Poll::Ready(Some(Err(Error::WebSocket(e.to_string()))));
+                }
+            };
+
+            if let Err(e) = self.json_limits.check(raw.as_bytes()) {
+                return Poll::Ready(Some(Err(self.undecodable(e))));
+            }
+            return match serde_json::from_str::<ExecutionUpdate>(&raw) {
+                Ok(update) => Poll::Ready(Some(Ok(update))),
+                Err(source) => Poll::Ready(Some(Err(
+                    self.undecodable(Error::MessageDecode { raw, source })
+                ))),
+            };
+        }
+    }
+}
+
+/// Execution updates interleaved with connection events, see
+/// [`WebSocketStream::with_connection_events`]
+pub struct ConnectionEventStream {
+    inner: WebSocketStream,
+    receiver: broadcast::Receiver<ConnectionEvent>,
+    current: Option<ConnectionEvent>,
+    /// Item from the stream held back until the events sent before it are yielded
+    pending: Option<Option<Result<ExecutionUpdate>>>,
+}
+
+impl ConnectionEventStream {
+    /// The underlying stream
+    pub fn get_mut(&mut self) -> &mut WebSocketStream {
+        &mut self.inner
+    }
+
+    fn next_event(&mut self) -> Option<ConnectionEvent> {
+        loop {
+            return match self.receiver.try_recv() {
+                Ok(event) => Some(event),
+                Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+impl Stream for ConnectionEventStream {
+    type Item = Result<StreamEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(current) = self.current.take() {
+            return Poll::Ready(Some(Ok(StreamEvent::Connection(current))));
+        }
+        loop {
+            if let Some(event) = self.next_event() {
+                return Poll::Ready(Some(Ok(StreamEvent::Connection(event))));
+            }
+            if let Some(item) = self.pending.take() {
+                return Poll::Ready(item.map(|item| item.map(StreamEvent::Update)));
+            }
+            match self.inner.poll_next_unpin(cx) {
+                Poll::Ready(item) => self.pending = Some(item),
+                // Events sent while polling are yielded before waiting
+                Poll::Pending => match self.next_event() {
+                    Some(event) => return Poll::Ready(Some(Ok(StreamEvent::Connection(event)))),
+                    None => return Poll::Pending,
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,921 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::{client, count, execution, ok, sequence, status, with_header, workflow, BASE_URL};
+use futures_util::future::{BoxFuture, FutureExt};
+use klikkflow_sdk::{
+    Client, ConfirmationHook, Error, ExecuteOptions, FieldMap, MemoryTransport, Mutation, Priority,
+    RequestInterceptor, RequestOptions, RequestParts, ResponseMeta, SchedulerConfig,
+    TransportResponse, ALLOW_PROD_ENV, DEFAULT_USER_AGENT, SDK_INTERNAL_TAG,
+};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn healthy() -> Arc<MemoryTransport> {
+    Arc::new(MemoryTransport::new().handle("GET", "/health", |_| ok(json!({}))))
+}
+
+#[tokio::test]
+async fn only_requests_with_a_body_declare_json_content() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/health", |_| ok(json!({})))
+            .handle("DELETE", "/api/workflows/wf-1", |_| ok(json!({})))
+            .handle("PUT", "/api/workflows/wf-1/static-data", |_| ok(json!({})))
+            .handle("POST", "/api/executions/ex-1/cancel", |_| ok(json!({}))),
+    );
+    let client = client(&transport);
+    client.health_check().await.unwrap();
+    client.delete_workflow("wf-1").await.unwrap();
+    client
+        .set_workflow_static_data("wf-1", Default::default())
+        .await
+        .unwrap();
+    client.cancel_execution("ex-1").await.unwrap();
+
+    let requests = transport.requests();
+    for request in &requests {
+        assert_eq!(request.headers["accept"], "application/json");
+    }
+    let content_types: Vec<_> = requests
+        .iter()
+        .map(|request| request.headers.get("content-type").is_some())
+        .collect();
+    assert_eq!(content_types, [false, false, true, false]);
+}
+
+#[tokio::test]
+async fn problem_details_are_parsed() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "POST",
+        "/api/executions/ex-1/cancel",
+        |_| {
+            let mut response = status(
+                404,
+                json!({ "type": "about:blank", "title": "Not Found", "status": 404, "detail": "no execution ex-1" }),
+            );
+            response.headers.insert(
+                "content-type",
+                "application/problem+json".parse().unwrap(),
+            );
+            response
+        },
+    ));
+    match client(&transport).cancel_execution("ex-1").await {
+        Err(Error::NotFound {
+            resource,
+            id,
+            problem: Some(problem),
+            ..
+        }) => {
+            assert_eq!(
+                (resource.as_str(), id.as_deref()),
+                ("execution", Some("ex-1"))
+            );
+            assert_eq!(problem.detail.as_deref(), Some("no execution ex-1"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn base_url_path_prefix_is_kept() {
+    let transport =
+        Arc::new(MemoryTransport::new().handle("GET", "/api-gateway/health", |_| ok(json!({}))));
+    let client = Client::builder()
+        .base_url(format!("{}/api-gateway/", BASE_URL))
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+    assert_eq!(client.base_url(), format!("{}/api-gateway", BASE_URL));
+    client.health_check().await.unwrap();
+    assert_eq!(count(&transport, "GET", "/api-gateway/health"), 1);
+}
+
+#[test]
+fn base_urls_are_normalized() {
+    for (base_url, normalized) in [
+        (
+            "https://klikkflow.example.com/",
+            "https://klikkflow.example.com",
+        ),
+        (
+            "https://klikkflow.example.com:8443//",
+            "https://klikkflow.example.com:8443",
+        ),
+        (
+            "https://klikkflow.example.com:443/edge",
+            "https://klikkflow.example.com/edge",
+        ),
+        ("http://127.0.0.1:3001/", "http://127.0.0.1:3001"),
+    ] {
+        let client = Client::builder().base_url(base_url).build().unwrap();
+        assert_eq!(client.base_url(), normalized);
+    }
+    for invalid in [
+        "",
+        "ftp://klikkflow.example.com",
+        "klikkflow.example.com",
+        "localhost:3001",
+        "https://h/api?x=1",
+    ] {
+        assert!(
+            Client::builder().base_url(invalid).build().is_err(),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn invalid_base_url_is_rejected_by_try_new_only() {
+    match Client::try_new("localhost:3000") {
+        Err(Error::Config(message)) => assert!(message.contains("no scheme"), "{}", message),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+    assert_eq!(Client::new("localhost:3000").base_url(), "localhost:3000");
+    assert_eq!(
+        Client::try_new("https://klikkflow.example.com/edge/")
+            .unwrap()
+            .base_url(),
+        "https://klikkflow.example.com/edge"
+    );
+}
+
+#[test]
+fn api_key_is_masked_in_debug_output() {
+    let key = "kf_live_0123456789abcd";
+    let client = format!("{:?}", Client::new(BASE_URL).with_api_key(key));
+    let builder = format!("{:?}", Client::builder().api_key(key));
+    for debug in [client, builder] {
+        assert!(!debug.contains(key), "{}", debug);
+        assert!(debug.contains(r#"api_key: Some("****abcd")"#), "{}", debug);
+    }
+
+    // Keys too short to show a suffix of are masked entirely
+    let debug = format!("{:?}", Client::new(BASE_URL).with_api_key("kf_0123abc"));
+    assert!(!debug.contains("3abc"), "{}", debug);
+    assert!(debug.contains(r#"api_key: Some("****")"#), "{}", debug);
+}
+
+#[tokio::test]
+async fn scheduler_admits_requests_by_priority() {
+    let transport = healthy();
+    let client = Client::builder()
+        .base_url(BASE_URL)
+        .transport(transport.clone())
+        .scheduler(SchedulerConfig::new(4))
+        .build()
+        .unwrap();
+    let sync = client.with_request_options(RequestOptions::new().priority(Priority::Background));
+    sync.health_check().await.unwrap();
+    client.health_check().await.unwrap();
+
+    let stats = client.scheduler_stats().unwrap();
+    assert_eq!(stats.classes[&Priority::Background].admitted, 1);
+    assert_eq!(stats.classes[&Priority::Interactive].admitted, 1);
+    assert_eq!(stats.in_flight, 0);
+}
+
+#[tokio::test]
+async fn execution_quota_is_shared_by_clones() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("POST", "/api/executions", |_| {
+            ok(execution("ex-1", "billing-sync", "pending"))
+        }),
+    );
+    let client = Client::builder()
+        .base_url(BASE_URL)
+        .transport(transport.clone())
+        .execution_quota("billing-*", 2)
+        .build()
+        .unwrap();
+    for _ in 0..2 {
+        client
+            .execute_workflow("billing-sync", FieldMap::new(), false)
+            .await
+            .unwrap();
+    }
+    match client
+        .clone()
+        .execute_workflow("billing-sync", FieldMap::new(), false)
+        .await
+    {
+        Err(Error::QuotaExceeded {
+            workflow_id,
+            resets_in,
+        }) => {
+            assert_eq!(workflow_id, "billing-sync");
+            assert!(resets_in.as_secs() > 3500);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let break_glass = ExecuteOptions::new().override_quota(true);
+    client
+        .execute_workflow_with_options("billing-sync", FieldMap::new(), break_glass)
+        .await
+        .unwrap();
+    client
+        .execute_workflow("reports", FieldMap::new(), false)
+        .await
+        .unwrap();
+    assert_eq!(count(&transport, "POST", "/api/executions"), 4);
+
+    let status = client.quota_status();
+    assert_eq!(status.len(), 1);
+    assert_eq!((status[0].used, status[0].max_per_hour), (3, 2));
+}
+
+#[tokio::test]
+async fn workspace_of_request_options_takes_precedence() {
+    let transport = healthy();
+    let client = client(&transport).with_workspace("acme");
+    let globex = client.with_request_options(RequestOptions::new().workspace("globex"));
+    assert_eq!(client.workspace(), Some("acme"));
+    assert_eq!(globex.workspace(), Some("globex"));
+    client.health_check().await.unwrap();
+    globex.health_check().await.unwrap();
+
+    let workspaces: Vec<_> = transport
+        .requests()
+        .iter()
+        .map(|request| request.headers["x-reporunner-workspace"].clone())
+        .collect();
+    assert_eq!(workspaces, ["acme", "globex"]);
+}
+
+/// Signs each request with a (toy) digest of its body
+struct Signer;
+
+impl RequestInterceptor for Signer {
+    fn before<'a>(&'a self, request: &'a mut RequestParts) -> BoxFuture<'a, ()> {
+        let digest = request
+            .body()
+            .unwrap_or_default()
+            .iter()
+            .map(|&b| b as u32)
+            .sum::<u32>();
+        request
+            .headers
+            .insert("x-signature", digest.to_string().parse().unwrap());
+        async {}.boxed()
+    }
+}
+
+/// Records the status of every response
+#[derive(Default)]
+struct Metrics(Mutex<Vec<(String, Option<u16>)>>);
+
+impl RequestInterceptor for Metrics {
+    fn after<'a>(&'a self, response: &'a ResponseMeta) -> BoxFuture<'a, ()> {
+        let status = response.status.map(|status| status.as_u16());
+        self.0.lock().unwrap().push((response.path.clone(), status));
+        async {}.boxed()
+    }
+}
+
+struct Broken;
+
+impl RequestInterceptor for Broken {
+    fn before<'a>(&'a self, _: &'a mut RequestParts) -> BoxFuture<'a, ()> {
+        panic!("interceptor bug")
+    }
+}
+
+#[tokio::test]
+async fn interceptors_run_in_order_and_survive_panics() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "PUT",
+        "/api/workflows/wf-1/static-data",
+        |_| ok(json!({ "runs": 1 })),
+    ));
+    let metrics = Arc::new(Metrics::default());
+    let client = client(&transport)
+        .with_interceptor(Arc::new(Broken))
+        .with_interceptor(Arc::new(Signer))
+        .with_interceptor(metrics.clone());
+    let data = [("runs".to_string(), json!(1))].into();
+    client.set_workflow_static_data("wf-1", data).await.unwrap();
+    assert!(client.get_workflow("wf-2").await.is_err());
+
+    assert_eq!(transport.requests()[0].headers["x-signature"], "2279");
+    let seen = metrics.0.lock().unwrap().clone();
+    assert_eq!(
+        seen,
+        [
+            ("/api/workflows/wf-1/static-data".to_string(), Some(200)),
+            ("/api/workflows/wf-2".to_string(), Some(404)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn user_agent_names_the_sdk_and_the_product() {
+    let transport = healthy();
+    let clients = [
+        client(&transport),
+        client(&transport)
+            .with_user_agent("deploy-bot/1.4")
+            .unwrap(),
+        client(&transport)
+            .with_header("User-Agent", "custom/2.0")
+            .unwrap(),
+    ];
+    for client in &clients {
+        client.health_check().await.unwrap();
+    }
+
+    let agents: Vec<_> = transport
+        .requests()
+        .iter()
+        .map(|request| request.headers["user-agent"].to_str().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        agents,
+        [
+            DEFAULT_USER_AGENT.to_string(),
+            format!("{} deploy-bot/1.4", DEFAULT_USER_AGENT),
+            "custom/2.0".to_string(),
+        ]
+    );
+    assert!(DEFAULT_USER_AGENT.starts_with("reporunner-rust-sdk/"));
+}
+
+#[tokio::test]
+async fn default_headers_never_replace_authorization() {
+    let transport = healthy();
+    let client = client(&transport)
+        .with_api_key("your-api-key")
+        .with_header("X-Tenant-Id", "acme")
+        .unwrap()
+        .with_header("X-Region", "eu")
+        .unwrap()
+        .with_header("Authorization", "Bearer someone-else")
+        .unwrap();
+    client.health_check().await.unwrap();
+
+    let headers = &transport.requests()[0].headers;
+    assert_eq!(headers["x-tenant-id"], "acme");
+    assert_eq!(headers["x-region"], "eu");
+    assert_eq!(headers["authorization"], "Bearer your-api-key");
+    assert!(client.with_header("X-Tenant-Id", "a\nb").is_err());
+}
+
+#[tokio::test]
+async fn timeout_applies_in_either_order() {
+    let transport = healthy();
+    let timeout = Duration::from_millis(200);
+    let key_first = client(&transport)
+        .with_api_key("your-api-key")
+        .with_timeout(timeout)
+        .unwrap();
+    let timeout_first = client(&transport)
+        .with_timeout(timeout)
+        .unwrap()
+        .with_api_key("your-api-key");
+    for client in [key_first, timeout_first] {
+        client.health_check().await.unwrap();
+    }
+
+    for request in transport.requests() {
+        assert_eq!(request.timeout, Some(timeout));
+        assert_eq!(request.headers["authorization"], "Bearer your-api-key");
+    }
+}
+
+#[tokio::test]
+async fn request_timeout_overrides_the_client_timeout() {
+    let transport = healthy();
+    let (short, long) = (Duration::from_millis(100), Duration::from_secs(5));
+    let fast = client(&transport).with_timeout(short).unwrap();
+    fast.with_request_timeout(long)
+        .health_check()
+        .await
+        .unwrap();
+    let slow = client(&transport).with_timeout(long).unwrap();
+    slow.with_request_timeout(short)
+        .health_check()
+        .await
+        .unwrap();
+
+    let timeouts: Vec<_> = transport
+        .requests()
+        .iter()
+        .map(|request| request.timeout)
+        .collect();
+    assert_eq!(timeouts, [Some(long), Some(short)]);
+}
+
+#[tokio::test]
+async fn api_revision_is_sent_with_every_request() {
+    let transport = healthy();
+    let client = client(&transport).with_api_revision("2024-06");
+    assert_eq!(client.api_revision(), Some("2024-06"));
+    client.health_check().await.unwrap();
+    assert_eq!(transport.requests()[0].headers["x-api-version"], "2024-06");
+}
+
+#[tokio::test]
+async fn server_errors_are_retried_until_attempts_run_out() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/workflows/wf-1", |_| {
+                TransportResponse::new(StatusCode::SERVICE_UNAVAILABLE, "upstream unavailable")
+            })
+            .handle("GET", "/api/workflows/wf-2", |_| status(503, json!({})))
+            .handle("POST", "/api/executions/ex-1/cancel", |_| {
+                status(503, json!({}))
+            }),
+    );
+    let client = client(&transport)
+        .with_retry(klikkflow_sdk::RetryPolicy::new().initial_backoff(Duration::from_millis(10)));
+    match client.get_workflow("wf-1").await {
+        Err(Error::Api {
+            status: 503,
+            message,
+            ..
+        }) => assert_eq!(message, "upstream unavailable"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 3);
+
+    // POSTs are only retried when flagged idempotent
+    assert!(client.cancel_execution("ex-1").await.is_err());
+    assert_eq!(count(&transport, "POST", "/api/executions/ex-1/cancel"), 1);
+
+    let no_retry = client
+        .with_request_options(RequestOptions::new().retry(klikkflow_sdk::RetryPolicy::disabled()));
+    assert!(no_retry.get_workflow("wf-2").await.is_err());
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-2"), 1);
+}
+
+#[tokio::test]
+async fn circuit_opens_after_consecutive_failures() {
+    use klikkflow_sdk::{CircuitBreakerConfig, CircuitState};
+
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/health",
+        sequence(vec![
+            status(503, json!({})),
+            status(503, json!({})),
+            ok(json!({})),
+        ]),
+    ));
+    let client = client(&transport).with_circuit_breaker(
+        CircuitBreakerConfig::new()
+            .failure_threshold(2)
+            .cool_down(Duration::from_millis(200)),
+    );
+    let worker = client.clone();
+
+    assert!(client.health_check().await.is_err());
+    assert!(client.health_check().await.is_err());
+    assert_eq!(worker.circuit_state(), CircuitState::Open);
+    assert!(matches!(
+        worker.health_check().await,
+        Err(Error::CircuitOpen { .. })
+    ));
+    assert_eq!(count(&transport, "GET", "/health"), 2);
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(client.circuit_state(), CircuitState::HalfOpen);
+    worker.health_check().await.unwrap();
+    assert_eq!(client.circuit_state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn fails_over_and_back_once_the_primary_recovers() {
+    const STANDBY: &str = "https://standby.klikkflow.example.com";
+    let primary = Arc::new(sequence(vec![status(503, json!({})), ok(json!({}))]));
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/health", move |request| {
+            if request.url.starts_with(STANDBY) {
+                ok(json!({}))
+            } else {
+                primary(request)
+            }
+        }),
+    );
+    let client = client(&transport)
+        .with_fallback_urls([STANDBY])
+        .unwrap()
+        .with_failback_interval(Duration::from_millis(100));
+    client.health_check().await.unwrap();
+    assert_eq!(client.base_url(), STANDBY);
+
+    // Still on the standby while the primary is probed in the background
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client.health_check().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(client.base_url(), BASE_URL);
+
+    let bases: Vec<_> = transport
+        .requests()
+        .iter()
+        .map(|request| request.url.starts_with(STANDBY))
+        .collect();
+    assert_eq!(bases, [false, true, true, false]);
+}
+
+#[tokio::test]
+async fn schema_cache_is_shared_across_clients() {
+    use klikkflow_sdk::SchemaCacheOptions;
+
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/version", |_| ok(json!({ "version": "2.4.0" })))
+            .handle("GET", "/api/node-types", |_| {
+                ok(json!({ "nodeTypes": [{ "name": "http-request" }] }))
+            }),
+    );
+    let dir = std::env::temp_dir().join(format!("klikkflow-test-{}", uuid::Uuid::new_v4()));
+    for _ in 0..2 {
+        // A fresh client per run, as in separate CI jobs
+        let client = client(&transport)
+            .with_schema_cache(SchemaCacheOptions::new(&dir))
+            .unwrap();
+        let node_types = client.list_node_types().await.unwrap();
+        assert_eq!(node_types.items[0].name, "http-request");
+    }
+    assert_eq!(count(&transport, "GET", "/api/node-types"), 1);
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test]
+async fn stale_responses_are_served_while_revalidating() {
+    use klikkflow_sdk::CachePolicy;
+
+    let node_type = Arc::new(Mutex::new("http-request"));
+    let current = node_type.clone();
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/node-types", move |_| {
+            ok(json!({ "nodeTypes": [{ "name": *current.lock().unwrap() }] }))
+        }),
+    );
+    let (refreshed, mut changes) = tokio::sync::mpsc::unbounded_channel();
+    let client = client(&transport)
+        .with_cache_policy(CachePolicy::StaleWhileRevalidate {
+            max_stale: Duration::from_secs(60),
+        })
+        .with_cache_refresh_callback(move |path, _| {
+            refreshed.send(path.to_string()).ok();
+        });
+    let name =
+        |page: klikkflow_sdk::Page<klikkflow_sdk::NodeTypeDescription>| page.items[0].name.clone();
+    assert_eq!(
+        name(client.list_node_types().await.unwrap()),
+        "http-request"
+    );
+
+    *node_type.lock().unwrap() = "graphql";
+    // The cached list is returned at once while the new one is fetched
+    assert_eq!(
+        name(client.list_node_types().await.unwrap()),
+        "http-request"
+    );
+    assert_eq!(changes.recv().await.as_deref(), Some("/api/node-types"));
+    assert_eq!(name(client.list_node_types().await.unwrap()), "graphql");
+}
+
+#[tokio::test]
+async fn responses_are_adapted_by_server_version() {
+    use klikkflow_sdk::{ResponseAdapter, ResponseAdapters, SERVER_VERSION_HEADER};
+
+    let versioned = |version: &'static str, node_result: serde_json::Value| {
+        move |_: &klikkflow_sdk::TransportRequest| {
+            let mut body = execution("ex-1", "wf-1", "success");
+            body["nodeResults"] = json!({ "fetch": node_result.clone() });
+            let mut response = ok(body);
+            response
+                .headers
+                .insert(SERVER_VERSION_HEADER, version.parse().unwrap());
+            response
+        }
+    };
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle(
+                "GET",
+                "/api/executions/ex-old",
+                versioned("2.2.4", json!({ "output": { "count": 2 } })),
+            )
+            .handle(
+                "GET",
+                "/api/executions/ex-new",
+                versioned("2.3.0", json!({ "data": { "count": 2 } })),
+            )
+            .handle(
+                "GET",
+                "/api/executions/ex-next",
+                versioned(
+                    "2.4.1",
+                    json!({ "data": { "count": 2 }, "failure": "boom" }),
+                ),
+            ),
+    );
+
+    // A rename in a future server, fixed without waiting for a new model
+    let adapters = ResponseAdapters::default().register(
+        ResponseAdapter::rename_entry_field(
+            "node-result-failure",
+            "nodeResults",
+            "failure",
+            "error",
+        )
+        .since("2.4.0")
+        .unwrap(),
+    );
+    let client = client(&transport).with_response_adapters(adapters);
+    for id in ["ex-old", "ex-new", "ex-next"] {
+        let execution = client.get_execution(id).await.unwrap();
+        assert_eq!(
+            execution.node_results["fetch"].output,
+            Some(json!({ "count": 2 })),
+            "{}",
+            id
+        );
+    }
+    let next = client.get_execution("ex-next").await.unwrap();
+    assert_eq!(next.node_results["fetch"].error.as_deref(), Some("boom"));
+}
+
+#[tokio::test]
+async fn retry_after_sets_the_wait_before_a_retry() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows/wf-1",
+        sequence(vec![
+            with_header(status(429, json!({})), "retry-after", "1"),
+            ok(workflow("wf-1", "Orders")),
+        ]),
+    ));
+    let client = client(&transport).with_retry(klikkflow_sdk::RetryPolicy::default());
+    let started = Instant::now();
+    client.get_workflow("wf-1").await.unwrap();
+    let waited = started.elapsed();
+    assert!(waited >= Duration::from_secs(1) && waited < Duration::from_secs(2));
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 2);
+}
+
+#[tokio::test]
+async fn budget_cuts_retries_short() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/workflows/wf-1", |_| status(503, json!({}))),
+    );
+    let client = client(&transport)
+        .with_retry(klikkflow_sdk::RetryPolicy::new().initial_backoff(Duration::from_secs(10)));
+    let bounded = client.with_request_options(RequestOptions::new().budget(Duration::from_secs(2)));
+    let started = Instant::now();
+    assert!(matches!(
+        bounded.get_workflow("wf-1").await,
+        Err(Error::DeadlineExceeded {
+            execution_id: None,
+            ..
+        })
+    ));
+    // The 10s backoff was never slept
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 1);
+}
+
+#[tokio::test]
+async fn tags_and_priority_are_sent_as_headers() {
+    let transport = healthy();
+    let client = client(&transport).with_tag("team", "platform").unwrap();
+    let checkout = client.with_request_options(
+        RequestOptions::new()
+            .priority(Priority::Background)
+            .tag("Feature", "checkout")
+            .tag("team", "payments"),
+    );
+    checkout.health_check().await.unwrap();
+    client.health_check().await.unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].headers["x-request-priority"], "background");
+    assert_eq!(requests[0].headers["x-request-tag-feature"], "checkout");
+    assert_eq!(requests[0].headers["x-request-tag-team"], "payments");
+    assert_eq!(requests[1].headers["x-request-tag-team"], "platform");
+
+    let invalid = client.with_request_options(RequestOptions::new().tag("feature", "a\nb"));
+    assert!(matches!(
+        invalid.health_check().await,
+        Err(Error::InvalidInput(_))
+    ));
+    assert!(client.clone().with_tag("no spaces", "x").is_err());
+}
+
+#[tokio::test]
+async fn requests_sent_by_the_sdk_are_tagged_internal() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/executions/ex-1", |_| {
+            ok(execution("ex-1", "wf-1", "success"))
+        }),
+    );
+    let client = Client::builder()
+        .base_url(BASE_URL)
+        .transport(transport.clone())
+        .default_tag("team", "platform")
+        .build()
+        .unwrap();
+    client.execution("ex-1").wait().await.unwrap();
+
+    let poll = &transport.requests()[0];
+    assert_eq!(
+        poll.headers[format!("x-request-tag-{}", SDK_INTERNAL_TAG).as_str()],
+        "wait-poll"
+    );
+    assert_eq!(poll.headers["x-request-tag-team"], "platform");
+}
+
+/// Execution `id` in the `/api/v2` shape
+fn v2_execution(id: &str) -> serde_json::Value {
+    let mut body = execution(id, "wf-1", "success");
+    let fields = body.as_object_mut().unwrap();
+    fields.remove("startedAt");
+    fields.remove("finishedAt");
+    fields.remove("nodeResults");
+    fields.insert("startTime".into(), json!("2024-01-01T00:00:00Z"));
+    fields.insert("endTime".into(), json!("2024-01-01T00:01:00Z"));
+    fields.insert(
+        "nodeRuns".into(),
+        json!({ "fetch": { "status": "success", "output": { "count": 2 } } }),
+    );
+    body
+}
+
+#[tokio::test]
+async fn api_version_is_negotiated_on_the_first_request() {
+    use klikkflow_sdk::ApiVersion;
+
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/version", |_| {
+                ok(json!({ "version": "3.1.0", "apiVersions": ["v1", "v2", "v9"] }))
+            })
+            .handle("GET", "/api/v2/executions/ex-1", |_| {
+                ok(v2_execution("ex-1"))
+            })
+            .handle("GET", "/api/v2/workflows/wf-1/executions", |_| {
+                ok(json!({ "items": [v2_execution("ex-2")] }))
+            }),
+    );
+    let client = Client::builder()
+        .base_url(BASE_URL)
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+
+    let execution = client.get_execution("ex-1").await.unwrap();
+    assert_eq!(client.api_version(), ApiVersion::V2);
+    assert_eq!(
+        execution.finished_at.unwrap().to_rfc3339(),
+        "2024-01-01T00:01:00+00:00"
+    );
+    assert_eq!(
+        execution.node_results["fetch"].output,
+        Some(json!({ "count": 2 }))
+    );
+
+    // Clones share the negotiated version and the server info it came from
+    let history = client
+        .clone()
+        .get_execution_history("wf-1", None)
+        .await
+        .unwrap();
+    assert_eq!(history.items[0].id, "ex-2");
+    assert_eq!(count(&transport, "GET", "/api/version"), 1);
+}
+
+#[tokio::test]
+async fn servers_without_version_information_get_v1() {
+    use klikkflow_sdk::ApiVersion;
+
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/workflows/wf-1", |_| {
+            ok(workflow("wf-1", "Nightly"))
+        }),
+    );
+    let client = Client::builder()
+        .base_url(BASE_URL)
+        .transport(transport.clone())
+        .build()
+        .unwrap();
+    let client = client.negotiate_api_version().await.unwrap();
+    assert_eq!(client.api_version(), ApiVersion::V1);
+
+    client.get_workflow("wf-1").await.unwrap();
+    client.get_workflow("wf-1").await.unwrap();
+    assert_eq!(count(&transport, "GET", "/api/version"), 1);
+}
+
+#[tokio::test]
+async fn selected_api_version_is_not_negotiated() {
+    use klikkflow_sdk::ApiVersion;
+
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/v2/executions/ex-1",
+        |_| ok(v2_execution("ex-1")),
+    ));
+    let client = Client::builder()
+        .base_url(BASE_URL)
+        .transport(transport.clone())
+        .api_version(ApiVersion::V2)
+        .build()
+        .unwrap();
+    client.get_execution("ex-1").await.unwrap();
+    assert_eq!(count(&transport, "GET", "/api/version"), 0);
+
+    // A base URL with an API path cannot take a version prefix too
+    let conflicting = Client::builder()
+        .base_url(format!("{}/api", BASE_URL))
+        .transport(transport.clone())
+        .api_version(ApiVersion::V2)
+        .build();
+    assert!(matches!(conflicting, Err(Error::Config(_))));
+}
+
+/// Transport accepting reads and deletes of workflow `wf-1`
+fn deletable() -> Arc<MemoryTransport> {
+    Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/workflows/wf-1", |_| {
+                ok(workflow("wf-1", "Orders"))
+            })
+            .handle("DELETE", "/api/workflows/wf-1", |_| ok(json!({}))),
+    )
+}
+
+#[tokio::test]
+async fn confirmation_hook_vetoes_mutations_of_guarded_environments() {
+    let transport = deletable();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let hook = {
+        let seen = Arc::clone(&seen);
+        ConfirmationHook::new(move |mutation: &Mutation| {
+            seen.lock().unwrap().push(mutation.clone());
+            Err(Error::Vetoed("not today".to_string()))
+        })
+    };
+    let guarded = client(&transport)
+        .with_environment_label("production")
+        .with_confirmation_for(["Production"], hook);
+
+    assert!(matches!(
+        guarded.delete_workflow("wf-1").await,
+        Err(Error::Vetoed(reason)) if reason == "not today"
+    ));
+    assert_eq!(count(&transport, "DELETE", "/api/workflows/wf-1"), 0);
+    {
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].environment, "production");
+        assert_eq!(seen[0].method, "DELETE");
+        assert_eq!(seen[0].path, "/api/workflows/wf-1");
+    }
+
+    // Reads are never checked
+    guarded.get_workflow("wf-1").await.unwrap();
+    assert_eq!(seen.lock().unwrap().len(), 1);
+
+    // Nor are environments the hook does not cover
+    guarded
+        .clone()
+        .with_environment_label("staging")
+        .delete_workflow("wf-1")
+        .await
+        .unwrap();
+    assert_eq!(seen.lock().unwrap().len(), 1);
+    assert_eq!(count(&transport, "DELETE", "/api/workflows/wf-1"), 1);
+}
+
+#[tokio::test]
+async fn allow_env_hook_requires_the_environment_variable() {
+    let transport = deletable();
+    let guarded = client(&transport)
+        .with_environment_label("prod")
+        .with_confirmation_for(["prod"], ConfirmationHook::allow_env());
+
+    std::env::remove_var(ALLOW_PROD_ENV);
+    match guarded.delete_workflow("wf-1").await {
+        Err(Error::Vetoed(reason)) => assert!(reason.contains(ALLOW_PROD_ENV), "{}", reason),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    std::env::set_var(ALLOW_PROD_ENV, "yes");
+    assert!(matches!(
+        guarded.delete_workflow("wf-1").await,
+        Err(Error::Vetoed(_))
+    ));
+    assert_eq!(count(&transport, "DELETE", "/api/workflows/wf-1"), 0);
+
+    std::env::set_var(ALLOW_PROD_ENV, "1");
+    guarded.delete_workflow("wf-1").await.unwrap();
+    std::env::remove_var(ALLOW_PROD_ENV);
+    assert_eq!(count(&transport, "DELETE", "/api/workflows/wf-1"), 1);
+}
@@ -0,0 +1,157 @@
+//! Fixtures shared by the integration tests
+#![allow(dead_code)]
+
+#[cfg(feature = "test-util")]
+use klikkflow_sdk::{ApiVersion, Client, MemoryTransport};
+use klikkflow_sdk::{TransportResponse, WorkflowDefinition};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "test-util")]
+use std::sync::Arc;
+
+pub const BASE_URL: &str = "https://klikkflow.example.com";
+
+/// Client sending its requests to `transport`
+///
+/// The API version is pinned, so no version negotiation shows up among the requests.
+#[cfg(feature = "test-util")]
+pub fn client(transport: &Arc<MemoryTransport>) -> Client {
+    Client::builder()
+        .base_url(BASE_URL)
+        .transport(transport.clone())
+        .api_version(ApiVersion::V1)
+        .build()
+        .expect("client builds")
+}
+
+/// Body of a workflow definition without nodes
+pub fn workflow(id: &str, name: &str) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "description": "",
+        "active": true,
+        "nodes": [],
+        "connections": [],
+        "settings": {},
+        "createdAt": "2024-01-01T00:00:00Z",
+        "updatedAt": "2024-01-01T00:00:00Z"
+    })
+}
+
+/// Body of a node of `node_type` at the origin, without parameters
+pub fn node(id: &str, name: &str, node_type: &str) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "type": node_type,
+        "position": { "x": 0.0, "y": 0.0 },
+        "parameters": {}
+    })
+}
+
+/// Body of a connection from node `source` to node `destination`
+pub fn connection(source: &str, destination: &str) -> Value {
+    json!({ "source": { "nodeId": source }, "destination": { "nodeId": destination } })
+}
+
+/// Definition of workflow `wf-1` with `nodes` and `connections`
+pub fn definition(nodes: Vec<Value>, connections: Vec<Value>) -> WorkflowDefinition {
+    let mut body = workflow("wf-1", "Orders");
+    body["nodes"] = nodes.into();
+    body["connections"] = connections.into();
+    serde_json::from_value(body).expect("workflow deserializes")
+}
+
+/// Body of an execution of `workflow_id`
+pub fn execution(id: &str, workflow_id: &str, status: &str) -> Value {
+    json!({
+        "id": id,
+        "workflowId": workflow_id,
+        "status": status,
+        "startedAt": "2024-01-01T00:00:00Z",
+        "finishedAt": null,
+        "inputData": {},
+        "outputData": {},
+        "error": null,
+        "nodeResults": {},
+        "metadata": { "totalNodes": 0, "completedNodes": 0, "failedNodes": 0, "retriedNodes": 0 }
+    })
+}
+
+/// `200 OK` with `body` as JSON
+pub fn ok(body: Value) -> TransportResponse {
+    TransportResponse::json(StatusCode::OK, &body)
+}
+
+/// `status` with `body` as JSON
+pub fn status(status: u16, body: Value) -> TransportResponse {
+    TransportResponse::json(StatusCode::from_u16(status).unwrap(), &body)
+}
+
+/// `response` with header `name` set to `value`
+pub fn with_header(
+    mut response: TransportResponse,
+    name: &'static str,
+    value: &str,
+) -> TransportResponse {
+    response
+        .headers
+        .insert(name, value.parse().expect("valid header value"));
+    response
+}
+
+/// Body of `request`, parsed as JSON
+pub fn json_body(request: &klikkflow_sdk::TransportRequest) -> Value {
+    serde_json::from_slice(request.body.as_deref().unwrap_or_default()).expect("JSON request body")
+}
+
+/// Handler giving `responses` in turn, then repeating the last one
+pub fn sequence(
+    responses: Vec<TransportResponse>,
+) -> impl Fn(&klikkflow_sdk::TransportRequest) -> TransportResponse + Send + Sync + 'static {
+    let next = AtomicUsize::new(0);
+    move |_| {
+        let index = next.fetch_add(1, Ordering::SeqCst);
+        responses[index.min(responses.len() - 1)].clone()
+    }
+}
+
+/// Number of `method` requests for `path` received by `transport`
+#[cfg(feature = "test-util")]
+pub fn count(transport: &MemoryTransport, method: &str, path: &str) -> usize {
+    transport
+        .requests()
+        .iter()
+        .filter(|request| request.method == method && request.path() == path)
+        .count()
+}
+
+/// A v1 list page under `key`, continued at `next_cursor` if set
+pub fn page(key: &str, items: Vec<Value>, next_cursor: Option<&str>) -> TransportResponse {
+    ok(json!({ key: items, "nextCursor": next_cursor }))
+}
+
+/// Value of query parameter `name` in a request, as sent
+pub fn query_param<'a>(
+    request: &'a klikkflow_sdk::TransportRequest,
+    name: &str,
+) -> Option<&'a str> {
+    request
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+}
+
+/// Handler answering a v1 list under `key` in two pages, linked by a cursor
+pub fn two_pages(
+    key: &'static str,
+    first: Vec<Value>,
+    second: Vec<Value>,
+) -> impl Fn(&klikkflow_sdk::TransportRequest) -> TransportResponse + Send + Sync + 'static {
+    move |request| match query_param(request, "cursor") {
+        None => page(key, first.clone(), Some("page-2")),
+        Some(_) => page(key, second.clone(), None),
+    }
+}
@@ -0,0 +1,147 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::{client, execution, ok};
+use klikkflow_sdk::{CompareOptions, ExecutionResult, MemoryTransport, ValueChange};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Execution whose node `key` produced `output`, for each pair
+fn with_outputs(id: &str, outputs: Value) -> Value {
+    let mut body = execution(id, "wf-1", "success");
+    let results = outputs
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|(node, output)| {
+            (
+                node.clone(),
+                json!({ "status": "success", "output": output }),
+            )
+        })
+        .collect();
+    body["nodeResults"] = Value::Object(results);
+    body
+}
+
+fn parsed(body: Value) -> ExecutionResult {
+    serde_json::from_value(body).unwrap()
+}
+
+#[test]
+fn equal_outputs_have_no_differences() {
+    let outputs = json!({ "fetch": [{ "json": { "id": 1 } }], "notify": { "sent": true } });
+    let baseline = parsed(with_outputs("ex-1", outputs.clone()));
+    let current = parsed(with_outputs("ex-2", outputs));
+    assert!(current
+        .compare_outputs(&baseline, &CompareOptions::new())
+        .is_empty());
+}
+
+#[test]
+fn changed_added_and_removed_values_are_reported_by_pointer() {
+    let baseline = parsed(with_outputs(
+        "ex-1",
+        json!({ "fetch": { "count": 2, "items": ["a", "b"], "cursor": "x" } }),
+    ));
+    let current = parsed(with_outputs(
+        "ex-2",
+        json!({ "fetch": { "count": 3, "items": ["a", "b", "c"], "page": 1 } }),
+    ));
+    let diff = current.compare_outputs(&baseline, &CompareOptions::new());
+    assert_eq!(
+        diff.changed_nodes["fetch"],
+        [
+            ValueChange::Changed {
+                pointer: "/count".to_string(),
+                before: json!(2),
+                after: json!(3),
+            },
+            ValueChange::Removed {
+                pointer: "/cursor".to_string(),
+                value: json!("x"),
+            },
+            ValueChange::Added {
+                pointer: "/items/2".to_string(),
+                value: json!("c"),
+            },
+            ValueChange::Added {
+                pointer: "/page".to_string(),
+                value: json!(1),
+            },
+        ]
+    );
+}
+
+#[test]
+fn added_and_removed_nodes_are_listed() {
+    let baseline = parsed(with_outputs("ex-1", json!({ "fetch": 1, "legacy": 2 })));
+    let current = parsed(with_outputs("ex-2", json!({ "fetch": 1, "notify": 3 })));
+    let diff = current.compare_outputs(&baseline, &CompareOptions::new());
+    assert_eq!(diff.added_nodes, ["notify"]);
+    assert_eq!(diff.removed_nodes, ["legacy"]);
+    assert!(diff.changed_nodes.is_empty());
+    assert!(!diff.is_empty());
+}
+
+#[test]
+fn ignored_pointers_and_keys_are_left_out() {
+    let baseline = parsed(with_outputs(
+        "ex-1",
+        json!({
+            "fetch": [{ "json": { "id": 1, "createdAt": "2024-01-01" } }],
+            "notify": { "messageId": "m-1", "meta": { "timestamp": 1 } }
+        }),
+    ));
+    let current = parsed(with_outputs(
+        "ex-2",
+        json!({
+            "fetch": [{ "json": { "id": 1, "createdAt": "2024-02-01" } }],
+            "notify": { "messageId": "m-2", "meta": { "timestamp": 2 } }
+        }),
+    ));
+    assert_eq!(
+        current
+            .compare_outputs(&baseline, &CompareOptions::new())
+            .changed_nodes
+            .len(),
+        2
+    );
+
+    let options = CompareOptions::new()
+        .ignore_pointer("/0/json/createdAt")
+        .ignore_pointer_for("notify", "/messageId")
+        .ignore_key("timestamp");
+    assert!(current.compare_outputs(&baseline, &options).is_empty());
+
+    // A pointer ignored for one node still counts in the others
+    let options = CompareOptions::new().ignore_pointer_for("notify", "/0/json/createdAt");
+    let diff = current.compare_outputs(&baseline, &options);
+    assert!(diff.changed_nodes.contains_key("fetch"));
+}
+
+#[tokio::test]
+async fn executions_are_fetched_and_compared() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/executions/ex-1", |_| {
+                ok(with_outputs("ex-1", json!({ "fetch": { "count": 2 } })))
+            })
+            .handle("GET", "/api/executions/ex-2", |_| {
+                ok(with_outputs("ex-2", json!({ "fetch": { "count": 3 } })))
+            }),
+    );
+    let diff = client(&transport)
+        .compare_executions("ex-2", "ex-1", &CompareOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(
+        diff.changed_nodes["fetch"],
+        [ValueChange::Changed {
+            pointer: "/count".to_string(),
+            before: json!(2),
+            after: json!(3),
+        }]
+    );
+}
@@ -0,0 +1,151 @@
+//! Connectivity diagnostics against servers on local sockets
+
+use klikkflow_sdk::{ApiVersion, CheckKind, CheckStatus, Client, DiagnosticsReport};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Server answering `/health`, `/api/workflows` with `workflows_status`, and WebSocket upgrades
+async fn server(workflows_status: u16) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(answer(socket, workflows_status));
+        }
+    });
+    base_url
+}
+
+/// Answer one connection, upgrading it when the request asks for a WebSocket
+async fn answer(mut socket: TcpStream, workflows_status: u16) {
+    let mut head = vec![0; 4096];
+    let peeked = socket.peek(&mut head).await.unwrap();
+    let request = String::from_utf8_lossy(&head[..peeked]).to_ascii_lowercase();
+    if request.contains("upgrade: websocket") {
+        let mut stream = tokio_tungstenite::accept_async(socket).await.unwrap();
+        let _ = stream.close(None).await;
+        return;
+    }
+
+    let read = socket.read(&mut head).await.unwrap();
+    let request = String::from_utf8_lossy(&head[..read]);
+    let (status, body) = if request.starts_with("GET /health ") {
+        (200, r#"{"status":"ok"}"#)
+    } else if request.starts_with("GET /api/workflows?limit=1 ") {
+        match workflows_status {
+            200 => (200, r#"{"data":[]}"#),
+            _ => (workflows_status, r#"{"message":"invalid API key"}"#),
+        }
+    } else {
+        (404, r#"{"message":"not found"}"#)
+    };
+    let response = format!(
+        "HTTP/1.1 {} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+}
+
+/// Client with an API key, so the auth check runs
+fn client(base_url: &str) -> Client {
+    Client::builder()
+        .base_url(base_url)
+        .api_key("klikk-key")
+        .api_version(ApiVersion::V1)
+        .build()
+        .unwrap()
+}
+
+/// Status of each check, in the order they ran
+fn statuses(report: &DiagnosticsReport) -> Vec<(CheckKind, CheckStatus)> {
+    report
+        .checks
+        .iter()
+        .map(|check| (check.kind, check.status))
+        .collect()
+}
+
+#[tokio::test]
+async fn reachable_server_passes_every_check() {
+    let base_url = server(200).await;
+    let report = client(&base_url).diagnose().await;
+
+    assert!(report.passed(), "{}", report);
+    assert!(report.first_failure().is_none());
+    assert_eq!(
+        statuses(&report),
+        [
+            (CheckKind::Dns, CheckStatus::Pass),
+            (CheckKind::Tcp, CheckStatus::Pass),
+            (CheckKind::Tls, CheckStatus::Skipped),
+            (CheckKind::Http, CheckStatus::Pass),
+            (CheckKind::Auth, CheckStatus::Pass),
+            (CheckKind::WebSocket, CheckStatus::Pass),
+        ]
+    );
+    assert!(report.checks[0].detail.contains("is an IP address"));
+    assert_eq!(report.checks[2].detail, "plain HTTP base URL");
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["baseUrl"], base_url.as_str());
+    assert_eq!(json["checks"][5]["kind"], "websocket");
+    assert_eq!(json["checks"][5]["status"], "pass");
+    assert!(json["checks"][1]["latencyMs"].is_number());
+}
+
+#[tokio::test]
+async fn unreachable_server_fails_at_the_connection() {
+    // A port that was just released has nothing listening on it
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let report = client(&base_url).diagnose().await;
+
+    assert!(!report.passed());
+    let failure = report.first_failure().unwrap();
+    assert_eq!(failure.kind, CheckKind::Tcp);
+    assert!(failure
+        .hint
+        .as_deref()
+        .unwrap()
+        .contains("Nothing is listening"));
+    assert_eq!(
+        statuses(&report),
+        [
+            (CheckKind::Dns, CheckStatus::Pass),
+            (CheckKind::Tcp, CheckStatus::Fail),
+            (CheckKind::Tls, CheckStatus::Skipped),
+            (CheckKind::Http, CheckStatus::Skipped),
+            (CheckKind::Auth, CheckStatus::Skipped),
+            (CheckKind::WebSocket, CheckStatus::Skipped),
+        ]
+    );
+    for check in &report.checks[3..] {
+        assert_eq!(check.detail, "tcp check failed");
+    }
+}
+
+#[tokio::test]
+async fn rejected_api_key_fails_the_auth_check() {
+    let base_url = server(401).await;
+    let report = client(&base_url).diagnose().await;
+
+    let failure = report.first_failure().unwrap();
+    assert_eq!(failure.kind, CheckKind::Auth);
+    assert!(failure
+        .hint
+        .as_deref()
+        .unwrap()
+        .contains("The API key was rejected"));
+    assert_eq!(
+        statuses(&report)[3..],
+        [
+            (CheckKind::Http, CheckStatus::Pass),
+            (CheckKind::Auth, CheckStatus::Fail),
+            (CheckKind::WebSocket, CheckStatus::Skipped),
+        ]
+    );
+    assert_eq!(report.checks[5].detail, "auth check failed");
+}
@@ -0,0 +1,89 @@
+//! Clients configured from the environment
+//!
+//! The environment is shared by the whole test binary, so every test holds
+//! [`ENV`] while it sets variables.
+
+use klikkflow_sdk::{
+    Client, Error, API_KEY_ENV, BASE_URL_ENV, DEFAULT_BASE_URL, INSECURE_SKIP_TLS_VERIFY_ENV,
+    TIMEOUT_SECS_ENV,
+};
+use std::sync::{Mutex, MutexGuard};
+
+static ENV: Mutex<()> = Mutex::new(());
+
+/// Hold the environment with only `vars` of the client's variables set
+fn environment(vars: &[(&str, &str)]) -> MutexGuard<'static, ()> {
+    let guard = ENV.lock().unwrap_or_else(|e| e.into_inner());
+    for name in [
+        BASE_URL_ENV,
+        API_KEY_ENV,
+        TIMEOUT_SECS_ENV,
+        INSECURE_SKIP_TLS_VERIFY_ENV,
+    ] {
+        std::env::remove_var(name);
+    }
+    for (name, value) in vars {
+        std::env::set_var(name, value);
+    }
+    guard
+}
+
+#[test]
+fn base_url_defaults_when_unset() {
+    let _env = environment(&[]);
+    assert_eq!(Client::from_env().unwrap().base_url(), DEFAULT_BASE_URL);
+}
+
+#[test]
+fn base_url_and_timeout_are_read() {
+    let _env = environment(&[
+        (BASE_URL_ENV, "https://klikkflow.example.com/"),
+        (API_KEY_ENV, "key-1"),
+        (TIMEOUT_SECS_ENV, " 45 "),
+    ]);
+    let client = Client::from_env().unwrap();
+    assert_eq!(client.base_url(), "https://klikkflow.example.com");
+}
+
+#[test]
+fn empty_api_key_is_rejected() {
+    let _env = environment(&[(API_KEY_ENV, " ")]);
+    match Client::from_env() {
+        Err(Error::Config(message)) => assert!(message.contains(API_KEY_ENV), "{}", message),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn timeout_must_be_whole_seconds() {
+    for timeout in ["soon", "1.5", "-1"] {
+        let _env = environment(&[(TIMEOUT_SECS_ENV, timeout)]);
+        match Client::from_env() {
+            Err(Error::Config(message)) => {
+                assert!(message.contains(TIMEOUT_SECS_ENV), "{}", message)
+            }
+            other => panic!("{}: unexpected result: {:?}", timeout, other.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn tls_flag_accepts_booleans_only() {
+    for flag in ["1", "true", "TRUE", "0", "false", ""] {
+        let _env = environment(&[(INSECURE_SKIP_TLS_VERIFY_ENV, flag)]);
+        assert!(Client::from_env().is_ok(), "{}", flag);
+    }
+    for flag in ["maybe", "yes", "2"] {
+        let _env = environment(&[(INSECURE_SKIP_TLS_VERIFY_ENV, flag)]);
+        match Client::from_env() {
+            Err(Error::Config(message)) => {
+                assert!(
+                    message.contains(INSECURE_SKIP_TLS_VERIFY_ENV),
+                    "{}",
+                    message
+                )
+            }
+            other => panic!("{}: unexpected result: {:?}", flag, other.map(|_| ())),
+        }
+    }
+}
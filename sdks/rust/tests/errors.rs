@@ -0,0 +1,216 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use chrono::Utc;
+use common::client;
+use klikkflow_sdk::{Error, ErrorCode, MemoryTransport, Result};
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Resource and ID of a `NotFound` result
+fn not_found<T: Debug>(result: Result<T>) -> (String, Option<String>) {
+    match result {
+        Err(Error::NotFound { resource, id, .. }) => (resource, id),
+        other => panic!("expected NotFound, got {:?}", other),
+    }
+}
+
+fn named(resource: &str, id: &str) -> (String, Option<String>) {
+    (resource.to_string(), Some(id.to_string()))
+}
+
+fn endpoint(path: &str) -> (String, Option<String>) {
+    (path.to_string(), None)
+}
+
+#[tokio::test]
+async fn not_found_names_the_missing_resource() {
+    // Every request is unmatched and answered with a 404
+    let transport = Arc::new(MemoryTransport::new());
+    let client = client(&transport);
+
+    assert_eq!(
+        not_found(client.get_workflow("wf-1").await),
+        named("workflow", "wf-1")
+    );
+    assert_eq!(
+        not_found(client.get_workflow("team/nightly").await),
+        named("workflow", "team/nightly")
+    );
+    assert_eq!(
+        not_found(client.get_workflow_static_data("wf-1").await),
+        named("workflow", "wf-1")
+    );
+    assert_eq!(
+        not_found(client.get_execution_history("wf-1", None).await),
+        named("workflow", "wf-1")
+    );
+    assert_eq!(
+        not_found(client.cancel_execution("ex-1").await),
+        named("execution", "ex-1")
+    );
+    assert_eq!(
+        not_found(client.list_execution_artifacts("ex-1").await),
+        named("execution", "ex-1")
+    );
+    assert_eq!(
+        not_found(client.test_credential("cred-1").await),
+        named("credential", "cred-1")
+    );
+}
+
+#[tokio::test]
+async fn not_found_on_fixed_endpoints_names_the_endpoint() {
+    let transport = Arc::new(MemoryTransport::new());
+    let client = client(&transport);
+
+    assert_eq!(
+        not_found(client.get_execution_statistics(None).await),
+        endpoint("executions/statistics")
+    );
+    assert_eq!(
+        not_found(client.get_execution_statistics(Some("wf-1")).await),
+        endpoint("executions/statistics")
+    );
+    assert_eq!(
+        not_found(client.get_workflow_schema().await),
+        endpoint("workflows/schema")
+    );
+    assert_eq!(
+        not_found(client.list_node_types().await),
+        endpoint("node-types")
+    );
+    assert_eq!(not_found(client.health_check().await), endpoint("health"));
+}
+
+#[tokio::test]
+async fn rejected_workflow_lists_its_field_errors() {
+    use klikkflow_sdk::{CreateWorkflowRequest, FieldError};
+
+    let transport = Arc::new(MemoryTransport::new().handle("POST", "/api/workflows", |_| {
+        common::status(
+            422,
+            serde_json::json!({ "errors": [{ "field": "name", "message": "must not be empty" }] }),
+        )
+    }));
+    let request = CreateWorkflowRequest {
+        name: String::new(),
+        description: String::new(),
+        nodes: vec![],
+        connections: vec![],
+        settings: None,
+    };
+    match client(&transport).create_workflow(request).await {
+        Err(Error::Validation { field_errors, .. }) => assert_eq!(
+            field_errors,
+            [FieldError {
+                field: "name".to_string(),
+                message: "must not be empty".to_string(),
+            }]
+        ),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn activation_with_node_issues_fails_with_those_issues() {
+    use klikkflow_sdk::{NodeIssue, NodeIssueKind};
+
+    let transport =
+        Arc::new(
+            MemoryTransport::new().handle("POST", "/api/workflows/wf-1/activate", |_| {
+                common::status(
+                    400,
+                    serde_json::json!({ "issues": [
+                    { "nodeId": "n1", "kind": "credentials", "message": "missing credential" },
+                    { "nodeId": "n2", "kind": "somethingNew", "message": "unknown problem" }
+                ] }),
+                )
+            }),
+        );
+    match client(&transport).activate_workflow("wf-1").await {
+        Err(Error::ActivationFailed { issues }) => assert_eq!(
+            issues,
+            [
+                NodeIssue {
+                    node_id: "n1".to_string(),
+                    kind: NodeIssueKind::Credentials,
+                    message: "missing credential".to_string(),
+                },
+                NodeIssue {
+                    node_id: "n2".to_string(),
+                    kind: NodeIssueKind::Other,
+                    message: "unknown problem".to_string(),
+                },
+            ]
+        ),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn activation_400_without_issues_stays_an_api_error() {
+    for body in [
+        serde_json::json!({ "issues": [] }),
+        serde_json::json!({ "message": "bad request" }),
+    ] {
+        let transport = Arc::new(MemoryTransport::new().handle(
+            "POST",
+            "/api/workflows/wf-1/activate",
+            move |_| common::status(400, body.clone()),
+        ));
+        match client(&transport).activate_workflow("wf-1").await {
+            Err(Error::Api { status: 400, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn workflow_issues_are_listed() {
+    use klikkflow_sdk::NodeIssueKind;
+
+    let transport =
+        Arc::new(
+            MemoryTransport::new().handle("GET", "/api/workflows/wf-1/issues", |_| {
+                common::ok(serde_json::json!({ "issues": [
+                { "nodeId": "n3", "kind": "typeUnknown", "message": "no such node type" }
+            ] }))
+            }),
+        );
+    let issues = client(&transport)
+        .get_workflow_issues("wf-1")
+        .await
+        .unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].node_id, "n3");
+    assert_eq!(issues[0].kind, NodeIssueKind::TypeUnknown);
+}
+
+#[test]
+fn checksum_and_execution_timeouts_are_not_retryable() {
+    let checksum = Error::ChecksumMismatch {
+        expected: "abc".to_string(),
+        actual: "def".to_string(),
+    };
+    let waited = Error::WaitTimeout {
+        execution_id: "ex-1".to_string(),
+        waited: Duration::from_secs(30),
+    };
+    let stalled = Error::ExecutionStalled {
+        execution_id: "ex-1".to_string(),
+        last_progress_at: Utc::now(),
+    };
+    assert_eq!(checksum.code(), ErrorCode::Decode);
+    assert_eq!(waited.code(), ErrorCode::ExecutionTimeout);
+    assert_eq!(stalled.code(), ErrorCode::ExecutionTimeout);
+    assert_eq!(stalled.code().as_str(), "execution_timeout");
+    for error in [checksum, waited, stalled] {
+        assert!(!error.is_retryable(), "{}", error);
+    }
+
+    // Requests that timed out are still worth retrying
+    assert!(Error::Timeout("GET /health".to_string()).is_retryable());
+}
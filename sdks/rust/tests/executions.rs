@@ -0,0 +1,824 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::{
+    client, count, execution, json_body, ok, query_param, sequence, status, with_header, workflow,
+};
+use klikkflow_sdk::{
+    is_transient_failure, CancellationToken, Error, ErrorCode, ExecuteOptions, ExecutionAttempts,
+    ExecutionRetryPolicy, ExecutionStatus, FieldMap, MemoryTransport, RetryPolicy, RunOptions,
+    SingletonPolicy, StallAction, StallDecision, StallPolicy, WaitOptions,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Execution `ex-1` of `wf-1`, with `nodeResults` and metadata replaced
+fn progress(status: &str, node_results: Value, completed_nodes: u32) -> Value {
+    let mut body = execution("ex-1", "wf-1", status);
+    body["nodeResults"] = node_results;
+    body["metadata"]["totalNodes"] = json!(2);
+    body["metadata"]["completedNodes"] = json!(completed_nodes);
+    body
+}
+
+/// Transport whose execution `ex-1` stays running
+fn running_forever() -> Arc<MemoryTransport> {
+    Arc::new(
+        MemoryTransport::new()
+            .handle("POST", "/api/executions", |_| {
+                ok(execution("ex-1", "wf-export", "running"))
+            })
+            .handle("GET", "/api/executions/ex-1", |_| {
+                ok(execution("ex-1", "wf-export", "running"))
+            })
+            .handle("POST", "/api/executions/ex-1/cancel", |_| ok(json!({}))),
+    )
+}
+
+#[tokio::test]
+async fn idempotency_key_is_kept_across_retries() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "POST",
+        "/api/executions",
+        sequence(vec![
+            status(503, json!({})),
+            with_header(
+                ok(execution("ex-1", "wf-billing", "running")),
+                "idempotency-key",
+                "charge-42",
+            ),
+        ]),
+    ));
+    let client = client(&transport)
+        .with_retry(RetryPolicy::new().initial_backoff(Duration::from_millis(10)));
+    let options = ExecuteOptions::new().idempotency_key("charge-42");
+    let first = client
+        .execute_workflow_with_options("wf-billing", FieldMap::new(), options.clone())
+        .await
+        .unwrap();
+    let second = client
+        .execute_workflow_with_options("wf-billing", FieldMap::new(), options)
+        .await
+        .unwrap();
+    assert_eq!(first.id, second.id);
+    assert_eq!(first.idempotency_key.as_deref(), Some("charge-42"));
+
+    // The POST was retried after the 503, with the same key
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    for request in &requests {
+        assert_eq!(request.headers["idempotency-key"], "charge-42");
+    }
+}
+
+#[tokio::test]
+async fn cancelled_wait_cancels_the_execution() {
+    let transport = running_forever();
+    let token = CancellationToken::new();
+    tokio::spawn({
+        let token = token.clone();
+        async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            token.cancel();
+        }
+    });
+
+    let options = ExecuteOptions::new()
+        .wait_for_completion(true)
+        .cancellation(token)
+        .cancel_on_drop(true);
+    let result = client(&transport)
+        .execute_workflow_with_options("wf-export", FieldMap::new(), options)
+        .await;
+    assert!(matches!(
+        result,
+        Err(Error::Cancelled { execution_id: Some(id) }) if id == "ex-1"
+    ));
+    assert!(count(&transport, "GET", "/api/executions/ex-1") >= 1);
+    assert_eq!(count(&transport, "POST", "/api/executions/ex-1/cancel"), 1);
+}
+
+#[tokio::test]
+async fn budget_cuts_a_wait_short() {
+    let transport = running_forever();
+    // Polls are a second apart, the deadline cuts the wait short
+    let started = Instant::now();
+    let options = ExecuteOptions::new()
+        .wait_for_completion(true)
+        .budget(Duration::from_millis(300));
+    let result = client(&transport)
+        .execute_workflow_with_options("wf-export", FieldMap::new(), options)
+        .await;
+    assert!(started.elapsed() < Duration::from_secs(1));
+    match result {
+        Err(Error::DeadlineExceeded {
+            execution_id,
+            last_status,
+        }) => {
+            assert_eq!(execution_id.as_deref(), Some("ex-1"));
+            assert_eq!(last_status, Some(ExecutionStatus::Running));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[derive(Serialize)]
+struct Order {
+    sku: String,
+    quantity: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Quote {
+    total: f64,
+}
+
+#[tokio::test]
+async fn run_returns_the_output_of_the_last_node() {
+    let mut finished = progress(
+        "success",
+        json!({
+            "lookup": { "status": "success", "finishedAt": "2024-01-01T00:00:01Z", "output": { "price": 2.5 } },
+            "quote": { "status": "success", "finishedAt": "2024-01-01T00:00:02Z", "output": { "total": 7.5 } }
+        }),
+        2,
+    );
+    finished["finishedAt"] = json!("2024-01-01T00:00:02Z");
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("POST", "/api/executions", |_| {
+                ok(execution("ex-1", "wf-quote", "running"))
+            })
+            .handle("GET", "/api/executions/ex-1", move |_| ok(finished.clone())),
+    );
+    let order = Order {
+        sku: "A-1".to_string(),
+        quantity: 3,
+    };
+    let quote: Quote = client(&transport)
+        .run("wf-quote", &order, RunOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(quote.total, 7.5);
+
+    let submitted = json_body(&transport.requests()[0]);
+    assert_eq!(submitted["workflowId"], "wf-quote");
+    assert_eq!(
+        submitted["inputData"],
+        json!({ "sku": "A-1", "quantity": 3 })
+    );
+}
+
+#[tokio::test]
+async fn run_fails_with_the_failed_node() {
+    let mut failed = execution("ex-2", "wf-broken", "error");
+    failed["error"] = json!("lookup failed");
+    failed["nodeResults"] = json!({ "lookup": { "status": "error", "error": "lookup failed" } });
+    let transport = Arc::new(
+        MemoryTransport::new().handle("POST", "/api/executions", move |_| ok(failed.clone())),
+    );
+    let order = Order {
+        sku: "A-1".to_string(),
+        quantity: 3,
+    };
+    match client(&transport)
+        .run::<Quote>("wf-broken", &order, RunOptions::new())
+        .await
+    {
+        Err(Error::ExecutionFailed {
+            execution_id, node, ..
+        }) => {
+            assert_eq!(execution_id, "ex-2");
+            assert_eq!(node.as_deref(), Some("lookup"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn retry_chain_runs_from_the_original_to_the_last_retry() {
+    let retry = |id: &str, parent: Option<&str>| {
+        let mut body = execution(id, "wf-1", "error");
+        body["error"] = json!("upstream timed out");
+        match parent {
+            Some(parent) => {
+                body["mode"] = json!("retry");
+                body["triggeredBy"] = json!({ "kind": "retry", "parentExecutionId": parent });
+            }
+            None => {
+                body["mode"] = json!("api");
+                body["triggeredBy"] = json!({ "kind": "api" });
+            }
+        }
+        body
+    };
+    let (original, second, third) = (
+        retry("ex-1", None),
+        retry("ex-2", Some("ex-1")),
+        retry("ex-3", Some("ex-2")),
+    );
+    let history = json!({ "executions": [third, second.clone()], "hasMore": false });
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/executions/ex-1", move |_| ok(original.clone()))
+            .handle("GET", "/api/executions/ex-2", move |_| ok(second.clone()))
+            .handle("GET", "/api/workflows/wf-1/executions", move |request| {
+                assert_eq!(common::query_param(request, "mode"), Some("retry"));
+                ok(history.clone())
+            }),
+    );
+
+    let chain = client(&transport).get_retry_chain("ex-2").await.unwrap();
+    let ids: Vec<&str> = chain.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["ex-1", "ex-2", "ex-3"]);
+    assert_eq!(chain[2].parent_execution_id(), Some("ex-2"));
+}
+
+#[tokio::test]
+async fn stall_is_measured_from_the_last_change() {
+    // The first node finishes, then nothing changes any more
+    let waiting = progress("running", json!({ "fetch": { "status": "running" } }), 0);
+    let stuck = progress("running", json!({ "fetch": { "status": "success" } }), 1);
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/executions/ex-1",
+        sequence(vec![
+            ok(waiting.clone()),
+            ok(waiting.clone()),
+            ok(waiting),
+            ok(stuck),
+        ]),
+    ));
+    let options = WaitOptions::new()
+        .poll_interval(Duration::from_millis(20))
+        .stall_detection(StallPolicy {
+            no_progress_after: Duration::from_millis(150),
+            action: StallAction::Fail,
+        });
+    let started = Instant::now();
+    let result = client(&transport).wait_future("ex-1", options).await;
+    assert!(
+        matches!(result, Err(Error::ExecutionStalled { .. })),
+        "{:?}",
+        result
+    );
+    assert!(started.elapsed() >= Duration::from_millis(190));
+}
+
+#[tokio::test]
+async fn stall_hook_decides_whether_to_keep_waiting() {
+    let transport = running_forever();
+    // Give the execution one more period, then give up on it
+    let asked = Arc::new(AtomicU32::new(0));
+    let action = StallAction::hook({
+        let asked = asked.clone();
+        move |_execution, _last_progress_at| match asked.fetch_add(1, Ordering::SeqCst) {
+            0 => StallDecision::KeepWaiting,
+            _ => StallDecision::Cancel,
+        }
+    });
+    let options = WaitOptions::new()
+        .poll_interval(Duration::from_millis(10))
+        .stall_detection(StallPolicy {
+            no_progress_after: Duration::from_millis(50),
+            action,
+        });
+
+    let result = client(&transport)
+        .execution("ex-1")
+        .wait_with(options)
+        .await;
+    assert!(
+        matches!(result, Err(Error::ExecutionStalled { execution_id, .. }) if execution_id == "ex-1")
+    );
+    assert_eq!(asked.load(Ordering::SeqCst), 2);
+    assert_eq!(count(&transport, "POST", "/api/executions/ex-1/cancel"), 1);
+}
+
+#[tokio::test]
+async fn wait_future_reports_its_progress() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/executions/ex-1", |_| {
+            ok(execution("ex-1", "wf-1", "success"))
+        }),
+    );
+    let mut wait = client(&transport).wait_future("ex-1", WaitOptions::default());
+    let execution = (&mut wait).await.unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Success);
+    assert_eq!(wait.polls(), 1);
+    assert_eq!(wait.last_seen().map(|e| e.id.as_str()), Some("ex-1"));
+}
+
+#[tokio::test]
+async fn concurrent_waits_share_their_polls() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/executions/ex-1",
+        sequence(vec![
+            ok(execution("ex-1", "wf-1", "running")),
+            ok(execution("ex-1", "wf-1", "success")),
+        ]),
+    ));
+    let client = client(&transport);
+    let handle = client.execution("ex-1");
+    let (first, second) = tokio::join!(handle.wait(), handle.wait());
+    assert_eq!(first.unwrap().status, ExecutionStatus::Success);
+    assert_eq!(second.unwrap().status, ExecutionStatus::Success);
+
+    // Both waits were answered by the same two polls
+    assert_eq!(count(&transport, "GET", "/api/executions/ex-1"), 2);
+    let stats = client.wait_stats();
+    assert_eq!((stats.waits, stats.coalesced, stats.pollers), (2, 1, 0));
+}
+
+#[tokio::test]
+async fn wait_times_out() {
+    let transport = running_forever();
+    let options = WaitOptions::new()
+        .poll_interval(Duration::from_millis(10))
+        .timeout(Duration::from_millis(100));
+    let error = client(&transport)
+        .wait_future("ex-1", options)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), ErrorCode::ExecutionTimeout);
+    assert!(!error.is_retryable());
+    match error {
+        Error::WaitTimeout {
+            execution_id,
+            waited,
+        } => {
+            assert_eq!(execution_id, "ex-1");
+            assert_eq!(waited, Duration::from_millis(100));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[tokio::test]
+async fn shutdown_aborts_waits_and_closes_the_client() {
+    let transport = running_forever();
+    let client = client(&transport);
+    let waiting = tokio::spawn({
+        let client = client.clone();
+        async move { client.execution("ex-1").wait().await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let aborted = client.shutdown(Duration::from_millis(200)).await;
+    assert_eq!(aborted, 1);
+    assert!(client.is_closed());
+    assert!(matches!(waiting.await.unwrap(), Err(Error::ClientClosed)));
+    assert!(matches!(
+        client.get_execution("ex-1").await,
+        Err(Error::ClientClosed)
+    ));
+}
+
+#[tokio::test]
+async fn downloaded_artifact_is_checked_against_its_checksum() {
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+    let artifact = |checksum: &str| {
+        let response = klikkflow_sdk::TransportResponse::new(reqwest::StatusCode::OK, "hello");
+        with_header(
+            with_header(response, "content-type", "text/plain"),
+            "x-checksum-sha256",
+            checksum,
+        )
+    };
+    let (intact, corrupt) = (artifact(HELLO_SHA256), artifact(&"0".repeat(64)));
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle(
+                "GET",
+                "/api/executions/ex-1/artifacts/report/content",
+                move |_| intact.clone(),
+            )
+            .handle(
+                "GET",
+                "/api/executions/ex-1/artifacts/corrupt/content",
+                move |_| corrupt.clone(),
+            ),
+    );
+    let client = client(&transport);
+
+    let mut contents = Vec::new();
+    let written = client
+        .download_artifact("ex-1", "report", &mut contents)
+        .await
+        .unwrap();
+    assert_eq!(written, 5);
+    assert_eq!(contents, b"hello");
+    assert_eq!(
+        transport.requests()[0].headers["accept-encoding"],
+        "identity"
+    );
+
+    let mut contents = Vec::new();
+    match client
+        .download_artifact("ex-1", "corrupt", &mut contents)
+        .await
+    {
+        Err(Error::ChecksumMismatch { actual, .. }) => assert_eq!(actual, HELLO_SHA256),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn checksums_match_the_nist_test_vectors() {
+    // Lengths around the padding boundaries: 55 bytes leave room for the
+    // length in one block, 56 and 64 need a second one
+    let vectors = [
+        ("".to_string(), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+        ("abc".to_string(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+        (
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".to_string(),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        ),
+        (
+            "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu".to_string(),
+            "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+        ),
+        ("a".repeat(55), "9f4390f8d30c2dd92ec9f095b65e2b9ae9b0a925a5258e241c9f1e910f734318"),
+        ("a".repeat(56), "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a"),
+        ("a".repeat(63), "7d3e74a05d7db15bce4ad9ec0658ea98e3f06eeecf16b4c6fff2da457ddc2f34"),
+        ("a".repeat(64), "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb"),
+        ("a".repeat(65), "635361c48bb9eab14198e76ea8ab7f1a41685d6ad62aa9146d301d4f17eb0ae0"),
+    ];
+    for (content, digest) in vectors {
+        let response = with_header(
+            klikkflow_sdk::TransportResponse::new(reqwest::StatusCode::OK, content.clone()),
+            "x-checksum-sha256",
+            digest,
+        );
+        let transport = Arc::new(MemoryTransport::new().handle(
+            "GET",
+            "/api/executions/ex-1/artifacts/report/content",
+            move |_| response.clone(),
+        ));
+        let mut contents = Vec::new();
+        let written = client(&transport)
+            .download_artifact("ex-1", "report", &mut contents)
+            .await
+            .unwrap_or_else(|e| panic!("{} bytes: {}", content.len(), e));
+        assert_eq!(written, content.len() as u64);
+    }
+}
+
+/// `wf-1` history answering running-status queries with successive `rounds`
+fn running_rounds(rounds: Vec<Vec<Value>>) -> MemoryTransport {
+    let calls = AtomicU32::new(0);
+    MemoryTransport::new().handle("GET", "/api/workflows/wf-1/executions", move |request| {
+        if query_param(request, "status") != Some("running") {
+            return ok(json!({ "executions": [] }));
+        }
+        let round = calls.fetch_add(1, Ordering::SeqCst) as usize;
+        ok(json!({ "executions": rounds[round.min(rounds.len() - 1)] }))
+    })
+}
+
+/// Execution `id` of `wf-1` started `second`s into the hour
+fn started(id: &str, status: &str, second: u32) -> Value {
+    let mut body = execution(id, "wf-1", status);
+    body["startedAt"] = json!(format!("2024-01-01T00:00:{:02}Z", second));
+    body
+}
+
+/// Position of the first `method` request for `path`
+fn position(transport: &MemoryTransport, method: &str, path: &str) -> usize {
+    transport
+        .requests()
+        .iter()
+        .position(|request| request.method == method && request.path() == path)
+        .unwrap()
+}
+
+fn singleton(policy: SingletonPolicy) -> ExecuteOptions {
+    ExecuteOptions::new().singleton(policy)
+}
+
+#[tokio::test]
+async fn singleton_reject_refuses_to_start_while_running() {
+    let transport = Arc::new(running_rounds(vec![vec![started("ex-0", "running", 0)]]));
+    let result = client(&transport)
+        .execute_workflow_with_options("wf-1", FieldMap::new(), singleton(SingletonPolicy::Reject))
+        .await;
+    match result {
+        Err(Error::AlreadyRunning { execution_id }) => assert_eq!(execution_id, "ex-0"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(count(&transport, "POST", "/api/executions"), 0);
+}
+
+#[tokio::test]
+async fn singleton_queue_starts_after_the_active_execution() {
+    let transport = Arc::new(
+        running_rounds(vec![vec![started("ex-0", "running", 0)]])
+            .handle("GET", "/api/executions/ex-0", |_| {
+                ok(started("ex-0", "success", 0))
+            })
+            .handle("POST", "/api/executions", |_| {
+                ok(started("ex-1", "running", 5))
+            }),
+    );
+    let execution = client(&transport)
+        .execute_workflow_with_options("wf-1", FieldMap::new(), singleton(SingletonPolicy::Queue))
+        .await
+        .unwrap();
+    assert_eq!(execution.id, "ex-1");
+    assert!(
+        position(&transport, "GET", "/api/executions/ex-0")
+            < position(&transport, "POST", "/api/executions")
+    );
+}
+
+#[tokio::test]
+async fn singleton_cancel_previous_cancels_before_starting() {
+    let transport = Arc::new(
+        running_rounds(vec![vec![started("ex-0", "running", 0)]])
+            .handle("POST", "/api/executions/ex-0/cancel", |_| ok(json!({})))
+            .handle("GET", "/api/executions/ex-0", |_| {
+                ok(started("ex-0", "cancelled", 0))
+            })
+            .handle("POST", "/api/executions", |_| {
+                ok(started("ex-1", "running", 5))
+            }),
+    );
+    client(&transport)
+        .execute_workflow_with_options(
+            "wf-1",
+            FieldMap::new(),
+            singleton(SingletonPolicy::CancelPrevious),
+        )
+        .await
+        .unwrap();
+    assert!(
+        position(&transport, "POST", "/api/executions/ex-0/cancel")
+            < position(&transport, "POST", "/api/executions")
+    );
+}
+
+#[tokio::test]
+async fn singleton_race_is_rechecked_when_the_server_reports_conflicts() {
+    let racing = || {
+        running_rounds(vec![
+            vec![],
+            vec![started("ex-0", "running", 0), started("ex-1", "running", 5)],
+        ])
+        .handle("POST", "/api/executions", |_| {
+            ok(started("ex-1", "running", 5))
+        })
+        .handle("POST", "/api/executions/ex-1/cancel", |_| ok(json!({})))
+    };
+
+    // The later of the two racing executions is cancelled
+    let transport = Arc::new(racing().handle("GET", "/api/capabilities", |_| {
+        ok(json!({ "capabilities": ["execution-conflicts"] }))
+    }));
+    let result = client(&transport)
+        .execute_workflow_with_options("wf-1", FieldMap::new(), singleton(SingletonPolicy::Reject))
+        .await;
+    match result {
+        Err(Error::AlreadyRunning { execution_id }) => assert_eq!(execution_id, "ex-0"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(count(&transport, "POST", "/api/executions/ex-1/cancel"), 1);
+
+    // Without the capability, the history is only checked before submitting
+    let transport = Arc::new(racing().handle("GET", "/api/capabilities", |_| {
+        ok(json!({ "capabilities": [] }))
+    }));
+    let execution = client(&transport)
+        .execute_workflow_with_options("wf-1", FieldMap::new(), singleton(SingletonPolicy::Reject))
+        .await
+        .unwrap();
+    assert_eq!(execution.id, "ex-1");
+    assert_eq!(
+        count(&transport, "GET", "/api/workflows/wf-1/executions"),
+        2
+    );
+}
+
+#[tokio::test]
+async fn conflict_naming_an_execution_is_already_running() {
+    let rejecting = |body: Value| {
+        Arc::new(
+            MemoryTransport::new().handle("POST", "/api/executions", move |_| {
+                status(409, body.clone())
+            }),
+        )
+    };
+
+    let transport = rejecting(json!({ "executionId": "ex-0" }));
+    match client(&transport)
+        .execute_workflow("wf-1", FieldMap::new(), false)
+        .await
+    {
+        Err(Error::AlreadyRunning { execution_id }) => assert_eq!(execution_id, "ex-0"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // E.g. an idempotency key reused with another input
+    let transport = rejecting(json!({ "message": "idempotency key reused" }));
+    match client(&transport)
+        .execute_workflow("wf-1", FieldMap::new(), false)
+        .await
+    {
+        Err(Error::Conflict { message, .. }) => assert!(message.contains("idempotency key reused")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+/// Execution `id` of `wf-1` that failed with `error`, or succeeded without one
+fn finished(id: &str, error: Option<&str>) -> Value {
+    let mut body = execution(
+        id,
+        "wf-1",
+        if error.is_some() { "error" } else { "success" },
+    );
+    body["error"] = json!(error);
+    body
+}
+
+/// Transport starting executions `ex-1`, `ex-2`, ... that end as `outcomes` in turn
+fn attempts(outcomes: Vec<Option<&'static str>>) -> Arc<MemoryTransport> {
+    let started = AtomicU32::new(0);
+    let mut transport = MemoryTransport::new().handle("POST", "/api/executions", move |_| {
+        let id = format!("ex-{}", started.fetch_add(1, Ordering::SeqCst) + 1);
+        ok(execution(&id, "wf-1", "running"))
+    });
+    for (index, error) in outcomes.into_iter().enumerate() {
+        let id = format!("ex-{}", index + 1);
+        let path = format!("/api/executions/{}", id);
+        transport = transport.handle("GET", &path, move |_| ok(finished(&id, error)));
+    }
+    Arc::new(transport)
+}
+
+#[tokio::test]
+async fn execution_workflow_snapshot_is_requested_and_decoded() {
+    let transport =
+        Arc::new(
+            MemoryTransport::new().handle("GET", "/api/executions/ex-1", |request| {
+                assert_eq!(query_param(request, "includeWorkflow"), Some("true"));
+                let mut body = execution("ex-1", "wf-1", "success");
+                body["workflowSnapshot"] = workflow("wf-1", "Billing at the time");
+                ok(body)
+            }),
+        );
+    let snapshot = client(&transport)
+        .get_execution_workflow("ex-1")
+        .await
+        .unwrap()
+        .expect("a workflow snapshot");
+    assert_eq!(snapshot.id, "wf-1");
+    assert_eq!(snapshot.name, "Billing at the time");
+    assert_eq!(count(&transport, "GET", "/api/executions/ex-1"), 1);
+}
+
+#[tokio::test]
+async fn execution_without_a_snapshot_has_no_workflow() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/executions/ex-1", |_| {
+            ok(execution("ex-1", "wf-1", "success"))
+        }),
+    );
+    let client = client(&transport);
+    assert!(client
+        .get_execution_workflow("ex-1")
+        .await
+        .unwrap()
+        .is_none());
+    let execution = client.get_execution_with_workflow("ex-1").await.unwrap();
+    assert!(execution.workflow_snapshot.is_none());
+}
+
+/// Failed execution whose node `fetch` failed with `error`
+fn failed_with(error: &str) -> klikkflow_sdk::ExecutionResult {
+    let mut body = execution("ex-1", "wf-1", "error");
+    body["nodeResults"] = json!({ "fetch": { "status": "error", "error": error } });
+    serde_json::from_value(body).unwrap()
+}
+
+#[test]
+fn transient_failures_are_recognized_by_status_and_phrase() {
+    for error in [
+        "upstream returned 503 Service Unavailable",
+        "HTTP 429",
+        "status code: 502",
+        "Gateway Timeout",
+        "request timed out after 30s",
+        "read ECONNRESET",
+    ] {
+        assert!(is_transient_failure(&failed_with(error)), "{}", error);
+    }
+}
+
+#[test]
+fn numbers_and_timeout_settings_are_not_transient_failures() {
+    for error in [
+        "order 15030 not found",
+        "invoice 4290 is already paid",
+        "customer id 25040 is invalid",
+        "invalid timeout parameter",
+        "timeout must be a positive number",
+    ] {
+        assert!(!is_transient_failure(&failed_with(error)), "{}", error);
+    }
+}
+
+#[tokio::test]
+async fn failed_executions_are_retried_as_linked_attempts() {
+    let transport = attempts(vec![
+        Some("upstream returned 503 Service Unavailable"),
+        Some("request timed out"),
+        None,
+    ]);
+    let options = ExecuteOptions::new()
+        .idempotency_key("nightly")
+        .retry_of("ex-0")
+        .retry_on_failure(ExecutionRetryPolicy::new(3, Duration::from_millis(10)));
+    let execution = client(&transport)
+        .execute_workflow_with_options("wf-1", FieldMap::new(), options)
+        .await
+        .unwrap();
+    assert_eq!(execution.id, "ex-3");
+    assert_eq!(execution.status, ExecutionStatus::Success);
+    assert_eq!(
+        execution.attempts,
+        Some(ExecutionAttempts {
+            count: 3,
+            failed_execution_ids: vec!["ex-1".to_string(), "ex-2".to_string()],
+        })
+    );
+
+    // Each attempt retries the one before it, under a key of its own
+    let submitted: Vec<_> = transport
+        .requests()
+        .into_iter()
+        .filter(|request| request.method == "POST")
+        .collect();
+    assert_eq!(submitted.len(), 3);
+    for (request, (key, retry_of)) in submitted.iter().zip([
+        ("nightly-1", "ex-0"),
+        ("nightly-2", "ex-1"),
+        ("nightly-3", "ex-2"),
+    ]) {
+        assert_eq!(request.headers["idempotency-key"], key);
+        assert_eq!(json_body(request)["retryOf"], retry_of);
+    }
+}
+
+#[tokio::test]
+async fn only_transient_failures_are_retried_up_to_the_attempt_limit() {
+    let policy = ExecutionRetryPolicy::new(2, Duration::from_millis(10));
+
+    let transport = attempts(vec![Some("invalid input: missing sku")]);
+    let options = ExecuteOptions::new().retry_on_failure(policy.clone());
+    let execution = client(&transport)
+        .execute_workflow_with_options("wf-1", FieldMap::new(), options)
+        .await
+        .unwrap();
+    assert_eq!(execution.id, "ex-1");
+    assert_eq!(execution.attempts.unwrap().count, 1);
+    assert_eq!(count(&transport, "POST", "/api/executions"), 1);
+
+    let transport = attempts(vec![Some("429 Too Many Requests"); 3]);
+    let options = ExecuteOptions::new().retry_on_failure(policy);
+    let execution = client(&transport)
+        .execute_workflow_with_options("wf-1", FieldMap::new(), options)
+        .await
+        .unwrap();
+    assert_eq!(execution.id, "ex-2");
+    assert_eq!(execution.status, ExecutionStatus::Error);
+    assert_eq!(execution.attempts.unwrap().count, 2);
+    assert_eq!(count(&transport, "POST", "/api/executions"), 2);
+}
+
+#[tokio::test]
+async fn no_retry_is_started_past_the_deadline() {
+    let transport = attempts(vec![Some("502 Bad Gateway"), None]);
+    let options = ExecuteOptions::new()
+        .budget(Duration::from_millis(500))
+        .retry_on_failure(ExecutionRetryPolicy::new(3, Duration::from_secs(1)));
+    let started = Instant::now();
+    let result = client(&transport)
+        .execute_workflow_with_options("wf-1", FieldMap::new(), options)
+        .await;
+    assert!(started.elapsed() < Duration::from_millis(500));
+    match result {
+        Err(Error::DeadlineExceeded {
+            execution_id,
+            last_status,
+        }) => {
+            assert_eq!(execution_id.as_deref(), Some("ex-1"));
+            assert_eq!(last_status, Some(ExecutionStatus::Error));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(count(&transport, "POST", "/api/executions"), 1);
+}
@@ -0,0 +1,155 @@
+use klikkflow_sdk::{ApiVersion, Client, Error, JsonLimit, JsonLimits, MAX_JSON_DEPTH};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const LIMIT: usize = 1024;
+
+/// Serve every connection with `head`, then keep writing `chunk` until the client hangs up
+async fn serve(head: &'static str, chunk: Option<String>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let chunk = chunk.clone();
+            tokio::spawn(async move {
+                let mut request = [0; 4096];
+                let _ = socket.read(&mut request).await;
+                if socket.write_all(head.as_bytes()).await.is_err() {
+                    return;
+                }
+                match chunk {
+                    Some(chunk) => while socket.write_all(chunk.as_bytes()).await.is_ok() {},
+                    // Hold the connection open without sending the announced body
+                    None => tokio::time::sleep(Duration::from_secs(60)).await,
+                }
+            });
+        }
+    });
+    format!("http://{}", addr)
+}
+
+/// Serve every connection with `body` as a complete JSON response
+async fn serve_json(body: String) -> String {
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    serve(Box::leak(head.into_boxed_str()), None).await
+}
+
+fn client(base_url: String) -> Client {
+    client_with(
+        base_url,
+        JsonLimits {
+            max_total_bytes: LIMIT,
+            ..Default::default()
+        },
+    )
+}
+
+/// Client with `limits`, pinned to API v1 since the server answers every request alike
+fn client_with(base_url: String, limits: JsonLimits) -> Client {
+    Client::builder()
+        .base_url(base_url)
+        .api_version(ApiVersion::V1)
+        .timeout(Duration::from_secs(10))
+        .json_limits(limits)
+        .build()
+        .unwrap()
+}
+
+/// The limit a result exceeded
+fn exceeded<T: std::fmt::Debug>(result: klikkflow_sdk::Result<T>) -> (JsonLimit, usize) {
+    match result {
+        Err(Error::JsonLimitExceeded { which, limit }) => (which, limit),
+        other => panic!("expected a JSON limit to be exceeded, got {:?}", other),
+    }
+}
+
+fn assert_too_large<T: std::fmt::Debug>(result: klikkflow_sdk::Result<T>) {
+    match result {
+        Err(Error::JsonLimitExceeded {
+            which: JsonLimit::TotalBytes,
+            limit,
+        }) => assert_eq!(limit, LIMIT),
+        other => panic!(
+            "expected the total bytes limit to be exceeded, got {:?}",
+            other
+        ),
+    }
+}
+
+#[tokio::test]
+async fn content_length_over_the_limit_fails_before_reading() {
+    let base_url = serve(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 10737418240\r\n\r\n",
+        None,
+    )
+    .await;
+    let started = std::time::Instant::now();
+    assert_too_large(client(base_url).get_workflow("wf-1").await);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn chunked_body_is_read_no_further_than_the_limit() {
+    // An endless body: only a capped read returns
+    let base_url = serve(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n",
+        Some(format!("100\r\n{}\r\n", "[".repeat(0x100))),
+    )
+    .await;
+    assert_too_large(client(base_url).get_workflow("wf-1").await);
+}
+
+#[tokio::test]
+async fn body_within_the_limit_is_read() {
+    let base_url = serve(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+        None,
+    )
+    .await;
+    client(base_url).health_check().await.unwrap();
+}
+
+#[tokio::test]
+async fn deeply_nested_body_fails_on_depth() {
+    let body = format!("{}{}", "[".repeat(50_000), "]".repeat(50_000));
+    let base_url = serve_json(body).await;
+    let client = client_with(base_url, JsonLimits::default());
+    assert_eq!(
+        exceeded(client.get_workflow("wf-1").await),
+        (JsonLimit::Depth, MAX_JSON_DEPTH)
+    );
+}
+
+#[tokio::test]
+async fn depth_limit_is_capped_at_what_the_parser_accepts() {
+    let body = format!("{}{}", "[".repeat(200), "]".repeat(200));
+    let base_url = serve_json(body).await;
+    let limits = JsonLimits {
+        max_depth: 1000,
+        ..Default::default()
+    };
+    assert_eq!(
+        exceeded(client_with(base_url, limits).get_workflow("wf-1").await),
+        (JsonLimit::Depth, MAX_JSON_DEPTH)
+    );
+}
+
+#[tokio::test]
+async fn long_string_fails_on_string_length() {
+    let body = format!(r#"{{"name":"{}"}}"#, "x".repeat(32));
+    let base_url = serve_json(body).await;
+    let limits = JsonLimits {
+        max_string_len: 16,
+        ..Default::default()
+    };
+    assert_eq!(
+        exceeded(client_with(base_url, limits).get_workflow("wf-1").await),
+        (JsonLimit::StringLength, 16)
+    );
+}
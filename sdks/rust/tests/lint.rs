@@ -0,0 +1,113 @@
+mod common;
+
+use common::{connection, definition, node};
+use klikkflow_sdk::lint::{
+    CredentialById, DefaultNodeName, DisconnectedNode, Linter, LongLinearChain,
+    OverlappingPositions, Rule, Severity,
+};
+use serde_json::{json, Value};
+
+fn flagged(rule: &dyn Rule, nodes: Vec<Value>, connections: Vec<Value>) -> Vec<String> {
+    rule.check(&definition(nodes, connections))
+        .into_iter()
+        .filter_map(|violation| violation.node_id)
+        .collect()
+}
+
+fn at(mut node: Value, x: f64, y: f64) -> Value {
+    node["position"] = json!({ "x": x, "y": y });
+    node
+}
+
+fn with_parameters(mut node: Value, parameters: Value) -> Value {
+    node["parameters"] = parameters;
+    node
+}
+
+#[test]
+fn configured_severity_applies_to_findings() {
+    let workflow = definition(vec![node("a", "HTTP Request 7", "http-request")], vec![]);
+    let linter = Linter::builder()
+        .severity("default-node-name", Severity::Error)
+        .build();
+    let findings = linter.lint(&workflow);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].rule, "default-node-name");
+    assert_eq!(findings[0].severity, Severity::Error);
+}
+
+#[test]
+fn default_node_names_are_flagged() {
+    let nodes = vec![
+        node("a", "HTTP Request 7", "http-request"),
+        node("b", "Webhook", "webhook"),
+        node("c", "Fetch invoices", "http-request"),
+    ];
+    assert_eq!(flagged(&DefaultNodeName, nodes, vec![]), ["a", "b"]);
+}
+
+#[test]
+fn disconnected_nodes_are_flagged() {
+    let nodes = vec![
+        node("a", "Start", "trigger"),
+        node("b", "Send", "email"),
+        node("c", "Orphan", "set"),
+    ];
+    let connections = vec![connection("a", "b")];
+    assert_eq!(flagged(&DisconnectedNode, nodes, connections), ["c"]);
+}
+
+#[test]
+fn credentials_referenced_by_id_are_flagged() {
+    let nodes = vec![
+        with_parameters(
+            node("a", "By id", "http-request"),
+            json!({ "credential": "3f2b8c1e-0d4a-4c55-9b1e-2a7c6f0e9d11" }),
+        ),
+        with_parameters(
+            node("b", "By id field", "http-request"),
+            json!({ "credentialId": "42" }),
+        ),
+        with_parameters(
+            node("c", "By name", "http-request"),
+            json!({ "credential": "Stripe production" }),
+        ),
+    ];
+    assert_eq!(flagged(&CredentialById, nodes, vec![]), ["a", "b"]);
+}
+
+#[test]
+fn overlapping_nodes_are_flagged() {
+    let nodes = vec![
+        at(node("a", "A", "set"), 100.0, 100.0),
+        at(node("b", "B", "set"), 110.0, 105.0),
+        at(node("c", "C", "set"), 400.0, 100.0),
+    ];
+    let rule = OverlappingPositions::default();
+    assert_eq!(flagged(&rule, nodes.clone(), vec![]), ["b"]);
+
+    let rule = OverlappingPositions { min_distance: 5.0 };
+    assert!(flagged(&rule, nodes, vec![]).is_empty());
+}
+
+#[test]
+fn chains_longer_than_the_limit_are_flagged() {
+    let workflow = definition(
+        (0..6)
+            .map(|i| {
+                at(
+                    node(&format!("n{}", i), &format!("Step {}", i), "set"),
+                    i as f64 * 200.0,
+                    0.0,
+                )
+            })
+            .collect(),
+        (0..5)
+            .map(|i| connection(&format!("n{}", i), &format!("n{}", i + 1)))
+            .collect(),
+    );
+    assert_eq!(LongLinearChain { max_length: 5 }.check(&workflow).len(), 1);
+    assert!(LongLinearChain { max_length: 6 }
+        .check(&workflow)
+        .is_empty());
+}
@@ -0,0 +1,49 @@
+#![cfg(all(feature = "test-util", feature = "loadtest"))]
+
+mod common;
+
+use common::{client, execution, ok};
+use klikkflow_sdk::loadtest::{LoadTest, RampProfile};
+use klikkflow_sdk::{MemoryTransport, WaitOptions};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn ramped_executions_are_waited_on() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("POST", "/api/executions", |_| {
+                ok(execution("ex-1", "wf-1", "running"))
+            })
+            .handle("GET", "/api/executions/ex-1", |_| {
+                ok(execution("ex-1", "wf-1", "success"))
+            }),
+    );
+    let report = LoadTest::new(client(&transport), "wf-1", |n| {
+        [("order".to_string(), serde_json::json!(n))]
+            .into_iter()
+            .collect()
+    })
+    .ramp(RampProfile::Linear {
+        ramp_up: Duration::from_millis(100),
+    })
+    .target_rps(50.0)
+    .duration(Duration::from_millis(300))
+    .max_concurrency(4)
+    .wait_options(WaitOptions::new().poll_interval(Duration::from_millis(10)))
+    .run()
+    .await;
+
+    assert!(report.launched > 0);
+    assert_eq!(report.succeeded, report.launched);
+    assert!(report.errors.is_empty());
+    let completion = report.completion_latency.unwrap();
+    assert!(completion.p50 >= report.submit_latency.p50);
+
+    let submitted = transport
+        .requests()
+        .iter()
+        .filter(|request| request.method == "POST")
+        .count();
+    assert_eq!(submitted as u64, report.launched);
+}
@@ -0,0 +1,240 @@
+mod common;
+
+use common::{connection, definition, node};
+use klikkflow_sdk::nodes::HttpRequestNode;
+use klikkflow_sdk::{
+    CreateRequestOptions, CreateWorkflowRequest, ExecutionResult, FieldMap, FieldUpdate,
+    IdStrategy, Position, SnapshotOptions, UpdateWorkflowRequest, WorkflowBuilder,
+    WorkflowDefinition,
+};
+use serde_json::json;
+
+fn execution_with_node_results(node_results: serde_json::Value) -> ExecutionResult {
+    let mut body = common::execution("ex-1", "wf-1", "success");
+    body["nodeResults"] = node_results;
+    serde_json::from_value(body).expect("execution deserializes")
+}
+
+#[test]
+fn node_results_keep_entries_of_any_shape() {
+    let execution = execution_with_node_results(json!({
+        "typed": { "status": "error", "error": "boom" },
+        "items": [{ "json": { "id": 1 } }],
+        "text": "done",
+        "wrong_types": { "status": 3, "finishedAt": "yesterday" }
+    }));
+    let results = &execution.node_results;
+    assert!(results["typed"].is_failed());
+    assert!(results["typed"].raw.is_none());
+    assert_eq!(results["items"].raw, Some(json!([{ "json": { "id": 1 } }])));
+    assert_eq!(results["text"].raw, Some(json!("done")));
+    assert_eq!(results["wrong_types"].raw.as_ref().unwrap()["status"], 3);
+}
+
+#[test]
+fn null_or_missing_node_results_are_empty() {
+    assert!(execution_with_node_results(json!(null))
+        .node_results
+        .is_empty());
+
+    let mut body = common::execution("ex-1", "wf-1", "success");
+    body.as_object_mut().unwrap().remove("nodeResults");
+    let execution: ExecutionResult = serde_json::from_value(body).unwrap();
+    assert!(execution.node_results.is_empty());
+}
+
+fn pipeline() -> WorkflowDefinition {
+    definition(
+        vec![
+            node("d", "Archive", "set"),
+            node("a", "Start", "trigger"),
+            node("b", "Fetch", "http-request"),
+            node("c", "Notify", "slack"),
+        ],
+        vec![
+            connection("a", "b"),
+            connection("b", "c"),
+            connection("c", "d"),
+        ],
+    )
+}
+
+fn keys<'a>(results: &[(&'a str, &klikkflow_sdk::NodeResult)]) -> Vec<&'a str> {
+    results.iter().map(|(key, _)| *key).collect()
+}
+
+#[test]
+fn node_results_are_ordered_by_start_then_graph_position() {
+    let execution = execution_with_node_results(json!({
+        "zz-extra": { "status": "success" },
+        "c": { "status": "success", "startedAt": "2024-01-01T00:00:05Z" },
+        "aa-extra": { "status": "success" },
+        "d": { "status": "success" },
+        "b": { "status": "success", "startedAt": "2024-01-01T00:00:05Z" },
+        "Start": { "status": "success", "startedAt": "2024-01-01T00:00:01Z" }
+    }));
+    let ordered = execution.node_results_ordered(&pipeline());
+    assert_eq!(
+        keys(&ordered),
+        ["Start", "b", "c", "d", "aa-extra", "zz-extra"]
+    );
+}
+
+#[test]
+fn node_results_are_grouped_by_outcome() {
+    let execution = execution_with_node_results(json!({
+        "a": { "status": "success", "startedAt": "2024-01-01T00:00:01Z" },
+        "b": { "status": "error", "error": { "message": "boom" } },
+        "c": "done"
+    }));
+    let workflow = pipeline();
+    let groups = execution.group_by_status(&workflow);
+    assert_eq!(keys(&groups.succeeded), ["a"]);
+    assert_eq!(keys(&groups.failed), ["b"]);
+    assert_eq!(keys(&groups.unknown), ["c"]);
+    assert!(groups.skipped.is_empty() && groups.in_progress.is_empty());
+    let not_run: Vec<&str> = groups.not_run.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(not_run, ["d"]);
+
+    let legacy = &execution.node_results["b"];
+    assert_eq!(legacy.status, None);
+    assert!(legacy.is_failed());
+    assert!(!execution.node_results["c"].is_failed());
+}
+
+#[test]
+fn skipped_and_running_nodes_have_their_own_groups() {
+    let execution = execution_with_node_results(json!({
+        "a": { "status": "success" },
+        "b": { "status": "skipped" },
+        "c": { "status": "running" },
+        "d": { "status": "paused" }
+    }));
+    let workflow = pipeline();
+    let groups = execution.group_by_status(&workflow);
+    assert_eq!(keys(&groups.skipped), ["b"]);
+    assert_eq!(keys(&groups.in_progress), ["c"]);
+    assert_eq!(keys(&groups.unknown), ["d"]);
+    assert!(groups.not_run.is_empty());
+}
+
+fn order_intake() -> WorkflowDefinition {
+    let mut hook = node("hook", "Order received", "webhook");
+    hook["parameters"] = json!({ "path": "orders", "webhookId": "4f1c" });
+    let mut workflow = definition(vec![hook], vec![]);
+    workflow.settings = FieldMap::from([("timezone".to_string(), json!("UTC"))]);
+    workflow
+}
+
+#[test]
+fn create_request_keeps_settings_and_drops_webhook_ids() {
+    let workflow = order_intake();
+    let copy = CreateWorkflowRequest::from(workflow.clone());
+    assert_eq!(copy.name, "Orders");
+    assert_eq!(copy.settings.unwrap()["timezone"], "UTC");
+    assert!(!copy.nodes[0].parameters.contains_key("webhookId"));
+    assert_eq!(copy.nodes[0].parameters["path"], "orders");
+
+    let body =
+        serde_json::to_value(workflow.to_create_request(&CreateRequestOptions::new())).unwrap();
+    assert!(body.get("id").is_none());
+    assert!(body.get("active").is_none());
+}
+
+#[test]
+fn create_request_options_drop_settings_or_keep_webhook_ids() {
+    let workflow = order_intake();
+    let bare = workflow.to_create_request(&CreateRequestOptions::new().settings(false));
+    assert!(bare.settings.is_none());
+
+    let same_urls = workflow.to_create_request(&CreateRequestOptions::new().webhook_ids(true));
+    assert_eq!(same_urls.nodes[0].parameters["webhookId"], "4f1c");
+}
+
+#[test]
+fn applied_update_changes_only_the_fields_it_sets() {
+    let mut workflow = definition(vec![node("a", "A", "set")], vec![connection("a", "a")]);
+    let updated_at = workflow.updated_at;
+
+    workflow.apply(UpdateWorkflowRequest {
+        name: Some("Orders v2".to_string()),
+        active: Some(false),
+        connections: FieldUpdate::Clear,
+        settings: FieldMap::from([("timezone".to_string(), json!("Europe/Oslo"))]).into(),
+        ..Default::default()
+    });
+    assert_eq!(workflow.name, "Orders v2");
+    assert!(!workflow.active);
+    assert_eq!(workflow.nodes.len(), 1);
+    assert!(workflow.connections.is_empty());
+    assert_eq!(workflow.settings["timezone"], "Europe/Oslo");
+    assert_eq!(workflow.updated_at, updated_at);
+}
+
+fn alerts(id: &str, http: &str, slack: &str, updated: &str) -> WorkflowDefinition {
+    let mut notify = node(slack, "Notify", "slack");
+    notify["position"]["x"] = json!(300.0);
+    notify["parameters"] = json!({ "channel": "#ops", "token": "xoxb-secret" });
+    let mut fetch = node(http, "Fetch", "http");
+    fetch["parameters"] = json!({ "url": "https://status.example.com", "method": "GET" });
+    let mut workflow = definition(vec![notify, fetch], vec![connection(http, slack)]);
+    workflow.id = id.to_string();
+    workflow.updated_at = updated.parse().unwrap();
+    workflow
+}
+
+#[test]
+fn snapshots_ignore_ids_and_timestamps() {
+    let first = alerts("wf-1", "a1b2", "c3d4", "2024-01-01T00:00:00Z");
+    let second = alerts("wf-2", "e5f6", "g7h8", "2024-03-01T12:00:00Z");
+    assert_eq!(first.canonical_snapshot(), second.canonical_snapshot());
+
+    let snapshot = first.canonical_snapshot();
+    assert!(snapshot.contains(r#""nodeId": "node-1""#));
+    assert!(!snapshot.contains("createdAt"));
+}
+
+#[test]
+fn snapshots_redact_parameters() {
+    let workflow = alerts("wf-1", "a1b2", "c3d4", "2024-01-01T00:00:00Z");
+    let snapshot =
+        workflow.canonical_snapshot_with(&SnapshotOptions::new().redact_parameter("token"));
+    assert!(!snapshot.contains("xoxb-secret"));
+    assert!(snapshot.contains(r#""token": "[REDACTED]""#));
+}
+
+#[test]
+fn sticky_notes_survive_export_and_import() {
+    let fetch = HttpRequestNode::new("Fetch users", "GET", "https://api.example.com/users").build();
+    let request = WorkflowBuilder::new("Sync users")
+        .node(fetch)
+        .sticky_note(
+            "Runs nightly; see the runbook before changing the URL",
+            Position { x: 0.0, y: -200.0 },
+            (320.0, 120.0),
+            "#fff5b1",
+        )
+        .id_strategy(IdStrategy::Slug)
+        .build()
+        .unwrap();
+
+    // Import the export as the server would return it
+    let mut exported = serde_json::to_value(&request).unwrap();
+    let stored = common::workflow("wf-1", "Sync users");
+    for field in ["id", "active", "settings", "createdAt", "updatedAt"] {
+        exported[field] = stored[field].clone();
+    }
+    let imported: WorkflowDefinition = serde_json::from_value(exported.clone()).unwrap();
+
+    let notes = imported.annotations();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].parameters["color"], "#fff5b1");
+    assert_eq!(notes[0].parameters["width"], 320.0);
+    let order: Vec<&str> = imported
+        .execution_order()
+        .iter()
+        .map(|node| node.id.as_str())
+        .collect();
+    assert_eq!(order, ["fetch-users"]);
+    assert_eq!(serde_json::to_value(&imported).unwrap(), exported);
+}
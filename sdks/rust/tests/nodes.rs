@@ -0,0 +1,145 @@
+use klikkflow_sdk::nodes::{BodyMode, HttpRequestNode};
+use klikkflow_sdk::NodeDefinition;
+use serde_json::json;
+
+/// Body parameters of each mode, besides `sendBody`
+const BODY_KEYS: [&str; 5] = [
+    "contentType",
+    "jsonBody",
+    "bodyParameters",
+    "rawContentType",
+    "body",
+];
+
+fn modes() -> Vec<BodyMode> {
+    vec![
+        BodyMode::None,
+        BodyMode::Json(json!({ "name": "Ada" })),
+        BodyMode::Form(vec![("name".to_string(), "Ada".to_string())]),
+        BodyMode::Raw {
+            content_type: "text/csv".to_string(),
+            body: "name\nAda".to_string(),
+        },
+    ]
+}
+
+fn node_with(body: BodyMode) -> NodeDefinition {
+    let mut builder = HttpRequestNode::new("Create user", "POST", "https://api.example.com/users");
+    builder.body = body;
+    builder.build()
+}
+
+/// Body parameters present on `node`, in `BODY_KEYS` order
+fn body_keys(node: &NodeDefinition) -> Vec<&'static str> {
+    BODY_KEYS
+        .into_iter()
+        .filter(|key| node.parameters.contains_key(*key))
+        .collect()
+}
+
+fn expected_keys(body: &BodyMode) -> Vec<&'static str> {
+    match body {
+        BodyMode::None => vec![],
+        BodyMode::Json(_) => vec!["contentType", "jsonBody"],
+        BodyMode::Form(_) => vec!["contentType", "bodyParameters"],
+        BodyMode::Raw { .. } => vec!["contentType", "rawContentType", "body"],
+    }
+}
+
+/// Switch the body with the builder method for `to`
+fn switch(builder: HttpRequestNode, to: &BodyMode) -> HttpRequestNode {
+    match to {
+        BodyMode::None => builder.no_body(),
+        BodyMode::Json(body) => builder.json_body(body).unwrap(),
+        BodyMode::Form(fields) => fields.iter().fold(builder, |builder, (key, value)| {
+            builder.form_field(key, value)
+        }),
+        BodyMode::Raw { content_type, body } => builder.raw_body(content_type, body),
+    }
+}
+
+#[test]
+fn body_modes_round_trip() {
+    for mode in modes() {
+        let node = node_with(mode.clone());
+        assert_eq!(body_keys(&node), expected_keys(&mode), "{:?}", mode);
+        assert_eq!(node.parameters["sendBody"], json!(mode != BodyMode::None));
+        assert_eq!(HttpRequestNode::from_node(&node).unwrap().body, mode);
+    }
+}
+
+#[test]
+fn switching_body_mode_clears_the_previous_body() {
+    for from in modes() {
+        for to in modes() {
+            let builder = HttpRequestNode::from_node(&node_with(from.clone())).unwrap();
+            let node = switch(builder, &to).build();
+
+            let context = format!("{:?} -> {:?}", from, to);
+            assert_eq!(body_keys(&node), expected_keys(&to), "{}", context);
+            assert_eq!(
+                node.parameters["sendBody"],
+                json!(to != BodyMode::None),
+                "{}",
+                context
+            );
+            let content_type = node.parameters.get("contentType").and_then(|v| v.as_str());
+            let expected_type = match &to {
+                BodyMode::None => None,
+                BodyMode::Json(_) => Some("json"),
+                BodyMode::Form(_) => Some("form-urlencoded"),
+                BodyMode::Raw { .. } => Some("raw"),
+            };
+            assert_eq!(content_type, expected_type, "{}", context);
+            // Form fields are appended to an existing form body
+            let expected_body = match (&from, &to) {
+                (BodyMode::Form(before), BodyMode::Form(added)) => {
+                    BodyMode::Form(before.iter().chain(added).cloned().collect())
+                }
+                _ => to.clone(),
+            };
+            assert_eq!(
+                HttpRequestNode::from_node(&node).unwrap().body,
+                expected_body,
+                "{}",
+                context
+            );
+        }
+    }
+}
+
+#[test]
+fn form_field_appends_to_a_form_body() {
+    let node = HttpRequestNode::new("Create user", "POST", "https://api.example.com/users")
+        .form_field("name", "Ada")
+        .form_field("role", "admin")
+        .build();
+    let body = HttpRequestNode::from_node(&node).unwrap().body;
+    assert_eq!(
+        body,
+        BodyMode::Form(vec![
+            ("name".to_string(), "Ada".to_string()),
+            ("role".to_string(), "admin".to_string()),
+        ])
+    );
+}
+
+#[test]
+fn leftovers_of_other_body_modes_are_dropped() {
+    let mut node = node_with(BodyMode::Json(json!({ "name": "Ada" })));
+    node.parameters
+        .insert("rawContentType".to_string(), json!("text/plain"));
+    node.parameters.insert("body".to_string(), json!("stale"));
+    node.parameters.insert(
+        "bodyParameters".to_string(),
+        json!({ "parameters": [{ "name": "stale", "value": "1" }] }),
+    );
+    node.parameters.insert("timeout".to_string(), json!(30));
+
+    let builder = HttpRequestNode::from_node(&node).unwrap();
+    assert_eq!(builder.body, BodyMode::Json(json!({ "name": "Ada" })));
+    let rebuilt = builder.build();
+    assert_eq!(body_keys(&rebuilt), ["contentType", "jsonBody"]);
+    // Parameters unrelated to the body are preserved
+    assert_eq!(rebuilt.parameters["timeout"], 30);
+}
@@ -0,0 +1,182 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::{client, execution, ok, query_param, two_pages, workflow};
+use klikkflow_sdk::{ClientRegistry, Error, MemoryTransport, WorkflowSettings};
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn find_workflow_searches_every_page() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows",
+        two_pages(
+            "workflows",
+            vec![workflow("wf-1", "Orders")],
+            vec![workflow("wf-2", "Billing")],
+        ),
+    ));
+    let mut registry = ClientRegistry::new();
+    registry.insert("eu", client(&transport));
+
+    let found = registry.find_workflow("Billing").await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        (found[0].0.as_str(), found[0].1.id.as_str()),
+        ("eu", "wf-2")
+    );
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn verify_retention_samples_every_page_of_workflows() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/settings/retention", |_| {
+                ok(json!({ "maxAgeSeconds": 2592000 }))
+            })
+            .handle(
+                "GET",
+                "/api/workflows",
+                two_pages(
+                    "workflows",
+                    vec![workflow("wf-1", "Signup")],
+                    vec![workflow("wf-2", "Billing")],
+                ),
+            )
+            .handle("GET", "/api/workflows/wf-1/executions", |_| {
+                ok(json!({ "executions": [] }))
+            })
+            .handle("GET", "/api/workflows/wf-2/executions", |_| {
+                ok(json!({ "executions": [
+                    { "id": "ex-old", "status": "success", "startedAt": "2024-01-01T00:00:00Z" }
+                ] }))
+            })
+            .handle("GET", "/api/executions/ex-old", |_| {
+                ok(json!({ "id": "ex-old", "inputData": { "email": "ada@example.com" } }))
+            }),
+    );
+
+    let report = client(&transport).verify_retention(10).await.unwrap();
+    assert_eq!(report.checked, 1);
+    assert_eq!(report.violations[0].execution_id, "ex-old");
+}
+
+#[tokio::test]
+async fn apply_defaults_updates_every_page_of_workflows() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle(
+                "GET",
+                "/api/workflows",
+                two_pages(
+                    "workflows",
+                    vec![workflow("wf-1", "Orders")],
+                    vec![workflow("wf-2", "Billing")],
+                ),
+            )
+            .handle("PUT", "/api/workflows/wf-1", |_| {
+                ok(workflow("wf-1", "Orders"))
+            })
+            .handle("PUT", "/api/workflows/wf-2", |_| {
+                ok(workflow("wf-2", "Billing"))
+            }),
+    );
+    let client = client(&transport).with_workflow_defaults(WorkflowSettings {
+        timezone: Some("UTC".to_string()),
+        ..Default::default()
+    });
+
+    let changes = client
+        .apply_defaults_to_existing(None, false)
+        .await
+        .unwrap();
+    let updated: Vec<&str> = changes.iter().map(|c| c.workflow_id.as_str()).collect();
+    assert_eq!(updated, ["wf-1", "wf-2"]);
+    let puts = transport
+        .requests()
+        .into_iter()
+        .filter(|request| request.method == "PUT")
+        .count();
+    assert_eq!(puts, 2);
+}
+
+#[tokio::test]
+async fn list_active_executions_reads_every_page() {
+    let running = two_pages(
+        "executions",
+        vec![execution("ex-1", "wf-1", "running")],
+        vec![execution("ex-2", "wf-1", "running")],
+    );
+    let pending = two_pages(
+        "executions",
+        vec![execution("ex-3", "wf-1", "pending")],
+        vec![execution("ex-4", "wf-1", "pending")],
+    );
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows/wf-1/executions",
+        move |request| match query_param(request, "status") {
+            Some("running") => running(request),
+            _ => pending(request),
+        },
+    ));
+
+    let active = client(&transport)
+        .list_active_executions("wf-1")
+        .await
+        .unwrap();
+    let ids: Vec<&str> = active.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["ex-1", "ex-2", "ex-3", "ex-4"]);
+    assert_eq!(transport.requests().len(), 4);
+}
+
+#[tokio::test]
+async fn list_without_envelope_key_is_an_error() {
+    let transport = Arc::new(MemoryTransport::new().handle("GET", "/api/workflows", |_| {
+        ok(json!({ "items": [workflow("wf-1", "Orders")] }))
+    }));
+    match client(&transport).list_workflows(None).await {
+        Err(Error::Serialization(message)) => {
+            assert!(message.contains("\"workflows\""), "{}", message)
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn stream_workflows_stops_at_a_page_without_envelope_fields() {
+    use futures_util::TryStreamExt;
+    use klikkflow_sdk::ListWorkflowsOptions;
+
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/workflows", |request| {
+            match query_param(request, "cursor") {
+                None => ok(json!({
+                    "workflows": [workflow("wf-1", "One"), workflow("wf-2", "Two")],
+                    "hasMore": true,
+                    "nextCursor": "c2"
+                })),
+                // The last page leaves out the envelope fields entirely
+                Some(_) => ok(json!({ "workflows": [workflow("wf-3", "Three")] })),
+            }
+        }),
+    );
+    let options = ListWorkflowsOptions {
+        limit: Some(2),
+        ..Default::default()
+    };
+    let ids: Vec<String> = client(&transport)
+        .stream_workflows(options)
+        .map_ok(|workflow| workflow.id)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(ids, ["wf-1", "wf-2", "wf-3"]);
+
+    let requests = transport.requests();
+    assert_eq!(query_param(&requests[0], "offset"), Some("0"));
+    assert_eq!(query_param(&requests[1], "cursor"), Some("c2"));
+}
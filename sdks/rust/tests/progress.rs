@@ -0,0 +1,143 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::{client, count, ok, sequence, status};
+use futures_util::StreamExt;
+use klikkflow_sdk::{ExecutionSnapshot, MemoryTransport, SDK_INTERNAL_TAG};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+const STATUS_PATH: &str = "/api/executions/ex-1/status";
+
+/// Status of `ex-1` started at midnight, finished at `finished_at` if set
+fn snapshot(status: &str, completed_nodes: usize, finished_at: Option<&str>) -> Value {
+    json!({
+        "id": "ex-1",
+        "status": status,
+        "startedAt": "2024-01-01T00:00:00Z",
+        "finishedAt": finished_at,
+        "completedNodes": completed_nodes,
+        "totalNodes": 3,
+        "lastNode": (completed_nodes > 0).then_some("Fetch"),
+    })
+}
+
+fn parse(body: Value) -> ExecutionSnapshot {
+    serde_json::from_value(body).unwrap()
+}
+
+async fn poll(transport: &Arc<MemoryTransport>) -> Vec<Result<String, String>> {
+    client(transport)
+        .poll_summaries("ex-1", Duration::from_millis(1))
+        .map(|line| line.map_err(|e| e.to_string()))
+        .collect()
+        .await
+}
+
+#[test]
+fn summary_line_format_is_stable() {
+    let finished = |at: &str| parse(snapshot("success", 3, Some(at)));
+    assert_eq!(
+        finished("2024-01-01T00:00:42Z").summary_line(),
+        "Success · 3/3 nodes · 42s · last: Fetch"
+    );
+    assert_eq!(
+        finished("2024-01-01T00:03:05Z").summary_line(),
+        "Success · 3/3 nodes · 3m05s · last: Fetch"
+    );
+    assert_eq!(
+        finished("2024-01-01T01:02:03Z").summary_line(),
+        "Success · 3/3 nodes · 1h02m03s · last: Fetch"
+    );
+
+    let mut pending = parse(snapshot("pending", 0, Some("2024-01-01T00:00:00Z")));
+    assert_eq!(pending.summary_line(), "Pending · 0/3 nodes · 0s");
+    pending.total_nodes = None;
+    assert_eq!(pending.summary_line(), "Pending · 0 nodes · 0s");
+}
+
+#[test]
+fn summary_json_mirrors_the_line() {
+    let finished = parse(snapshot("error", 2, Some("2024-01-01T00:03:05Z")));
+    assert_eq!(
+        finished.summary_json(),
+        json!({
+            "executionId": "ex-1",
+            "status": "error",
+            "completedNodes": 2,
+            "totalNodes": 3,
+            "elapsedSeconds": 185,
+            "lastNode": "Fetch",
+        })
+    );
+    let started = parse(snapshot("running", 0, Some("2024-01-01T00:00:00Z")));
+    assert_eq!(started.summary_json()["lastNode"], Value::Null);
+}
+
+#[tokio::test]
+async fn polling_stops_after_the_terminal_status() {
+    let finished_at = Some("2024-01-01T00:00:09Z");
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        STATUS_PATH,
+        sequence(vec![
+            ok(snapshot("running", 1, finished_at)),
+            ok(snapshot("success", 3, finished_at)),
+            ok(snapshot("success", 3, finished_at)),
+        ]),
+    ));
+    assert_eq!(
+        poll(&transport).await,
+        [
+            Ok("Running · 1/3 nodes · 9s · last: Fetch".to_string()),
+            Ok("Success · 3/3 nodes · 9s · last: Fetch".to_string()),
+        ]
+    );
+    assert_eq!(count(&transport, "GET", STATUS_PATH), 2);
+}
+
+#[tokio::test]
+async fn polling_continues_after_a_retryable_error() {
+    let finished_at = Some("2024-01-01T00:00:09Z");
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        STATUS_PATH,
+        sequence(vec![
+            status(503, json!({ "message": "restarting" })),
+            ok(snapshot("cancelled", 1, finished_at)),
+        ]),
+    ));
+    let lines = poll(&transport).await;
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].is_err());
+    assert_eq!(
+        lines[1],
+        Ok("Cancelled · 1/3 nodes · 9s · last: Fetch".to_string())
+    );
+}
+
+#[tokio::test]
+async fn polling_ends_at_an_error_that_is_not_retryable() {
+    let transport = Arc::new(MemoryTransport::new().handle("GET", STATUS_PATH, |_| {
+        status(403, json!({ "message": "forbidden" }))
+    }));
+    let lines = poll(&transport).await;
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].is_err());
+    assert_eq!(count(&transport, "GET", STATUS_PATH), 1);
+}
+
+#[tokio::test]
+async fn polls_are_tagged_as_sdk_internal() {
+    let transport = Arc::new(MemoryTransport::new().handle("GET", STATUS_PATH, |_| {
+        ok(snapshot("success", 3, Some("2024-01-01T00:00:09Z")))
+    }));
+    poll(&transport).await;
+    let request = &transport.requests()[0];
+    assert_eq!(
+        request.headers[format!("x-request-tag-{}", SDK_INTERNAL_TAG).as_str()],
+        "summary-poll"
+    );
+}
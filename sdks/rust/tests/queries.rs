@@ -0,0 +1,185 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use chrono::{DateTime, Duration as ChronoDuration, TimeZone, Utc};
+use common::{client, execution, ok, page, query_param};
+use klikkflow_sdk::{
+    BucketSize, ExecutionHistoryOptions, ExecutionStatus, ListWorkflowsOptions, MemoryTransport,
+};
+use serde_json::{json, Value};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A base64 cursor with every character that means something in a query
+const CURSOR: &str = "a+b/c==&limit=1";
+const ENCODED_CURSOR: &str = "a%2Bb%2Fc%3D%3D%26limit%3D1";
+
+#[tokio::test]
+async fn list_workflows_encodes_cursor() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/workflows", |_| page("workflows", vec![], None)),
+    );
+    let options = ListWorkflowsOptions {
+        cursor: Some(CURSOR.to_string()),
+        limit: Some(10),
+        ..Default::default()
+    };
+    client(&transport)
+        .list_workflows(Some(options))
+        .await
+        .unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(query_param(&requests[0], "cursor"), Some(ENCODED_CURSOR));
+    assert_eq!(query_param(&requests[0], "limit"), Some("10"));
+}
+
+#[tokio::test]
+async fn execution_history_encodes_cursor() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows/wf-1/executions",
+        |_| page("executions", vec![], None),
+    ));
+    let options = ExecutionHistoryOptions {
+        cursor: Some(CURSOR.to_string()),
+        ..Default::default()
+    };
+    client(&transport)
+        .get_execution_history("wf-1", Some(options))
+        .await
+        .unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(query_param(&requests[0], "cursor"), Some(ENCODED_CURSOR));
+    assert_eq!(requests[0].query().unwrap().matches('&').count(), 0);
+}
+
+#[tokio::test]
+async fn execution_statistics_encodes_workflow_id() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/executions/statistics",
+        |_| common::ok(serde_json::json!({})),
+    ));
+    client(&transport)
+        .get_execution_statistics(Some("team a&b#1"))
+        .await
+        .unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].query(), Some("workflowId=team%20a%26b%231"));
+}
+
+#[tokio::test]
+async fn ids_are_encoded_as_a_single_path_segment() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows/team%2Fnightly%3Fv=2",
+        |_| common::ok(common::workflow("team/nightly?v=2", "Nightly")),
+    ));
+    let workflow = client(&transport)
+        .get_workflow("team/nightly?v=2")
+        .await
+        .unwrap();
+    assert_eq!(workflow.name, "Nightly");
+    assert_eq!(transport.requests()[0].query(), None);
+}
+
+/// The first two days of 2024
+fn two_days() -> Range<DateTime<Utc>> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ..Utc.with_ymd_and_hms(2024, 1, 3, 0, 0, 0).unwrap()
+}
+
+#[tokio::test]
+async fn timeseries_is_aggregated_by_the_server() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows/wf-1/executions/timeseries",
+        |_| {
+            ok(json!({ "buckets": [
+                { "start": "2024-01-01T00:00:00Z", "countsByStatus": { "success": 3, "error": 1 }, "avgDuration": 1500.0 },
+                { "start": "2024-01-02T00:00:00Z" }
+            ] }))
+        },
+    ));
+    let buckets = client(&transport)
+        .get_execution_timeseries("wf-1", two_days(), BucketSize::Day)
+        .await
+        .unwrap();
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets[0].total(), 4);
+    assert_eq!(buckets[0].counts_by_status[&ExecutionStatus::Error], 1);
+    assert_eq!(buckets[0].avg_duration, Some(Duration::from_millis(1500)));
+    assert_eq!(buckets[1].total(), 0);
+    assert_eq!(buckets[1].avg_duration, None);
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(query_param(&requests[0], "bucket"), Some("day"));
+    assert_eq!(
+        query_param(&requests[0], "from"),
+        Some("2024-01-01T00:00:00Z")
+    );
+}
+
+#[tokio::test]
+async fn timeseries_falls_back_to_bucketing_the_history_once() {
+    // Newest first: 120 executions on January 2nd, 130 on the 1st, then one before the range
+    let history: Vec<Value> = (0..251)
+        .map(|index| {
+            let started = match index {
+                0..=119 => {
+                    Utc.with_ymd_and_hms(2024, 1, 2, 23, 59, 0).unwrap()
+                        - ChronoDuration::minutes(index)
+                }
+                120..=249 => {
+                    Utc.with_ymd_and_hms(2024, 1, 1, 23, 59, 0).unwrap()
+                        - ChronoDuration::minutes(index - 120)
+                }
+                _ => Utc.with_ymd_and_hms(2023, 12, 31, 12, 0, 0).unwrap(),
+            };
+            let mut body = execution(&format!("ex-{}", index), "wf-1", "success");
+            body["startedAt"] = json!(started);
+            body["finishedAt"] = json!(started + ChronoDuration::minutes(1));
+            // Listed without data
+            body.as_object_mut().unwrap().remove("inputData");
+            body.as_object_mut().unwrap().remove("outputData");
+            body
+        })
+        .collect();
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows/wf-1/executions",
+        move |request| {
+            let offset: usize = query_param(request, "offset").unwrap().parse().unwrap();
+            let limit: usize = query_param(request, "limit").unwrap().parse().unwrap();
+            // An execution started after the first page was read, shifting the rest by one
+            let start = offset.saturating_sub(1).min(history.len());
+            let end = (start + limit).min(history.len());
+            page("executions", history[start..end].to_vec(), None)
+        },
+    ));
+
+    let buckets = client(&transport)
+        .get_execution_timeseries("wf-1", two_days(), BucketSize::Day)
+        .await
+        .unwrap();
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets[0].counts_by_status[&ExecutionStatus::Success], 130);
+    assert_eq!(buckets[1].counts_by_status[&ExecutionStatus::Success], 120);
+    assert_eq!(buckets[0].avg_duration, Some(Duration::from_secs(60)));
+
+    let requests = transport.requests();
+    let pages: Vec<_> = requests
+        .iter()
+        .filter(|request| request.path() == "/api/workflows/wf-1/executions")
+        .collect();
+    assert_eq!(pages.len(), 2);
+    for request in pages {
+        assert_eq!(query_param(request, "includeData"), Some("false"));
+    }
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use bytes::Bytes;
+use common::{client, execution, ok};
+use klikkflow_sdk::{Error, MemoryTransport};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// A workflow ID with every character JSON strings must escape
+const WORKFLOW_ID: &str = "wf \"quoted\" \\ back\nslash\t\u{1}";
+
+fn transport() -> Arc<MemoryTransport> {
+    Arc::new(
+        MemoryTransport::new().handle("POST", "/api/executions", |_| {
+            ok(execution("ex-1", WORKFLOW_ID, "running"))
+        }),
+    )
+}
+
+#[tokio::test]
+async fn spliced_body_is_valid_json() {
+    let transport = transport();
+    let input =
+        Bytes::from_static(b"\n  {\"order\": {\"id\": 7, \"note\": \"}\\\"{\"}, \"lines\": []}");
+    client(&transport)
+        .execute_workflow_raw_input(WORKFLOW_ID, input)
+        .await
+        .unwrap();
+
+    let requests = transport.requests();
+    let body: Value = serde_json::from_slice(requests[0].body.as_deref().unwrap()).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "workflowId": WORKFLOW_ID,
+            "inputData": { "order": { "id": 7, "note": "}\"{" }, "lines": [] }
+        })
+    );
+}
+
+#[tokio::test]
+async fn input_other_than_an_object_is_rejected() {
+    let transport = transport();
+    for input in [&b"[1, 2]"[..], b"\"text\"", b"", b"  "] {
+        let result = client(&transport)
+            .execute_workflow_raw_input("wf-1", Bytes::from_static(input))
+            .await;
+        assert!(matches!(result, Err(Error::InvalidInput(_))), "{:?}", input);
+    }
+    assert!(transport.requests().is_empty());
+}
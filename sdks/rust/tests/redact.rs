@@ -0,0 +1,112 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::{execution, json_body, ok, BASE_URL};
+use klikkflow_sdk::{
+    Client, FieldMap, MemoryTransport, Redaction, RedactionAction, RedactionAudit, RedactionPolicy,
+};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+/// Body of the execution submitted to `transport`
+fn submitted(transport: &MemoryTransport) -> serde_json::Value {
+    let requests = transport.requests();
+    let request = requests
+        .iter()
+        .find(|request| request.method == "POST")
+        .expect("an execution was submitted");
+    json_body(request)
+}
+
+#[tokio::test]
+async fn execution_input_is_redacted_before_it_is_sent() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("POST", "/api/executions", |_| {
+            ok(execution("ex-1", "wf-1", "pending"))
+        }),
+    );
+    let audits = Arc::new(Mutex::new(Vec::<RedactionAudit>::new()));
+    let recorded = audits.clone();
+    let policy = RedactionPolicy::new()
+        .mask_keys("*token*")
+        .drop_pointer("/users/*/email")
+        .on_redact(move |audit| recorded.lock().unwrap().push(audit.clone()));
+    let client = Client::builder()
+        .base_url(BASE_URL)
+        .transport(transport.clone())
+        .input_redaction(policy)
+        .build()
+        .unwrap();
+
+    let input: FieldMap = serde_json::from_value(json!({
+        "apiToken": "secret",
+        "users": [
+            { "name": "Ada", "email": "ada@example.com" },
+            { "name": "Bob", "email": "bob@example.com", "refresh_token": "x" }
+        ]
+    }))
+    .unwrap();
+    let original = input.clone();
+    client.execute_workflow_ref("wf-1", &input).await.unwrap();
+
+    assert_eq!(
+        submitted(&transport)["inputData"],
+        json!({
+            "apiToken": "[REDACTED]",
+            "users": [
+                { "name": "Ada" },
+                { "name": "Bob", "refresh_token": "[REDACTED]" }
+            ]
+        })
+    );
+
+    let audits = audits.lock().unwrap();
+    assert_eq!(audits.len(), 1);
+    assert_eq!(audits[0].workflow_id, "wf-1");
+    let mut redactions = audits[0].redactions.clone();
+    redactions.sort_by(|a, b| a.pointer.cmp(&b.pointer));
+    let redaction = |pointer: &str, action| Redaction {
+        pointer: pointer.to_string(),
+        action,
+    };
+    assert_eq!(
+        redactions,
+        [
+            redaction("/apiToken", RedactionAction::Mask),
+            redaction("/users/0/email", RedactionAction::Drop),
+            redaction("/users/1/email", RedactionAction::Drop),
+            redaction("/users/1/refresh_token", RedactionAction::Mask),
+        ]
+    );
+
+    // The caller's data is untouched
+    assert_eq!(input, original);
+    assert_eq!(input["apiToken"], "secret");
+}
+
+#[tokio::test]
+async fn input_without_redacted_values_is_not_audited() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("POST", "/api/executions", |_| {
+            ok(execution("ex-1", "wf-1", "pending"))
+        }),
+    );
+    let audited = Arc::new(Mutex::new(0));
+    let counter = audited.clone();
+    let client = Client::builder()
+        .base_url(BASE_URL)
+        .transport(transport.clone())
+        .input_redaction(
+            RedactionPolicy::new()
+                .drop_keys("password")
+                .on_redact(move |_| *counter.lock().unwrap() += 1),
+        )
+        .build()
+        .unwrap();
+    let input: FieldMap = serde_json::from_value(json!({ "user": "ada" })).unwrap();
+    client.execute_workflow("wf-1", input, false).await.unwrap();
+
+    assert_eq!(submitted(&transport)["inputData"], json!({ "user": "ada" }));
+    assert_eq!(*audited.lock().unwrap(), 0);
+}
@@ -0,0 +1,179 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::{client, count, ok, sequence, status, workflow};
+use klikkflow_sdk::{Error, MemoryTransport, RequestOptions, RetryPolicy, TransportErrorKind};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Retries after 1ms, so tests do not wait
+fn fast() -> RetryPolicy {
+    RetryPolicy::new()
+        .initial_backoff(Duration::from_millis(1))
+        .max_backoff(Duration::from_millis(1))
+}
+
+/// `GET /api/workflows/wf-1` and `POST /api/executions/ex-1/cancel` both unavailable
+fn unavailable() -> Arc<MemoryTransport> {
+    Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/workflows/wf-1", |_| status(503, json!({})))
+            .handle("POST", "/api/executions/ex-1/cancel", |_| {
+                status(503, json!({}))
+            }),
+    )
+}
+
+#[test]
+fn seeded_schedule_is_reproducible() {
+    let policy = RetryPolicy::new()
+        .initial_backoff(Duration::from_millis(100))
+        .max_backoff(Duration::from_millis(500))
+        .jitter(0.5)
+        .jitter_seed(7);
+    let schedule: Vec<Duration> = (1..=5).map(|attempt| policy.delay_after(attempt)).collect();
+    assert_eq!(
+        schedule,
+        [
+            86_990_851,
+            138_676_805,
+            286_291_164,
+            278_362_586,
+            397_797_434
+        ]
+        .map(Duration::from_nanos)
+    );
+    assert_eq!(
+        (1..=5)
+            .map(|attempt| policy.clone().delay_after(attempt))
+            .collect::<Vec<_>>(),
+        schedule
+    );
+
+    let unjittered = policy.clone().jitter(0.0);
+    let backoff = [100, 200, 400, 500, 500].map(Duration::from_millis);
+    assert_eq!(
+        (1..=5)
+            .map(|attempt| unjittered.delay_after(attempt))
+            .collect::<Vec<_>>(),
+        backoff
+    );
+    for (delay, backoff) in schedule.iter().zip(backoff) {
+        assert!(*delay >= backoff / 2 && *delay <= backoff, "{:?}", delay);
+    }
+
+    let reseeded = policy.jitter_seed(8);
+    assert_ne!(
+        (1..=5)
+            .map(|attempt| reseeded.delay_after(attempt))
+            .collect::<Vec<_>>(),
+        schedule
+    );
+}
+
+#[test]
+fn jitter_out_of_range_or_not_a_number_is_clamped() {
+    let policy = RetryPolicy::new()
+        .initial_backoff(Duration::from_millis(100))
+        .jitter_seed(3);
+    assert_eq!(
+        policy.clone().jitter(f64::NAN).delay_after(1),
+        Duration::from_millis(100)
+    );
+
+    // The field is public, so set it directly past what the setter allows
+    for jitter in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 2.0, -1.0] {
+        let mut policy = policy.clone();
+        policy.jitter = jitter;
+        for attempt in 1..=3 {
+            let delay = policy.delay_after(attempt);
+            let backoff = Duration::from_millis(100) * 2u32.pow(attempt - 1);
+            assert!(delay <= backoff, "{}: {:?}", jitter, delay);
+        }
+    }
+}
+
+#[tokio::test]
+async fn unavailable_gets_are_retried_and_posts_are_not() {
+    let transport = unavailable();
+    let client = client(&transport).with_retry(fast());
+    assert_eq!(
+        client.get_workflow("wf-1").await.unwrap_err().status(),
+        Some(503)
+    );
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 3);
+
+    assert!(client.cancel_execution("ex-1").await.is_err());
+    assert_eq!(count(&transport, "POST", "/api/executions/ex-1/cancel"), 1);
+}
+
+#[tokio::test]
+async fn idempotent_posts_are_retried() {
+    let transport = unavailable();
+    let client = client(&transport)
+        .with_retry(fast())
+        .with_request_options(RequestOptions::new().idempotent(true));
+    assert!(client.cancel_execution("ex-1").await.is_err());
+    assert_eq!(count(&transport, "POST", "/api/executions/ex-1/cancel"), 3);
+}
+
+#[tokio::test]
+async fn retry_predicate_replaces_the_status_rules() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/workflows/wf-1", |_| status(503, json!({})))
+            .handle(
+                "GET",
+                "/api/workflows/wf-2",
+                sequence(vec![
+                    status(409, json!({ "message": "locked" })),
+                    ok(workflow("wf-2", "Billing")),
+                ]),
+            ),
+    );
+    let client = client(&transport).with_retry(
+        fast().retry_if(|error, attempt| matches!(error, Error::Conflict { .. }) && attempt < 2),
+    );
+    assert!(client.get_workflow("wf-1").await.is_err());
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 1);
+
+    assert_eq!(client.get_workflow("wf-2").await.unwrap().name, "Billing");
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-2"), 2);
+}
+
+#[tokio::test]
+async fn retryable_statuses_can_be_replaced() {
+    let transport = unavailable();
+    let client = client(&transport).with_retry(fast().retryable_statuses([502]));
+    assert!(client.get_workflow("wf-1").await.is_err());
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 1);
+}
+
+#[tokio::test]
+async fn per_call_policy_replaces_the_client_policy() {
+    let transport = unavailable();
+    let client = client(&transport).with_retry(fast());
+
+    let disabled =
+        client.with_request_options(RequestOptions::new().retry(RetryPolicy::disabled()));
+    assert!(disabled.get_workflow("wf-1").await.is_err());
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 1);
+
+    let more = client.with_request_options(RequestOptions::new().retry(fast().max_attempts(5)));
+    assert!(more.get_workflow("wf-1").await.is_err());
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 6);
+}
+
+#[test]
+fn failures_before_sending_are_retried_whatever_the_method() {
+    let failed = |kind| Error::Transport {
+        kind,
+        message: String::new(),
+    };
+    let policy = RetryPolicy::new();
+    assert!(policy.should_retry("POST", false, &failed(TransportErrorKind::Connect), 1));
+    assert!(!policy.should_retry("POST", false, &failed(TransportErrorKind::Request), 1));
+    assert!(!policy.should_retry("POST", false, &failed(TransportErrorKind::Connect), 3));
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{client, count, json_body, ok, status, with_header, workflow};
+use klikkflow_sdk::{
+    Capability, CapabilitySource, Error, EventType, MemoryTransport, SubscriptionRequest,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn capabilities_are_read_from_the_manifest_once() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/capabilities", |_| {
+            ok(json!({ "capabilities": ["bulk-import", "cursors", "live-debugging"] }))
+        }),
+    );
+    let client = client(&transport);
+    let capabilities = client.capabilities().await.unwrap();
+    assert!(capabilities.supports(Capability::BulkImport));
+    assert!(!capabilities.supports(Capability::Trash));
+    assert!(capabilities.features.contains("live-debugging"));
+
+    client.capabilities().await.unwrap();
+    assert_eq!(count(&transport, "GET", "/api/capabilities"), 1);
+}
+
+#[tokio::test]
+async fn capabilities_are_probed_without_a_manifest() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/capabilities", |_| status(404, json!({})))
+            .handle("GET", "/api/workflows/import", |_| status(405, json!({})))
+            .handle("GET", "/api/workflows/trash", |_| ok(json!([])))
+            .handle("GET", "/api/executions/replay", |_| status(404, json!({}))),
+    );
+    let capabilities = client(&transport).capabilities().await.unwrap();
+    assert_eq!(capabilities.source, CapabilitySource::Probed);
+    assert!(capabilities.supports(Capability::BulkImport));
+    assert!(capabilities.supports(Capability::Trash));
+    assert!(!capabilities.supports(Capability::Replay));
+}
+
+#[tokio::test]
+async fn server_version_is_checked_against_the_required_one() {
+    let transport = Arc::new(MemoryTransport::new().handle("GET", "/api/version", |_| {
+        ok(json!({ "version": "2.4.1", "features": ["trash"] }))
+    }));
+    let client = client(&transport);
+    client.assert_compatible("2.4").await.unwrap();
+    match client.assert_compatible("2.5.0").await {
+        Err(e @ Error::IncompatibleServer { .. }) => assert_eq!(
+            e.to_string(),
+            "Server version 2.4.1 is older than the required 2.5.0"
+        ),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(client.server_info().await.unwrap().has_feature("trash"));
+    assert_eq!(count(&transport, "GET", "/api/version"), 1);
+}
+
+#[tokio::test]
+async fn clock_skew_is_estimated_from_the_date_header() {
+    let ahead = Utc::now() + chrono::Duration::seconds(90);
+    let date = ahead.to_rfc2822().replace("+0000", "GMT");
+    let transport = Arc::new(MemoryTransport::new().handle("GET", "/health", move |_| {
+        with_header(ok(json!({})), "date", &date)
+    }));
+    let client = client(&transport);
+    assert!(client.estimated_clock_skew().is_none());
+    client.health_check().await.unwrap();
+
+    let skew = client.estimated_clock_skew().unwrap();
+    assert!((skew - chrono::Duration::seconds(90)).num_seconds().abs() <= 1);
+}
+
+#[tokio::test]
+async fn deprecation_headers_are_recorded() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/workflows/wf-1", |_| {
+            let response = with_header(ok(workflow("wf-1", "Orders")), "deprecation", "true");
+            with_header(response, "sunset", "Sat, 01 Jun 2030 00:00:00 GMT")
+        }),
+    );
+    let client = client(&transport);
+    assert!(client.last_deprecation().is_none());
+    client.get_workflow("wf-1").await.unwrap();
+
+    let deprecation = client.last_deprecation().unwrap();
+    assert_eq!(deprecation.path, "/api/workflows/wf-1");
+    assert_eq!(deprecation.deprecated.as_deref(), Some("true"));
+    assert_eq!(
+        deprecation.sunset,
+        Some(Utc.with_ymd_and_hms(2030, 6, 1, 0, 0, 0).unwrap())
+    );
+}
+
+#[tokio::test]
+async fn event_subscription_is_created() {
+    let transport =
+        Arc::new(
+            MemoryTransport::new().handle("POST", "/api/event-subscriptions", |_| {
+                ok(json!({
+                    "id": "sub-1",
+                    "url": "https://hooks.example.com/klikkflow",
+                    "events": ["execution.failed"]
+                }))
+            }),
+        );
+    let subscription = client(&transport)
+        .create_event_subscription(SubscriptionRequest {
+            url: "https://hooks.example.com/klikkflow".to_string(),
+            events: vec![EventType::ExecutionFailed],
+            workflow_ids: Vec::new(),
+            secret: Some("s3cret".to_string()),
+        })
+        .await
+        .unwrap();
+    assert_eq!(subscription.id, "sub-1");
+    assert!(subscription.active);
+
+    let sent = json_body(&transport.requests()[0]);
+    assert_eq!(sent["url"], "https://hooks.example.com/klikkflow");
+    assert_eq!(sent["events"], json!(["execution.failed"]));
+    assert_eq!(sent["secret"], "s3cret");
+}
@@ -0,0 +1,270 @@
+use klikkflow_sdk::spec::{NodeTypeRegistry, WorkflowSpec};
+use klikkflow_sdk::Error;
+use serde_json::{json, Value};
+
+const PIPELINE_TOML: &str = r#"
+name = "Order pipeline"
+description = "Route orders by region and report"
+
+[settings]
+timezone = "Europe/Oslo"
+
+[[steps]]
+id = "orders"
+uses = "http"
+with = { method = "GET", url = "https://shop.example.com/orders" }
+
+[[steps]]
+id = "eu"
+uses = "filter"
+with = { condition = "{{ $json.region == 'eu' }}" }
+needs = ["orders"]
+
+[[steps]]
+id = "us"
+uses = "filter"
+with = { condition = "{{ $json.region == 'us' }}" }
+needs = ["orders"]
+
+[[steps]]
+id = "vat"
+name = "Add VAT"
+uses = "set"
+with = { rate = 0.25 }
+needs = ["eu"]
+
+[[steps]]
+id = "ship"
+uses = "http"
+with = { method = "POST", url = "https://ship.example.com/labels", retries = [1, 5, 30] }
+needs = ["vat", "us"]
+
+[[steps]]
+id = "report"
+uses = "http"
+with = { method = "POST", url = "https://chat.example.com/hooks/orders" }
+needs = ["ship", "orders"]
+"#;
+
+const PIPELINE_JSON: &str = r#"{
+  "name": "Order pipeline",
+  "description": "Route orders by region and report",
+  "settings": { "timezone": "Europe/Oslo" },
+  "steps": [
+    { "id": "orders", "uses": "http", "with": { "method": "GET", "url": "https://shop.example.com/orders" } },
+    { "id": "eu", "uses": "filter", "with": { "condition": "{{ $json.region == 'eu' }}" }, "needs": ["orders"] },
+    { "id": "us", "uses": "filter", "with": { "condition": "{{ $json.region == 'us' }}" }, "needs": ["orders"] },
+    { "id": "vat", "name": "Add VAT", "uses": "set", "with": { "rate": 0.25 }, "needs": ["eu"] },
+    {
+      "id": "ship",
+      "uses": "http",
+      "with": { "method": "POST", "url": "https://ship.example.com/labels", "retries": [1, 5, 30] },
+      "needs": ["vat", "us"]
+    },
+    {
+      "id": "report",
+      "uses": "http",
+      "with": { "method": "POST", "url": "https://chat.example.com/hooks/orders" },
+      "needs": ["ship", "orders"]
+    }
+  ]
+}"#;
+
+fn registry() -> NodeTypeRegistry {
+    NodeTypeRegistry::new()
+        .alias("filter", "filter")
+        .alias("set", "set")
+}
+
+fn compiled(source: &str) -> Value {
+    let request = WorkflowSpec::parse(source)
+        .unwrap()
+        .compile(&registry())
+        .unwrap();
+    serde_json::to_value(request).unwrap()
+}
+
+/// Issues of compiling `source`, as `line:column: message`
+fn issues(source: &str) -> Vec<String> {
+    match WorkflowSpec::parse(source).unwrap().compile(&registry()) {
+        Err(Error::InvalidSpec { issues }) => issues.iter().map(ToString::to_string).collect(),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn multi_branch_spec_compiles_to_a_laid_out_workflow() {
+    let request = compiled(PIPELINE_TOML);
+    assert_eq!(request["name"], "Order pipeline");
+    assert_eq!(request["settings"], json!({ "timezone": "Europe/Oslo" }));
+
+    let nodes = request["nodes"].as_array().unwrap();
+    let layout: Vec<(&str, &str, f64, f64)> = nodes
+        .iter()
+        .map(|node| {
+            (
+                node["name"].as_str().unwrap(),
+                node["type"].as_str().unwrap(),
+                node["position"]["x"].as_f64().unwrap(),
+                node["position"]["y"].as_f64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        layout,
+        [
+            ("orders", "http-request", 250.0, 300.0),
+            ("eu", "filter", 500.0, 300.0),
+            ("us", "filter", 500.0, 450.0),
+            ("Add VAT", "set", 750.0, 300.0),
+            ("ship", "http-request", 1000.0, 300.0),
+            ("report", "http-request", 1250.0, 300.0),
+        ]
+    );
+    assert_eq!(nodes[4]["parameters"]["retries"], json!([1, 5, 30]));
+
+    let connections: Vec<(&str, &str)> = request["connections"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|connection| {
+            (
+                connection["source"]["nodeId"].as_str().unwrap(),
+                connection["destination"]["nodeId"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        connections,
+        [
+            ("orders", "eu"),
+            ("orders", "us"),
+            ("eu", "vat"),
+            ("vat", "ship"),
+            ("us", "ship"),
+            ("ship", "report"),
+            ("orders", "report"),
+        ]
+    );
+}
+
+#[test]
+fn toml_and_json_specs_compile_to_the_same_workflow() {
+    let from_toml = compiled(PIPELINE_TOML);
+    assert_eq!(compiled(PIPELINE_JSON), from_toml);
+
+    let round_tripped = WorkflowSpec::parse(PIPELINE_TOML)
+        .unwrap()
+        .to_json()
+        .unwrap();
+    assert_eq!(compiled(&round_tripped), from_toml);
+}
+
+#[test]
+fn toml_issues_point_at_the_offending_values() {
+    let source = r#"name = "Broken"
+
+[settings]
+uuid = "fetch"
+workflowId = "fetch"
+
+[[steps]]
+id = "fetch"
+uses = "ftp"
+
+[[steps]]
+id = "store"
+uses = "http"
+needs = ["fetch", "fetc"]
+
+[[steps]]
+id = "fetch"
+uses = "http"
+"#;
+    assert_eq!(
+        issues(source),
+        [
+            "17:6: duplicate step id `fetch`",
+            "9:8: step `fetch` uses unknown node kind `ftp`",
+            "14:19: step `store` needs unknown step `fetc`",
+        ]
+    );
+}
+
+#[test]
+fn toml_cycles_point_at_the_step_id() {
+    let source = r#"name = "Loop"
+
+[[steps]]
+id = "start"
+uses = "http"
+
+[[steps]]
+id = "ping"
+uses = "http"
+needs = ["start", "pong"]
+
+[[steps]]
+id = "pong"
+uses = "http"
+needs = ["ping"]
+"#;
+    assert_eq!(
+        issues(source),
+        ["8:6: step `ping` is part of a dependency cycle"]
+    );
+}
+
+#[test]
+fn json_issues_point_at_the_offending_values() {
+    let source = r#"{
+  "name": "Broken",
+  "settings": { "uuid": "fetch", "workflowId": "fetch" },
+  "steps": [
+    { "id": "fetch", "uses": "ftp" },
+    { "id": "store", "uses": "http", "needs": ["fetch", "fetc"] },
+    { "id": "fetch", "uses": "http" }
+  ]
+}"#;
+    assert_eq!(
+        issues(source),
+        [
+            "7:13: duplicate step id `fetch`",
+            "5:30: step `fetch` uses unknown node kind `ftp`",
+            "6:57: step `store` needs unknown step `fetc`",
+        ]
+    );
+}
+
+#[test]
+fn json_cycles_point_at_the_step_id() {
+    let source = r#"{
+  "name": "Loop",
+  "steps": [
+    { "id": "ping", "uses": "http", "needs": ["pong"] },
+    { "id": "pong", "uses": "http", "needs": ["ping"] }
+  ]
+}"#;
+    assert_eq!(
+        issues(source),
+        ["4:13: step `ping` is part of a dependency cycle"]
+    );
+}
+
+#[test]
+fn specs_built_in_code_report_issues_without_positions() {
+    let parsed = WorkflowSpec::parse(PIPELINE_JSON).unwrap();
+    let mut spec = WorkflowSpec::new(parsed.name, parsed.steps);
+    spec.steps[1].uses = "ftp".to_string();
+    match spec.compile(&registry()) {
+        Err(Error::InvalidSpec { issues }) => {
+            assert_eq!(issues.len(), 1);
+            assert_eq!(issues[0].position, None);
+            assert_eq!(
+                issues[0].to_string(),
+                "step `eu` uses unknown node kind `ftp`"
+            );
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,686 @@
+//! Execution streams, against a WebSocket server on a real socket
+
+mod common;
+
+use futures_util::{SinkExt, StreamExt};
+use klikkflow_sdk::{
+    ApiVersion, CancellationToken, Client, ConnectionEvent, RequestOptions, SharedUpdate,
+    StreamEvent, TailEvent,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Listener on a free local port, and the base URL reaching it
+async fn listen() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    (listener, base_url)
+}
+
+#[tokio::test]
+async fn updates_are_written_as_ndjson() {
+    let (listener, base_url) = listen().await;
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(socket).await.unwrap();
+        for message in [
+            r#"{"type":"executionStarted","data":{},"timestamp":"2024-01-01T00:00:00Z"}"#,
+            r#"{"type":"nodeCompleted","data":{"nodeId":"fetch","status":"success","output":{"count":2}},"timestamp":"2024-01-01T00:00:01Z"}"#,
+            "not json",
+            r#"{"type":"executionCompleted","data":{"status":"success"},"timestamp":"2024-01-01T00:00:02Z"}"#,
+        ] {
+            socket
+                .send(Message::Text(message.to_string()))
+                .await
+                .unwrap();
+        }
+    });
+
+    let mut output = Vec::new();
+    Client::new(base_url)
+        .stream_execution_ndjson("ex-1", &mut output)
+        .await
+        .unwrap();
+    let golden = include_str!("../testdata/execution-stream.ndjson");
+    assert_eq!(String::from_utf8(output).unwrap(), golden);
+}
+
+#[tokio::test]
+async fn cancelled_stream_closes_the_connection() {
+    let (listener, base_url) = listen().await;
+    let server = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(socket).await.unwrap();
+        let update = r#"{"type":"nodeStarted","data":{},"timestamp":"2024-01-01T00:00:00Z"}"#;
+        socket
+            .send(Message::Text(update.to_string()))
+            .await
+            .unwrap();
+        // Wait for the client to close the connection
+        while let Some(Ok(message)) = socket.next().await {
+            if message.is_close() {
+                return true;
+            }
+        }
+        false
+    });
+
+    let token = CancellationToken::new();
+    let client = Client::new(base_url);
+    let mut stream = client
+        .stream_execution("ex-1")
+        .await
+        .unwrap()
+        .cancel_on(token.clone());
+    assert!(stream.next().await.is_some());
+
+    token.cancel();
+    assert!(stream.next().await.is_none());
+    assert!(server.await.unwrap());
+}
+
+#[tokio::test]
+async fn connection_events_report_reconnects_and_unanswered_pings() {
+    let (listener, base_url) = listen().await;
+    tokio::spawn(async move {
+        // The first connection drops without a close handshake
+        let (socket, _) = listener.accept().await.unwrap();
+        drop(tokio_tungstenite::accept_async(socket).await.unwrap());
+
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(socket).await.unwrap();
+        // Pings go unanswered while the server is not reading
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        let update =
+            r#"{"type":"executionCompleted","data":{},"timestamp":"2024-01-01T00:00:00Z"}"#;
+        socket
+            .send(Message::Text(update.to_string()))
+            .await
+            .unwrap();
+        // Reading answers the pings again
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(400);
+        while let Ok(Some(_)) = tokio::time::timeout_at(deadline, socket.next()).await {}
+        socket.close(None).await.unwrap();
+    });
+
+    let events: Vec<StreamEvent> = Client::new(base_url)
+        .stream_execution("ex-1")
+        .await
+        .unwrap()
+        .keepalive(Duration::from_millis(50))
+        .degraded_after(Duration::from_millis(150))
+        .reconnect(3)
+        .with_connection_events()
+        .filter_map(|event| async move { event.ok() })
+        .collect()
+        .await;
+
+    let connection: Vec<&ConnectionEvent> = events
+        .iter()
+        .filter_map(|event| match event {
+            StreamEvent::Connection(event) => Some(event),
+            StreamEvent::Update(_) => None,
+        })
+        .collect();
+    assert_eq!(connection[0], &ConnectionEvent::Connected);
+    assert_eq!(connection[1], &ConnectionEvent::Reconnecting { attempt: 1 });
+    assert_eq!(connection[2], &ConnectionEvent::Connected);
+    assert!(connection
+        .iter()
+        .any(|event| matches!(event, ConnectionEvent::Degraded { .. })));
+    assert!(matches!(
+        connection.last(),
+        Some(ConnectionEvent::Disconnected { .. })
+    ));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, StreamEvent::Update(_))));
+}
+
+#[tokio::test]
+async fn followed_tail_switches_to_the_newest_execution() {
+    let execution = |id: &str, started: &str| {
+        let mut body = common::execution(id, "wf-ingest", "running");
+        body["startedAt"] = started.into();
+        body
+    };
+    let first = execution("ex-1", "2024-01-01T00:00:00Z");
+    let second = execution("ex-2", "2024-01-01T00:05:00Z");
+
+    // History requests over HTTP, execution streams over WebSocket, on one port
+    let (listener, base_url) = listen().await;
+    tokio::spawn(async move {
+        let mut history_calls = 0;
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut head = [0; 64];
+            let read = socket.peek(&mut head).await.unwrap();
+            let head = String::from_utf8_lossy(&head[..read]).to_string();
+            if let Some(rest) = head.strip_prefix("GET /ws/execution/") {
+                let id = rest[..4].to_string();
+                tokio::spawn(async move {
+                    let mut socket = tokio_tungstenite::accept_async(socket).await.unwrap();
+                    let update = serde_json::json!({
+                        "type": "nodeStarted",
+                        "data": { "execution": id },
+                        "timestamp": "2024-01-01T00:00:00Z"
+                    });
+                    socket
+                        .send(Message::Text(update.to_string()))
+                        .await
+                        .unwrap();
+                    while let Some(Ok(_)) = socket.next().await {}
+                });
+            } else {
+                let mut request = vec![0; 4096];
+                let _ = socket.read(&mut request).await.unwrap();
+                history_calls += 1;
+                let executions = if history_calls == 1 {
+                    vec![first.clone()]
+                } else {
+                    vec![first.clone(), second.clone()]
+                };
+                let body = serde_json::json!({ "executions": executions }).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        }
+    });
+
+    // Pinned, since the server answers every HTTP request with the history
+    let client = Client::builder()
+        .base_url(base_url)
+        .api_version(ApiVersion::V1)
+        .build()
+        .unwrap();
+    let events: Vec<TailEvent> = client
+        .tail_latest_execution("wf-ingest", true)
+        .take(3)
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    assert!(matches!(&events[0], TailEvent::Update { execution_id, .. } if execution_id == "ex-1"));
+    assert!(matches!(
+        &events[1],
+        TailEvent::SwitchedExecution { from, to } if from == "ex-1" && to == "ex-2"
+    ));
+    assert!(matches!(&events[2], TailEvent::Update { execution_id, .. } if execution_id == "ex-2"));
+}
+
+/// Server sending an undecodable frame followed by a valid update
+async fn bad_then_good_frame(listener: TcpListener) {
+    let (socket, _) = listener.accept().await.unwrap();
+    let mut socket = tokio_tungstenite::accept_async(socket).await.unwrap();
+    for message in [
+        "{\"type\":",
+        r#"{"type":"nodeStarted","data":{},"timestamp":"2024-01-01T00:00:00Z"}"#,
+    ] {
+        socket
+            .send(Message::Text(message.to_string()))
+            .await
+            .unwrap();
+    }
+    // Keep the connection open until the client is done
+    while let Some(Ok(message)) = socket.next().await {
+        if message.is_close() {
+            break;
+        }
+    }
+}
+
+#[tokio::test]
+async fn undecodable_frame_is_reported_and_skipped() {
+    let (listener, base_url) = listen().await;
+    tokio::spawn(bad_then_good_frame(listener));
+
+    let client = Client::new(base_url);
+    let mut stream = client.stream_execution("ex-1").await.unwrap();
+    match stream.next().await {
+        Some(Err(klikkflow_sdk::Error::MessageDecode { raw, .. })) => assert_eq!(raw, "{\"type\":"),
+        other => panic!("unexpected item: {:?}", other),
+    }
+    let update = stream.next().await.unwrap().unwrap();
+    assert_eq!(update.update_type, "nodeStarted");
+    assert_eq!(stream.decode_failures(), 1);
+}
+
+#[tokio::test]
+async fn strict_stream_ends_at_the_undecodable_frame() {
+    let (listener, base_url) = listen().await;
+    tokio::spawn(bad_then_good_frame(listener));
+
+    let client = Client::new(base_url);
+    let mut stream = client.stream_execution("ex-1").await.unwrap().strict(true);
+    assert!(matches!(
+        stream.next().await,
+        Some(Err(klikkflow_sdk::Error::MessageDecode { .. }))
+    ));
+    assert!(stream.next().await.is_none());
+    assert_eq!(stream.decode_failures(), 1);
+}
+
+/// Subscriber recording the status each span is closed with, by node ID or span name
+#[derive(Clone, Default)]
+struct ClosedSpans(Arc<Mutex<SpanLog>>);
+
+#[derive(Default)]
+struct SpanLog {
+    names: HashMap<u64, String>,
+    closed: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct Fields(HashMap<&'static str, String>);
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name(), format!("{:?}", value));
+    }
+}
+
+impl ClosedSpans {
+    fn closed(&self) -> Vec<(String, String)> {
+        self.0.lock().unwrap().closed.clone()
+    }
+}
+
+impl Subscriber for ClosedSpans {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut fields = Fields::default();
+        span.record(&mut fields);
+        let mut log = self.0.lock().unwrap();
+        let id = log.names.len() as u64 + 1;
+        let name = fields
+            .0
+            .remove("node_id")
+            .unwrap_or_else(|| span.metadata().name().to_string());
+        log.names.insert(id, name);
+        Id::from_u64(id)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        if fields.0.get("message").map(String::as_str) != Some("span closed") {
+            return;
+        }
+        let mut log = self.0.lock().unwrap();
+        let name = event
+            .parent()
+            .and_then(|parent| log.names.get(&parent.into_u64()))
+            .cloned()
+            .unwrap_or_default();
+        let status = fields.0.remove("status").unwrap_or_default();
+        log.closed.push((name, status));
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// Server sending `updates` of `(type, node ID, status)`, then waiting for the client to close
+async fn send_updates(
+    listener: TcpListener,
+    updates: Vec<(&'static str, &'static str, &'static str)>,
+) {
+    let (socket, _) = listener.accept().await.unwrap();
+    let mut socket = tokio_tungstenite::accept_async(socket).await.unwrap();
+    for (kind, node_id, status) in updates {
+        let update = serde_json::json!({
+            "type": kind,
+            "data": { "nodeId": node_id, "status": status },
+            "timestamp": "2024-01-01T00:00:00Z"
+        });
+        socket
+            .send(Message::Text(update.to_string()))
+            .await
+            .unwrap();
+    }
+    while let Some(Ok(message)) = socket.next().await {
+        if message.is_close() {
+            break;
+        }
+    }
+}
+
+#[tokio::test]
+async fn traced_stream_closes_node_spans_with_their_status() {
+    let spans = ClosedSpans::default();
+    let _guard = tracing::subscriber::set_default(spans.clone());
+    let (listener, base_url) = listen().await;
+    tokio::spawn(send_updates(
+        listener,
+        vec![
+            ("nodeStarted", "n1", ""),
+            ("nodeCompleted", "n1", "success"),
+            // Completed before it started, e.g. after a reordering proxy
+            ("nodeCompleted", "n2", "error"),
+            ("nodeStarted", "n2", ""),
+            ("executionCompleted", "", "success"),
+        ],
+    ));
+
+    let client = Client::new(base_url);
+    let stream = client.stream_execution_traced("ex-1").await.unwrap();
+    let updates: Vec<_> = stream.take(5).collect().await;
+    assert!(updates.iter().all(Result::is_ok));
+    assert_eq!(
+        spans.closed(),
+        [
+            ("n1".to_string(), "success".to_string()),
+            ("n2".to_string(), "error".to_string()),
+            ("execution".to_string(), "success".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn traced_stream_abandons_node_spans_without_completion() {
+    let spans = ClosedSpans::default();
+    let _guard = tracing::subscriber::set_default(spans.clone());
+    let (listener, base_url) = listen().await;
+    tokio::spawn(send_updates(listener, vec![("nodeStarted", "n3", "")]));
+
+    let client = Client::new(base_url);
+    let mut stream = client
+        .stream_execution_traced("ex-1")
+        .await
+        .unwrap()
+        .node_timeout(Duration::from_millis(10));
+    stream.next().await.unwrap().unwrap();
+    assert!(spans.closed().is_empty());
+
+    // Node spans are swept at most once a second
+    assert!(
+        tokio::time::timeout(Duration::from_millis(1500), stream.next())
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        spans.closed(),
+        [("n3".to_string(), "abandoned".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn stream_messages_are_checked_against_the_json_limits() {
+    let (listener, base_url) = listen().await;
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(socket).await.unwrap();
+        let nested = format!("{}{}", "[".repeat(50_000), "]".repeat(50_000));
+        for message in [
+            nested.as_str(),
+            r#"{"type":"nodeStarted","data":{},"timestamp":"2024-01-01T00:00:00Z"}"#,
+        ] {
+            socket
+                .send(Message::Text(message.to_string()))
+                .await
+                .unwrap();
+        }
+        while let Some(Ok(message)) = socket.next().await {
+            if message.is_close() {
+                break;
+            }
+        }
+    });
+
+    let client = Client::new(base_url);
+    let mut stream = client.stream_execution("ex-1").await.unwrap();
+    assert!(matches!(
+        stream.next().await,
+        Some(Err(klikkflow_sdk::Error::JsonLimitExceeded {
+            which: klikkflow_sdk::JsonLimit::Depth,
+            ..
+        }))
+    ));
+    assert!(stream.next().await.unwrap().is_ok());
+    assert_eq!(stream.decode_failures(), 1);
+}
+
+#[tokio::test]
+async fn per_call_timeout_bounds_the_stream_connect() {
+    let (listener, base_url) = listen().await;
+    tokio::spawn(async move {
+        // Accept the connection but never answer the handshake
+        let (_socket, _) = listener.accept().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(30)).await;
+    });
+
+    let client = Client::new(base_url)
+        .with_request_options(RequestOptions::new().timeout(Duration::from_millis(200)));
+    let started = std::time::Instant::now();
+    match tokio::time::timeout(Duration::from_secs(5), client.stream_execution("ex-1")).await {
+        Ok(Err(klikkflow_sdk::Error::Timeout(_))) => {}
+        Ok(other) => panic!("expected a timeout, got {:?}", other.map(|_| ())),
+        Err(_) => panic!("the connect was not bounded by the per-call timeout"),
+    }
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+/// Node ID of a shared update, or what else it was
+fn node_of(update: SharedUpdate) -> String {
+    match update {
+        SharedUpdate::Update(update) => update.data["nodeId"].as_str().unwrap().to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fanned_out_receivers_each_get_every_update() {
+    let (listener, base_url) = listen().await;
+    tokio::spawn(send_updates(
+        listener,
+        vec![
+            ("nodeStarted", "n1", ""),
+            ("nodeStarted", "n2", ""),
+            ("nodeStarted", "n3", ""),
+        ],
+    ));
+
+    let client = Client::new(base_url);
+    let receivers = client.stream_execution("ex-1").await.unwrap().fan_out(3);
+    assert_eq!(receivers.len(), 3);
+    for receiver in receivers {
+        let nodes: Vec<_> = receiver.take(3).map(node_of).collect().await;
+        assert_eq!(nodes, ["n1", "n2", "n3"]);
+    }
+}
+
+#[tokio::test]
+async fn receiver_falling_behind_skips_to_the_oldest_buffered_update() {
+    let (listener, base_url) = listen().await;
+    tokio::spawn(send_updates(
+        listener,
+        ["n1", "n2", "n3", "n4", "n5"]
+            .into_iter()
+            .map(|node| ("nodeStarted", node, ""))
+            .collect(),
+    ));
+
+    let client = Client::new(base_url);
+    let mut shared = client.stream_execution("ex-1").await.unwrap().shareable(2);
+    // Let the background task read every update before this receiver does
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(matches!(shared.next().await, Some(SharedUpdate::Lagged(3))));
+    let nodes: Vec<_> = shared.take(2).map(node_of).collect().await;
+    assert_eq!(nodes, ["n4", "n5"]);
+}
+
+#[tokio::test]
+async fn shared_stream_closes_once_every_receiver_is_dropped() {
+    let (listener, base_url) = listen().await;
+    let server = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(socket).await.unwrap();
+        while let Some(Ok(message)) = socket.next().await {
+            if message.is_close() {
+                return true;
+            }
+        }
+        false
+    });
+
+    let client = Client::new(base_url);
+    let mut receivers = client.stream_execution("ex-1").await.unwrap().fan_out(2);
+    let last = receivers.pop().unwrap();
+    let clone = last.clone();
+    drop(receivers);
+    drop(last);
+    // A clone still holds the connection open
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!server.is_finished());
+
+    drop(clone);
+    let closed = tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap();
+    assert!(closed.unwrap());
+}
+
+/// Server answering a pre-subscription with `greeting`, if any, and replays with an update
+///
+/// Returns the requested paths as connections come in.
+#[cfg(feature = "test-util")]
+#[allow(clippy::result_large_err)] // the handshake callback's error is tungstenite's response type
+fn subscription_server(
+    listener: TcpListener,
+    greeting: Option<&'static str>,
+) -> Arc<Mutex<Vec<String>>> {
+    let paths = Arc::new(Mutex::new(Vec::new()));
+    let requested = Arc::clone(&paths);
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            let requested = Arc::clone(&requested);
+            tokio::spawn(async move {
+                let mut path = String::new();
+                let mut socket = tokio_tungstenite::accept_hdr_async(
+                    socket,
+                    |request: &tokio_tungstenite::tungstenite::handshake::server::Request,
+                     response| {
+                        path = request.uri().to_string();
+                        Ok(response)
+                    },
+                )
+                .await
+                .unwrap();
+                requested.lock().unwrap().push(path.clone());
+                let subscribed = match greeting {
+                    Some(greeting) if path.ends_with("/subscribe") => {
+                        socket
+                            .send(Message::Text(greeting.to_string()))
+                            .await
+                            .unwrap();
+                        greeting.contains("subscriptionToken")
+                    }
+                    _ => false,
+                };
+                if subscribed || !path.ends_with("/subscribe") {
+                    let update = r#"{"type":"nodeStarted","data":{"nodeId":"n1"},"timestamp":"2024-01-01T00:00:00Z"}"#;
+                    socket
+                        .send(Message::Text(update.to_string()))
+                        .await
+                        .unwrap();
+                }
+                while let Some(Ok(message)) = socket.next().await {
+                    if message.is_close() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    paths
+}
+
+/// Execute `wf-1` and stream it through `base_url`, returning the execution request body and the first update
+#[cfg(feature = "test-util")]
+async fn execute_and_stream(base_url: String) -> (serde_json::Value, String) {
+    let transport = Arc::new(klikkflow_sdk::MemoryTransport::new().handle(
+        "POST",
+        "/api/executions",
+        |_| common::ok(common::execution("ex-1", "wf-1", "running")),
+    ));
+    let client = Client::builder()
+        .base_url(base_url)
+        .transport(transport.clone())
+        .api_version(ApiVersion::V1)
+        .timeout_profile(klikkflow_sdk::TimeoutProfile {
+            read: Duration::from_secs(1),
+            mutate: Duration::from_secs(1),
+            long_running: Duration::from_secs(1),
+            stream: Duration::from_millis(300),
+        })
+        .build()
+        .unwrap();
+    let (handle, mut stream) = tokio::time::timeout(
+        Duration::from_secs(5),
+        client.execute_and_stream("wf-1", klikkflow_sdk::FieldMap::new()),
+    )
+    .await
+    .expect("execute_and_stream returns")
+    .unwrap();
+    assert_eq!(handle.id(), "ex-1");
+    let update = stream.next().await.unwrap().unwrap();
+    (
+        common::json_body(&transport.requests()[0]),
+        update.update_type,
+    )
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn pre_subscribed_stream_receives_the_execution() {
+    let (listener, base_url) = listen().await;
+    let paths = subscription_server(listener, Some(r#"{"subscriptionToken":"tok-1"}"#));
+    let (request, update) = execute_and_stream(base_url).await;
+    assert_eq!(request["subscriptionToken"], "tok-1");
+    assert_eq!(update, "nodeStarted");
+    assert_eq!(*paths.lock().unwrap(), ["/ws/workflow/wf-1/subscribe"]);
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn pre_subscription_without_a_token_falls_back_to_replay() {
+    // One server never sends a token, the other sends something else
+    for greeting in [None, Some(r#"{"type":"hello"}"#)] {
+        let (listener, base_url) = listen().await;
+        let paths = subscription_server(listener, greeting);
+        let (request, update) = execute_and_stream(base_url).await;
+        assert!(request.get("subscriptionToken").is_none(), "{:?}", greeting);
+        assert_eq!(update, "nodeStarted");
+        assert_eq!(
+            *paths.lock().unwrap(),
+            [
+                "/ws/workflow/wf-1/subscribe",
+                "/ws/execution/ex-1?replay=true"
+            ]
+        );
+    }
+}
@@ -0,0 +1,402 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use common::BASE_URL;
+use klikkflow_sdk::{
+    BucketSize, Client, CreateCredentialRequest, CreateWorkflowRequest, EventType, FieldMap,
+    MemoryTransport, OperationClass, RetentionPolicy, SubscriptionRequest, TimeoutProfile,
+    UpdateWorkflowRequest, UsageGroupBy,
+};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A distinct timeout per class, so a request's timeout names its class
+const PROFILE: TimeoutProfile = TimeoutProfile {
+    read: Duration::from_secs(1),
+    mutate: Duration::from_secs(2),
+    long_running: Duration::from_secs(3),
+    stream: Duration::from_secs(4),
+};
+
+/// Class of the `method` request to `path` that `call` sends
+async fn class_of<F, Fut>(method: &str, path: &str, call: F) -> OperationClass
+where
+    F: FnOnce(Client) -> Fut,
+    Fut: Future,
+{
+    let transport = Arc::new(MemoryTransport::new());
+    let client = Client::builder()
+        .base_url(BASE_URL)
+        .transport(transport.clone())
+        .timeout_profile(PROFILE)
+        .build()
+        .unwrap();
+    // Unhandled requests fail with 404, after the transport has seen them
+    let _ = call(client).await;
+
+    let requests = transport.requests();
+    let request = requests
+        .iter()
+        .find(|request| request.method == method && request.path() == path)
+        .unwrap_or_else(|| panic!("no {} {} request", method, path));
+    [
+        OperationClass::Read,
+        OperationClass::Mutate,
+        OperationClass::LongRunning,
+        OperationClass::Stream,
+    ]
+    .into_iter()
+    .find(|class| request.timeout == Some(PROFILE.timeout_for(*class)))
+    .unwrap_or_else(|| panic!("{} {} timeout {:?}", method, path, request.timeout))
+}
+
+/// Assert that each `(method name, class)` case was sent with the timeout of `expected`
+fn assert_classes(cases: Vec<(&str, OperationClass)>, expected: OperationClass) {
+    for (method, class) in cases {
+        assert_eq!(class, expected, "{}", method);
+    }
+}
+
+#[tokio::test]
+async fn reads_use_the_read_timeout() {
+    let cases = vec![
+        (
+            "health_check",
+            class_of("GET", "/health", |c| async move { c.health_check().await }).await,
+        ),
+        (
+            "list_workflows",
+            class_of("GET", "/api/workflows", |c| async move {
+                c.list_workflows(None).await
+            })
+            .await,
+        ),
+        (
+            "get_workflow",
+            class_of("GET", "/api/workflows/wf-1", |c| async move {
+                c.get_workflow("wf-1").await
+            })
+            .await,
+        ),
+        (
+            "get_workflow_static_data",
+            class_of("GET", "/api/workflows/wf-1/static-data", |c| async move {
+                c.get_workflow_static_data("wf-1").await
+            })
+            .await,
+        ),
+        (
+            "get_workflow_issues",
+            class_of("GET", "/api/workflows/wf-1/issues", |c| async move {
+                c.get_workflow_issues("wf-1").await
+            })
+            .await,
+        ),
+        (
+            "get_workflow_schema",
+            class_of("GET", "/api/workflows/schema", |c| async move {
+                c.get_workflow_schema().await
+            })
+            .await,
+        ),
+        (
+            "get_execution",
+            class_of("GET", "/api/executions/ex-1", |c| async move {
+                c.get_execution("ex-1").await
+            })
+            .await,
+        ),
+        (
+            "get_execution_with_workflow",
+            class_of("GET", "/api/executions/ex-1", |c| async move {
+                c.get_execution_with_workflow("ex-1").await
+            })
+            .await,
+        ),
+        (
+            "get_execution_history",
+            class_of("GET", "/api/workflows/wf-1/executions", |c| async move {
+                c.get_execution_history("wf-1", None).await
+            })
+            .await,
+        ),
+        (
+            "list_active_executions",
+            class_of("GET", "/api/workflows/wf-1/executions", |c| async move {
+                c.list_active_executions("wf-1").await
+            })
+            .await,
+        ),
+        (
+            "get_execution_statistics",
+            class_of("GET", "/api/executions/statistics", |c| async move {
+                c.get_execution_statistics(None).await
+            })
+            .await,
+        ),
+        (
+            "list_execution_artifacts",
+            class_of("GET", "/api/executions/ex-1/artifacts", |c| async move {
+                c.list_execution_artifacts("ex-1").await
+            })
+            .await,
+        ),
+        (
+            "list_node_types",
+            class_of("GET", "/api/node-types", |c| async move {
+                c.list_node_types().await
+            })
+            .await,
+        ),
+        (
+            "server_info",
+            class_of(
+                "GET",
+                "/api/version",
+                |c| async move { c.server_info().await },
+            )
+            .await,
+        ),
+        (
+            "capabilities",
+            class_of("GET", "/api/capabilities", |c| async move {
+                c.capabilities().await
+            })
+            .await,
+        ),
+        (
+            "get_retention_policy",
+            class_of("GET", "/api/settings/retention", |c| async move {
+                c.get_retention_policy().await
+            })
+            .await,
+        ),
+        (
+            "list_event_subscriptions",
+            class_of("GET", "/api/event-subscriptions", |c| async move {
+                c.list_event_subscriptions().await
+            })
+            .await,
+        ),
+    ];
+    assert_classes(cases, OperationClass::Read);
+}
+
+#[tokio::test]
+async fn mutations_use_the_mutate_timeout() {
+    let cases = vec![
+        (
+            "create_workflow",
+            class_of("POST", "/api/workflows", |c| async move {
+                c.create_workflow(CreateWorkflowRequest {
+                    name: "Orders".to_string(),
+                    description: String::new(),
+                    nodes: vec![],
+                    connections: vec![],
+                    settings: None,
+                })
+                .await
+            })
+            .await,
+        ),
+        (
+            "update_workflow",
+            class_of("PUT", "/api/workflows/wf-1", |c| async move {
+                c.update_workflow("wf-1", UpdateWorkflowRequest::default())
+                    .await
+            })
+            .await,
+        ),
+        (
+            "delete_workflow",
+            class_of("DELETE", "/api/workflows/wf-1", |c| async move {
+                c.delete_workflow("wf-1").await
+            })
+            .await,
+        ),
+        (
+            "activate_workflow",
+            class_of("POST", "/api/workflows/wf-1/activate", |c| async move {
+                c.activate_workflow("wf-1").await
+            })
+            .await,
+        ),
+        (
+            "deactivate_workflow",
+            class_of("POST", "/api/workflows/wf-1/deactivate", |c| async move {
+                c.deactivate_workflow("wf-1").await
+            })
+            .await,
+        ),
+        (
+            "set_workflow_static_data",
+            class_of("PUT", "/api/workflows/wf-1/static-data", |c| async move {
+                c.set_workflow_static_data("wf-1", HashMap::new()).await
+            })
+            .await,
+        ),
+        (
+            "clear_workflow_static_data",
+            class_of(
+                "DELETE",
+                "/api/workflows/wf-1/static-data",
+                |c| async move { c.clear_workflow_static_data("wf-1").await },
+            )
+            .await,
+        ),
+        (
+            "update_node_parameters",
+            class_of(
+                "PATCH",
+                "/api/workflows/wf-1/nodes/n1/parameters",
+                |c| async move {
+                    c.update_node_parameters("wf-1", "n1", FieldMap::new(), true)
+                        .await
+                },
+            )
+            .await,
+        ),
+        (
+            "execute_workflow",
+            class_of("POST", "/api/executions", |c| async move {
+                c.execute_workflow("wf-1", FieldMap::new(), false).await
+            })
+            .await,
+        ),
+        (
+            "execute_workflow_ref",
+            class_of("POST", "/api/executions", |c| async move {
+                c.execute_workflow_ref("wf-1", &FieldMap::new()).await
+            })
+            .await,
+        ),
+        (
+            "execute_workflow_raw_input",
+            class_of("POST", "/api/executions", |c| async move {
+                c.execute_workflow_raw_input("wf-1", Bytes::from_static(b"{}"))
+                    .await
+            })
+            .await,
+        ),
+        (
+            "cancel_execution",
+            class_of("POST", "/api/executions/ex-1/cancel", |c| async move {
+                c.cancel_execution("ex-1").await
+            })
+            .await,
+        ),
+        (
+            "create_credential",
+            class_of("POST", "/api/credentials", |c| async move {
+                c.create_credential(CreateCredentialRequest {
+                    name: "Billing".to_string(),
+                    credential_type: "apiKey".to_string(),
+                    integration: "stripe".to_string(),
+                    data: HashMap::new(),
+                    expires_at: None,
+                })
+                .await
+            })
+            .await,
+        ),
+        (
+            "delete_credential",
+            class_of("DELETE", "/api/credentials/cred-1", |c| async move {
+                c.delete_credential("cred-1").await
+            })
+            .await,
+        ),
+        (
+            "test_credential",
+            class_of("POST", "/api/credentials/cred-1/test", |c| async move {
+                c.test_credential("cred-1").await
+            })
+            .await,
+        ),
+        (
+            "set_retention_policy",
+            class_of("PUT", "/api/settings/retention", |c| async move {
+                c.set_retention_policy(RetentionPolicy {
+                    max_age: Duration::from_secs(86_400),
+                    keep_successful: true,
+                    keep_failed: true,
+                })
+                .await
+            })
+            .await,
+        ),
+        (
+            "create_event_subscription",
+            class_of("POST", "/api/event-subscriptions", |c| async move {
+                c.create_event_subscription(SubscriptionRequest {
+                    url: "https://hooks.example.com".to_string(),
+                    events: vec![EventType::ExecutionFailed],
+                    workflow_ids: vec![],
+                    secret: None,
+                })
+                .await
+            })
+            .await,
+        ),
+        (
+            "delete_event_subscription",
+            class_of("DELETE", "/api/event-subscriptions/sub-1", |c| async move {
+                c.delete_event_subscription("sub-1").await
+            })
+            .await,
+        ),
+        (
+            "ping_event_subscription",
+            class_of(
+                "POST",
+                "/api/event-subscriptions/sub-1/ping",
+                |c| async move { c.ping_event_subscription("sub-1").await },
+            )
+            .await,
+        ),
+    ];
+    assert_classes(cases, OperationClass::Mutate);
+}
+
+#[tokio::test]
+async fn reports_over_date_ranges_use_the_long_running_timeout() {
+    let range = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ..Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap();
+    let cases = vec![
+        (
+            "get_usage",
+            class_of("GET", "/api/usage", |c| {
+                let range = range.clone();
+                async move { c.get_usage(range, UsageGroupBy::Workflow).await }
+            })
+            .await,
+        ),
+        (
+            "get_execution_timeseries",
+            class_of("GET", "/api/workflows/wf-1/executions/timeseries", |c| {
+                let range = range.clone();
+                async move {
+                    c.get_execution_timeseries("wf-1", range, BucketSize::Day)
+                        .await
+                }
+            })
+            .await,
+        ),
+    ];
+    assert_classes(cases, OperationClass::LongRunning);
+}
+
+#[tokio::test]
+async fn downloads_use_the_stream_timeout() {
+    let path = "/api/executions/ex-1/artifacts/report/content";
+    let class = class_of("GET", path, |c| async move {
+        c.download_artifact("ex-1", "report", Vec::new()).await
+    })
+    .await;
+    assert_eq!(class, OperationClass::Stream);
+}
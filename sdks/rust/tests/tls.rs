@@ -0,0 +1,52 @@
+use klikkflow_sdk::{Client, ClientBuilder, Error, Result};
+
+fn build_with(configure: impl FnOnce(ClientBuilder) -> ClientBuilder) -> Result<Client> {
+    configure(Client::builder().base_url("https://klikkflow.lab")).build()
+}
+
+#[test]
+fn invalid_root_certificates_fail_the_build() {
+    let client = build_with(|builder| builder.add_root_certificate(b"not a certificate".to_vec()));
+    assert!(matches!(client, Err(Error::Config(_))));
+}
+
+#[test]
+fn invalid_identities_fail_the_build() {
+    use klikkflow_sdk::ClientIdentity;
+
+    let client = build_with(|builder| {
+        builder.identity(ClientIdentity::pkcs12(
+            b"not an archive".to_vec(),
+            "password",
+        ))
+    });
+    assert!(matches!(client, Err(Error::Config(_))));
+}
+
+#[cfg(feature = "native-tls")]
+#[test]
+fn native_tls_accepts_invalid_certificates() {
+    assert!(build_with(|builder| builder.danger_accept_invalid_certs(true)).is_ok());
+}
+
+/// API requests and execution streams reject the same settings
+#[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+#[test]
+fn rustls_rejects_settings_it_cannot_apply() {
+    use klikkflow_sdk::ClientIdentity;
+
+    let lab = build_with(|builder| builder.danger_accept_invalid_certs(true));
+    assert!(matches!(lab, Err(Error::Config(message)) if message.contains("native-tls")));
+
+    let archive = build_with(|builder| {
+        builder.identity(ClientIdentity::pkcs12(b"archive".to_vec(), "password"))
+    });
+    assert!(matches!(archive, Err(Error::Config(message)) if message.contains("PKCS #12")));
+}
+
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+#[test]
+fn tls_settings_require_a_backend() {
+    let lab = build_with(|builder| builder.danger_accept_invalid_certs(true));
+    assert!(matches!(lab, Err(Error::Config(message)) if message.contains("feature")));
+}
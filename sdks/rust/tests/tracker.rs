@@ -0,0 +1,210 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::{client, count, execution, ok, sequence, BASE_URL};
+use futures_util::future::{BoxFuture, FutureExt};
+use klikkflow_sdk::{
+    Client, Error, ExecuteOptions, ExecutionTracker, FieldMap, MemoryTransport, Transport,
+    TransportRequest, TransportResponse,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Transport whose execution `ex-1` is `running` until polled `polls` times, then succeeds
+fn finishing_after(polls: usize) -> Arc<MemoryTransport> {
+    let mut statuses = vec![ok(execution("ex-1", "wf-1", "running")); polls];
+    statuses.push(ok(execution("ex-1", "wf-1", "success")));
+    Arc::new(
+        MemoryTransport::new()
+            .handle("POST", "/api/executions", |_| {
+                ok(execution("ex-1", "wf-1", "running"))
+            })
+            .handle("GET", "/api/executions/ex-1", sequence(statuses)),
+    )
+}
+
+/// Transport whose execution `ex-1` stays running
+fn never_finishing() -> Arc<MemoryTransport> {
+    Arc::new(
+        MemoryTransport::new()
+            .handle("POST", "/api/executions", |_| {
+                ok(execution("ex-1", "wf-1", "running"))
+            })
+            .handle("GET", "/api/executions/ex-1", |_| {
+                ok(execution("ex-1", "wf-1", "running"))
+            }),
+    )
+}
+
+/// Fresh tracker file path in the temporary directory, unique to this test
+fn tracker_file(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "klikkflow-tracker-{}-{}.json",
+        std::process::id(),
+        test
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// IDs stored in the tracker file at `path`
+fn stored(path: &Path) -> Vec<String> {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn drain_waits_for_tracked_executions_and_refuses_new_ones() {
+    let transport = finishing_after(1);
+    let tracker = ExecutionTracker::new(client(&transport));
+    let execution = tracker
+        .execute("wf-1", FieldMap::new(), ExecuteOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(execution.id, "ex-1");
+    assert_eq!(tracker.tracked(), ["ex-1"]);
+
+    assert!(tracker
+        .drain(Duration::from_secs(5))
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(tracker.tracked().is_empty());
+    assert!(matches!(
+        tracker
+            .execute("wf-1", FieldMap::new(), ExecuteOptions::new())
+            .await,
+        Err(Error::Draining)
+    ));
+    assert_eq!(count(&transport, "POST", "/api/executions"), 1);
+}
+
+#[tokio::test]
+async fn drain_returns_the_executions_still_running_at_its_deadline() {
+    let transport = never_finishing();
+    let tracker = ExecutionTracker::new(client(&transport));
+    tracker
+        .execute("wf-1", FieldMap::new(), ExecuteOptions::new())
+        .await
+        .unwrap();
+    let remaining = tracker.drain(Duration::from_millis(300)).await.unwrap();
+    assert_eq!(remaining, ["ex-1"]);
+    assert_eq!(tracker.tracked(), ["ex-1"]);
+}
+
+/// Transport answering requests only after a delay, like a slow server
+struct Slow(Arc<MemoryTransport>, Duration);
+
+impl Transport for Slow {
+    fn execute(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, klikkflow_sdk::Result<TransportResponse>> {
+        async move {
+            tokio::time::sleep(self.1).await;
+            self.0.execute(request).await
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test]
+async fn drain_waits_for_executions_being_submitted() {
+    let transport = finishing_after(0);
+    let client = Client::builder()
+        .base_url(BASE_URL)
+        .transport(Arc::new(Slow(
+            transport.clone(),
+            Duration::from_millis(300),
+        )))
+        .api_version(klikkflow_sdk::ApiVersion::V1)
+        .build()
+        .unwrap();
+    let tracker = ExecutionTracker::new(client);
+    let submitting = tokio::spawn({
+        let tracker = tracker.clone();
+        async move {
+            tracker
+                .execute("wf-1", FieldMap::new(), ExecuteOptions::new())
+                .await
+        }
+    });
+    // Let the submission pass the draining check
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let remaining = tracker.drain(Duration::from_secs(5)).await.unwrap();
+    assert!(remaining.is_empty());
+    assert!(submitting.is_finished());
+    assert_eq!(submitting.await.unwrap().unwrap().id, "ex-1");
+    // The execution submitted during the drain was waited for
+    assert_eq!(count(&transport, "GET", "/api/executions/ex-1"), 1);
+}
+
+#[tokio::test]
+async fn waited_executions_are_tracked_from_their_start() {
+    let transport = never_finishing();
+    let tracker = ExecutionTracker::new(client(&transport));
+    let options = ExecuteOptions::new()
+        .wait_for_completion(true)
+        .budget(Duration::from_millis(200));
+    assert!(tracker
+        .execute("wf-1", FieldMap::new(), options)
+        .await
+        .is_err());
+    assert_eq!(tracker.tracked(), ["ex-1"]);
+
+    let transport = finishing_after(0);
+    let tracker = ExecutionTracker::new(client(&transport));
+    let options = ExecuteOptions::new().wait_for_completion(true);
+    let execution = tracker
+        .execute("wf-1", FieldMap::new(), options)
+        .await
+        .unwrap();
+    assert!(execution.status.is_terminal());
+    assert!(tracker.tracked().is_empty());
+}
+
+#[tokio::test]
+async fn tracked_executions_are_persisted_and_resumed() {
+    let path = tracker_file("resume");
+    let transport = finishing_after(0);
+    let tracker = ExecutionTracker::with_persistence(client(&transport), &path).unwrap();
+    tracker
+        .execute("wf-1", FieldMap::new(), ExecuteOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(stored(&path), ["ex-1"]);
+    drop(tracker);
+
+    // A restarted process picks up where the previous one left off
+    let resumed = ExecutionTracker::with_persistence(client(&transport), &path).unwrap();
+    assert_eq!(resumed.tracked(), ["ex-1"]);
+    assert!(resumed
+        .drain(Duration::from_secs(5))
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(stored(&path).is_empty());
+    std::fs::remove_file(&path).unwrap();
+
+    std::fs::write(&path, "not json").unwrap();
+    assert!(matches!(
+        ExecutionTracker::with_persistence(client(&transport), &path),
+        Err(Error::Config(_))
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn failing_to_persist_does_not_fail_the_execution() {
+    let path = tracker_file("unwritable").join("tracked.json");
+    let transport = finishing_after(0);
+    let tracker = ExecutionTracker::with_persistence(client(&transport), &path).unwrap();
+    let execution = tracker
+        .execute("wf-1", FieldMap::new(), ExecuteOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(execution.id, "ex-1");
+    assert_eq!(tracker.tracked(), ["ex-1"]);
+}
@@ -0,0 +1,129 @@
+//! Requests to a `unix://` base URL, over a Unix domain socket
+#![cfg(unix)]
+
+use klikkflow_sdk::{ApiVersion, Client, Error};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixListener;
+
+/// Fresh socket path in the temporary directory, unique to this test
+fn socket_path(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("klikkflow-{}-{}.sock", std::process::id(), test));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Client for the socket at `path`
+fn client(path: &Path) -> Client {
+    Client::builder()
+        .base_url(format!("unix://{}", path.display()))
+        .api_version(ApiVersion::V1)
+        .build()
+        .unwrap()
+}
+
+/// Error message of a failed health check
+async fn health_error(client: &Client) -> String {
+    match client.health_check().await {
+        Err(error) => error.to_string(),
+        Ok(_) => panic!("health check succeeded"),
+    }
+}
+
+#[tokio::test]
+async fn requests_are_sent_over_the_socket() {
+    let path = socket_path("requests");
+    let listener = UnixListener::bind(&path).unwrap();
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0; 4096];
+        let read = socket.read(&mut request).await.unwrap();
+        let body = r#"{"status":"ok"}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request[..read]).into_owned()
+    });
+
+    client(&path).health_check().await.unwrap();
+    let request = server.await.unwrap();
+    assert!(
+        request.starts_with("GET /health HTTP/1.1\r\n"),
+        "{}",
+        request
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn missing_socket_is_named_in_the_error() {
+    let path = socket_path("missing");
+    let message = health_error(&client(&path)).await;
+    assert!(
+        message.contains(&format!("Unix socket {} does not exist", path.display())),
+        "{}",
+        message
+    );
+}
+
+#[tokio::test]
+async fn inaccessible_socket_is_reported_as_permission_denied() {
+    let path = socket_path("denied");
+    let _listener = UnixListener::bind(&path).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o000)).unwrap();
+    if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+        // Running as root, which may connect regardless of permissions
+        std::fs::remove_file(&path).unwrap();
+        return;
+    }
+
+    let message = health_error(&client(&path)).await;
+    std::fs::remove_file(&path).unwrap();
+    assert!(
+        message.contains(&format!(
+            "permission denied connecting to Unix socket {}",
+            path.display()
+        )),
+        "{}",
+        message
+    );
+}
+
+#[tokio::test]
+async fn streams_are_unsupported_over_a_socket() {
+    let client = client(&socket_path("streams"));
+    assert!(matches!(
+        client.stream_execution("ex-1").await,
+        Err(Error::Unsupported(_))
+    ));
+    assert!(matches!(
+        client.download_artifact("ex-1", "report", Vec::new()).await,
+        Err(Error::Unsupported(_))
+    ));
+}
+
+#[test]
+fn fallback_urls_are_rejected_with_a_socket() {
+    let base_url = "unix:///run/klikkflow.sock";
+    let fallbacks = ["https://klikkflow.example.com"];
+    assert!(matches!(
+        Client::builder()
+            .base_url(base_url)
+            .fallback_urls(fallbacks)
+            .build(),
+        Err(Error::Config(_))
+    ));
+    assert!(matches!(
+        Client::new(base_url).with_fallback_urls(fallbacks),
+        Err(Error::Config(_))
+    ));
+    // Nor can a socket serve as a fallback
+    assert!(matches!(
+        Client::new("https://klikkflow.example.com").with_fallback_urls([base_url]),
+        Err(Error::Config(_))
+    ));
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::{client, ok, query_param};
+use klikkflow_sdk::{MemoryTransport, UsageGroupBy, UsageReport};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn at(timestamp: &str) -> DateTime<Utc> {
+    timestamp.parse().unwrap()
+}
+
+/// Usage of two projects from `from` to February
+fn usage(from: &str, partial: bool) -> Value {
+    json!({
+        "from": from,
+        "to": "2024-02-01T00:00:00Z",
+        "groupBy": "project",
+        "groups": [
+            { "key": "p-1", "name": "Billing", "executionCount": 31, "executionSeconds": 93.5 },
+            { "key": "p-2", "executionCount": 2, "executionSeconds": 0.25 },
+        ],
+        "partial": partial,
+    })
+}
+
+async fn fetch(body: Value) -> (UsageReport, Arc<MemoryTransport>) {
+    let transport =
+        Arc::new(MemoryTransport::new().handle("GET", "/api/usage", move |_| ok(body.clone())));
+    let report = client(&transport)
+        .get_usage(
+            at("2024-01-01T00:00:00Z")..at("2024-02-01T00:00:00Z"),
+            UsageGroupBy::Project,
+        )
+        .await
+        .unwrap();
+    (report, transport)
+}
+
+fn csv(report: &UsageReport) -> String {
+    let mut csv = Vec::new();
+    report.write_csv(&mut csv).unwrap();
+    String::from_utf8(csv).unwrap()
+}
+
+#[tokio::test]
+async fn usage_is_requested_for_the_range_and_grouping() {
+    let (report, transport) = fetch(usage("2024-01-01T00:00:00Z", false)).await;
+    let request = &transport.requests()[0];
+    assert_eq!(query_param(request, "from"), Some("2024-01-01T00:00:00Z"));
+    assert_eq!(query_param(request, "to"), Some("2024-02-01T00:00:00Z"));
+    assert_eq!(query_param(request, "groupBy"), Some("project"));
+
+    assert!(!report.partial);
+    assert_eq!(report.total_executions(), 33);
+    assert_eq!(report.total_execution_seconds(), 93.75);
+    assert_eq!(
+        csv(&report),
+        "project,name,executions,execution_seconds,from,to,partial\n\
+         p-1,Billing,31,93.5,2024-01-01T00:00:00Z,2024-02-01T00:00:00Z,false\n\
+         p-2,,2,0.25,2024-01-01T00:00:00Z,2024-02-01T00:00:00Z,false\n"
+    );
+}
+
+#[tokio::test]
+async fn report_starting_after_the_requested_range_is_partial() {
+    let (report, _) = fetch(usage("2024-01-15T00:00:00Z", false)).await;
+    assert!(report.partial);
+    assert!(csv(&report).ends_with("2024-01-15T00:00:00Z,2024-02-01T00:00:00Z,true\n"));
+
+    // The server's own flag is kept
+    let (report, _) = fetch(usage("2024-01-01T00:00:00Z", true)).await;
+    assert!(report.partial);
+}
+
+#[test]
+fn csv_fields_are_quoted_when_needed() {
+    let mut body = usage("2024-01-01T00:00:00Z", false);
+    body["groups"] = json!([
+        { "key": "wf,1", "name": "Sync, nightly", "executionCount": 1, "executionSeconds": 1.0 },
+        { "key": "wf-2", "name": "The \"main\" one", "executionCount": 1, "executionSeconds": 1.0 },
+        { "key": "wf-3", "name": "Two\nlines", "executionCount": 1, "executionSeconds": 1.0 },
+    ]);
+    let report: UsageReport = serde_json::from_value(body).unwrap();
+    let range = "2024-01-01T00:00:00Z,2024-02-01T00:00:00Z,false";
+    assert_eq!(
+        csv(&report),
+        format!(
+            "project,name,executions,execution_seconds,from,to,partial\n\
+             \"wf,1\",\"Sync, nightly\",1,1,{0}\n\
+             wf-2,\"The \"\"main\"\" one\",1,1,{0}\n\
+             wf-3,\"Two\nlines\",1,1,{0}\n",
+            range
+        )
+    );
+}
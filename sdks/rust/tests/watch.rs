@@ -0,0 +1,192 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use common::{client, execution, json_body, ok, query_param, status};
+use futures_util::StreamExt;
+use klikkflow_sdk::{
+    ExecutionResult, FailureWebhook, MemoryTransport, TransportRequest, WatchOptions,
+};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Execution of `wf-1` that finished `minute` minutes into the day
+fn finished(id: &str, status: &str, minute: u32) -> Value {
+    let mut body = execution(id, "wf-1", status);
+    body["finishedAt"] = format!("2024-01-01T00:{:02}:00Z", minute).into();
+    if status == "error" {
+        body["error"] = format!("{} broke", id).into();
+    }
+    body
+}
+
+/// Handler serving the history of `wf-1`, newest first, one round per poll
+///
+/// A poll starts with the first page; the last round is repeated.
+fn history(
+    rounds: Vec<Vec<Value>>,
+) -> impl Fn(&TransportRequest) -> klikkflow_sdk::TransportResponse + Send + Sync + 'static {
+    let polls = AtomicUsize::new(0);
+    move |request| {
+        let offset: usize = query_param(request, "offset").map_or(0, |o| o.parse().unwrap());
+        let limit: usize = query_param(request, "limit").unwrap().parse().unwrap();
+        let round = if offset == 0 {
+            polls.fetch_add(1, Ordering::SeqCst)
+        } else {
+            polls.load(Ordering::SeqCst) - 1
+        };
+        let executions = &rounds[round.min(rounds.len() - 1)];
+        let page: Vec<Value> = executions
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect();
+        ok(json!({ "executions": page }))
+    }
+}
+
+fn options() -> WatchOptions {
+    WatchOptions::new().poll_interval(Duration::from_millis(10))
+}
+
+async fn watched(transport: MemoryTransport, options: WatchOptions, count: usize) -> Vec<String> {
+    let transport = Arc::new(transport);
+    client(&transport)
+        .watch_executions("wf-1", options)
+        .take(count)
+        .map(|execution| execution.unwrap().id)
+        .collect()
+        .await
+}
+
+#[tokio::test]
+async fn executions_finished_after_the_first_poll_are_yielded_oldest_first() {
+    let before = finished("ex-1", "error", 0);
+    let transport = MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows/wf-1/executions",
+        history(vec![
+            vec![before.clone()],
+            vec![
+                finished("ex-3", "error", 3),
+                finished("ex-2", "success", 2),
+                before,
+            ],
+        ]),
+    );
+    assert_eq!(watched(transport, options(), 2).await, ["ex-2", "ex-3"]);
+}
+
+#[tokio::test]
+async fn executions_beyond_the_first_page_are_not_missed() {
+    let before = finished("ex-0", "success", 0);
+    let mut after: Vec<Value> = (1..=5)
+        .rev()
+        .map(|i| finished(&format!("ex-{}", i), "success", i))
+        .collect();
+    after.push(before.clone());
+    let transport = MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows/wf-1/executions",
+        history(vec![vec![before], after]),
+    );
+    let mut options = options();
+    options.page_size = 2;
+    assert_eq!(
+        watched(transport, options, 5).await,
+        ["ex-1", "ex-2", "ex-3", "ex-4", "ex-5"]
+    );
+}
+
+#[tokio::test]
+async fn executions_running_at_the_last_poll_are_found_on_later_pages() {
+    let transport = MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows/wf-1/executions",
+        history(vec![
+            vec![execution("ex-1", "wf-1", "running")],
+            vec![
+                finished("ex-4", "success", 4),
+                finished("ex-3", "success", 3),
+                finished("ex-2", "success", 2),
+                finished("ex-1", "error", 5),
+            ],
+        ]),
+    );
+    let mut options = options();
+    options.page_size = 2;
+    assert_eq!(
+        watched(transport, options, 4).await,
+        ["ex-2", "ex-3", "ex-4", "ex-1"]
+    );
+}
+
+#[tokio::test]
+async fn failure_callbacks_are_debounced_and_survive_panics() {
+    let transport = MemoryTransport::new().handle(
+        "GET",
+        "/api/workflows/wf-1/executions",
+        history(vec![
+            vec![],
+            vec![finished("ex-2", "error", 2), finished("ex-1", "error", 1)],
+        ]),
+    );
+    let alerted = Arc::new(Mutex::new(Vec::new()));
+    let options = options()
+        .on_failure(|_| panic!("alerting is down"))
+        .on_failure({
+            let alerted = alerted.clone();
+            move |execution: &ExecutionResult| alerted.lock().unwrap().push(execution.id.clone())
+        })
+        .debounce_failures(Duration::from_secs(3600));
+
+    assert_eq!(watched(transport, options, 2).await, ["ex-1", "ex-2"]);
+    assert_eq!(*alerted.lock().unwrap(), ["ex-1"]);
+}
+
+#[tokio::test]
+async fn failure_webhook_receives_the_rendered_template() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle(
+                "GET",
+                "/api/workflows/wf-1/executions",
+                history(vec![
+                    vec![],
+                    vec![finished("ex-2", "success", 2), finished("ex-1", "error", 1)],
+                ]),
+            )
+            .handle("POST", "/alerts", |_| status(204, json!({}))),
+    );
+    let options = options().failure_webhook(
+        FailureWebhook::new("https://hooks.example.com/alerts").with_template(json!({
+            "text": "{{workflow_id}}: {{execution_id}} is {{status}} ({{error}})",
+            "tags": ["{{status}}"],
+            "priority": 1
+        })),
+    );
+    let ids: Vec<String> = client(&transport)
+        .with_api_key("your-api-key")
+        .watch_executions("wf-1", options)
+        .take(2)
+        .map(|execution| execution.unwrap().id)
+        .collect()
+        .await;
+    assert_eq!(ids, ["ex-1", "ex-2"]);
+
+    let hooks: Vec<TransportRequest> = transport
+        .requests()
+        .into_iter()
+        .filter(|request| request.path() == "/alerts")
+        .collect();
+    assert_eq!(hooks.len(), 1);
+    assert_eq!(hooks[0].url, "https://hooks.example.com/alerts");
+    assert!(!hooks[0].headers.contains_key("authorization"));
+    assert_eq!(
+        json_body(&hooks[0]),
+        json!({ "text": "wf-1: ex-1 is error (ex-1 broke)", "tags": ["error"], "priority": 1 })
+    );
+}
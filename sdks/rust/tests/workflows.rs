@@ -0,0 +1,1116 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{client, count, json_body, ok, query_param, sequence, status, with_header, workflow};
+use futures_util::{StreamExt, TryStreamExt};
+use klikkflow_sdk::lint::Severity;
+use klikkflow_sdk::{
+    ComplexityThresholds, ConsistencyOptions, CreateCredentialRequest, CreateWorkflowRequest,
+    DataSavingPolicy, DeploymentPlan, Error, ListWorkflowsOptions, MemoryTransport, Resource,
+    RetentionPolicy, RotationOptions, ScanCheckpoint, ScanOptions, SearchOptions, SyncAction,
+    SyncOptions, UpdateWorkflowRequest, WorkflowDefinition, CONSISTENCY_TOKEN_HEADER,
+    MAX_STATIC_DATA_BYTES,
+};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Workflow `id` with a single HTTP request node taking `parameters`
+fn with_step(id: &str, parameters: Value) -> Value {
+    let mut body = workflow(id, id);
+    body["nodes"] = json!([{
+        "id": "n1",
+        "name": "Step",
+        "type": "http-request",
+        "position": { "x": 0.0, "y": 0.0 },
+        "parameters": parameters
+    }]);
+    body
+}
+
+/// Transport listing `workflows` on `GET /api/workflows`
+fn listing(workflows: Vec<Value>) -> MemoryTransport {
+    MemoryTransport::new().handle("GET", "/api/workflows", move |_| {
+        ok(json!({ "workflows": workflows.clone() }))
+    })
+}
+
+#[test]
+fn complexity_report_measures_the_workflow() {
+    let node = |id: &str, parameters: Value| {
+        json!({
+            "id": id,
+            "name": id,
+            "type": "set",
+            "position": { "x": 0.0, "y": 0.0 },
+            "parameters": parameters
+        })
+    };
+    let link = |from: &str, to: &str| {
+        json!({
+            "source": { "nodeId": from },
+            "destination": { "nodeId": to }
+        })
+    };
+    let mut body = workflow("wf-1", "Sync");
+    body["nodes"] = json!([
+        node("trigger", json!({})),
+        node(
+            "fetch",
+            json!({ "credentialId": "cred-1", "url": "{{ $json.url }}" })
+        ),
+        node(
+            "store",
+            json!({ "rows": ["{{ $json.a }}", { "b": "{{ $json.b }}" }] })
+        ),
+    ]);
+    body["connections"] = json!([
+        link("trigger", "fetch"),
+        link("fetch", "store"),
+        link("trigger", "store")
+    ]);
+    let workflow: WorkflowDefinition = serde_json::from_value(body).unwrap();
+
+    let report = workflow.complexity_report();
+    assert_eq!((report.node_count, report.connection_count), (3, 3));
+    assert_eq!(report.max_branch_depth, 3);
+    assert_eq!(report.expression_count, 3);
+    assert_eq!(report.credential_references, 1);
+    assert_eq!(
+        report.serialized_bytes,
+        serde_json::to_vec(&workflow).unwrap().len()
+    );
+    assert!(report.findings.is_empty());
+
+    let strict = ComplexityThresholds::new()
+        .max_branch_depth(2)
+        .severity(Severity::Error);
+    let report = workflow.complexity_report_with(&strict);
+    assert_eq!(report.findings.len(), 1);
+    assert_eq!(report.findings[0].rule, "max-branch-depth");
+    assert_eq!(report.findings[0].severity, Severity::Error);
+}
+
+#[tokio::test]
+async fn complexity_of_all_workflows_is_written_as_csv() {
+    let transport = Arc::new(listing(vec![workflow("wf-1", "Sync, nightly")]));
+    let mut csv = Vec::new();
+    let thresholds = ComplexityThresholds::new().max_serialized_bytes(100);
+    let rows = client(&transport)
+        .report_all(None, &thresholds, &mut csv)
+        .await
+        .unwrap();
+    assert_eq!(rows, 1);
+    let csv = String::from_utf8(csv).unwrap();
+    let row = csv.lines().nth(1).unwrap();
+    assert!(row.starts_with("wf-1,\"Sync, nightly\",0,0,"));
+    assert!(row.ends_with(",0,0,0,max-serialized-bytes"));
+}
+
+#[tokio::test]
+async fn rotation_keeps_the_old_credential_while_still_in_use() {
+    let uses = |id: &str, credential: &str| with_step(id, json!({ "credentialId": credential }));
+    let (wf_1, wf_2) = (uses("wf-1", "old"), uses("wf-2", "old"));
+    let rotated = uses("wf-1", "new");
+    let transport = Arc::new(
+        listing(vec![wf_1.clone(), wf_2.clone(), uses("wf-3", "other")])
+            .handle("GET", "/api/workflows/wf-1", move |_| ok(wf_1.clone()))
+            .handle("GET", "/api/workflows/wf-2", move |_| ok(wf_2.clone()))
+            .handle("POST", "/api/credentials", |_| {
+                ok(json!({ "id": "new", "name": "Stripe", "type": "apiKey", "integration": "stripe" }))
+            })
+            .handle("POST", "/api/credentials/new/test", |_| {
+                ok(json!({ "success": true, "message": "Credential test successful" }))
+            })
+            .handle("PUT", "/api/workflows/wf-1", move |request| {
+                let nodes = &json_body(request)["nodes"];
+                assert_eq!(nodes[0]["parameters"]["credentialId"], "new");
+                ok(rotated.clone())
+            })
+            .handle("PUT", "/api/workflows/wf-2", |_| status(409, json!({})))
+            .handle("DELETE", "/api/credentials/old", |_| ok(json!({}))),
+    );
+    let client = client(&transport);
+    let new_credential = CreateCredentialRequest {
+        name: "Stripe".to_string(),
+        credential_type: "apiKey".to_string(),
+        integration: "stripe".to_string(),
+        data: [("apiKey".to_string(), json!("sk_live_2"))]
+            .into_iter()
+            .collect(),
+        expires_at: None,
+    };
+
+    let dry_run = RotationOptions {
+        dry_run: true,
+        ..Default::default()
+    };
+    let plan = client
+        .rotate_credential("old", new_credential.clone(), dry_run)
+        .await
+        .unwrap();
+    assert_eq!(plan.usages.len(), 2);
+    assert!(plan.new_credential.is_none());
+    assert_eq!(count(&transport, "POST", "/api/credentials"), 0);
+
+    let options = RotationOptions {
+        delete_old: true,
+        ..Default::default()
+    };
+    let rotation = client
+        .rotate_credential("old", new_credential, options)
+        .await
+        .unwrap();
+    assert!(!rotation.is_complete());
+    assert_eq!(rotation.updated_workflows, ["wf-1"]);
+    assert_eq!(rotation.failures[0].workflow_id, "wf-2");
+    // wf-2 still needs the old credential
+    assert!(!rotation.old_deleted);
+    assert_eq!(count(&transport, "DELETE", "/api/credentials/old"), 0);
+}
+
+#[tokio::test]
+async fn dependency_graph_follows_sub_workflows() {
+    let transport = Arc::new(listing(vec![
+        with_step(
+            "billing",
+            json!({ "credentialId": "stripe", "url": "https://{{ $vars.region }}.api.example.com" }),
+        ),
+        with_step("nightly", json!({ "workflowId": "billing" })),
+        with_step("report", json!({ "body": "{{ $vars[\"region\"] }}" })),
+    ]));
+    let graph = client(&transport)
+        .build_dependency_graph(ListWorkflowsOptions::default())
+        .await
+        .unwrap();
+
+    let stripe = Resource::Credential("stripe".to_string());
+    assert_eq!(graph.dependents_of(&stripe)[0].workflow_id, "billing");
+    // Deleting the credential also breaks the workflow running billing
+    assert_eq!(
+        graph.affected_by(&stripe).into_iter().collect::<Vec<_>>(),
+        ["billing", "nightly"]
+    );
+
+    let region = Resource::Variable("region".to_string());
+    assert_eq!(graph.affected_by(&region).len(), 3);
+    assert!(graph
+        .to_dot()
+        .contains(r#""workflow:nightly" -> "workflow:billing";"#));
+}
+
+#[tokio::test]
+async fn failed_deployment_is_rolled_back() {
+    let deployed = |id: &str, name: &str| {
+        let mut body = workflow(id, name);
+        body["active"] = json!(id != "wf-refunds");
+        body
+    };
+    // Updates carry the new name; rollbacks restore the whole workflow
+    let update = |id: &'static str, accept: bool| {
+        move |request: &klikkflow_sdk::TransportRequest| {
+            let body = json_body(request);
+            match body["name"].as_str() {
+                Some(name) if name.ends_with(" v2") && !accept => status(422, json!({})),
+                Some(name) => ok(deployed(id, name)),
+                None => panic!("update of {} without a name", id),
+            }
+        }
+    };
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/workflows/wf-orders", move |_| {
+                ok(deployed("wf-orders", "wf-orders"))
+            })
+            .handle("GET", "/api/workflows/wf-billing", move |_| {
+                ok(deployed("wf-billing", "wf-billing"))
+            })
+            .handle("POST", "/api/workflows", move |_| {
+                ok(deployed("wf-refunds", "wf-refunds"))
+            })
+            .handle("PUT", "/api/workflows/wf-orders", update("wf-orders", true))
+            .handle(
+                "PUT",
+                "/api/workflows/wf-billing",
+                update("wf-billing", false),
+            )
+            .handle("DELETE", "/api/workflows/wf-refunds", |_| {
+                status(403, json!({}))
+            }),
+    );
+
+    let rename = |name: &str| UpdateWorkflowRequest {
+        name: Some(name.to_string()),
+        ..Default::default()
+    };
+    let plan = DeploymentPlan::new()
+        .create(CreateWorkflowRequest {
+            name: "wf-refunds".to_string(),
+            description: String::new(),
+            nodes: vec![],
+            connections: vec![],
+            settings: None,
+        })
+        .update("wf-orders", rename("wf-orders v2"))
+        .update("wf-billing", rename("wf-billing v2"))
+        .activate("wf-billing");
+
+    let report = client(&transport).deploy(plan).await.unwrap();
+    assert!(!report.is_success());
+    assert_eq!(report.applied.len(), 2);
+    assert_eq!(report.failure.as_ref().unwrap().step, 2);
+    assert_eq!(report.rolled_back, ["wf-billing", "wf-orders"]);
+    // The new workflow is still there
+    assert!(report.needs_manual_intervention());
+    assert_eq!(report.rollback_failures[0].workflow_id, "wf-refunds");
+
+    let restores: Vec<Value> = transport
+        .requests()
+        .iter()
+        .filter(|request| request.method == "PUT")
+        .map(json_body)
+        .filter(|body| {
+            body["name"]
+                .as_str()
+                .is_some_and(|name| !name.ends_with(" v2"))
+        })
+        .collect();
+    assert_eq!(restores.len(), 2);
+    for restore in restores {
+        assert_eq!(restore["active"], true);
+        assert_eq!(restore["nodes"], json!([]));
+    }
+}
+
+#[tokio::test]
+async fn scan_is_paced_by_the_rate_limit_and_resumes_from_a_checkpoint() {
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/workflows", |request| {
+            let offset: usize = query_param(request, "offset").unwrap().parse().unwrap();
+            let remaining = match offset {
+                0 => "100",
+                2 => "10",
+                _ => "90",
+            };
+            let items: Vec<Value> = (offset + 1..=offset + 2)
+                .map(|n| workflow(&format!("wf-{}", n), "Scanned"))
+                .collect();
+            let page = ok(json!({ "workflows": items, "hasMore": offset < 4 }));
+            let page = with_header(page, "x-ratelimit-limit", "100");
+            with_header(page, "x-ratelimit-remaining", remaining)
+        }),
+    );
+    let client = client(&transport);
+    let options = ListWorkflowsOptions {
+        limit: Some(2),
+        ..Default::default()
+    };
+    let scan = ScanOptions {
+        pages_per_second: Some(20.0),
+        adaptive: true,
+    };
+
+    // Interrupted after three workflows
+    let started = Instant::now();
+    let first: Vec<_> = client
+        .scan_workflows(options.clone(), scan, None)
+        .take(3)
+        .try_collect()
+        .await
+        .unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    let saved = serde_json::to_string(&first[2].checkpoint).unwrap();
+
+    // Picked up the next night; 10 of 100 requests left makes it wait 5x longer
+    let resume: ScanCheckpoint = serde_json::from_str(&saved).unwrap();
+    let started = Instant::now();
+    let rest: Vec<String> = client
+        .scan_workflows(options, scan, Some(resume))
+        .map_ok(|scanned| scanned.item.id)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(rest, ["wf-4", "wf-5", "wf-6"]);
+    assert!(started.elapsed() >= Duration::from_millis(250));
+}
+
+#[tokio::test]
+async fn execution_errors_in_range_are_searched() {
+    let day = |d: u32| Utc.with_ymd_and_hms(2024, 3, d, 12, 0, 0).unwrap();
+    let row = |id: &str, status: &str, d: u32| json!({ "id": id, "status": status, "startedAt": day(d), "error": null });
+    let failed = |id: &str, d: u32, node_error: &str| {
+        let mut body = common::execution(id, "wf-1", "error");
+        body["startedAt"] = json!(day(d));
+        body["error"] = json!("Node fetch failed");
+        body["nodeResults"] = json!({ "fetch": { "status": "error", "error": node_error } });
+        body
+    };
+    let history = json!({ "total": 4, "executions": [
+        row("ex-4", "error", 9), row("ex-3", "success", 8),
+        row("ex-2", "error", 7), row("ex-1", "error", 1),
+    ]});
+    let (reset, unauthorized) = (
+        failed(
+            "ex-4",
+            9,
+            "request to https://api.example.com failed: read ECONNRESET",
+        ),
+        failed("ex-2", 7, "401 Unauthorized"),
+    );
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/workflows/wf-1/executions", move |request| {
+                assert_eq!(query_param(request, "includeData"), Some("false"));
+                ok(history.clone())
+            })
+            .handle("GET", "/api/executions/ex-4", move |_| ok(reset.clone()))
+            .handle("GET", "/api/executions/ex-2", move |_| {
+                ok(unauthorized.clone())
+            }),
+    );
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&progress);
+    let options = SearchOptions::new()
+        .context(10)
+        .on_progress(move |p| seen.lock().unwrap().push(p));
+    let pattern = Regex::new("ECONN(RESET|REFUSED)").unwrap();
+    let matches: Vec<_> = client(&transport)
+        .search_execution_errors(
+            "wf-1",
+            &pattern,
+            day(10) - chrono::Duration::weeks(1)..day(10),
+            options,
+        )
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].execution_id, "ex-4");
+    assert_eq!(matches[0].node_id.as_deref(), Some("fetch"));
+    assert_eq!(matches[0].matched, "ECONNRESET");
+    assert_eq!(matches[0].context, "…led: read ECONNRESET");
+    let last = *progress.lock().unwrap().last().unwrap();
+    assert_eq!(
+        (last.scanned, last.total, last.checked, last.matches),
+        (4, Some(4), 2, 1)
+    );
+    // Outside the range, never fetched
+    assert_eq!(count(&transport, "GET", "/api/executions/ex-1"), 0);
+}
+
+#[tokio::test]
+async fn data_saving_policy_is_planned_then_applied() {
+    let with_settings = |id: &str, settings: Value| {
+        let mut body = workflow(id, id);
+        body["settings"] = settings;
+        body
+    };
+    let transport = Arc::new(
+        listing(vec![
+            with_settings("wf-1", json!({ "timezone": "UTC" })),
+            with_settings("wf-2", json!({ "saveSuccessfulExecutions": false })),
+        ])
+        .handle("PUT", "/api/workflows/wf-1", move |_| {
+            ok(with_settings("wf-1", json!({})))
+        }),
+    );
+    let client = client(&transport);
+    let policy = DataSavingPolicy {
+        save_execution_data_on_success: Some(false),
+        ..Default::default()
+    };
+    let planned = client
+        .set_data_saving_policy(None, policy, true)
+        .await
+        .unwrap();
+    assert_eq!(planned.len(), 1);
+    assert_eq!(planned[0].workflow_id, "wf-1");
+    assert_eq!(planned[0].changes[0].from, None);
+    assert_eq!(count(&transport, "PUT", "/api/workflows/wf-1"), 0);
+
+    client
+        .set_data_saving_policy(None, policy, false)
+        .await
+        .unwrap();
+    let updates: Vec<_> = transport
+        .requests()
+        .iter()
+        .filter(|request| request.method == "PUT")
+        .map(json_body)
+        .collect();
+    assert_eq!(
+        updates,
+        [json!({ "settings": { "timezone": "UTC", "saveSuccessfulExecutions": false } })]
+    );
+}
+
+#[tokio::test]
+async fn directory_is_synced_to_the_server() {
+    let described = |id: &str, name: &str, description: &str| {
+        let mut body = workflow(id, name);
+        body["active"] = json!(false);
+        body["description"] = json!(description);
+        body
+    };
+    let dir = std::env::temp_dir().join(format!("klikkflow-sync-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, name, description) in [
+        ("orders.json", "Orders", "Process orders"),
+        ("billing.json", "Billing", "Send invoices"),
+        ("refunds.json", "Refunds", ""),
+    ] {
+        let contents = json!({ "name": name, "description": description, "nodes": [] });
+        std::fs::write(dir.join(file), contents.to_string()).unwrap();
+    }
+    let transport = Arc::new(
+        listing(vec![
+            described("wf-1", "Orders", "Process orders"),
+            described("wf-2", "Billing", "Old description"),
+            described("wf-3", "Legacy", ""),
+            described("wf-4", "Scratch", ""),
+        ])
+        .handle("POST", "/api/workflows", move |_| {
+            ok(described("wf-5", "Refunds", ""))
+        })
+        .handle("PUT", "/api/workflows/wf-2", move |request| {
+            assert_eq!(json_body(request)["description"], "Send invoices");
+            ok(described("wf-2", "Billing", "Send invoices"))
+        })
+        .handle("DELETE", "/api/workflows/wf-4", |_| ok(json!({}))),
+    );
+
+    let options = SyncOptions {
+        delete_extraneous: true,
+        protected: vec!["Legacy".to_string()],
+        ..Default::default()
+    };
+    let report = client(&transport)
+        .sync_workflows(&dir, options)
+        .await
+        .unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(
+        report.plan.to_string(),
+        "~ Billing (description)\n= Orders\n+ Refunds\n! Legacy (no file, kept)\n- Scratch\n"
+    );
+    assert_eq!(
+        report.plan.items[0].action,
+        SyncAction::Update {
+            fields: vec!["description"]
+        }
+    );
+    assert!(report.is_success());
+    assert_eq!(report.outcomes.len(), 3);
+    assert_eq!(count(&transport, "DELETE", "/api/workflows/wf-4"), 1);
+    assert_eq!(count(&transport, "DELETE", "/api/workflows/wf-3"), 0);
+}
+
+#[tokio::test]
+async fn synced_directory_plans_nothing_the_second_time() {
+    let dir = std::env::temp_dir().join(format!("klikkflow-sync-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let step = |id: &str, x: f64| {
+        json!({
+            "id": id,
+            "name": id,
+            "type": "set",
+            "position": { "x": x, "y": 0.0 },
+            "parameters": { "value": id }
+        })
+    };
+    let orders = json!({
+        "name": "Orders",
+        "active": true,
+        "nodes": [step("fetch", 0.0), step("store", 200.0)],
+        "connections": [{ "source": { "nodeId": "fetch" }, "destination": { "nodeId": "store" } }]
+    });
+    std::fs::write(dir.join("orders.json"), orders.to_string()).unwrap();
+    let refunds = json!({ "name": "Refunds", "nodes": [step("refund", 0.0)] });
+    std::fs::write(dir.join("refunds.json"), refunds.to_string()).unwrap();
+
+    // Workflows are created inactive, with server-generated node IDs
+    let stored = Arc::new(Mutex::new(Vec::<Value>::new()));
+    let (listed, created, activated) = (stored.clone(), stored.clone(), stored.clone());
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/workflows", move |_| {
+                ok(json!({ "workflows": listed.lock().unwrap().clone() }))
+            })
+            .handle("POST", "/api/workflows", move |request| {
+                let mut stored = created.lock().unwrap();
+                let request = json_body(request);
+                let id = format!("wf-{}", stored.len() + 1);
+                let mut body = workflow(&id, request["name"].as_str().unwrap());
+                body["active"] = json!(false);
+                let mut nodes = request["nodes"].clone();
+                let mut connections = request["connections"].clone();
+                for node in nodes.as_array_mut().unwrap() {
+                    let generated = format!("{}-{}", id, node["id"].as_str().unwrap());
+                    for connection in connections.as_array_mut().unwrap() {
+                        for end in ["source", "destination"] {
+                            if connection[end]["nodeId"] == node["id"] {
+                                connection[end]["nodeId"] = json!(generated);
+                            }
+                        }
+                    }
+                    node["id"] = json!(generated);
+                }
+                body["nodes"] = nodes;
+                body["connections"] = connections;
+                stored.push(body.clone());
+                ok(body)
+            })
+            .handle("POST", "/api/workflows/wf-1/activate", move |_| {
+                let mut stored = activated.lock().unwrap();
+                stored[0]["active"] = json!(true);
+                ok(stored[0].clone())
+            }),
+    );
+    let client = client(&transport);
+
+    let first = client
+        .sync_workflows(&dir, SyncOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(first.plan.to_string(), "+ Orders\n+ Refunds\n");
+    assert!(first.is_success());
+    assert_eq!(stored.lock().unwrap()[0]["active"], true);
+    assert_eq!(stored.lock().unwrap()[1]["active"], false);
+
+    let second = client
+        .sync_workflows(&dir, SyncOptions::default())
+        .await
+        .unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(second.plan.is_empty(), "{}", second.plan);
+    assert_eq!(second.plan.to_string(), "= Orders\n= Refunds\n");
+    assert_eq!(count(&transport, "POST", "/api/workflows"), 2);
+}
+
+/// `wf-1` with node `n1`, whose options are nested
+fn with_options() -> Value {
+    with_step(
+        "wf-1",
+        json!({ "url": "https://api.example.com", "options": { "timeout": 30, "retries": 3 } }),
+    )
+}
+
+#[tokio::test]
+async fn node_parameters_are_patched_in_place() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "PATCH",
+        "/api/workflows/wf-1/nodes/n1/parameters",
+        |request| {
+            assert_eq!(
+                json_body(request),
+                json!({ "parameters": { "options": { "retries": 5 } }, "merge": true })
+            );
+            ok(with_options()["nodes"][0].clone())
+        },
+    ));
+    let updates = serde_json::from_value(json!({ "options": { "retries": 5 } })).unwrap();
+    let node = client(&transport)
+        .update_node_parameters("wf-1", "n1", updates, true)
+        .await
+        .unwrap();
+    assert_eq!(node.id, "n1");
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 0);
+    assert_eq!(count(&transport, "PUT", "/api/workflows/wf-1"), 0);
+}
+
+#[tokio::test]
+async fn node_parameters_fall_back_to_a_conditional_update() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("PATCH", "/api/workflows/wf-1/nodes/n1/parameters", |_| {
+                status(405, json!({ "message": "Method not allowed" }))
+            })
+            .handle("GET", "/api/workflows/wf-1", |_| {
+                with_header(ok(with_options()), "etag", "\"v7\"")
+            })
+            .handle("PUT", "/api/workflows/wf-1", |request| {
+                assert_eq!(request.headers["if-match"], "\"v7\"");
+                assert_eq!(
+                    json_body(request)["nodes"][0]["parameters"],
+                    json!({
+                        "url": "https://api.example.com",
+                        "options": { "timeout": 30, "retries": 5 }
+                    })
+                );
+                ok(with_options())
+            }),
+    );
+    let updates = serde_json::from_value(json!({ "options": { "retries": 5 } })).unwrap();
+    let node = client(&transport)
+        .update_node_parameters("wf-1", "n1", updates, true)
+        .await
+        .unwrap();
+    assert_eq!(node.parameters["options"]["retries"], 5);
+    assert_eq!(count(&transport, "PUT", "/api/workflows/wf-1"), 1);
+}
+
+#[tokio::test]
+async fn node_parameters_need_an_etag_to_fall_back() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("PATCH", "/api/workflows/wf-1/nodes/n1/parameters", |_| {
+                status(501, json!({ "message": "Not implemented" }))
+            })
+            .handle("GET", "/api/workflows/wf-1", |_| ok(with_options())),
+    );
+    let result = client(&transport)
+        .update_node_parameters("wf-1", "n1", Default::default(), false)
+        .await;
+    assert!(matches!(result, Err(Error::Unsupported(_))), "{:?}", result);
+    assert_eq!(count(&transport, "PUT", "/api/workflows/wf-1"), 0);
+}
+
+#[tokio::test]
+async fn concurrent_edit_fails_the_fallback_update() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("PATCH", "/api/workflows/wf-1/nodes/n1/parameters", |_| {
+                status(405, json!({ "message": "Method not allowed" }))
+            })
+            .handle("GET", "/api/workflows/wf-1", |_| {
+                with_header(ok(with_options()), "etag", "\"v7\"")
+            })
+            .handle("PUT", "/api/workflows/wf-1", |_| {
+                status(412, json!({ "message": "Precondition failed" }))
+            }),
+    );
+    let result = client(&transport)
+        .update_node_parameters("wf-1", "n1", Default::default(), false)
+        .await;
+    match result {
+        Err(e) => assert_eq!(e.status(), Some(412)),
+        Ok(node) => panic!("unexpected update of {:?}", node),
+    }
+}
+
+#[tokio::test]
+async fn missing_node_is_not_found_without_a_fallback() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "PATCH",
+        "/api/workflows/wf-1/nodes/n9/parameters",
+        |_| status(404, json!({ "message": "Node not found" })),
+    ));
+    match client(&transport)
+        .update_node_parameters("wf-1", "n9", Default::default(), false)
+        .await
+    {
+        Err(Error::NotFound { resource, id, .. }) => {
+            assert_eq!((resource.as_str(), id.as_deref()), ("node", Some("n9")));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 0);
+}
+
+#[tokio::test]
+async fn retained_execution_data_is_reported() {
+    let transport = Arc::new(
+        listing(vec![workflow("wf-1", "Signup")])
+            .handle("GET", "/api/settings/retention", |_| {
+                ok(json!({ "maxAgeSeconds": 2592000 }))
+            })
+            .handle("GET", "/api/workflows/wf-1/executions", |_| {
+                ok(json!({ "executions": [
+                    { "id": "ex-new", "status": "success", "startedAt": "2999-01-01T00:00:00Z" },
+                    { "id": "ex-purged", "status": "success", "startedAt": "2024-01-02T00:00:00Z" },
+                    { "id": "ex-kept", "status": "error", "startedAt": "2024-01-01T00:00:00Z" }
+                ]}))
+            })
+            .handle("GET", "/api/executions/ex-purged", |_| {
+                ok(json!({ "id": "ex-purged", "inputData": {}, "outputData": null }))
+            })
+            .handle("GET", "/api/executions/ex-kept", |_| {
+                ok(json!({ "id": "ex-kept", "inputData": { "email": "ada@example.com" } }))
+            }),
+    );
+    let report = client(&transport).verify_retention(10).await.unwrap();
+    assert_eq!(report.checked, 2);
+    assert!(!report.is_compliant());
+    assert_eq!(report.violations[0].execution_id, "ex-kept");
+    assert_eq!(report.violations[0].fields, ["inputData"]);
+    // Too recent to be purged yet
+    assert_eq!(count(&transport, "GET", "/api/executions/ex-new"), 0);
+}
+
+/// Transport whose `wf-1` is renamed on every fetch: "v1", "v2", ...
+fn renamed_on_every_fetch() -> MemoryTransport {
+    let fetches = AtomicUsize::new(0);
+    MemoryTransport::new()
+        .handle("GET", "/api/workflows/wf-1", move |_| {
+            let version = fetches.fetch_add(1, Ordering::SeqCst) + 1;
+            ok(workflow("wf-1", &format!("v{}", version)))
+        })
+        .handle("PUT", "/api/workflows/wf-1", |request| {
+            ok(workflow(
+                "wf-1",
+                json_body(request)["name"].as_str().unwrap(),
+            ))
+        })
+        .handle("DELETE", "/api/workflows/wf-1", |_| ok(json!({})))
+}
+
+#[tokio::test]
+async fn workflow_handle_memoizes_the_definition_until_refreshed() {
+    let transport = Arc::new(renamed_on_every_fetch());
+    let handle = client(&transport).workflow("wf-1");
+
+    assert_eq!(handle.get().await.unwrap().name, "v1");
+    // Clones share the memoized definition
+    assert_eq!(handle.clone().get().await.unwrap().name, "v1");
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 1);
+
+    assert_eq!(handle.refresh().await.unwrap().name, "v2");
+    assert_eq!(handle.get().await.unwrap().name, "v2");
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 2);
+}
+
+#[tokio::test]
+async fn workflow_handle_changes_replace_or_drop_the_memoized_definition() {
+    let transport = Arc::new(renamed_on_every_fetch());
+    let handle = client(&transport).workflow("wf-1");
+    assert_eq!(handle.get().await.unwrap().name, "v1");
+
+    let request = UpdateWorkflowRequest {
+        name: Some("renamed".to_string()),
+        ..Default::default()
+    };
+    handle.update(request).await.unwrap();
+    assert_eq!(handle.get().await.unwrap().name, "renamed");
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 1);
+
+    handle.delete().await.unwrap();
+    assert_eq!(handle.get().await.unwrap().name, "v2");
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 2);
+}
+
+/// Workflow `id` as stored at `updated_at`
+fn stamped(id: &str, updated_at: &str) -> Value {
+    let mut body = workflow(id, id);
+    body["updatedAt"] = updated_at.into();
+    body
+}
+
+/// Client reading its writes back within `window`, retrying every 20ms
+fn reading_its_writes(transport: &Arc<MemoryTransport>, window: Duration) -> klikkflow_sdk::Client {
+    client(transport).with_consistency(
+        ConsistencyOptions::read_your_writes(true)
+            .window(window)
+            .retry_interval(Duration::from_millis(20)),
+    )
+}
+
+#[tokio::test]
+async fn reads_of_a_written_workflow_are_retried_until_the_replica_catches_up() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("POST", "/api/workflows", |_| {
+                with_header(
+                    ok(stamped("wf-1", "2024-01-02T00:00:00Z")),
+                    CONSISTENCY_TOKEN_HEADER,
+                    "lsn-42",
+                )
+            })
+            .handle(
+                "GET",
+                "/api/workflows/wf-1",
+                sequence(vec![
+                    status(404, json!({ "message": "not found" })),
+                    ok(stamped("wf-1", "2024-01-01T00:00:00Z")),
+                    ok(stamped("wf-1", "2024-01-02T00:00:00Z")),
+                ]),
+            ),
+    );
+    let client = reading_its_writes(&transport, Duration::from_secs(5));
+    client
+        .create_workflow(CreateWorkflowRequest {
+            name: "wf-1".to_string(),
+            description: String::new(),
+            nodes: vec![],
+            connections: vec![],
+            settings: None,
+        })
+        .await
+        .unwrap();
+
+    let read = client.get_workflow("wf-1").await.unwrap();
+    assert_eq!(
+        read.updated_at,
+        Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap()
+    );
+    let reads: Vec<_> = transport
+        .requests()
+        .into_iter()
+        .filter(|request| request.method == "GET")
+        .collect();
+    assert_eq!(reads.len(), 3);
+    for request in &reads {
+        assert_eq!(request.headers[CONSISTENCY_TOKEN_HEADER], "lsn-42");
+    }
+}
+
+#[tokio::test]
+async fn stale_reads_are_returned_once_the_window_ends() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("PUT", "/api/workflows/wf-1", |_| {
+                ok(stamped("wf-1", "2024-01-02T00:00:00Z"))
+            })
+            .handle("GET", "/api/workflows/wf-1", |_| {
+                status(404, json!({ "message": "not found" }))
+            }),
+    );
+    let client = reading_its_writes(&transport, Duration::from_millis(200));
+    client
+        .update_workflow("wf-1", UpdateWorkflowRequest::default())
+        .await
+        .unwrap();
+
+    let started = Instant::now();
+    let read = client.get_workflow("wf-1").await;
+    assert!(matches!(read, Err(klikkflow_sdk::Error::NotFound { .. })));
+    assert!(started.elapsed() < Duration::from_millis(400));
+    let reads = count(&transport, "GET", "/api/workflows/wf-1");
+    assert!((2..=10).contains(&reads), "{} reads", reads);
+
+    // Without read-your-writes, the first answer stands
+    let transport = Arc::new(
+        MemoryTransport::new().handle("GET", "/api/workflows/wf-1", |_| {
+            status(404, json!({ "message": "not found" }))
+        }),
+    );
+    assert!(common::client(&transport)
+        .get_workflow("wf-1")
+        .await
+        .is_err());
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 1);
+}
+
+#[tokio::test]
+async fn only_the_most_recent_writes_are_tracked() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("PUT", "/api/workflows/wf-1", |_| {
+                ok(stamped("wf-1", "2024-01-02T00:00:00Z"))
+            })
+            .handle("PUT", "/api/workflows/wf-2", |_| {
+                ok(stamped("wf-2", "2024-01-02T00:00:00Z"))
+            })
+            .handle("GET", "/api/workflows/wf-1", |_| {
+                status(404, json!({ "message": "not found" }))
+            })
+            .handle("GET", "/api/workflows/wf-2", |_| {
+                status(404, json!({ "message": "not found" }))
+            }),
+    );
+    let client = common::client(&transport).with_consistency(
+        ConsistencyOptions::read_your_writes(true)
+            .window(Duration::from_millis(200))
+            .retry_interval(Duration::from_millis(20))
+            .max_tracked(1),
+    );
+    for id in ["wf-1", "wf-2"] {
+        client
+            .update_workflow(id, UpdateWorkflowRequest::default())
+            .await
+            .unwrap();
+    }
+
+    // The write of wf-1 was evicted by the one of wf-2
+    assert!(client.get_workflow("wf-1").await.is_err());
+    assert_eq!(count(&transport, "GET", "/api/workflows/wf-1"), 1);
+    assert!(client.get_workflow("wf-2").await.is_err());
+    assert!(count(&transport, "GET", "/api/workflows/wf-2") > 1);
+}
+
+#[tokio::test]
+async fn retention_policy_is_read_and_replaced() {
+    let transport = Arc::new(
+        MemoryTransport::new()
+            .handle("GET", "/api/settings/retention", |_| {
+                ok(json!({ "maxAgeSeconds": 86400, "keepFailed": true }))
+            })
+            .handle("PUT", "/api/settings/retention", |request| {
+                ok(json_body(request))
+            }),
+    );
+    let client = client(&transport);
+    let policy = client.get_retention_policy().await.unwrap();
+    assert_eq!(
+        policy,
+        RetentionPolicy {
+            max_age: Duration::from_secs(86400),
+            keep_successful: false,
+            keep_failed: true,
+        }
+    );
+
+    let replaced = RetentionPolicy {
+        max_age: Duration::from_secs(3600),
+        ..policy
+    };
+    assert_eq!(
+        client.set_retention_policy(replaced).await.unwrap(),
+        replaced
+    );
+    let requests = transport.requests();
+    assert_eq!(
+        json_body(&requests[1]),
+        json!({ "maxAgeSeconds": 3600, "keepSuccessful": false, "keepFailed": true })
+    );
+}
+
+#[tokio::test]
+async fn missing_retention_settings_are_unsupported() {
+    for code in [404, 405, 501] {
+        let transport = Arc::new(
+            MemoryTransport::new()
+                .handle("GET", "/api/settings/retention", move |_| {
+                    status(code, json!({}))
+                })
+                .handle("PUT", "/api/settings/retention", move |_| {
+                    status(code, json!({}))
+                }),
+        );
+        let client = client(&transport);
+        assert!(
+            matches!(
+                client.get_retention_policy().await,
+                Err(Error::Unsupported(_))
+            ),
+            "{}",
+            code
+        );
+        assert!(matches!(
+            client
+                .set_retention_policy(RetentionPolicy {
+                    max_age: Duration::from_secs(60),
+                    keep_successful: false,
+                    keep_failed: false,
+                })
+                .await,
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            client.verify_retention(10).await,
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    // Other failures are reported as they are
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "GET",
+        "/api/settings/retention",
+        |_| status(403, json!({ "message": "admins only" })),
+    ));
+    let error = client(&transport).get_retention_policy().await.unwrap_err();
+    assert_eq!(error.status(), Some(403));
+}
+
+#[tokio::test]
+async fn retention_is_verified_on_a_sample_across_workflows() {
+    let history = |ids: &'static [(&'static str, &'static str)]| {
+        move |_: &klikkflow_sdk::TransportRequest| {
+            let executions: Vec<Value> = ids
+                .iter()
+                .map(|(id, status)| {
+                    json!({ "id": id, "status": status, "startedAt": "2024-01-01T00:00:00Z" })
+                })
+                .collect();
+            ok(json!({ "executions": executions }))
+        }
+    };
+    let transport = Arc::new(
+        listing(vec![
+            workflow("wf-1", "Signup"),
+            workflow("wf-2", "Billing"),
+        ])
+        .handle("GET", "/api/settings/retention", |_| {
+            ok(json!({ "maxAgeSeconds": 60, "keepSuccessful": true }))
+        })
+        .handle(
+            "GET",
+            "/api/workflows/wf-1/executions",
+            history(&[
+                ("ex-1a", "error"),
+                ("ex-1b", "cancelled"),
+                ("ex-1c", "error"),
+                ("ex-1d", "success"),
+            ]),
+        )
+        .handle(
+            "GET",
+            "/api/workflows/wf-2/executions",
+            history(&[("ex-2a", "error"), ("ex-2b", "running")]),
+        )
+        .handle("GET", "/api/executions/ex-1a", |_| {
+            ok(json!({ "id": "ex-1a", "nodeResults": { "n1": { "output": { "total": 3 } } } }))
+        })
+        .handle("GET", "/api/executions/ex-1b", |_| {
+            ok(json!({ "id": "ex-1b", "outputData": { "receipt": "r-1" } }))
+        })
+        // Deleted executions satisfy the policy too
+        .handle("GET", "/api/executions/ex-2a", |_| {
+            status(404, json!({ "message": "not found" }))
+        }),
+    );
+    let report = client(&transport).verify_retention(3).await.unwrap();
+    assert_eq!(report.checked, 3);
+    let violations: Vec<_> = report
+        .violations
+        .iter()
+        .map(|violation| (violation.execution_id.as_str(), violation.fields.clone()))
+        .collect();
+    assert_eq!(
+        violations,
+        [
+            ("ex-1a", vec!["nodeResults"]),
+            ("ex-1b", vec!["outputData"])
+        ]
+    );
+    assert_eq!(report.violations[0].workflow_id, "wf-1");
+
+    // Both workflows are sampled before a third execution of either
+    assert_eq!(count(&transport, "GET", "/api/executions/ex-2a"), 1);
+    assert_eq!(count(&transport, "GET", "/api/executions/ex-1c"), 0);
+    // Successful executions are kept by the policy, running ones have no data yet
+    assert_eq!(count(&transport, "GET", "/api/executions/ex-1d"), 0);
+    assert_eq!(count(&transport, "GET", "/api/executions/ex-2b"), 0);
+}
+
+#[tokio::test]
+async fn oversized_static_data_is_rejected_before_sending() {
+    let transport = Arc::new(MemoryTransport::new().handle(
+        "PUT",
+        "/api/workflows/wf-1/static-data",
+        |_| ok(json!({})),
+    ));
+    let client = client(&transport);
+    let blob = "x".repeat(MAX_STATIC_DATA_BYTES);
+    let data = [("cursor".to_string(), json!(blob))].into();
+    match client.set_workflow_static_data("wf-1", data).await {
+        Err(Error::InvalidInput(message)) => assert!(message.contains("byte limit")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(
+        count(&transport, "PUT", "/api/workflows/wf-1/static-data"),
+        0
+    );
+
+    let data = [("cursor".to_string(), json!("x".repeat(1024)))].into();
+    client.set_workflow_static_data("wf-1", data).await.unwrap();
+    assert_eq!(
+        count(&transport, "PUT", "/api/workflows/wf-1/static-data"),
+        1
+    );
+}